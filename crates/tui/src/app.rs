@@ -17,9 +17,10 @@ use solana_sdk::{
 };
 
 use hardig::state::{
-    KeyState, MarketConfig, PositionState, PromoConfig, ProtocolConfig, RateBucket, TrustedProvider,
-    PERM_BORROW, PERM_BUY, PERM_LIMITED_BORROW, PERM_LIMITED_SELL, PERM_MANAGE_KEYS,
-    PERM_REINVEST, PERM_REPAY, PERM_SELL, PRESET_ADMIN, PRESET_OPERATOR,
+    ClaimReceipt, GateReceipt, GateStandard, KeyState, MarketConfig, MarketUpdate, PositionState, PromoBudget, PromoConfig, ProtocolConfig, RateBucket, ReferralReward, ReinvestMode,
+    TrustedProvider, WithdrawRequest,
+    PERM_BORROW, PERM_BUY, PERM_LIMITED_BORROW, PERM_LIMITED_SELL, PERM_MANAGE_KEYS, PERM_UNWIND,
+    PERM_GUARDIAN, PERM_REINVEST, PERM_REPAY, PERM_SELL, PRESET_ADMIN, PRESET_OPERATOR,
 };
 
 // Mayflower constants and helpers
@@ -89,7 +90,7 @@ pub enum Screen {
 pub struct DiscoveredPosition {
     pub position_pda: Pubkey,
     pub admin_asset: Pubkey,
-    pub permissions: u8,
+    pub permissions: u16,
     pub key_asset: Pubkey,
     pub key_state_pda: Option<Pubkey>,
     pub deposited_nav: u64,
@@ -109,6 +110,15 @@ pub enum FormKind {
     Sell,
     Borrow,
     Repay,
+    Unwind,
+    SetPayout,
+    ConfigureLeverage,
+    RedeemShares,
+    ConfigureCosign,
+    ConfigureWithdrawQueue,
+    ConfigureKeeper,
+    ExecuteWithdraw,
+    CancelWithdraw,
     ConfigureRecovery,
     CreatePromo,
     UpdatePromo,
@@ -117,7 +127,7 @@ pub enum FormKind {
 pub struct KeyEntry {
     pub pda: Pubkey,
     pub asset: Pubkey,
-    pub permissions: u8,
+    pub permissions: u16,
     pub held_by_signer: bool,
     /// On-chain MPL-Core asset name (e.g. "Härdig Admin Key - Savings").
     pub name: String,
@@ -133,6 +143,14 @@ pub struct KeyEntry {
     pub total_borrow_limit: u64,
     /// Lifetime total borrowed so far.
     pub total_borrowed: u64,
+    /// PromoConfig the key was claimed from (default = not a promo key).
+    pub promo: Pubkey,
+    /// navSOL shares deposited at claim and not yet withdrawn.
+    pub principal_shares: u64,
+    /// Lamports borrowed through this key and not yet repaid through it.
+    pub debt: u64,
+    /// May withdraw its principal (less debt) without a sell permission.
+    pub principal_refundable: bool,
 }
 
 pub struct PromoEntry {
@@ -167,12 +185,15 @@ pub struct App {
 
     // Protocol state
     pub protocol_exists: bool,
+    pub protocol_paused: bool,
+    pub protocol_fee_bps: u16,
+    pub treasury: Pubkey,
     pub collection: Option<Pubkey>,
 
     // Position state (single position mode)
     pub position_pda: Option<Pubkey>,
     pub position: Option<PositionState>,
-    pub my_permissions: Option<u8>,
+    pub my_permissions: Option<u16>,
     pub my_key_state_pda: Option<Pubkey>,
     pub my_asset: Option<Pubkey>,
     pub keyring: Vec<KeyEntry>,
@@ -197,6 +218,8 @@ pub struct App {
     pub mf_debt: u64,
     pub mf_floor_price: u64,
     pub mf_borrow_capacity: u64,
+    /// Signer's share token balance (None = position has no share token or no ATA).
+    pub share_token_balance: Option<u64>,
 
     // Refresh tracking
     pub last_refresh: Option<Instant>,
@@ -213,7 +236,7 @@ pub struct App {
     pub input_buf: String,
 
     // Permission checkboxes for authorize_key form
-    pub perm_bits: u8,
+    pub perm_bits: u16,
     pub perm_cursor: usize,
 
     // Multi-position discovery
@@ -259,6 +282,9 @@ impl App {
             message_log: Vec::new(),
             verbose,
             protocol_exists: false,
+            protocol_paused: false,
+            protocol_fee_bps: 0,
+            treasury: Pubkey::default(),
             collection: None,
             position_pda: None,
             position: None,
@@ -282,6 +308,7 @@ impl App {
             mf_debt: 0,
             mf_floor_price: 0,
             mf_borrow_capacity: 0,
+            share_token_balance: None,
             last_refresh: None,
             current_slot: 0,
             form_kind: None,
@@ -459,16 +486,32 @@ impl App {
             }
             KeyCode::Char('x') if self.has_perm(PERM_MANAGE_KEYS) => self.enter_revoke_key(),
             KeyCode::Char('h') if self.has_perm(PERM_MANAGE_KEYS) => self.build_heartbeat(),
+            KeyCode::Char('X') if self.has_perm(PERM_MANAGE_KEYS) => self.build_close_position(),
+            KeyCode::Char('o') if self.has_perm(PERM_MANAGE_KEYS) => self.enter_set_payout(),
+            KeyCode::Char('L') if self.has_perm(PERM_MANAGE_KEYS) => self.enter_configure_leverage(),
+            KeyCode::Char('T') if self.has_perm(PERM_MANAGE_KEYS) && self.share_mint().is_none() => {
+                self.build_enable_share_mint()
+            }
+            KeyCode::Char('R') if self.can_redeem_shares() => self.enter_redeem_shares(),
+            KeyCode::Char('M') if self.has_perm(PERM_MANAGE_KEYS) => self.enter_configure_cosign(),
+            KeyCode::Char('Q') if self.has_perm(PERM_MANAGE_KEYS) => self.enter_configure_withdraw_queue(),
+            KeyCode::Char('K') if self.has_perm(PERM_MANAGE_KEYS) => self.enter_configure_keeper(),
+            KeyCode::Char('w') if self.can_sell() && self.has_queued_withdraws() => self.enter_queued_withdraw(FormKind::ExecuteWithdraw),
+            KeyCode::Char('W') if self.can_cancel_withdraw() => self.enter_queued_withdraw(FormKind::CancelWithdraw),
             KeyCode::Char('c') if self.has_perm(PERM_MANAGE_KEYS) => self.enter_configure_recovery(),
             KeyCode::Char('P') if self.has_perm(PERM_MANAGE_KEYS) => self.enter_promo_list(),
             KeyCode::Char('e') => self.build_execute_recovery(),
+            KeyCode::Char('S') if self.position.is_some() => self.build_sync_position(),
             KeyCode::Char('s') if self.can_sell() => self.enter_sell(),
             KeyCode::Char('d') if self.can_borrow() => self.enter_borrow(),
 
             // Role-gated actions
             KeyCode::Char('b') if self.can_buy() => self.enter_buy(),
             KeyCode::Char('p') if self.can_repay() => self.enter_repay(),
-            KeyCode::Char('i') if self.can_reinvest() => self.build_reinvest(0),
+            KeyCode::Char('i') if self.can_reinvest() => {
+                self.build_reinvest(0, ReinvestMode::Fraction { bps: 10_000 }, 0)
+            }
+            KeyCode::Char('u') if self.can_unwind() => self.enter_unwind(),
 
             // Navigate keyring
             KeyCode::Up | KeyCode::Char('k') => {
@@ -485,7 +528,7 @@ impl App {
         }
     }
 
-    pub fn has_perm(&self, perm: u8) -> bool {
+    pub fn has_perm(&self, perm: u16) -> bool {
         self.my_permissions.map_or(false, |p| p & perm != 0)
     }
     pub fn cpi_ready(&self) -> bool {
@@ -493,22 +536,62 @@ impl App {
         // so we only need the Mayflower PersonalPosition to be initialized.
         self.mayflower_initialized
     }
+    /// Buy/sell/borrow/reinvest are blocked protocol-wide or for this market.
+    pub fn is_paused(&self) -> bool {
+        self.protocol_paused || self.market_config.as_ref().is_some_and(|mc| mc.paused)
+    }
+    /// Retired markets only allow exits: repay, unwind and sell.
+    pub fn is_retired(&self) -> bool {
+        self.market_config.as_ref().is_some_and(|mc| mc.retired)
+    }
     pub fn can_buy(&self) -> bool {
-        self.cpi_ready() && self.has_perm(PERM_BUY)
+        self.cpi_ready() && !self.is_paused() && !self.is_retired() && self.has_perm(PERM_BUY)
     }
     pub fn can_sell(&self) -> bool {
-        self.cpi_ready() && (self.has_perm(PERM_SELL) || self.has_perm(PERM_LIMITED_SELL))
+        self.cpi_ready() && !self.is_paused()
+            && (self.has_perm(PERM_SELL) || self.has_perm(PERM_LIMITED_SELL) || self.principal_refundable())
     }
     pub fn can_borrow(&self) -> bool {
-        self.cpi_ready() && (self.has_perm(PERM_BORROW) || self.has_perm(PERM_LIMITED_BORROW))
+        self.cpi_ready() && !self.is_paused() && !self.is_retired()
+            && (self.has_perm(PERM_BORROW) || self.has_perm(PERM_LIMITED_BORROW))
     }
     pub fn can_repay(&self) -> bool {
         self.cpi_ready()
             && self.position.as_ref().map(|p| p.user_debt > 0).unwrap_or(false)
-            && self.has_perm(PERM_REPAY)
+            && (self.has_perm(PERM_REPAY) || self.principal_refundable())
+    }
+    /// Our key was claimed from a refundable-principal promo: it may withdraw its
+    /// own deposit and repay its own debt without sell/repay permissions.
+    fn principal_refundable(&self) -> bool {
+        self.keyring
+            .iter()
+            .any(|k| Some(k.pda) == self.my_key_state_pda && k.principal_refundable)
+    }
+    /// The position's share token mint, if it has one.
+    pub fn share_mint(&self) -> Option<Pubkey> {
+        self.position
+            .as_ref()
+            .map(|p| p.share_mint)
+            .filter(|m| *m != Pubkey::default())
+    }
+    /// Share tokens are redeemable by any holder, key or not.
+    pub fn can_redeem_shares(&self) -> bool {
+        self.cpi_ready() && !self.is_paused() && self.share_token_balance.unwrap_or(0) > 0
     }
     pub fn can_reinvest(&self) -> bool {
-        self.cpi_ready() && self.has_perm(PERM_REINVEST)
+        self.cpi_ready() && !self.is_paused() && !self.is_retired() && self.has_perm(PERM_REINVEST)
+    }
+    pub fn has_queued_withdraws(&self) -> bool {
+        self.position.as_ref().map(|p| p.withdraw_request_count > 0).unwrap_or(false)
+    }
+    pub fn can_cancel_withdraw(&self) -> bool {
+        self.has_queued_withdraws()
+            && (self.has_perm(PERM_GUARDIAN) || self.has_perm(PERM_MANAGE_KEYS))
+    }
+    pub fn can_unwind(&self) -> bool {
+        self.cpi_ready()
+            && self.position.as_ref().map(|p| p.user_debt > 0).unwrap_or(false)
+            && (self.has_perm(PERM_UNWIND) || self.has_perm(PERM_MANAGE_KEYS))
     }
 
     // -----------------------------------------------------------------------
@@ -541,6 +624,31 @@ impl App {
                             self.input_buf = self.form_fields[idx].1.clone();
                         }
                     }
+                    KeyCode::Char('a') => {
+                        // Switch to editable mode for the allowlist root only
+                        self.form_readonly = false;
+                        self.form_info = Some("Enter new allowlist root (64 hex chars, empty = open to anyone)".into());
+                        if let Some(idx) = self.form_fields.iter().position(|(l, _)| l.starts_with("Allowlist Root")) {
+                            self.input_field = idx;
+                            self.input_buf = match self.promos.get(self.promo_cursor) {
+                                Some(e) if e.config.merkle_root != [0u8; 32] => hex32(&e.config.merkle_root),
+                                _ => String::new(),
+                            };
+                        }
+                    }
+                    KeyCode::Char('e') => {
+                        // Switch to editable mode for the end of the claim window only
+                        self.form_readonly = false;
+                        self.form_info = Some("Enter days from now until claims close (0 = no end)".into());
+                        if let Some(idx) = self.form_fields.iter().position(|(l, _)| l.starts_with("Claim Window")) {
+                            self.input_field = idx;
+                            self.input_buf = "0".into();
+                        }
+                    }
+                    KeyCode::Char('c') => {
+                        // Close a paused promo and sweep its receipts
+                        self.build_close_promo();
+                    }
                     _ => {}
                 }
                 return;
@@ -605,7 +713,7 @@ impl App {
                 self.perm_cursor = self.perm_cursor.saturating_sub(1);
             }
             KeyCode::Right | KeyCode::Down if is_perm_field => {
-                if self.perm_cursor < 6 {
+                if self.perm_cursor < 8 {
                     self.perm_cursor += 1;
                 }
             }
@@ -613,8 +721,10 @@ impl App {
                 self.input_buf.pop();
             }
             KeyCode::Char(c) if is_perm_field => {
-                const PERM_ORDER: [u8; 7] =
-                    [PERM_BUY, PERM_SELL, PERM_BORROW, PERM_REPAY, PERM_REINVEST, PERM_LIMITED_SELL, PERM_LIMITED_BORROW];
+                const PERM_ORDER: [u16; 9] = [
+                    PERM_BUY, PERM_SELL, PERM_BORROW, PERM_REPAY, PERM_REINVEST,
+                    PERM_LIMITED_SELL, PERM_LIMITED_BORROW, PERM_UNWIND, PERM_GUARDIAN,
+                ];
                 match c {
                     ' ' => { self.perm_bits ^= PERM_ORDER[self.perm_cursor]; self.sync_perm_field(); }
                     '1' => { self.perm_bits ^= PERM_BUY; self.sync_perm_field(); }
@@ -624,6 +734,8 @@ impl App {
                     '5' => { self.perm_bits ^= PERM_REINVEST; self.sync_perm_field(); }
                     '6' => { self.perm_bits ^= PERM_LIMITED_SELL; self.sync_perm_field(); }
                     '7' => { self.perm_bits ^= PERM_LIMITED_BORROW; self.sync_perm_field(); }
+                    '8' => { self.perm_bits ^= PERM_UNWIND; self.sync_perm_field(); }
+                    '9' => { self.perm_bits ^= PERM_GUARDIAN; self.sync_perm_field(); }
                     _ => {}
                 }
             }
@@ -655,6 +767,7 @@ impl App {
         let borrow_days = self.find_field_value("Borrow Refill Days");
         let borrow_hours = self.find_field_value("Borrow Refill Hours");
         let borrow_mins = self.find_field_value("Borrow Refill Minutes");
+        let expires_val = self.find_field_value("Expires In Days");
         let name_val = self.find_field_value("Label (optional)");
 
         // Rebuild fields after index 1
@@ -671,6 +784,7 @@ impl App {
             self.form_fields.push(("Borrow Refill Hours".into(), borrow_hours.unwrap_or("0".into())));
             self.form_fields.push(("Borrow Refill Minutes".into(), borrow_mins.unwrap_or("0".into())));
         }
+        self.form_fields.push(("Expires In Days (0=never)".into(), expires_val.unwrap_or("0".into())));
         self.form_fields.push(("Label (optional)".into(), name_val.unwrap_or_default()));
 
         // Clamp cursor if fields were removed
@@ -780,6 +894,7 @@ impl App {
         self.form_fields = vec![
             ("Target Wallet (pubkey)".into(), my_wallet.clone()),
             ("Permissions".into(), PRESET_OPERATOR.to_string()),
+            ("Expires In Days (0=never)".into(), "0".into()),
             ("Label (optional)".into(), String::new()),
         ];
         self.input_field = 0;
@@ -867,6 +982,31 @@ impl App {
         self.input_buf = self.form_fields[0].1.clone();
     }
 
+    fn enter_unwind(&mut self) {
+        let (nav_max, debt) = self
+            .position
+            .as_ref()
+            .map(|p| (p.deposited_nav, p.user_debt))
+            .unwrap_or((0, 0));
+        self.screen = Screen::Form;
+        self.form_readonly = false;
+        self.form_locked = false;
+        self.form_kind = Some(FormKind::Unwind);
+        let nav = self.market_config.as_ref().map(|mc| nav_token_name(&mc.nav_mint)).unwrap_or("shares");
+        self.form_info = Some(format!(
+            "Available: {} {}\nDebt: {} SOL",
+            lamports_to_sol(nav_max),
+            nav,
+            lamports_to_sol(debt)
+        ));
+        self.form_fields = vec![
+            (format!("Sell Amount ({})", nav), String::new()),
+            ("Target Debt (SOL)".into(), "0".into()),
+        ];
+        self.input_field = 0;
+        self.input_buf.clear();
+    }
+
     // -----------------------------------------------------------------------
     // Promo list handler
     // -----------------------------------------------------------------------
@@ -980,7 +1120,19 @@ impl App {
             ("Min Deposit (SOL)".into(), "0.02".into()),
             ("Max Claims (0=unlimited)".into(), "0".into()),
             ("Initial Fill %".into(), "100".into()),
+            ("Key Expires In Days (0=never)".into(), "0".into()),
             ("Image URI (optional)".into(), String::new()),
+            ("Allowlist Root (hex, optional)".into(), String::new()),
+            ("Gate Collection (optional)".into(), String::new()),
+            ("Gate Standard (core/tm)".into(), "core".into()),
+            ("Claims Open In Days (0=now)".into(), "0".into()),
+            ("Claims Close In Days (0=never)".into(), "0".into()),
+            ("Campaign Borrow Budget (SOL, 0=none)".into(), "0".into()),
+            ("Campaign Sell Budget (SOL, 0=none)".into(), "0".into()),
+            ("Referral Capacity Bonus (SOL)".into(), "0".into()),
+            ("Referral Limit Bonus (SOL)".into(), "0".into()),
+            ("Referrals Per Referrer".into(), "0".into()),
+            ("Refundable Principal (y/n)".into(), "n".into()),
         ];
         self.input_field = 0;
         self.input_buf.clear();
@@ -1007,7 +1159,7 @@ impl App {
         self.form_locked = false;
         self.form_info = Some(format!(
             "Promo: {}  |  Status: {}\n\
-             [Enter] Toggle active/paused  [m] Change max claims  [Esc] Back",
+             [Enter] Toggle active/paused  [m] Change max claims  [a] Rotate allowlist  [e] Set end  [Esc] Back",
             promo.name_suffix, status,
         ));
         self.form_fields = vec![
@@ -1021,6 +1173,41 @@ impl App {
             ("Max Claims".into(), max_str),
             ("Claims Count".into(), promo.claims_count.to_string()),
             ("Image URI".into(), promo.image_uri.clone()),
+            (
+                "Allowlist Root".into(),
+                if promo.merkle_root == [0u8; 32] { "none (open)".into() } else { hex32(&promo.merkle_root) },
+            ),
+            (
+                "Gate Collection".into(),
+                match (promo.gate_collection == Pubkey::default(), promo.gate_standard) {
+                    (true, _) => "none (ungated)".into(),
+                    (false, GateStandard::MplCore) => format!("{} (MPL-Core)", promo.gate_collection),
+                    (false, GateStandard::TokenMetadata) => format!("{} (Token Metadata)", promo.gate_collection),
+                },
+            ),
+            (
+                "Claim Window".into(),
+                hardig::instructions::format_promo_window(promo.starts_at, promo.ends_at).unwrap_or_else(|| "always open".into()),
+            ),
+            ("Campaign Borrowed".into(), budget_label(promo.budget_borrowed, promo.budget_borrow_limit)),
+            ("Campaign Sold".into(), budget_label(promo.budget_sold, promo.budget_sell_limit)),
+            (
+                "Referral Reward".into(),
+                if !promo.referral_reward.has_bonus() {
+                    "none".into()
+                } else {
+                    format!(
+                        "+{} SOL capacity, +{} SOL lifetime limit, up to {} per referrer",
+                        lamports_to_sol(promo.referral_reward.borrow_capacity_bonus),
+                        lamports_to_sol(promo.referral_reward.total_borrow_limit_bonus),
+                        promo.referral_reward.max_referrals_per_referrer,
+                    )
+                },
+            ),
+            (
+                "Refundable Principal".into(),
+                if promo.refundable_principal { "yes" } else { "no" }.into(),
+            ),
             ("PDA".into(), entry.pda.to_string()),
         ];
         self.input_field = 0;
@@ -1043,9 +1230,34 @@ impl App {
             Some(FormKind::Sell) => self.build_sell(),
             Some(FormKind::Borrow) => self.build_borrow(),
             Some(FormKind::Repay) => self.build_repay(),
+            Some(FormKind::Unwind) => self.build_unwind(),
+            Some(FormKind::SetPayout) => self.build_set_payout(),
+            Some(FormKind::ConfigureLeverage) => self.build_configure_leverage(),
+            Some(FormKind::RedeemShares) => self.build_redeem_shares(),
+            Some(FormKind::ConfigureCosign) => self.build_configure_cosign(),
+            Some(FormKind::ConfigureWithdrawQueue) => self.build_configure_withdraw_queue(),
+            Some(FormKind::ConfigureKeeper) => self.build_configure_keeper(),
+            Some(FormKind::ExecuteWithdraw) => self.build_execute_withdraw(),
+            Some(FormKind::CancelWithdraw) => self.build_cancel_withdraw(),
             Some(FormKind::ConfigureRecovery) => self.build_configure_recovery(),
             Some(FormKind::CreatePromo) => self.build_create_promo(),
-            Some(FormKind::UpdatePromo) => self.build_update_promo_max_claims(),
+            Some(FormKind::UpdatePromo) => {
+                let editing_root = self
+                    .form_fields
+                    .get(self.input_field)
+                    .is_some_and(|(label, _)| label.starts_with("Allowlist Root"));
+                let editing_window = self
+                    .form_fields
+                    .get(self.input_field)
+                    .is_some_and(|(label, _)| label.starts_with("Claim Window"));
+                if editing_root {
+                    self.build_update_promo_root()
+                } else if editing_window {
+                    self.build_update_promo_end()
+                } else {
+                    self.build_update_promo_max_claims()
+                }
+            }
             None => {}
         }
     }
@@ -1094,7 +1306,7 @@ impl App {
 
         self.goto_confirm(PendingAction {
             description: vec![
-                "Migrate Config (add collection field)".into(),
+                "Migrate Config (to latest layout)".into(),
                 format!("Config PDA: {}", config_pda),
             ],
             instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
@@ -1227,6 +1439,10 @@ impl App {
                     market_nav_vault: mnv,
                     fee_vault: fv,
                     bump: 0,
+                    paused: false,
+                    retired: false,
+                    pending_update: MarketUpdate::default(),
+                    pending_update_at: 0,
                 }
             }
         };
@@ -1320,18 +1536,18 @@ impl App {
                 return;
             }
         };
-        let permissions_u8: u8 = match self.form_fields[1].1.trim().parse() {
+        let permissions_bits: u16 = match self.form_fields[1].1.trim().parse() {
             Ok(v) => v,
             Err(_) => {
                 self.push_log("Invalid permissions");
                 return;
             }
         };
-        if permissions_u8 == 0 {
+        if permissions_bits == 0 {
             self.push_log("Permissions cannot be zero");
             return;
         }
-        if permissions_u8 & PERM_MANAGE_KEYS != 0 {
+        if permissions_bits & PERM_MANAGE_KEYS != 0 {
             self.push_log("Cannot grant PERM_MANAGE_KEYS to delegated keys");
             return;
         }
@@ -1383,7 +1599,7 @@ impl App {
             .unwrap_or(0);
 
         // Validate rate-limit fields: both capacity and refill must be nonzero
-        if permissions_u8 & PERM_LIMITED_SELL != 0 {
+        if permissions_bits & PERM_LIMITED_SELL != 0 {
             if sell_cap == 0 {
                 self.push_log("LimSell capacity must be > 0");
                 return;
@@ -1393,7 +1609,7 @@ impl App {
                 return;
             }
         }
-        if permissions_u8 & PERM_LIMITED_BORROW != 0 {
+        if permissions_bits & PERM_LIMITED_BORROW != 0 {
             if borrow_cap == 0 {
                 self.push_log("LimBorrow capacity must be > 0");
                 return;
@@ -1423,8 +1639,10 @@ impl App {
             }
         };
 
+        let expires_at = days_to_expires_at(&self.find_field_value("Expires In Days"));
+
         let mut data = sighash("authorize_key");
-        data.push(permissions_u8);
+        data.extend_from_slice(&permissions_bits.to_le_bytes());
         data.extend_from_slice(&sell_cap.to_le_bytes());
         data.extend_from_slice(&sell_refill.to_le_bytes());
         data.extend_from_slice(&borrow_cap.to_le_bytes());
@@ -1432,6 +1650,7 @@ impl App {
         data.extend_from_slice(&total_sell_limit.to_le_bytes());
         data.extend_from_slice(&total_borrow_limit.to_le_bytes());
         data.extend_from_slice(&name_bytes);
+        data.extend_from_slice(&expires_at.to_le_bytes());
 
         let accounts = vec![
             AccountMeta::new(self.keypair.pubkey(), true),
@@ -1449,7 +1668,7 @@ impl App {
         let mut desc = vec![
             "Authorize Key".into(),
             format!("Target: {}", target_wallet),
-            format!("Permissions: {} (0x{:02X})", permissions_name(permissions_u8), permissions_u8),
+            format!("Permissions: {} (0x{:02X})", permissions_name(permissions_bits), permissions_bits),
         ];
         let name_trimmed = name_str.trim();
         if name_trimmed.is_empty() {
@@ -1574,7 +1793,7 @@ impl App {
         data.extend_from_slice(&amount.to_le_bytes());
         data.extend_from_slice(&min_out.to_le_bytes());
 
-        let accounts = vec![
+        let mut accounts = vec![
             AccountMeta::new(self.keypair.pubkey(), true),          // signer
             AccountMeta::new_readonly(key_asset, false),            // key_asset
             AccountMeta::new(position_pda, false),                  // position
@@ -1599,6 +1818,24 @@ impl App {
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),         // token_program
             AccountMeta::new(self.log_pda, false),                  // log_account
         ];
+        let mut share_ata_ixs = Vec::new();
+        match self.share_mint() {
+            Some(share_mint) => {
+                // Share-token positions mint to the depositor's share ATA
+                let share_ata = get_ata(&self.keypair.pubkey(), &share_mint);
+                accounts.push(AccountMeta::new(share_mint, false)); // share_mint
+                accounts.push(AccountMeta::new(share_ata, false)); // depositor_share_ata
+                share_ata_ixs.push(create_ata_idempotent_ix(
+                    &self.keypair.pubkey(),
+                    &self.keypair.pubkey(),
+                    &share_mint,
+                ));
+            }
+            None => {
+                accounts.push(AccountMeta::new_readonly(hardig::ID, false)); // share_mint (None)
+                accounts.push(AccountMeta::new_readonly(hardig::ID, false)); // depositor_share_ata (None)
+            }
+        }
 
         // Ensure PDA's wSOL ATA exists (may have been closed by a previous sell)
         let create_wsol_ata_ix = create_ata_idempotent_ix(
@@ -1636,7 +1873,10 @@ impl App {
                     permissions_name(self.my_permissions.unwrap_or(0))
                 ),
             ],
-            instructions: vec![create_wsol_ata_ix, create_nav_ata_ix, transfer_ix, sync_ix, buy_ix],
+            instructions: share_ata_ixs
+                .into_iter()
+                .chain([create_wsol_ata_ix, create_nav_ata_ix, transfer_ix, sync_ix, buy_ix])
+                .collect(),
             extra_signers: vec![],
         });
     }
@@ -1686,9 +1926,86 @@ impl App {
         let (config_pda, _) =
             Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
 
+        // Past the rolling allowance the sell has to wait out the position's delay
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let must_queue = self.position.clone().is_some_and(|mut p| p.must_queue_withdraw(amount, now));
+        if let Some(pos) = self.position.as_ref().filter(|_| must_queue) {
+            let request_id = pos.withdraw_request_count;
+            let delay_secs = pos.withdraw_delay_secs;
+            let (request_pda, _) = Pubkey::find_program_address(
+                &[WithdrawRequest::SEED, position_pda.as_ref(), &request_id.to_le_bytes()],
+                &hardig::ID,
+            );
+            let mut data = sighash("request_withdraw");
+            data.extend_from_slice(&amount.to_le_bytes());
+            data.extend_from_slice(&min_out.to_le_bytes());
+            let accounts = vec![
+                AccountMeta::new(self.keypair.pubkey(), true),          // payer
+                AccountMeta::new_readonly(key_asset, false),            // key_asset
+                AccountMeta::new(position_pda, false),                  // position
+                AccountMeta::new_readonly(config_pda, false),           // config
+                AccountMeta::new(request_pda, false),                   // withdraw_request
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),    // system_program
+                // key_state (Some) lets a principal-mode key queue; None sentinel otherwise
+                AccountMeta::new_readonly(self.my_key_state_pda.unwrap_or(hardig::ID), false),
+            ];
+            let nav = nav_token_name(&mc.nav_mint);
+            self.goto_confirm(PendingAction {
+                description: vec![
+                    format!("Queue Sell {}", nav),
+                    format!("Amount: {} {}", lamports_to_sol(amount), nav),
+                    format!("Request ID: {}", request_id),
+                    format!("Executable in: {}h", delay_secs as f64 / 3600.0),
+                    format!("Position: {}", short_pubkey(&position_pda)),
+                ],
+                instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+                extra_signers: vec![],
+            });
+            return;
+        }
+
         let mut data = sighash("withdraw");
         data.extend_from_slice(&amount.to_le_bytes());
         data.extend_from_slice(&min_out.to_le_bytes());
+        let accounts = self.withdraw_accounts(position_pda, key_asset, mc_pda, mc);
+
+        // Sell CPI uses ~170K CUs inside Mayflower + close_account — needs extra compute
+        let compute_ix = solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(400_000);
+
+        // Ensure PDA's wSOL ATA exists (may have been closed by a previous sell)
+        let create_ata_ix = create_ata_idempotent_ix(
+            &self.keypair.pubkey(),
+            &self.program_pda,
+            &mc.base_mint,
+        );
+
+        let nav = nav_token_name(&mc.nav_mint);
+        self.goto_confirm(PendingAction {
+            description: vec![
+                format!("Sell {}", nav),
+                format!("Amount: {} {}", lamports_to_sol(amount), nav),
+                format!("Position: {}", short_pubkey(&position_pda)),
+                format!("Payout: {}", self.payout_label()),
+            ],
+            instructions: vec![compute_ix, create_ata_ix, Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    /// Accounts for `withdraw`, in order. `execute_withdraw` takes the same list
+    /// followed by its request accounts.
+    fn withdraw_accounts(
+        &self,
+        position_pda: Pubkey,
+        key_asset: Pubkey,
+        mc_pda: Pubkey,
+        mc: &MarketConfig,
+    ) -> Vec<AccountMeta> {
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
 
         // For sell/withdraw, key_state is optional (only needed for rate-limited keys).
         // Pass it if we have a KeyState PDA for this key.
@@ -1727,9 +2044,108 @@ impl App {
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),         // token_program
             AccountMeta::new(self.log_pda, false),                  // log_account
         ]);
+        accounts.push(self.payout_meta());                          // payout (Option)
+        accounts.push(self.promo_meta());                           // promo (Option)
+        accounts
+    }
 
-        // Sell CPI uses ~170K CUs inside Mayflower + close_account — needs extra compute
-        let compute_ix = solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(400_000);
+    pub fn build_unwind(&mut self) {
+        let amount = match parse_sol_to_lamports(&self.form_fields[0].1) {
+            Some(v) => v,
+            None => {
+                self.push_log("Invalid sell amount");
+                return;
+            }
+        };
+        let target_debt = match parse_sol_to_lamports(&self.form_fields[1].1) {
+            Some(v) => v,
+            None => {
+                self.push_log("Invalid target debt");
+                return;
+            }
+        };
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => { self.push_log("No key asset loaded"); return; }
+        };
+        let mc_pda = match self.market_config_pda {
+            Some(p) => p,
+            None => { self.push_log("No market config loaded"); return; }
+        };
+        let mc = match self.market_config.as_ref() {
+            Some(c) => c,
+            None => { self.push_log("No market config loaded"); return; }
+        };
+
+        // Slippage protection on the sell leg, same estimate as build_sell
+        let min_out = if self.mf_floor_price > 0 {
+            let expected = (amount as u128)
+                .saturating_mul(self.mf_floor_price as u128)
+                / 1_000_000_000u128;
+            (expected * 99 / 100) as u64
+        } else {
+            0u64
+        };
+
+        // The program only sells what the repay needs at the floor price, so any buy-back
+        // is just the sale's premium over the floor. Without a market price to size it,
+        // the TUI doesn't set a minimum for it.
+        let repay_shares = hardig::mayflower::shares_covering(
+            self.mf_debt.saturating_sub(target_debt),
+            self.mf_floor_price,
+        )
+        .unwrap_or(0);
+        if amount > repay_shares {
+            self.push_log("Sell amount exceeds what repaying to the target debt needs");
+            return;
+        }
+        let min_shares_rebought = 0u64;
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("unwind");
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&min_out.to_le_bytes());
+        data.extend_from_slice(&target_debt.to_le_bytes());
+        data.extend_from_slice(&min_shares_rebought.to_le_bytes());
+
+        let accounts = vec![
+            AccountMeta::new(self.keypair.pubkey(), true),          // signer
+            AccountMeta::new_readonly(key_asset, false),            // key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+            AccountMeta::new_readonly(mc_pda, false),               // market_config
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),    // system_program
+            AccountMeta::new(self.program_pda, false),              // program_pda
+            AccountMeta::new(self.pp_pda, false),                   // personal_position
+            AccountMeta::new(self.escrow_pda, false),               // user_shares
+            AccountMeta::new(self.nav_sol_ata, false),              // user_nav_sol_ata
+            AccountMeta::new(self.wsol_ata, false),                 // user_wsol_ata
+            AccountMeta::new_readonly(MAYFLOWER_TENANT, false),     // tenant
+            AccountMeta::new_readonly(mc.market_group, false),      // market_group
+            AccountMeta::new_readonly(mc.market_meta, false),       // market_meta
+            AccountMeta::new(mc.mayflower_market, false),           // mayflower_market
+            AccountMeta::new(mc.nav_mint, false),                   // nav_sol_mint
+            AccountMeta::new(mc.market_base_vault, false),          // market_base_vault
+            AccountMeta::new(mc.market_nav_vault, false),           // market_nav_vault
+            AccountMeta::new(mc.fee_vault, false),                  // fee_vault
+            AccountMeta::new_readonly(mc.base_mint, false),         // wsol_mint
+            AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false), // mayflower_program
+            AccountMeta::new_readonly(SPL_TOKEN_ID, false),         // token_program
+            AccountMeta::new(self.log_pda, false),                  // log_account
+        ];
+
+        // Sell + repay CPIs back to back — needs extra compute
+        let compute_ix = solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(600_000);
 
         // Ensure PDA's wSOL ATA exists (may have been closed by a previous sell)
         let create_ata_ix = create_ata_idempotent_ix(
@@ -1741,8 +2157,9 @@ impl App {
         let nav = nav_token_name(&mc.nav_mint);
         self.goto_confirm(PendingAction {
             description: vec![
-                format!("Sell {}", nav),
-                format!("Amount: {} {}", lamports_to_sol(amount), nav),
+                "Unwind".into(),
+                format!("Sell: {} {}", lamports_to_sol(amount), nav),
+                format!("Repay from proceeds down to {} SOL debt", lamports_to_sol(target_debt)),
                 format!("Position: {}", short_pubkey(&position_pda)),
             ],
             instructions: vec![compute_ix, create_ata_ix, Instruction::new_with_bytes(hardig::ID, &data, accounts)],
@@ -1818,6 +2235,8 @@ impl App {
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),         // token_program
             AccountMeta::new(self.log_pda, false),                  // log_account
         ]);
+        accounts.push(self.payout_meta());                          // payout (Option)
+        accounts.push(self.promo_meta());                           // promo (Option)
 
         // Ensure PDA's wSOL ATA exists (may have been closed by a previous sell/borrow)
         let create_ata_ix = create_ata_idempotent_ix(
@@ -1831,6 +2250,7 @@ impl App {
                 "Borrow".into(),
                 format!("Amount: {} SOL", lamports_to_sol(amount)),
                 format!("Position: {}", short_pubkey(&position_pda)),
+                format!("Payout: {}", self.payout_label()),
             ],
             instructions: vec![create_ata_ix, Instruction::new_with_bytes(hardig::ID, &data, accounts)],
             extra_signers: vec![],
@@ -1889,6 +2309,11 @@ impl App {
             AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false), // mayflower_program
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),         // token_program
             AccountMeta::new(self.log_pda, false),                  // log_account
+            // key_state (Option): credits the repayment against our key's debt
+            match self.my_key_state_pda {
+                Some(ks_pda) => AccountMeta::new(ks_pda, false),
+                None => AccountMeta::new_readonly(hardig::ID, false),
+            },
         ];
 
         // Ensure PDA's wSOL ATA exists (may have been closed by a previous sell/borrow)
@@ -1920,18 +2345,79 @@ impl App {
         });
     }
 
-    pub fn build_reinvest(&mut self, max_spread_bps: u16) {
-        let position_pda = match self.position_pda {
+    pub fn build_reinvest(&mut self, max_spread_bps: u16, mode: ReinvestMode, min_amount: u64) {
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => { self.push_log("No key asset loaded"); return; }
+        };
+        self.push_reinvest(Some(key_asset), max_spread_bps, mode, min_amount);
+    }
+
+    /// Keyless reinvest of someone else's position that opted in to keeper bounties.
+    /// Uses the position's own mode and spread limit; the bounty goes to the signer's wSOL ATA.
+    pub fn build_keeper_reinvest(&mut self, position_pda: Pubkey) {
+        let pos = match self
+            .rpc
+            .get_account(&position_pda)
+            .ok()
+            .and_then(|acc| PositionState::try_deserialize(&mut acc.data.as_slice()).ok())
+        {
             Some(p) => p,
             None => {
-                self.push_log("No position loaded");
+                self.push_log(format!("No position at {}", position_pda));
+                return;
+            }
+        };
+        if pos.keeper_bounty_bps == 0 {
+            self.push_log("Position has not enabled keeper bounties");
+            return;
+        }
+        let mc = match self
+            .rpc
+            .get_account(&pos.market_config)
+            .ok()
+            .and_then(|acc| MarketConfig::try_deserialize(&mut acc.data.as_slice()).ok())
+        {
+            Some(mc) => mc,
+            None => {
+                self.push_log("No market config loaded");
                 return;
             }
         };
 
-        let key_asset = match self.my_asset {
-            Some(a) => a,
-            None => { self.push_log("No key asset loaded"); return; }
+        let (program_pda, _) = Pubkey::find_program_address(
+            &[b"authority", pos.authority_seed.as_ref()],
+            &hardig::ID,
+        );
+        let (pp_pda, _) = derive_personal_position(&program_pda, &mc.market_meta);
+        self.program_pda = program_pda;
+        self.pp_pda = pp_pda;
+        self.escrow_pda = derive_personal_position_escrow(&pp_pda).0;
+        self.wsol_ata = get_ata(&program_pda, &mc.base_mint);
+        self.nav_sol_ata = get_ata(&program_pda, &mc.nav_mint);
+        self.position_pda = Some(position_pda);
+        self.market_config_pda = Some(pos.market_config);
+        self.market_config = Some(mc);
+        let mode = pos.keeper_mode;
+        self.position = Some(pos);
+
+        self.push_reinvest(None, 0, mode, 0);
+    }
+
+    /// `key_asset: None` builds a keyless keeper call.
+    fn push_reinvest(
+        &mut self,
+        key_asset: Option<Pubkey>,
+        max_spread_bps: u16,
+        mode: ReinvestMode,
+        min_amount: u64,
+    ) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
         };
         let mc_pda = match self.market_config_pda {
             Some(p) => p,
@@ -1953,10 +2439,18 @@ impl App {
         let mut data = sighash("reinvest");
         data.extend_from_slice(&min_out.to_le_bytes());
         data.extend_from_slice(&max_spread_bps.to_le_bytes());
+        // mode: ReinvestMode (Borsh enum: variant index + bps)
+        let (variant, mode_desc) = match mode {
+            ReinvestMode::Fraction { bps } => (0u8, format!("Borrows {}% of available capacity", bps as f64 / 100.0)),
+            ReinvestMode::TargetLtv { bps } => (1u8, format!("Borrows up to {}% debt/floor value", bps as f64 / 100.0)),
+        };
+        data.push(variant);
+        data.extend_from_slice(&mode.bps().to_le_bytes());
+        data.extend_from_slice(&min_amount.to_le_bytes());
 
-        let accounts = vec![
+        let mut accounts = vec![
             AccountMeta::new(self.keypair.pubkey(), true),          // signer
-            AccountMeta::new_readonly(key_asset, false),            // key_asset
+            AccountMeta::new_readonly(key_asset.unwrap_or(hardig::ID), false), // key_asset
             AccountMeta::new(position_pda, false),                  // position
             AccountMeta::new_readonly(mc_pda, false),               // market_config
             AccountMeta::new_readonly(config_pda, false),           // config
@@ -1991,23 +2485,113 @@ impl App {
             &self.program_pda,
             &mc.base_mint,
         );
+        let mut instructions = vec![compute_ix, create_ata_ix];
+
+        // Protocol fee: the treasury's base-mint ATA receives it (the program creates it if needed)
+        let mut fee_desc = Vec::new();
+        if self.protocol_fee_bps > 0 {
+            accounts.push(AccountMeta::new(get_ata(&self.treasury, &mc.base_mint), false));
+            fee_desc.push(format!(
+                "Protocol fee: {}% of borrowed SOL",
+                self.protocol_fee_bps as f64 / 100.0
+            ));
+        } else {
+            accounts.push(AccountMeta::new_readonly(hardig::ID, false)); // treasury_base_ata (None)
+        }
+
+        // Keeper bounty is paid in the base token to the signer's own ATA
+        let caller_desc = if key_asset.is_some() {
+            accounts.push(AccountMeta::new_readonly(hardig::ID, false)); // keeper_base_ata (None)
+            format!(
+                "Permissions: {}",
+                permissions_name(self.my_permissions.unwrap_or(0))
+            )
+        } else {
+            let keeper = self.keypair.pubkey();
+            accounts.push(AccountMeta::new(get_ata(&keeper, &mc.base_mint), false));
+            instructions.push(create_ata_idempotent_ix(&keeper, &keeper, &mc.base_mint));
+            let pos = self.position.as_ref();
+            format!(
+                "Keeper bounty: {}% of borrowed SOL, max {} SOL",
+                pos.map(|p| p.keeper_bounty_bps).unwrap_or(0) as f64 / 100.0,
+                lamports_to_sol(pos.map(|p| p.keeper_bounty_max).unwrap_or(0)),
+            )
+        };
+        if self.protocol_fee_bps > 0 {
+            accounts.push(AccountMeta::new_readonly(self.treasury, false));
+            accounts.push(AccountMeta::new_readonly(ATA_PROGRAM_ID, false));
+        } else {
+            accounts.push(AccountMeta::new_readonly(hardig::ID, false)); // treasury (None)
+            accounts.push(AccountMeta::new_readonly(hardig::ID, false)); // associated_token_program (None)
+        }
+        instructions.push(Instruction::new_with_bytes(hardig::ID, &data, accounts));
+
+        let mut description = vec![
+            "Reinvest (CPI)".into(),
+            format!("Position: {}", short_pubkey(&position_pda)),
+            caller_desc,
+            format!("{} and buys more {}", mode_desc, nav_token_name(&mc.nav_mint)),
+            format!("Skipped below: {} SOL", lamports_to_sol(min_amount)),
+        ];
+        description.extend(fee_desc);
 
         self.goto_confirm(PendingAction {
-            description: vec![
-                "Reinvest (CPI)".into(),
-                format!("Position: {}", short_pubkey(&position_pda)),
-                format!(
-                    "Permissions: {}",
-                    permissions_name(self.my_permissions.unwrap_or(0))
-                ),
-                format!("Borrows available capacity and buys more {}", nav_token_name(&mc.nav_mint)),
-            ],
-            instructions: vec![compute_ix, create_ata_ix, Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            description,
+            instructions,
             extra_signers: vec![],
         });
     }
 
-    pub fn build_heartbeat(&mut self) {
+    /// Trailing `payout` account for withdraw/borrow: the pinned address, or the None sentinel.
+    fn payout_meta(&self) -> AccountMeta {
+        match self.position.as_ref().map(|p| p.payout_address) {
+            Some(addr) if addr != Pubkey::default() => AccountMeta::new(addr, false),
+            _ => AccountMeta::new_readonly(hardig::ID, false),
+        }
+    }
+
+    /// Trailing `promo` account for withdraw/borrow: the PromoConfig our key was
+    /// claimed from (its campaign budget is drawn down), or the None sentinel.
+    fn promo_meta(&self) -> AccountMeta {
+        let promo = self
+            .keyring
+            .iter()
+            .find(|k| Some(k.pda) == self.my_key_state_pda)
+            .map(|k| k.promo)
+            .unwrap_or_default();
+        if promo == Pubkey::default() {
+            AccountMeta::new_readonly(hardig::ID, false)
+        } else {
+            AccountMeta::new(promo, false)
+        }
+    }
+
+    fn payout_label(&self) -> String {
+        match self.position.as_ref().map(|p| p.payout_address) {
+            Some(addr) if addr != Pubkey::default() => short_pubkey(&addr),
+            _ => "signer".into(),
+        }
+    }
+
+    fn enter_set_payout(&mut self) {
+        let current = self
+            .position
+            .as_ref()
+            .map(|p| p.payout_address)
+            .filter(|a| *a != Pubkey::default())
+            .map(|a| a.to_string())
+            .unwrap_or_default();
+        self.screen = Screen::Form;
+        self.form_readonly = false;
+        self.form_locked = false;
+        self.form_kind = Some(FormKind::SetPayout);
+        self.form_info = Some("Withdraw and borrow pay out to this address.\nLeave blank to pay the signer.".into());
+        self.form_fields = vec![("Payout Address (pubkey)".into(), current)];
+        self.input_field = 0;
+        self.input_buf.clear();
+    }
+
+    pub fn build_set_payout(&mut self) {
         let position_pda = match self.position_pda {
             Some(p) => p,
             None => {
@@ -2022,11 +2606,794 @@ impl App {
                 return;
             }
         };
-
-        let (config_pda, _) =
-            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
-
-        let data = sighash("heartbeat");
+        let input = self.form_fields[0].1.trim().to_string();
+        let payout = if input.is_empty() {
+            None
+        } else {
+            match input.parse::<Pubkey>() {
+                Ok(pk) => Some(pk),
+                Err(_) => {
+                    self.push_log("Invalid payout address");
+                    return;
+                }
+            }
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("set_payout_address");
+        match payout {
+            Some(pk) => {
+                data.push(1);
+                data.extend_from_slice(pk.as_ref());
+            }
+            None => data.push(0),
+        }
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true), // admin
+            AccountMeta::new_readonly(key_asset, false),            // admin_key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Set Payout Address".into(),
+                match payout {
+                    Some(pk) => format!("Payout: {}", pk),
+                    None => "Payout: signer (cleared)".into(),
+                },
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    fn enter_configure_leverage(&mut self) {
+        let current = self
+            .position
+            .as_ref()
+            .map(|p| p.max_debt_to_floor_bps)
+            .filter(|bps| *bps > 0)
+            .map(|bps| (bps as f64 / 100.0).to_string())
+            .unwrap_or_default();
+        self.screen = Screen::Form;
+        self.form_readonly = false;
+        self.form_locked = false;
+        self.form_kind = Some(FormKind::ConfigureLeverage);
+        self.form_info = Some("Borrow and reinvest fail if debt would exceed this share of\nthe floor value. Leave blank or 0 for no ceiling.".into());
+        self.form_fields = vec![("Max Debt/Floor (%)".into(), current)];
+        self.input_field = 0;
+        self.input_buf.clear();
+    }
+
+    pub fn build_configure_leverage(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => {
+                self.push_log("No key asset");
+                return;
+            }
+        };
+        let input = self.form_fields[0].1.trim().to_string();
+        let pct: f64 = if input.is_empty() {
+            0.0
+        } else {
+            match input.parse() {
+                Ok(v) => v,
+                Err(_) => {
+                    self.push_log("Invalid Max Debt/Floor %");
+                    return;
+                }
+            }
+        };
+        if !(0.0..=100.0).contains(&pct) {
+            self.push_log("Max Debt/Floor % must be between 0 and 100");
+            return;
+        }
+        let max_debt_to_floor_bps: u16 = (pct * 100.0) as u16;
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("configure_leverage");
+        data.extend_from_slice(&max_debt_to_floor_bps.to_le_bytes());
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true), // admin
+            AccountMeta::new_readonly(key_asset, false),            // admin_key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Configure Leverage Ceiling".into(),
+                if max_debt_to_floor_bps == 0 {
+                    "Max debt/floor: none (cleared)".into()
+                } else {
+                    format!("Max debt/floor: {}%", max_debt_to_floor_bps as f64 / 100.0)
+                },
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    pub fn build_enable_share_mint(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => {
+                self.push_log("No key asset");
+                return;
+            }
+        };
+        let (mc_pda, mc) = match (self.market_config_pda, self.market_config.as_ref()) {
+            (Some(p), Some(c)) => (p, c),
+            _ => {
+                self.push_log("No market config loaded");
+                return;
+            }
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+        let (share_mint, _) = Pubkey::find_program_address(
+            &[PositionState::SHARE_MINT_SEED, position_pda.as_ref()],
+            &hardig::ID,
+        );
+
+        let data = sighash("enable_share_mint");
+        let accounts = vec![
+            AccountMeta::new(self.keypair.pubkey(), true),          // admin
+            AccountMeta::new_readonly(key_asset, false),            // admin_key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+            AccountMeta::new_readonly(mc_pda, false),               // market_config
+            AccountMeta::new_readonly(self.program_pda, false),     // program_pda
+            AccountMeta::new_readonly(self.pp_pda, false),          // personal_position
+            AccountMeta::new_readonly(mc.nav_mint, false),          // nav_sol_mint
+            AccountMeta::new(share_mint, false),                    // share_mint
+            AccountMeta::new_readonly(SPL_TOKEN_ID, false),         // token_program
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),    // system_program
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Enable Share Token".into(),
+                "Creates an SPL mint tracking pro-rata ownership of the position.".into(),
+                "The position must be empty; afterwards only redeem_shares pays out.".into(),
+                format!("Share mint: {}", short_pubkey(&share_mint)),
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    fn enter_redeem_shares(&mut self) {
+        let balance = self.share_token_balance.unwrap_or(0);
+        self.screen = Screen::Form;
+        self.form_readonly = false;
+        self.form_locked = false;
+        self.form_kind = Some(FormKind::RedeemShares);
+        self.form_info = Some("Burns share tokens for their pro-rata navSOL net of debt,\nsold to SOL and paid to your wallet.".into());
+        self.form_fields = vec![("Share Tokens".into(), lamports_to_sol(balance))];
+        self.input_field = 0;
+        self.input_buf = self.form_fields[0].1.clone();
+    }
+
+    pub fn build_redeem_shares(&mut self) {
+        let amount = match parse_sol_to_lamports(&self.form_fields[0].1) {
+            Some(v) if v > 0 => v,
+            _ => {
+                self.push_log("Invalid share token amount");
+                return;
+            }
+        };
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let share_mint = match self.share_mint() {
+            Some(m) => m,
+            None => {
+                self.push_log("Position has no share token");
+                return;
+            }
+        };
+        let (mc_pda, mc) = match (self.market_config_pda, self.market_config.as_ref()) {
+            (Some(p), Some(c)) => (p, c),
+            _ => {
+                self.push_log("No market config loaded");
+                return;
+            }
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("redeem_shares");
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes()); // min_out: proceeds depend on the live net value
+
+        let accounts = vec![
+            AccountMeta::new(self.keypair.pubkey(), true),          // holder
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+            AccountMeta::new_readonly(mc_pda, false),               // market_config
+            AccountMeta::new(share_mint, false),                    // share_mint
+            AccountMeta::new(get_ata(&self.keypair.pubkey(), &share_mint), false), // holder_share_ata
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),    // system_program
+            AccountMeta::new(self.program_pda, false),              // program_pda
+            AccountMeta::new(self.pp_pda, false),                   // personal_position
+            AccountMeta::new(self.escrow_pda, false),               // user_shares
+            AccountMeta::new(self.nav_sol_ata, false),              // user_nav_sol_ata
+            AccountMeta::new(self.wsol_ata, false),                 // user_wsol_ata
+            AccountMeta::new_readonly(MAYFLOWER_TENANT, false),     // tenant
+            AccountMeta::new_readonly(mc.market_group, false),      // market_group
+            AccountMeta::new_readonly(mc.market_meta, false),       // market_meta
+            AccountMeta::new(mc.mayflower_market, false),           // mayflower_market
+            AccountMeta::new(mc.nav_mint, false),                   // nav_sol_mint
+            AccountMeta::new(mc.market_base_vault, false),          // market_base_vault
+            AccountMeta::new(mc.market_nav_vault, false),           // market_nav_vault
+            AccountMeta::new(mc.fee_vault, false),                  // fee_vault
+            AccountMeta::new_readonly(mc.base_mint, false),         // wsol_mint
+            AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false), // mayflower_program
+            AccountMeta::new_readonly(SPL_TOKEN_ID, false),         // token_program
+            AccountMeta::new(self.log_pda, false),                  // log_account
+        ];
+
+        // Ensure PDA's wSOL ATA exists (may have been closed by a previous sell)
+        let create_wsol_ata_ix = create_ata_idempotent_ix(
+            &self.keypair.pubkey(),
+            &self.program_pda,
+            &mc.base_mint,
+        );
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Redeem Share Tokens".into(),
+                format!("Amount: {} share tokens", lamports_to_sol(amount)),
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![
+                create_wsol_ata_ix,
+                Instruction::new_with_bytes(hardig::ID, &data, accounts),
+            ],
+            extra_signers: vec![],
+        });
+    }
+
+    fn enter_configure_cosign(&mut self) {
+        let (sell, borrow, required, approvers) = match self.position.as_ref() {
+            Some(p) if p.cosign_required > 0 => (
+                lamports_to_sol(p.cosign_sell_threshold),
+                lamports_to_sol(p.cosign_borrow_threshold),
+                p.cosign_required.to_string(),
+                p.cosign_approvers
+                    .iter()
+                    .filter(|a| **a != Pubkey::default())
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            _ => (String::new(), String::new(), String::new(), String::new()),
+        };
+        self.screen = Screen::Form;
+        self.form_readonly = false;
+        self.form_locked = false;
+        self.form_kind = Some(FormKind::ConfigureCosign);
+        self.form_info = Some("Sells/borrows above a threshold need this many approver keys\nto sign. Required 0 disables. Changing an active policy needs\nits approvals too.".into());
+        self.form_fields = vec![
+            ("Sell Threshold (navSOL)".into(), sell),
+            ("Borrow Threshold (SOL)".into(), borrow),
+            ("Required Approvals".into(), required),
+            ("Approver Key Assets (comma-separated)".into(), approvers),
+        ];
+        self.input_field = 0;
+        self.input_buf.clear();
+    }
+
+    pub fn build_configure_cosign(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => {
+                self.push_log("No key asset");
+                return;
+            }
+        };
+        let threshold = |v: Option<String>| -> Option<u64> {
+            let v = v.unwrap_or_default();
+            if v.trim().is_empty() { Some(0) } else { parse_sol_to_lamports(v.trim()) }
+        };
+        let (sell_threshold, borrow_threshold) = match (
+            threshold(self.find_field_value("Sell Threshold")),
+            threshold(self.find_field_value("Borrow Threshold")),
+        ) {
+            (Some(s), Some(b)) => (s, b),
+            _ => {
+                self.push_log("Invalid threshold amount");
+                return;
+            }
+        };
+        let required: u8 = match self
+            .find_field_value("Required Approvals")
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+        {
+            None => 0,
+            Some(v) => match v.parse() {
+                Ok(n) => n,
+                Err(_) => {
+                    self.push_log("Invalid Required Approvals");
+                    return;
+                }
+            },
+        };
+        let mut approvers = Vec::new();
+        for s in self
+            .find_field_value("Approver Key Assets")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            match s.parse::<Pubkey>() {
+                Ok(pk) => approvers.push(pk),
+                Err(_) => {
+                    self.push_log(format!("Invalid approver key asset: {}", s));
+                    return;
+                }
+            }
+        }
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("configure_cosign");
+        data.extend_from_slice(&sell_threshold.to_le_bytes());
+        data.extend_from_slice(&borrow_threshold.to_le_bytes());
+        data.push(required);
+        data.extend_from_slice(&(approvers.len() as u32).to_le_bytes());
+        for approver in &approvers {
+            data.extend_from_slice(approver.as_ref());
+        }
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true), // admin
+            AccountMeta::new_readonly(key_asset, false),            // admin_key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+        ];
+
+        let mut description = vec!["Configure Co-signing".into()];
+        if required == 0 {
+            description.push("Co-signing: disabled".into());
+        } else {
+            description.push(format!("Requires: {} of {} approver keys", required, approvers.len()));
+            description.push(format!("Sell threshold: {} navSOL", lamports_to_sol(sell_threshold)));
+            description.push(format!("Borrow threshold: {} SOL", lamports_to_sol(borrow_threshold)));
+            for approver in &approvers {
+                description.push(format!("  Approver: {}", short_pubkey(approver)));
+            }
+        }
+        description.push(format!("Position: {}", short_pubkey(&position_pda)));
+
+        self.goto_confirm(PendingAction {
+            description,
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    fn enter_configure_withdraw_queue(&mut self) {
+        let (delay, threshold) = match self.position.as_ref() {
+            Some(p) if p.withdraw_delay_secs > 0 => (
+                (p.withdraw_delay_secs as f64 / 3600.0).to_string(),
+                lamports_to_sol(p.withdraw_queue_threshold),
+            ),
+            _ => (String::new(), String::new()),
+        };
+        self.screen = Screen::Form;
+        self.form_readonly = false;
+        self.form_locked = false;
+        self.form_kind = Some(FormKind::ConfigureWithdrawQueue);
+        self.form_info = Some("Sells above the threshold must be requested and wait out the\ndelay. Guardian keys can cancel them meanwhile. Delay 0 disables.".into());
+        self.form_fields = vec![
+            ("Delay (hours)".into(), delay),
+            ("Threshold (navSOL)".into(), threshold),
+        ];
+        self.input_field = 0;
+        self.input_buf.clear();
+    }
+
+    pub fn build_configure_withdraw_queue(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => {
+                self.push_log("No key asset");
+                return;
+            }
+        };
+        let delay_input = self.find_field_value("Delay").unwrap_or_default();
+        let hours: f64 = if delay_input.trim().is_empty() {
+            0.0
+        } else {
+            match delay_input.trim().parse() {
+                Ok(v) if v >= 0.0 => v,
+                _ => {
+                    self.push_log("Invalid delay");
+                    return;
+                }
+            }
+        };
+        let delay_secs = (hours * 3600.0) as i64;
+        let threshold_input = self.find_field_value("Threshold").unwrap_or_default();
+        let threshold = if threshold_input.trim().is_empty() {
+            0
+        } else {
+            match parse_sol_to_lamports(threshold_input.trim()) {
+                Some(v) => v,
+                None => {
+                    self.push_log("Invalid threshold amount");
+                    return;
+                }
+            }
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("configure_withdraw_queue");
+        data.extend_from_slice(&delay_secs.to_le_bytes());
+        data.extend_from_slice(&threshold.to_le_bytes());
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true), // admin
+            AccountMeta::new_readonly(key_asset, false),            // admin_key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Configure Withdrawal Queue".into(),
+                if delay_secs == 0 {
+                    "Queue: disabled".into()
+                } else {
+                    format!(
+                        "Delay: {}h for sells above {} navSOL",
+                        hours,
+                        lamports_to_sol(threshold)
+                    )
+                },
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    fn enter_configure_keeper(&mut self) {
+        let fields = match self.position.as_ref() {
+            Some(p) if p.keeper_bounty_bps > 0 => {
+                let (fraction, ltv) = match p.keeper_mode {
+                    ReinvestMode::Fraction { bps } => (bps.to_string(), String::new()),
+                    ReinvestMode::TargetLtv { bps } => (String::new(), bps.to_string()),
+                };
+                [
+                    p.keeper_bounty_bps.to_string(),
+                    lamports_to_sol(p.keeper_bounty_max),
+                    lamports_to_sol(p.keeper_min_borrow),
+                    p.keeper_max_spread_bps.to_string(),
+                    fraction,
+                    ltv,
+                ]
+            }
+            _ => Default::default(),
+        };
+        let [bounty, max_bounty, min_borrow, spread, fraction, ltv] = fields;
+        self.screen = Screen::Form;
+        self.form_readonly = false;
+        self.form_locked = false;
+        self.form_kind = Some(FormKind::ConfigureKeeper);
+        self.form_info = Some("Anyone may reinvest with this mode and spread limit, earning\nthe bounty from the borrowed SOL. Bounty 0 disables.".into());
+        self.form_fields = vec![
+            ("Bounty (bps)".into(), bounty),
+            ("Max Bounty (SOL)".into(), max_bounty),
+            ("Min Borrow (SOL)".into(), min_borrow),
+            ("Max Spread (bps)".into(), spread),
+            ("Borrow Fraction (bps)".into(), fraction),
+            ("Target LTV (bps)".into(), ltv),
+        ];
+        self.input_field = 0;
+        self.input_buf.clear();
+    }
+
+    pub fn build_configure_keeper(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => {
+                self.push_log("No key asset");
+                return;
+            }
+        };
+        let field = |app: &Self, prefix: &str| app.find_field_value(prefix).unwrap_or_default().trim().to_string();
+        let parse_bps = |s: String| -> Option<u16> {
+            if s.is_empty() { Some(0) } else { s.parse().ok() }
+        };
+        let parse_sol = |s: String| -> Option<u64> {
+            if s.is_empty() { Some(0) } else { parse_sol_to_lamports(&s) }
+        };
+        let (bounty_bps, max_bounty, min_borrow, max_spread_bps, fraction, ltv) = match (
+            parse_bps(field(self, "Bounty")),
+            parse_sol(field(self, "Max Bounty")),
+            parse_sol(field(self, "Min Borrow")),
+            parse_bps(field(self, "Max Spread")),
+            parse_bps(field(self, "Borrow Fraction")),
+            parse_bps(field(self, "Target LTV")),
+        ) {
+            (Some(a), Some(b), Some(c), Some(d), Some(e), Some(f)) => (a, b, c, d, e, f),
+            _ => {
+                self.push_log("Invalid keeper settings");
+                return;
+            }
+        };
+        let mode = if ltv > 0 {
+            ReinvestMode::TargetLtv { bps: ltv }
+        } else {
+            ReinvestMode::Fraction { bps: if fraction > 0 { fraction } else { 10_000 } }
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("configure_keeper");
+        data.extend_from_slice(&bounty_bps.to_le_bytes());
+        data.extend_from_slice(&max_bounty.to_le_bytes());
+        data.extend_from_slice(&min_borrow.to_le_bytes());
+        data.extend_from_slice(&max_spread_bps.to_le_bytes());
+        // mode: ReinvestMode (Borsh enum: variant index + bps)
+        data.push(match mode {
+            ReinvestMode::Fraction { .. } => 0u8,
+            ReinvestMode::TargetLtv { .. } => 1u8,
+        });
+        data.extend_from_slice(&mode.bps().to_le_bytes());
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true), // admin
+            AccountMeta::new_readonly(key_asset, false),            // admin_key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Configure Keeper Bounty".into(),
+                if bounty_bps == 0 {
+                    "Keeper reinvest: disabled".into()
+                } else {
+                    format!(
+                        "Bounty: {}% (max {} SOL) above {} SOL, spread <= {} bps",
+                        bounty_bps as f64 / 100.0,
+                        lamports_to_sol(max_bounty),
+                        lamports_to_sol(min_borrow),
+                        max_spread_bps,
+                    )
+                },
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    fn enter_queued_withdraw(&mut self, kind: FormKind) {
+        let latest = self
+            .position
+            .as_ref()
+            .and_then(|p| p.withdraw_request_count.checked_sub(1))
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+        self.screen = Screen::Form;
+        self.form_readonly = false;
+        self.form_locked = false;
+        self.form_kind = Some(kind);
+        self.form_info = None;
+        self.form_fields = vec![("Request ID".into(), latest)];
+        self.input_field = 0;
+        self.input_buf.clear();
+    }
+
+    /// Look up a queued sell by the ID entered in the form.
+    fn fetch_withdraw_request(&mut self) -> Option<(Pubkey, WithdrawRequest)> {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return None;
+            }
+        };
+        let request_id: u64 = match self.form_fields[0].1.trim().parse() {
+            Ok(v) => v,
+            Err(_) => {
+                self.push_log("Invalid request ID");
+                return None;
+            }
+        };
+        let (request_pda, _) = Pubkey::find_program_address(
+            &[WithdrawRequest::SEED, position_pda.as_ref(), &request_id.to_le_bytes()],
+            &hardig::ID,
+        );
+        let request = self
+            .rpc
+            .get_account(&request_pda)
+            .ok()
+            .and_then(|acc| WithdrawRequest::try_deserialize(&mut acc.data.as_slice()).ok());
+        match request {
+            Some(r) => Some((request_pda, r)),
+            None => {
+                self.push_log(format!("No pending sell request #{}", request_id));
+                None
+            }
+        }
+    }
+
+    pub fn build_execute_withdraw(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => { self.push_log("No key asset loaded"); return; }
+        };
+        let mc_pda = match self.market_config_pda {
+            Some(p) => p,
+            None => { self.push_log("No market config loaded"); return; }
+        };
+        let (request_pda, request) = match self.fetch_withdraw_request() {
+            Some(r) => r,
+            None => return,
+        };
+        let mc = match self.market_config.as_ref() {
+            Some(c) => c,
+            None => { self.push_log("No market config loaded"); return; }
+        };
+
+        let mut accounts = self.withdraw_accounts(position_pda, key_asset, mc_pda, mc);
+        accounts.push(AccountMeta::new(request_pda, false));        // withdraw_request
+        accounts.push(AccountMeta::new(request.requester, false));  // requester
+
+        let compute_ix = solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(400_000);
+        let create_ata_ix = create_ata_idempotent_ix(
+            &self.keypair.pubkey(),
+            &self.program_pda,
+            &mc.base_mint,
+        );
+
+        let nav = nav_token_name(&mc.nav_mint);
+        self.goto_confirm(PendingAction {
+            description: vec![
+                format!("Execute Queued Sell #{}", request.request_id),
+                format!("Amount: {} {}", lamports_to_sol(request.amount), nav),
+                format!("Position: {}", short_pubkey(&position_pda)),
+                format!("Payout: {}", self.payout_label()),
+            ],
+            instructions: vec![
+                compute_ix,
+                create_ata_ix,
+                Instruction::new_with_bytes(hardig::ID, &sighash("execute_withdraw"), accounts),
+            ],
+            extra_signers: vec![],
+        });
+    }
+
+    pub fn build_cancel_withdraw(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => {
+                self.push_log("No key asset");
+                return;
+            }
+        };
+        let (request_pda, request) = match self.fetch_withdraw_request() {
+            Some(r) => r,
+            None => return,
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true), // signer
+            AccountMeta::new_readonly(key_asset, false),            // key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(config_pda, false),           // config
+            AccountMeta::new(request_pda, false),                   // withdraw_request
+            AccountMeta::new(request.requester, false),             // requester
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                format!("Cancel Queued Sell #{}", request.request_id),
+                format!("Amount: {} navSOL", lamports_to_sol(request.amount)),
+                format!("Requested by key: {}", short_pubkey(&request.key_asset)),
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &sighash("cancel_withdraw"), accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    pub fn build_heartbeat(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => {
+                self.push_log("No key asset");
+                return;
+            }
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let data = sighash("heartbeat");
         let accounts = vec![
             AccountMeta::new_readonly(self.keypair.pubkey(), true), // admin
             AccountMeta::new_readonly(key_asset, false),            // admin_key_asset
@@ -2036,9 +3403,140 @@ impl App {
 
         self.goto_confirm(PendingAction {
             description: vec![
-                "Heartbeat".into(),
-                "Updates last_admin_activity timestamp to prove liveness.".into(),
+                "Heartbeat".into(),
+                "Updates last_admin_activity timestamp to prove liveness.".into(),
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    pub fn build_sync_position(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let personal_position = match self.position.as_ref() {
+            Some(p) => p.position_pda,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+
+        let data = sighash("sync_position");
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true), // caller
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new_readonly(personal_position, false),    // personal_position
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Sync Position".into(),
+                "Overwrites local navSOL / debt tracking with Mayflower's values.".into(),
+                format!("Position: {}", short_pubkey(&position_pda)),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    pub fn build_close_position(&mut self) {
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        let key_asset = match self.my_asset {
+            Some(a) => a,
+            None => {
+                self.push_log("No key asset");
+                return;
+            }
+        };
+        let (recovery_asset, has_funds) = match &self.position {
+            Some(p) => (p.recovery_asset, p.deposited_nav > 0 || p.user_debt > 0),
+            None => {
+                self.push_log("No position loaded");
+                return;
+            }
+        };
+        if has_funds {
+            self.push_log("Position still has deposits or debt — withdraw and repay first");
+            return;
+        }
+        let collection = match self.collection {
+            Some(c) => c,
+            None => {
+                self.push_log("No collection set — run create-collection first");
+                return;
+            }
+        };
+        let market_config_pda = match self.market_config_pda {
+            Some(p) => p,
+            None => {
+                self.push_log("No market config loaded");
+                return;
+            }
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        // Close every KeyState and PromoConfig of this position along with it
+        self.discover_promos();
+        let key_states: Vec<Pubkey> = self.keyring.iter().skip(1).map(|k| k.pda).collect();
+        let mut promos: Vec<Pubkey> = self.promos.iter().map(|p| p.pda).collect();
+        promos.extend(self.discover_closed_promos());
+
+        // Option<UncheckedAccount>: program ID is the "None" sentinel
+        let recovery = if recovery_asset != Pubkey::default() {
+            recovery_asset
+        } else {
+            hardig::ID
+        };
+
+        let data = sighash("close_position");
+        let mut accounts = vec![
+            AccountMeta::new(self.keypair.pubkey(), true),          // admin
+            AccountMeta::new(key_asset, false),                     // admin_key_asset
+            AccountMeta::new(position_pda, false),                  // position
+            AccountMeta::new(recovery, false),                      // recovery_asset
+            AccountMeta::new_readonly(config_pda, false),           // config
+            AccountMeta::new(collection, false),                    // collection
+            AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),  // mpl_core_program
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),     // system_program
+            AccountMeta::new_readonly(market_config_pda, false),    // market_config
+            AccountMeta::new(self.program_pda, false),              // program_pda
+            AccountMeta::new_readonly(self.pp_pda, false),          // personal_position
+            AccountMeta::new(self.nav_sol_ata, false),              // user_nav_sol_ata
+            AccountMeta::new(self.wsol_ata, false),                 // user_wsol_ata
+            AccountMeta::new_readonly(SPL_TOKEN_ID, false),         // token_program
+        ];
+        accounts.extend(
+            key_states
+                .iter()
+                .chain(promos.iter())
+                .map(|pda| AccountMeta::new(*pda, false)),
+        );
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Close Position".into(),
+                "Burns the admin and recovery keys and returns all rent to you.".into(),
                 format!("Position: {}", short_pubkey(&position_pda)),
+                format!(
+                    "Also closes {} key state(s) and {} promo(s)",
+                    key_states.len(),
+                    promos.len()
+                ),
             ],
             instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
             extra_signers: vec![],
@@ -2322,6 +3820,37 @@ impl App {
             return;
         }
         let initial_fill_bps: u16 = (initial_fill_pct * 100.0) as u16;
+        let key_expires_at = days_to_expires_at(&self.find_field_value("Key Expires In Days"));
+        let starts_at = days_to_expires_at(&self.find_field_value("Claims Open In Days"));
+        let ends_at = days_to_expires_at(&self.find_field_value("Claims Close In Days"));
+        if ends_at != 0 && ends_at <= starts_at {
+            self.push_log("Claims must close after they open");
+            return;
+        }
+        let budget = PromoBudget {
+            total_borrow_limit: self.find_field_value("Campaign Borrow Budget")
+                .and_then(|v| parse_sol_to_lamports(&v))
+                .unwrap_or(0),
+            total_sell_limit: self.find_field_value("Campaign Sell Budget")
+                .and_then(|v| parse_sol_to_lamports(&v))
+                .unwrap_or(0),
+            ..Default::default()
+        };
+        let referral_reward = ReferralReward {
+            borrow_capacity_bonus: self.find_field_value("Referral Capacity Bonus")
+                .and_then(|v| parse_sol_to_lamports(&v))
+                .unwrap_or(0),
+            total_borrow_limit_bonus: self.find_field_value("Referral Limit Bonus")
+                .and_then(|v| parse_sol_to_lamports(&v))
+                .unwrap_or(0),
+            max_referrals_per_referrer: self.find_field_value("Referrals Per Referrer")
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(0),
+        };
+        if referral_reward.has_bonus() && referral_reward.max_referrals_per_referrer == 0 {
+            self.push_log("A referral bonus needs a nonzero Referrals Per Referrer");
+            return;
+        }
         let image_uri = self.find_field_value("Image URI")
             .unwrap_or_default()
             .trim()
@@ -2331,6 +3860,188 @@ impl App {
             self.push_log(format!("Image URI too long ({} chars, max {})", image_uri.len(), PromoConfig::MAX_IMAGE_URI_LEN));
             return;
         }
+        let merkle_root = match parse_hex32(&self.find_field_value("Allowlist Root").unwrap_or_default()) {
+            Some(r) => r,
+            None => {
+                self.push_log("Allowlist root must be 64 hex characters");
+                return;
+            }
+        };
+        let gate_collection = self.find_field_value("Gate Collection").unwrap_or_default();
+        let gate_collection = match gate_collection.trim() {
+            "" => None,
+            s => match s.parse::<Pubkey>() {
+                Ok(p) => Some(p),
+                Err(_) => {
+                    self.push_log("Invalid gate collection pubkey");
+                    return;
+                }
+            },
+        };
+        let refundable_principal = match self.find_field_value("Refundable Principal").unwrap_or_default().trim() {
+            "" | "n" => false,
+            "y" => true,
+            _ => {
+                self.push_log("Refundable principal must be 'y' or 'n'");
+                return;
+            }
+        };
+        let gate_standard = match self.find_field_value("Gate Standard").unwrap_or_default().trim() {
+            "" | "core" => GateStandard::MplCore,
+            "tm" => GateStandard::TokenMetadata,
+            _ => {
+                self.push_log("Gate standard must be 'core' or 'tm'");
+                return;
+            }
+        };
+
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => { self.push_log("No position loaded"); return; }
+        };
+        let admin_key_asset = match self.my_asset {
+            Some(a) => a,
+            None => { self.push_log("No key asset"); return; }
+        };
+        let authority_seed = match &self.position {
+            Some(p) => p.authority_seed,
+            None => { self.push_log("No position loaded"); return; }
+        };
+
+        // Derive promo PDA
+        let (promo_pda, _) = Pubkey::find_program_address(
+            &[PromoConfig::SEED, authority_seed.as_ref(), name_suffix.as_bytes()],
+            &hardig::ID,
+        );
+
+        // Serialize instruction data (Borsh format)
+        let mut data = sighash("create_promo");
+        // name_suffix: String (4-byte len + utf8)
+        let name_bytes = name_suffix.as_bytes();
+        data.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+        data.extend_from_slice(name_bytes);
+        // permissions: u16
+        data.extend_from_slice(&permissions.to_le_bytes());
+        // limits: KeyLimits
+        data.extend_from_slice(&sell_capacity.to_le_bytes());
+        data.extend_from_slice(&sell_refill.to_le_bytes());
+        data.extend_from_slice(&borrow_capacity.to_le_bytes());
+        data.extend_from_slice(&borrow_refill.to_le_bytes());
+        data.extend_from_slice(&total_sell_limit.to_le_bytes());
+        data.extend_from_slice(&total_borrow_limit.to_le_bytes());
+        // terms: PromoTerms { min_deposit_lamports, max_claims, initial_fill_bps, key_expires_at (0 = never), refundable_principal }
+        data.extend_from_slice(&min_deposit.to_le_bytes());
+        data.extend_from_slice(&max_claims.to_le_bytes());
+        data.extend_from_slice(&initial_fill_bps.to_le_bytes());
+        data.extend_from_slice(&key_expires_at.to_le_bytes());
+        data.push(refundable_principal as u8);
+        // image_uri: String (4-byte len + utf8)
+        let uri_bytes = image_uri.as_bytes();
+        data.extend_from_slice(&(uri_bytes.len() as u32).to_le_bytes());
+        data.extend_from_slice(uri_bytes);
+        // market_name: String (4-byte len + utf8)
+        let market_name = self.market_name_override.clone().unwrap_or_default();
+        let mn_bytes = market_name.as_bytes();
+        data.extend_from_slice(&(mn_bytes.len() as u32).to_le_bytes());
+        data.extend_from_slice(mn_bytes);
+        // gate: PromoGate { collection: Option<Pubkey>, standard, merkle_root (all zeros = open) }
+        match gate_collection {
+            Some(gate) => {
+                data.push(1);
+                data.extend_from_slice(gate.as_ref());
+            }
+            None => data.push(0),
+        }
+        data.push(gate_standard as u8);
+        data.extend_from_slice(&merkle_root);
+        // window: PromoWindow { starts_at, ends_at } (0 = unbounded)
+        data.extend_from_slice(&starts_at.to_le_bytes());
+        data.extend_from_slice(&ends_at.to_le_bytes());
+        // budget: PromoBudget (campaign totals; no shared buckets from the TUI)
+        data.extend_from_slice(&budget.total_borrow_limit.to_le_bytes());
+        data.extend_from_slice(&budget.total_sell_limit.to_le_bytes());
+        data.extend_from_slice(&[0u8; 32]); // borrow/sell capacity + refill_period
+        // referral_reward: ReferralReward
+        data.extend_from_slice(&referral_reward.borrow_capacity_bonus.to_le_bytes());
+        data.extend_from_slice(&referral_reward.total_borrow_limit_bonus.to_le_bytes());
+        data.extend_from_slice(&referral_reward.max_referrals_per_referrer.to_le_bytes());
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let accounts = vec![
+            AccountMeta::new(self.keypair.pubkey(), true),    // admin
+            AccountMeta::new_readonly(admin_key_asset, false), // admin_key_asset
+            AccountMeta::new_readonly(position_pda, false),    // position
+            AccountMeta::new(promo_pda, false),                // promo
+            AccountMeta::new_readonly(config_pda, false),      // config
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system_program
+        ];
+
+        let mut desc = vec![
+            "Create Promo".into(),
+            format!("Name: {}", name_suffix),
+            format!("Permissions: {} (0x{:02X})", permissions_name(permissions), permissions),
+        ];
+        if borrow_capacity > 0 {
+            desc.push(format!("Borrow: {} SOL / {}", lamports_to_sol(borrow_capacity), slots_to_human(borrow_refill)));
+        }
+        if sell_capacity > 0 {
+            desc.push(format!("Sell: {} SOL / {}", lamports_to_sol(sell_capacity), slots_to_human(sell_refill)));
+        }
+        if min_deposit > 0 {
+            desc.push(format!("Min deposit: {} SOL", lamports_to_sol(min_deposit)));
+        }
+        if max_claims > 0 {
+            desc.push(format!("Max claims: {}", max_claims));
+        } else {
+            desc.push("Max claims: unlimited".into());
+        }
+        if initial_fill_bps < 10_000 {
+            desc.push(format!("Initial fill: {}%", initial_fill_bps as f64 / 100.0));
+        }
+        if merkle_root != [0u8; 32] {
+            desc.push(format!("Allowlist root: {}", hex32(&merkle_root)));
+        }
+        if let Some(gate) = gate_collection {
+            desc.push(format!("Gated on collection: {}", short_pubkey(&gate)));
+        }
+        if let Some(window) = hardig::instructions::format_promo_window(starts_at, ends_at) {
+            desc.push(format!("Claim window: {}", window));
+        }
+        if budget.total_borrow_limit > 0 {
+            desc.push(format!("Campaign borrow budget: {} SOL", lamports_to_sol(budget.total_borrow_limit)));
+        }
+        if budget.total_sell_limit > 0 {
+            desc.push(format!("Campaign sell budget: {} SOL", lamports_to_sol(budget.total_sell_limit)));
+        }
+        if referral_reward.has_bonus() {
+            desc.push(format!(
+                "Referral reward: +{} SOL capacity, +{} SOL limit, up to {} referrals per referrer",
+                lamports_to_sol(referral_reward.borrow_capacity_bonus),
+                lamports_to_sol(referral_reward.total_borrow_limit_bonus),
+                referral_reward.max_referrals_per_referrer,
+            ));
+        }
+        if refundable_principal {
+            desc.push("Claimers may withdraw their own deposit (less their debt)".into());
+        }
+        desc.push(format!("Promo PDA: {}", short_pubkey(&promo_pda)));
+
+        self.goto_confirm(PendingAction {
+            description: desc,
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    fn build_update_promo_toggle_active(&mut self) {
+        let idx = self.promo_cursor;
+        let entry = match self.promos.get(idx) {
+            Some(e) => e,
+            None => { self.push_log("No promo selected"); return; }
+        };
+        let new_active = !entry.config.active;
 
         let position_pda = match self.position_pda {
             Some(p) => p,
@@ -2340,52 +4051,213 @@ impl App {
             Some(a) => a,
             None => { self.push_log("No key asset"); return; }
         };
+
+        // Serialize update_promo instruction data
+        let mut data = sighash("update_promo");
+        // active: Option<bool> — Some(new_active)
+        data.push(0x01); // Some
+        data.push(if new_active { 1 } else { 0 });
+        // max_claims: Option<u32> — None
+        data.push(0x00); // None
+        // merkle_root: Option<[u8; 32]> — None
+        data.push(0x00);
+        // starts_at, ends_at: Option<i64> — None
+        data.extend_from_slice(&[0x00, 0x00]);
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let accounts = vec![
+            AccountMeta::new(self.keypair.pubkey(), true),    // admin
+            AccountMeta::new_readonly(admin_key_asset, false), // admin_key_asset
+            AccountMeta::new_readonly(position_pda, false),    // position
+            AccountMeta::new(entry.pda, false),                // promo
+            AccountMeta::new_readonly(config_pda, false),      // config
+        ];
+
+        let status_str = if new_active { "Active" } else { "Paused" };
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Update Promo".into(),
+                format!("Promo: {}", entry.config.name_suffix),
+                format!("Set status: {}", status_str),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    /// Tombstones left by close_promo for this position (discriminator + authority_seed only).
+    fn discover_closed_promos(&self) -> Vec<Pubkey> {
+        use solana_client::rpc_filter::{Memcmp, RpcFilterType as F};
         let authority_seed = match &self.position {
             Some(p) => p.authority_seed,
+            None => return vec![],
+        };
+        let disc = solana_sdk::hash::hash(b"account:PromoConfig").to_bytes();
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                F::DataSize(PromoConfig::CLOSED_SIZE as u64),
+                F::Memcmp(Memcmp::new_raw_bytes(0, disc[..8].to_vec())),
+                F::Memcmp(Memcmp::new_raw_bytes(8, authority_seed.to_bytes().to_vec())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        self.rpc
+            .get_program_accounts_with_config(&hardig::ID, config)
+            .map(|accounts| accounts.into_iter().map(|(pda, _)| pda).collect())
+            .unwrap_or_default()
+    }
+
+    /// Find the ClaimReceipts / GateReceipts of `promo` as (receipt, claimer) pairs.
+    fn discover_promo_receipts(&self, promo: &Pubkey) -> Vec<(Pubkey, Pubkey)> {
+        use solana_client::rpc_filter::{Memcmp, RpcFilterType as F};
+        let mut receipts = Vec::new();
+        // ClaimReceipt: claimer(32) then promo(32); GateReceipt: promo(32) first
+        for (name, promo_offset) in [("ClaimReceipt", 40), ("GateReceipt", 8)] {
+            let disc = solana_sdk::hash::hash(format!("account:{}", name).as_bytes()).to_bytes();
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    F::Memcmp(Memcmp::new_raw_bytes(0, disc[..8].to_vec())),
+                    F::Memcmp(Memcmp::new_raw_bytes(promo_offset, promo.to_bytes().to_vec())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            if let Ok(accounts) = self.rpc.get_program_accounts_with_config(&hardig::ID, config) {
+                for (pda, acc) in accounts {
+                    let claimer = match name {
+                        "ClaimReceipt" => ClaimReceipt::try_deserialize(&mut acc.data.as_slice()).map(|r| r.claimer),
+                        _ => GateReceipt::try_deserialize(&mut acc.data.as_slice()).map(|r| r.claimer),
+                    };
+                    if let Ok(claimer) = claimer {
+                        receipts.push((pda, claimer));
+                    }
+                }
+            }
+        }
+        receipts
+    }
+
+    fn build_close_promo(&mut self) {
+        // (receipt, claimer) pairs that fit in one transaction next to close_promo
+        const MAX_RECEIPTS_PER_TX: usize = 10;
+
+        let idx = self.promo_cursor;
+        let (promo_pda, name_suffix, active) = match self.promos.get(idx) {
+            Some(e) => (e.pda, e.config.name_suffix.clone(), e.config.active),
+            None => { self.push_log("No promo selected"); return; }
+        };
+        if active {
+            self.push_log("Pause the promo before closing it");
+            return;
+        }
+        let position_pda = match self.position_pda {
+            Some(p) => p,
+            None => { self.push_log("No position loaded"); return; }
+        };
+        let admin_key_asset = match self.my_asset {
+            Some(a) => a,
+            None => { self.push_log("No key asset"); return; }
+        };
+
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let accounts = vec![
+            AccountMeta::new(self.keypair.pubkey(), true),    // admin
+            AccountMeta::new_readonly(admin_key_asset, false), // admin_key_asset
+            AccountMeta::new_readonly(position_pda, false),    // position
+            AccountMeta::new(promo_pda, false),                // promo
+            AccountMeta::new_readonly(config_pda, false),      // config
+        ];
+        let mut instructions = vec![Instruction::new_with_bytes(
+            hardig::ID,
+            &sighash("close_promo"),
+            accounts,
+        )];
+
+        // Receipts can only be closed once the promo is, so sweep them in the same transaction
+        let receipts = self.discover_promo_receipts(&promo_pda);
+        let batch = receipts.len().min(MAX_RECEIPTS_PER_TX);
+        if batch > 0 {
+            let mut accounts = vec![AccountMeta::new_readonly(promo_pda, false)]; // promo
+            for (receipt, claimer) in &receipts[..batch] {
+                accounts.push(AccountMeta::new(*receipt, false));
+                accounts.push(AccountMeta::new(*claimer, false));
+            }
+            instructions.push(Instruction::new_with_bytes(
+                hardig::ID,
+                &sighash("close_claim_receipts"),
+                accounts,
+            ));
+        }
+
+        let mut description = vec![
+            "Close Promo".into(),
+            format!("Promo: {}", name_suffix),
+            "Rent returns to you; the name cannot be reused".into(),
+            format!("Close {} receipt(s), rent back to each claimer", batch),
+        ];
+        if receipts.len() > batch {
+            description.push(format!(
+                "{} more receipt(s) left for close_claim_receipts (anyone can send it)",
+                receipts.len() - batch
+            ));
+        }
+        self.goto_confirm(PendingAction {
+            description,
+            instructions,
+            extra_signers: vec![],
+        });
+    }
+
+    fn build_update_promo_max_claims(&mut self) {
+        let idx = self.promo_cursor;
+        let entry = match self.promos.get(idx) {
+            Some(e) => e,
+            None => { self.push_log("No promo selected"); return; }
+        };
+
+        let new_max: u32 = match self.find_field_value("Max Claims")
+            .and_then(|v| v.trim().parse().ok())
+        {
+            Some(v) => v,
+            None => {
+                self.push_log("Invalid max claims value");
+                return;
+            }
+        };
+
+        let position_pda = match self.position_pda {
+            Some(p) => p,
             None => { self.push_log("No position loaded"); return; }
         };
+        let admin_key_asset = match self.my_asset {
+            Some(a) => a,
+            None => { self.push_log("No key asset"); return; }
+        };
 
-        // Derive promo PDA
-        let (promo_pda, _) = Pubkey::find_program_address(
-            &[PromoConfig::SEED, authority_seed.as_ref(), name_suffix.as_bytes()],
-            &hardig::ID,
-        );
-
-        // Serialize instruction data (Borsh format)
-        let mut data = sighash("create_promo");
-        // name_suffix: String (4-byte len + utf8)
-        let name_bytes = name_suffix.as_bytes();
-        data.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
-        data.extend_from_slice(name_bytes);
-        // permissions: u8
-        data.push(permissions);
-        // borrow_capacity: u64
-        data.extend_from_slice(&borrow_capacity.to_le_bytes());
-        // borrow_refill_period: u64
-        data.extend_from_slice(&borrow_refill.to_le_bytes());
-        // sell_capacity: u64
-        data.extend_from_slice(&sell_capacity.to_le_bytes());
-        // sell_refill_period: u64
-        data.extend_from_slice(&sell_refill.to_le_bytes());
-        // total_borrow_limit: u64
-        data.extend_from_slice(&total_borrow_limit.to_le_bytes());
-        // total_sell_limit: u64
-        data.extend_from_slice(&total_sell_limit.to_le_bytes());
-        // min_deposit_lamports: u64
-        data.extend_from_slice(&min_deposit.to_le_bytes());
-        // max_claims: u32
-        data.extend_from_slice(&max_claims.to_le_bytes());
-        // initial_fill_bps: u16
-        data.extend_from_slice(&initial_fill_bps.to_le_bytes());
-        // image_uri: String (4-byte len + utf8)
-        let uri_bytes = image_uri.as_bytes();
-        data.extend_from_slice(&(uri_bytes.len() as u32).to_le_bytes());
-        data.extend_from_slice(uri_bytes);
-        // market_name: String (4-byte len + utf8)
-        let market_name = self.market_name_override.clone().unwrap_or_default();
-        let mn_bytes = market_name.as_bytes();
-        data.extend_from_slice(&(mn_bytes.len() as u32).to_le_bytes());
-        data.extend_from_slice(mn_bytes);
+        // Serialize update_promo instruction data
+        let mut data = sighash("update_promo");
+        // active: Option<bool> — None
+        data.push(0x00);
+        // max_claims: Option<u32> — Some(new_max)
+        data.push(0x01); // Some
+        data.extend_from_slice(&new_max.to_le_bytes());
+        // merkle_root: Option<[u8; 32]> — None
+        data.push(0x00);
+        // starts_at, ends_at: Option<i64> — None
+        data.extend_from_slice(&[0x00, 0x00]);
 
         let (config_pda, _) =
             Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
@@ -2394,49 +4266,36 @@ impl App {
             AccountMeta::new(self.keypair.pubkey(), true),    // admin
             AccountMeta::new_readonly(admin_key_asset, false), // admin_key_asset
             AccountMeta::new_readonly(position_pda, false),    // position
-            AccountMeta::new(promo_pda, false),                // promo
+            AccountMeta::new(entry.pda, false),                // promo
             AccountMeta::new_readonly(config_pda, false),      // config
-            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false), // system_program
-        ];
-
-        let mut desc = vec![
-            "Create Promo".into(),
-            format!("Name: {}", name_suffix),
-            format!("Permissions: {} (0x{:02X})", permissions_name(permissions), permissions),
         ];
-        if borrow_capacity > 0 {
-            desc.push(format!("Borrow: {} SOL / {}", lamports_to_sol(borrow_capacity), slots_to_human(borrow_refill)));
-        }
-        if sell_capacity > 0 {
-            desc.push(format!("Sell: {} SOL / {}", lamports_to_sol(sell_capacity), slots_to_human(sell_refill)));
-        }
-        if min_deposit > 0 {
-            desc.push(format!("Min deposit: {} SOL", lamports_to_sol(min_deposit)));
-        }
-        if max_claims > 0 {
-            desc.push(format!("Max claims: {}", max_claims));
-        } else {
-            desc.push("Max claims: unlimited".into());
-        }
-        if initial_fill_bps < 10_000 {
-            desc.push(format!("Initial fill: {}%", initial_fill_bps as f64 / 100.0));
-        }
-        desc.push(format!("Promo PDA: {}", short_pubkey(&promo_pda)));
 
+        let max_str = if new_max == 0 { "unlimited".to_string() } else { new_max.to_string() };
         self.goto_confirm(PendingAction {
-            description: desc,
+            description: vec![
+                "Update Promo".into(),
+                format!("Promo: {}", entry.config.name_suffix),
+                format!("Set max claims: {}", max_str),
+            ],
             instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
             extra_signers: vec![],
         });
     }
 
-    fn build_update_promo_toggle_active(&mut self) {
+    fn build_update_promo_root(&mut self) {
         let idx = self.promo_cursor;
         let entry = match self.promos.get(idx) {
             Some(e) => e,
             None => { self.push_log("No promo selected"); return; }
         };
-        let new_active = !entry.config.active;
+
+        let root = match parse_hex32(&self.find_field_value("Allowlist Root").unwrap_or_default()) {
+            Some(r) => r,
+            None => {
+                self.push_log("Allowlist root must be 64 hex characters");
+                return;
+            }
+        };
 
         let position_pda = match self.position_pda {
             Some(p) => p,
@@ -2449,11 +4308,15 @@ impl App {
 
         // Serialize update_promo instruction data
         let mut data = sighash("update_promo");
-        // active: Option<bool> — Some(new_active)
-        data.push(0x01); // Some
-        data.push(if new_active { 1 } else { 0 });
+        // active: Option<bool> — None
+        data.push(0x00);
         // max_claims: Option<u32> — None
-        data.push(0x00); // None
+        data.push(0x00);
+        // merkle_root: Option<[u8; 32]> — Some(root)
+        data.push(0x01);
+        data.extend_from_slice(&root);
+        // starts_at, ends_at: Option<i64> — None
+        data.extend_from_slice(&[0x00, 0x00]);
 
         let (config_pda, _) =
             Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
@@ -2466,34 +4329,31 @@ impl App {
             AccountMeta::new_readonly(config_pda, false),      // config
         ];
 
-        let status_str = if new_active { "Active" } else { "Paused" };
+        let root_str = if root == [0u8; 32] { "none (open to anyone)".to_string() } else { hex32(&root) };
         self.goto_confirm(PendingAction {
             description: vec![
                 "Update Promo".into(),
                 format!("Promo: {}", entry.config.name_suffix),
-                format!("Set status: {}", status_str),
+                format!("Set allowlist root: {}", root_str),
             ],
             instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
             extra_signers: vec![],
         });
     }
 
-    fn build_update_promo_max_claims(&mut self) {
+    fn build_update_promo_end(&mut self) {
         let idx = self.promo_cursor;
         let entry = match self.promos.get(idx) {
             Some(e) => e,
             None => { self.push_log("No promo selected"); return; }
         };
 
-        let new_max: u32 = match self.find_field_value("Max Claims")
-            .and_then(|v| v.trim().parse().ok())
-        {
-            Some(v) => v,
-            None => {
-                self.push_log("Invalid max claims value");
-                return;
-            }
-        };
+        let days = self.find_field_value("Claim Window");
+        if days.as_ref().and_then(|v| v.trim().parse::<i64>().ok()).is_none_or(|d| d < 0) {
+            self.push_log("Enter a whole number of days (0 = no end)");
+            return;
+        }
+        let ends_at = days_to_expires_at(&days);
 
         let position_pda = match self.position_pda {
             Some(p) => p,
@@ -2506,11 +4366,11 @@ impl App {
 
         // Serialize update_promo instruction data
         let mut data = sighash("update_promo");
-        // active: Option<bool> — None
-        data.push(0x00);
-        // max_claims: Option<u32> — Some(new_max)
-        data.push(0x01); // Some
-        data.extend_from_slice(&new_max.to_le_bytes());
+        // active, max_claims, merkle_root, starts_at: None
+        data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        // ends_at: Option<i64> — Some(ends_at)
+        data.push(0x01);
+        data.extend_from_slice(&ends_at.to_le_bytes());
 
         let (config_pda, _) =
             Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
@@ -2523,18 +4383,119 @@ impl App {
             AccountMeta::new_readonly(config_pda, false),      // config
         ];
 
-        let max_str = if new_max == 0 { "unlimited".to_string() } else { new_max.to_string() };
+        let window = hardig::instructions::format_promo_window(entry.config.starts_at, ends_at)
+            .unwrap_or_else(|| "always open".into());
         self.goto_confirm(PendingAction {
             description: vec![
                 "Update Promo".into(),
                 format!("Promo: {}", entry.config.name_suffix),
-                format!("Set max claims: {}", max_str),
+                format!("Set claim window: {}", window),
+            ],
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    pub fn build_migrate_market_config(&mut self, nav_mint: Pubkey) {
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+        let (mc_pda, _) =
+            Pubkey::find_program_address(&[MarketConfig::SEED, nav_mint.as_ref()], &hardig::ID);
+
+        let data = sighash("migrate_market_config");
+        let accounts = vec![
+            AccountMeta::new(self.keypair.pubkey(), true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(mc_pda, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Migrate MarketConfig (add paused flag)".into(),
+                format!("Nav Mint: {}", nav_mint),
+                format!("MarketConfig PDA: {}", mc_pda),
             ],
             instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
             extra_signers: vec![],
         });
     }
 
+    /// Pause or resume the whole protocol, or a single market when `nav_mint` is given.
+    pub fn build_set_paused(&mut self, nav_mint: Option<Pubkey>, paused: bool) {
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+        let verb = if paused { "Pause" } else { "Resume" };
+
+        let (data, accounts, description) = match nav_mint {
+            None => {
+                let mut data = sighash("set_protocol_paused");
+                data.push(paused as u8);
+                let accounts = vec![
+                    AccountMeta::new_readonly(self.keypair.pubkey(), true),
+                    AccountMeta::new(config_pda, false),
+                ];
+                (data, accounts, vec![format!("{} Protocol", verb)])
+            }
+            Some(nav_mint) => {
+                let (mc_pda, _) = Pubkey::find_program_address(
+                    &[MarketConfig::SEED, nav_mint.as_ref()],
+                    &hardig::ID,
+                );
+                let mut data = sighash("set_market_paused");
+                data.push(paused as u8);
+                let accounts = vec![
+                    AccountMeta::new_readonly(self.keypair.pubkey(), true),
+                    AccountMeta::new_readonly(config_pda, false),
+                    AccountMeta::new(mc_pda, false),
+                ];
+                (
+                    data,
+                    accounts,
+                    vec![
+                        format!("{} Market {}", verb, nav_token_name(&nav_mint)),
+                        format!("MarketConfig PDA: {}", mc_pda),
+                    ],
+                )
+            }
+        };
+
+        self.goto_confirm(PendingAction {
+            description,
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    /// Set the protocol fee on reinvest and the treasury wallet that receives it.
+    pub fn build_set_protocol_fee(&mut self, fee_bps: u16, treasury: Pubkey) {
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("set_protocol_fee");
+        data.extend_from_slice(&fee_bps.to_le_bytes());
+        data.extend_from_slice(treasury.as_ref());
+
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true),
+            AccountMeta::new(config_pda, false),
+        ];
+
+        let mut description = vec![
+            "Set Protocol Fee".into(),
+            format!("Fee: {}% of each reinvest borrow", fee_bps as f64 / 100.0),
+        ];
+        if fee_bps > 0 {
+            description.push(format!("Treasury: {}", treasury));
+        }
+
+        self.goto_confirm(PendingAction {
+            description,
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
     pub fn build_transfer_admin(&mut self, new_admin: Pubkey) {
         let (config_pda, _) =
             Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
@@ -2589,6 +4550,8 @@ impl App {
             AccountMeta::new(self.keypair.pubkey(), true),
             AccountMeta::new_readonly(config_pda, false),
             AccountMeta::new(mc_pda, false),
+            AccountMeta::new_readonly(market_meta, false),
+            AccountMeta::new_readonly(mayflower_market, false),
             AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
         ];
 
@@ -2603,6 +4566,151 @@ impl App {
         });
     }
 
+    /// Fetch a market's MarketConfig by nav mint, logging on failure.
+    fn fetch_market_config(&mut self, nav_mint: &Pubkey) -> Option<(Pubkey, MarketConfig)> {
+        let (mc_pda, _) =
+            Pubkey::find_program_address(&[MarketConfig::SEED, nav_mint.as_ref()], &hardig::ID);
+        let mc = self
+            .rpc
+            .get_account(&mc_pda)
+            .ok()
+            .and_then(|acc| MarketConfig::try_deserialize(&mut acc.data.as_slice()).ok());
+        match mc {
+            Some(mc) => Some((mc_pda, mc)),
+            None => {
+                self.push_log(format!("No MarketConfig for nav mint {}", nav_mint));
+                None
+            }
+        }
+    }
+
+    /// Replace a market's Mayflower accounts. `overrides` follows MarketUpdate's
+    /// field order; `None` keeps the current on-chain value.
+    pub fn build_update_market_config(
+        &mut self,
+        nav_mint: Pubkey,
+        overrides: [Option<Pubkey>; 5],
+        delay_secs: i64,
+    ) {
+        let (mc_pda, mc) = match self.fetch_market_config(&nav_mint) {
+            Some(m) => m,
+            None => return,
+        };
+        let [market_group, mayflower_market, market_base_vault, market_nav_vault, fee_vault] =
+            overrides;
+        let update = MarketUpdate {
+            market_group: market_group.unwrap_or(mc.market_group),
+            mayflower_market: mayflower_market.unwrap_or(mc.mayflower_market),
+            market_base_vault: market_base_vault.unwrap_or(mc.market_base_vault),
+            market_nav_vault: market_nav_vault.unwrap_or(mc.market_nav_vault),
+            fee_vault: fee_vault.unwrap_or(mc.fee_vault),
+        };
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+
+        let mut data = sighash("update_market_config");
+        data.extend_from_slice(update.market_group.as_ref());
+        data.extend_from_slice(update.mayflower_market.as_ref());
+        data.extend_from_slice(update.market_base_vault.as_ref());
+        data.extend_from_slice(update.market_nav_vault.as_ref());
+        data.extend_from_slice(update.fee_vault.as_ref());
+        data.extend_from_slice(&delay_secs.to_le_bytes());
+
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(mc_pda, false),
+            AccountMeta::new_readonly(mc.market_meta, false),
+            AccountMeta::new_readonly(update.mayflower_market, false),
+        ];
+
+        let mut description = vec![
+            "Update Market Config".into(),
+            format!("Nav Mint: {}", nav_mint),
+        ];
+        for (label, old, new) in [
+            ("Market Group", mc.market_group, update.market_group),
+            ("Mayflower Market", mc.mayflower_market, update.mayflower_market),
+            ("Base Vault", mc.market_base_vault, update.market_base_vault),
+            ("Nav Vault", mc.market_nav_vault, update.market_nav_vault),
+            ("Fee Vault", mc.fee_vault, update.fee_vault),
+        ] {
+            if old != new {
+                description.push(format!("{}: {} -> {}", label, short_pubkey(&old), short_pubkey(&new)));
+            }
+        }
+        description.push(if delay_secs == 0 {
+            "Applies: immediately".into()
+        } else {
+            format!("Applies: after {}h (apply-market-config-update)", delay_secs as f64 / 3600.0)
+        });
+
+        self.goto_confirm(PendingAction {
+            description,
+            instructions: vec![Instruction::new_with_bytes(hardig::ID, &data, accounts)],
+            extra_signers: vec![],
+        });
+    }
+
+    pub fn build_apply_market_config_update(&mut self, nav_mint: Pubkey) {
+        let (mc_pda, mc) = match self.fetch_market_config(&nav_mint) {
+            Some(m) => m,
+            None => return,
+        };
+        if mc.pending_update_at == 0 {
+            self.push_log("No MarketConfig update is staged");
+            return;
+        }
+
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true),
+            AccountMeta::new(mc_pda, false),
+            AccountMeta::new_readonly(mc.market_meta, false),
+            AccountMeta::new_readonly(mc.pending_update.mayflower_market, false),
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Apply Market Config Update".into(),
+                format!("Nav Mint: {}", nav_mint),
+                format!("Ready at: {}", mc.pending_update_at),
+            ],
+            instructions: vec![Instruction::new_with_bytes(
+                hardig::ID,
+                &sighash("apply_market_config_update"),
+                accounts,
+            )],
+            extra_signers: vec![],
+        });
+    }
+
+    pub fn build_retire_market_config(&mut self, nav_mint: Pubkey) {
+        let (config_pda, _) =
+            Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
+        let (mc_pda, _) =
+            Pubkey::find_program_address(&[MarketConfig::SEED, nav_mint.as_ref()], &hardig::ID);
+
+        let accounts = vec![
+            AccountMeta::new_readonly(self.keypair.pubkey(), true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(mc_pda, false),
+        ];
+
+        self.goto_confirm(PendingAction {
+            description: vec![
+                "Retire Market Config (irreversible)".into(),
+                format!("Nav Mint: {}", nav_mint),
+                "No new positions, buys or borrows; exits stay open.".into(),
+            ],
+            instructions: vec![Instruction::new_with_bytes(
+                hardig::ID,
+                &sighash("retire_market_config"),
+                accounts,
+            )],
+            extra_signers: vec![],
+        });
+    }
+
     pub fn build_add_trusted_provider(&mut self, program_id: Pubkey) {
         let (config_pda, _) =
             Pubkey::find_program_address(&[ProtocolConfig::SEED], &hardig::ID);
@@ -2788,10 +4896,16 @@ impl App {
                     } else {
                         self.collection = None;
                     }
+                    self.protocol_paused = config.paused;
+                    self.protocol_fee_bps = config.fee_bps;
+                    self.treasury = config.treasury;
                 }
             }
             Err(_) => {
                 self.protocol_exists = false;
+                self.protocol_paused = false;
+                self.protocol_fee_bps = 0;
+                self.treasury = Pubkey::default();
                 self.collection = None;
             }
         }
@@ -3071,6 +5185,10 @@ impl App {
             total_sold: 0,
             total_borrow_limit: 0,
             total_borrowed: 0,
+            promo: Pubkey::default(),
+            principal_shares: 0,
+            debt: 0,
+            principal_refundable: false,
         });
 
         // Add delegated keys from cached key_states
@@ -3111,6 +5229,10 @@ impl App {
                 total_sold: ks.total_sold,
                 total_borrow_limit: ks.total_borrow_limit,
                 total_borrowed: ks.total_borrowed,
+                promo: ks.promo,
+                principal_shares: ks.principal_shares,
+                debt: ks.debt,
+                principal_refundable: ks.principal_refundable,
             });
         }
 
@@ -3131,6 +5253,10 @@ impl App {
                     total_sold: 0,
                     total_borrow_limit: 0,
                     total_borrowed: 0,
+                    promo: Pubkey::default(),
+                    principal_shares: 0,
+                    debt: 0,
+                    principal_refundable: false,
                 });
             }
         }
@@ -3151,6 +5277,9 @@ impl App {
     }
 
     pub fn refresh_mayflower_state(&mut self) {
+        self.share_token_balance = self
+            .share_mint()
+            .and_then(|mint| self.read_token_balance(&get_ata(&self.keypair.pubkey(), &mint)));
         self.wsol_balance = 0;
         self.nav_sol_balance = 0;
         self.atas_exist = false;
@@ -3238,7 +5367,7 @@ impl App {
     }
 
     /// Read the permissions value from an MPL-Core asset's Attributes plugin.
-    fn read_asset_permissions(&self, asset: &Pubkey) -> Option<u8> {
+    fn read_asset_permissions(&self, asset: &Pubkey) -> Option<u16> {
         use mpl_core::{
             accounts::BaseAssetV1,
             fetch_plugin,
@@ -3267,7 +5396,7 @@ impl App {
             .attribute_list
             .iter()
             .find(|a| a.key == "permissions")
-            .and_then(|a| a.value.parse::<u8>().ok())
+            .and_then(|a| a.value.parse::<u16>().ok())
     }
 
     /// Read the name field from an MPL-Core asset's BaseAssetV1 data.
@@ -3325,14 +5454,14 @@ impl App {
 // Helpers
 // ---------------------------------------------------------------------------
 
-pub fn permissions_name(permissions: u8) -> String {
+pub fn permissions_name(permissions: u16) -> String {
     if permissions == PRESET_ADMIN {
         return "Admin".into();
     }
     if permissions == 0 {
         return "None".into();
     }
-    let bits: &[(u8, &str)] = &[
+    let bits: &[(u16, &str)] = &[
         (PERM_BUY, "Buy"),
         (PERM_SELL, "Sell"),
         (PERM_BORROW, "Borrow"),
@@ -3341,6 +5470,8 @@ pub fn permissions_name(permissions: u8) -> String {
         (PERM_MANAGE_KEYS, "ManageKeys"),
         (PERM_LIMITED_SELL, "LimSell"),
         (PERM_LIMITED_BORROW, "LimBorrow"),
+        (PERM_UNWIND, "Unwind"),
+        (PERM_GUARDIAN, "Guardian"),
     ];
     let names: Vec<&str> = bits
         .iter()
@@ -3404,6 +5535,15 @@ pub fn lamports_to_sol(lamports: u64) -> String {
     }
 }
 
+/// "used / limit SOL" for a promo campaign budget (limit 0 = uncapped).
+pub fn budget_label(used: u64, limit: u64) -> String {
+    if limit == 0 {
+        format!("{} SOL (uncapped)", lamports_to_sol(used))
+    } else {
+        format!("{} / {} SOL", lamports_to_sol(used), lamports_to_sol(limit))
+    }
+}
+
 // Solana slot-to-time constants (~400ms per slot)
 pub const SLOTS_PER_MINUTE: u64 = 150;
 pub const SLOTS_PER_HOUR: u64 = 9_000;
@@ -3421,6 +5561,41 @@ pub fn time_fields_to_slots(
     d * SLOTS_PER_DAY + h * SLOTS_PER_HOUR + m * SLOTS_PER_MINUTE
 }
 
+/// Parse a 32-byte hex string (e.g. a merkle root). Empty means all zeros.
+pub fn parse_hex32(s: &str) -> Option<[u8; 32]> {
+    let s = s.trim().trim_start_matches("0x");
+    let mut out = [0u8; 32];
+    if s.is_empty() {
+        return Some(out);
+    }
+    if s.len() != 64 {
+        return None;
+    }
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(s.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(out)
+}
+
+/// Format 32 bytes as lowercase hex.
+pub fn hex32(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Convert an "expires in N days" form field to an absolute unix timestamp.
+/// Empty or 0 means the key never expires (returns 0).
+pub fn days_to_expires_at(days: &Option<String>) -> i64 {
+    let d: i64 = days.as_ref().and_then(|v| v.trim().parse().ok()).unwrap_or(0);
+    if d <= 0 {
+        return 0;
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    now + d * 86_400
+}
+
 /// Convert a slot count to a human-readable time estimate using Solana's ~400ms slot time.
 ///
 /// - < 150 slots (~1 min): show seconds, e.g. "~40s"
@@ -48,8 +48,71 @@ enum Action {
     Status,
     /// Initialize the protocol config
     InitProtocol,
-    /// Migrate ProtocolConfig to add collection field (protocol admin only, one-time)
+    /// Migrate ProtocolConfig to the latest layout (protocol admin only)
     MigrateConfig,
+    /// Migrate a MarketConfig to add the paused flag (protocol admin only, one-time)
+    MigrateMarketConfig {
+        /// Nav token mint of the market
+        #[arg(long)]
+        nav_mint: String,
+    },
+    /// Replace a market's Mayflower accounts (protocol admin only; omitted accounts are kept)
+    UpdateMarketConfig {
+        /// Nav token mint of the market
+        #[arg(long)]
+        nav_mint: String,
+        /// New Mayflower market group
+        #[arg(long)]
+        market_group: Option<String>,
+        /// New Mayflower market account
+        #[arg(long)]
+        mayflower_market: Option<String>,
+        /// New market base vault
+        #[arg(long)]
+        market_base_vault: Option<String>,
+        /// New market nav vault
+        #[arg(long)]
+        market_nav_vault: Option<String>,
+        /// New fee vault
+        #[arg(long)]
+        fee_vault: Option<String>,
+        /// Stage the update for this many hours before it can be applied (0 = now)
+        #[arg(long, default_value_t = 0.0)]
+        delay_hours: f64,
+    },
+    /// Apply a staged MarketConfig update after its delay (any signer)
+    ApplyMarketConfigUpdate {
+        /// Nav token mint of the market
+        #[arg(long)]
+        nav_mint: String,
+    },
+    /// Permanently retire a market: no new positions, buys or borrows (protocol admin only)
+    RetireMarketConfig {
+        /// Nav token mint of the market
+        #[arg(long)]
+        nav_mint: String,
+    },
+    /// Set the protocol fee taken from reinvest borrows (protocol admin only)
+    SetProtocolFee {
+        /// Fee in bps of each reinvest's borrowed SOL (max 1000 = 10%, 0 = off)
+        #[arg(long)]
+        fee_bps: u16,
+        /// Treasury wallet receiving the fee (required when fee_bps > 0)
+        #[arg(long)]
+        treasury: Option<String>,
+    },
+    /// Emergency-pause buy/sell/borrow/reinvest/promo claims (protocol admin only)
+    Pause {
+        /// Pause only this market (nav token mint); omit to pause the whole protocol
+        #[arg(long)]
+        nav_mint: Option<String>,
+    },
+    /// Lift a pause set with `pause` (protocol admin only)
+    Unpause {
+        /// Resume only this market (nav token mint); omit for the protocol-wide flag
+        #[arg(long)]
+        nav_mint: Option<String>,
+    },
     /// Create the MPL-Core collection for key NFTs (protocol admin only, one-time)
     CreateCollection {
         /// Metadata URI (upload collection-metadata.json to Irys/Arweave first)
@@ -81,6 +144,15 @@ enum Action {
         #[arg(long)]
         amount: f64,
     },
+    /// Sell navSOL and repay debt from the proceeds in one transaction
+    Unwind {
+        /// navSOL amount to sell
+        #[arg(long)]
+        amount: f64,
+        /// Debt to leave outstanding, in SOL
+        #[arg(long, default_value = "0")]
+        target_debt: f64,
+    },
     /// Borrow against deposited navSOL
     Borrow {
         /// Amount in SOL
@@ -94,7 +166,23 @@ enum Action {
         amount: f64,
     },
     /// Reinvest (borrow + buy) to compound position
-    Reinvest,
+    Reinvest {
+        /// Borrow this fraction of available capacity, in bps (default: all of it)
+        #[arg(long, conflicts_with = "target_ltv_bps")]
+        fraction_bps: Option<u16>,
+        /// Borrow until debt / floor value reaches this ratio, in bps
+        #[arg(long)]
+        target_ltv_bps: Option<u16>,
+        /// Skip the reinvest if it would borrow less than this many SOL
+        #[arg(long, default_value = "0")]
+        min_amount: f64,
+    },
+    /// Reinvest a position that pays keeper bounties, without holding one of its keys
+    KeeperReinvest {
+        /// PositionState PDA to reinvest
+        #[arg(long)]
+        position_pda: String,
+    },
     /// Authorize a new key for the position
     AuthorizeKey {
         /// Target wallet public key
@@ -102,7 +190,7 @@ enum Action {
         wallet: String,
         /// Permissions bitmask: 25=Operator, 9=Depositor, 16=Keeper, or custom
         #[arg(long)]
-        permissions: u8,
+        permissions: u16,
         /// Sell rate-limit capacity in SOL (for PERM_LIMITED_SELL)
         #[arg(long, default_value = "0")]
         sell_capacity: f64,
@@ -115,6 +203,9 @@ enum Action {
         /// Borrow rate-limit refill period in slots
         #[arg(long, default_value = "0")]
         borrow_refill_slots: u64,
+        /// Days until the key expires (0 = never)
+        #[arg(long, default_value = "0")]
+        expires_in_days: u64,
         /// Optional name for the key NFT (max 32 characters)
         #[arg(long)]
         name: Option<String>,
@@ -127,6 +218,79 @@ enum Action {
     },
     /// Send heartbeat to prove admin liveness (resets recovery timer)
     Heartbeat,
+    /// Resync local deposited/debt tracking with Mayflower (permissionless)
+    SyncPosition,
+    /// Close a fully unwound position, burning its keys and reclaiming all rent
+    ClosePosition,
+    /// Pin the address that receives SOL from sell/borrow (admin only)
+    SetPayout {
+        /// Payout wallet public key (omit to pay the signer again)
+        #[arg(long)]
+        address: Option<String>,
+    },
+    /// Set the max debt as a % of floor value for borrow/reinvest (admin only)
+    ConfigureLeverage {
+        /// Max debt/floor value in percent (0 = no ceiling)
+        #[arg(long)]
+        max_pct: f64,
+    },
+    /// Require co-signers for large sells/borrows (admin only; --required 0 disables)
+    ConfigureCosign {
+        /// Sells above this many navSOL need co-signers
+        #[arg(long, default_value_t = 0.0)]
+        sell_threshold: f64,
+        /// Borrows above this many SOL need co-signers
+        #[arg(long, default_value_t = 0.0)]
+        borrow_threshold: f64,
+        /// Number of distinct approver keys that must sign
+        #[arg(long)]
+        required: u8,
+        /// Comma-separated approver key asset pubkeys
+        #[arg(long, default_value = "")]
+        approvers: String,
+    },
+    /// Delay large sells behind a cancellable request (admin only; --delay-hours 0 disables)
+    ConfigureWithdrawQueue {
+        /// Hours a queued sell must wait before it can execute
+        #[arg(long)]
+        delay_hours: f64,
+        /// Sells above this many navSOL must be queued
+        #[arg(long, default_value_t = 0.0)]
+        threshold: f64,
+    },
+    /// Let anyone reinvest the position for a bounty (admin only; --bounty-bps 0 disables)
+    ConfigureKeeper {
+        /// Bounty paid to the keeper, in bps of the borrowed SOL (max 100)
+        #[arg(long)]
+        bounty_bps: u16,
+        /// Cap on a single bounty, in SOL
+        #[arg(long, default_value_t = 0.0)]
+        max_bounty: f64,
+        /// Keepers may not reinvest less than this many SOL
+        #[arg(long, default_value_t = 0.0)]
+        min_borrow: f64,
+        /// Maximum market/floor spread a keeper may reinvest at, in bps
+        #[arg(long, default_value = "0")]
+        max_spread_bps: u16,
+        /// Keepers borrow this fraction of available capacity, in bps (default: all of it)
+        #[arg(long, conflicts_with = "target_ltv_bps")]
+        fraction_bps: Option<u16>,
+        /// Keepers borrow until debt / floor value reaches this ratio, in bps
+        #[arg(long)]
+        target_ltv_bps: Option<u16>,
+    },
+    /// Execute a queued sell once its delay has passed (requesting key only)
+    ExecuteWithdraw {
+        /// ID of the queued sell request
+        #[arg(long)]
+        request_id: u64,
+    },
+    /// Cancel a queued sell (guardian or admin only)
+    CancelWithdraw {
+        /// ID of the queued sell request
+        #[arg(long)]
+        request_id: u64,
+    },
     /// Configure a recovery key for the position (admin only)
     ConfigureRecovery {
         /// Target wallet to receive the recovery key NFT
@@ -658,16 +822,34 @@ fn action_to_name(action: &Action) -> String {
         Action::Status => "status".into(),
         Action::InitProtocol => "init-protocol".into(),
         Action::MigrateConfig => "migrate-config".into(),
+        Action::MigrateMarketConfig { .. } => "migrate-market-config".into(),
+        Action::UpdateMarketConfig { .. } => "update-market-config".into(),
+        Action::ApplyMarketConfigUpdate { .. } => "apply-market-config-update".into(),
+        Action::RetireMarketConfig { .. } => "retire-market-config".into(),
+        Action::SetProtocolFee { .. } => "set-protocol-fee".into(),
+        Action::Pause { .. } => "pause".into(),
+        Action::Unpause { .. } => "unpause".into(),
         Action::CreateCollection { .. } => "create-collection".into(),
         Action::CreatePosition { .. } => "create-position".into(),
         Action::Buy { .. } => "buy".into(),
         Action::Sell { .. } => "sell".into(),
         Action::Borrow { .. } => "borrow".into(),
         Action::Repay { .. } => "repay".into(),
-        Action::Reinvest => "reinvest".into(),
+        Action::Reinvest { .. } => "reinvest".into(),
+        Action::KeeperReinvest { .. } => "keeper-reinvest".into(),
+        Action::Unwind { .. } => "unwind".into(),
         Action::AuthorizeKey { .. } => "authorize-key".into(),
         Action::RevokeKey { .. } => "revoke-key".into(),
         Action::Heartbeat => "heartbeat".into(),
+        Action::SyncPosition => "sync-position".into(),
+        Action::ClosePosition => "close-position".into(),
+        Action::SetPayout { .. } => "set-payout".into(),
+        Action::ConfigureLeverage { .. } => "configure-leverage".into(),
+        Action::ConfigureCosign { .. } => "configure-cosign".into(),
+        Action::ConfigureWithdrawQueue { .. } => "configure-withdraw-queue".into(),
+        Action::ConfigureKeeper { .. } => "configure-keeper".into(),
+        Action::ExecuteWithdraw { .. } => "execute-withdraw".into(),
+        Action::CancelWithdraw { .. } => "cancel-withdraw".into(),
         Action::ConfigureRecovery { .. } => "configure-recovery".into(),
         Action::ExecuteRecovery => "execute-recovery".into(),
         Action::Balances => "balances".into(),
@@ -698,6 +880,108 @@ fn populate_and_build(app: &mut app::App, action: &Action, markets_file: Option<
         Action::MigrateConfig => {
             app.build_migrate_config();
         }
+        Action::MigrateMarketConfig { nav_mint } => {
+            use std::str::FromStr;
+            match solana_sdk::pubkey::Pubkey::from_str(nav_mint) {
+                Ok(pk) => app.build_migrate_market_config(pk),
+                Err(_) => {
+                    return Some(CliOutput::Error {
+                        action: "migrate-market-config".into(),
+                        error: format!("Invalid pubkey: {}", nav_mint),
+                    });
+                }
+            }
+        }
+        Action::UpdateMarketConfig {
+            nav_mint,
+            market_group,
+            mayflower_market,
+            market_base_vault,
+            market_nav_vault,
+            fee_vault,
+            delay_hours,
+        } => {
+            use std::str::FromStr;
+            let parse = |s: &str| {
+                solana_sdk::pubkey::Pubkey::from_str(s).map_err(|_| format!("Invalid pubkey: {}", s))
+            };
+            let parsed = parse(nav_mint).and_then(|nav_mint| {
+                let mut overrides = [None; 5];
+                for (slot, value) in overrides.iter_mut().zip([
+                    market_group,
+                    mayflower_market,
+                    market_base_vault,
+                    market_nav_vault,
+                    fee_vault,
+                ]) {
+                    *slot = value.as_deref().map(parse).transpose()?;
+                }
+                Ok((nav_mint, overrides))
+            });
+            match parsed {
+                Ok((nav_mint, overrides)) => {
+                    app.build_update_market_config(nav_mint, overrides, (delay_hours * 3600.0) as i64);
+                }
+                Err(error) => {
+                    return Some(CliOutput::Error {
+                        action: "update-market-config".into(),
+                        error,
+                    });
+                }
+            }
+        }
+        Action::ApplyMarketConfigUpdate { nav_mint } | Action::RetireMarketConfig { nav_mint } => {
+            use std::str::FromStr;
+            match solana_sdk::pubkey::Pubkey::from_str(nav_mint) {
+                Ok(pk) if matches!(action, Action::RetireMarketConfig { .. }) => {
+                    app.build_retire_market_config(pk)
+                }
+                Ok(pk) => app.build_apply_market_config_update(pk),
+                Err(_) => {
+                    return Some(CliOutput::Error {
+                        action: action_to_name(action),
+                        error: format!("Invalid pubkey: {}", nav_mint),
+                    });
+                }
+            }
+        }
+        Action::KeeperReinvest { position_pda } => {
+            use std::str::FromStr;
+            match solana_sdk::pubkey::Pubkey::from_str(position_pda) {
+                Ok(pk) => app.build_keeper_reinvest(pk),
+                Err(_) => {
+                    return Some(CliOutput::Error {
+                        action: "keeper-reinvest".into(),
+                        error: format!("Invalid pubkey: {}", position_pda),
+                    });
+                }
+            }
+        }
+        Action::SetProtocolFee { fee_bps, treasury } => {
+            use std::str::FromStr;
+            match treasury.as_deref().map(solana_sdk::pubkey::Pubkey::from_str).transpose() {
+                Ok(pk) => app.build_set_protocol_fee(*fee_bps, pk.unwrap_or_default()),
+                Err(_) => {
+                    return Some(CliOutput::Error {
+                        action: "set-protocol-fee".into(),
+                        error: format!("Invalid pubkey: {}", treasury.as_deref().unwrap_or_default()),
+                    });
+                }
+            }
+        }
+        Action::Pause { nav_mint } | Action::Unpause { nav_mint } => {
+            use std::str::FromStr;
+            let paused = matches!(action, Action::Pause { .. });
+            match nav_mint.as_deref().map(solana_sdk::pubkey::Pubkey::from_str).transpose() {
+                Ok(pk) => app.build_set_paused(pk, paused),
+                Err(_) => {
+                    return Some(CliOutput::Error {
+                        action: action_to_name(action),
+                        error: format!("Invalid pubkey: {}", nav_mint.as_deref().unwrap_or_default()),
+                    });
+                }
+            }
+        }
         Action::CreateCollection { ref uri } => {
             if app.collection.is_some() {
                 return Some(CliOutput::Noop {
@@ -791,12 +1075,81 @@ fn populate_and_build(app: &mut app::App, action: &Action, markets_file: Option<
             app.form_fields = vec![("Amount (SOL)".into(), sol_amount_to_field(*amount))];
             app.build_repay();
         }
-        Action::Reinvest => {
-            app.build_reinvest(0);
+        Action::Reinvest { fraction_bps, target_ltv_bps, min_amount } => {
+            let mode = match target_ltv_bps {
+                Some(bps) => hardig::state::ReinvestMode::TargetLtv { bps: *bps },
+                None => hardig::state::ReinvestMode::Fraction { bps: fraction_bps.unwrap_or(10_000) },
+            };
+            let min_amount = match app::parse_sol_to_lamports(&sol_amount_to_field(*min_amount)) {
+                Some(v) => v,
+                None => {
+                    return Some(CliOutput::Error {
+                        action: "reinvest".into(),
+                        error: "Invalid --min-amount".into(),
+                    });
+                }
+            };
+            app.build_reinvest(0, mode, min_amount);
+        }
+        Action::Unwind { amount, target_debt } => {
+            app.form_fields = vec![
+                ("Sell Amount".into(), sol_amount_to_field(*amount)),
+                ("Target Debt (SOL)".into(), sol_amount_to_field(*target_debt)),
+            ];
+            app.build_unwind();
         }
         Action::Heartbeat => {
             app.build_heartbeat();
         }
+        Action::SyncPosition => {
+            app.build_sync_position();
+        }
+        Action::ClosePosition => {
+            app.build_close_position();
+        }
+        Action::SetPayout { address } => {
+            app.form_fields = vec![("Payout Address (pubkey)".into(), address.clone().unwrap_or_default())];
+            app.build_set_payout();
+        }
+        Action::ConfigureLeverage { max_pct } => {
+            app.form_fields = vec![("Max Debt/Floor (%)".into(), max_pct.to_string())];
+            app.build_configure_leverage();
+        }
+        Action::ConfigureCosign { sell_threshold, borrow_threshold, required, approvers } => {
+            app.form_fields = vec![
+                ("Sell Threshold (navSOL)".into(), sol_amount_to_field(*sell_threshold)),
+                ("Borrow Threshold (SOL)".into(), sol_amount_to_field(*borrow_threshold)),
+                ("Required Approvals".into(), required.to_string()),
+                ("Approver Key Assets (comma-separated)".into(), approvers.clone()),
+            ];
+            app.build_configure_cosign();
+        }
+        Action::ConfigureWithdrawQueue { delay_hours, threshold } => {
+            app.form_fields = vec![
+                ("Delay (hours)".into(), delay_hours.to_string()),
+                ("Threshold (navSOL)".into(), sol_amount_to_field(*threshold)),
+            ];
+            app.build_configure_withdraw_queue();
+        }
+        Action::ConfigureKeeper { bounty_bps, max_bounty, min_borrow, max_spread_bps, fraction_bps, target_ltv_bps } => {
+            app.form_fields = vec![
+                ("Bounty (bps)".into(), bounty_bps.to_string()),
+                ("Max Bounty (SOL)".into(), sol_amount_to_field(*max_bounty)),
+                ("Min Borrow (SOL)".into(), sol_amount_to_field(*min_borrow)),
+                ("Max Spread (bps)".into(), max_spread_bps.to_string()),
+                ("Borrow Fraction (bps)".into(), fraction_bps.map(|b| b.to_string()).unwrap_or_default()),
+                ("Target LTV (bps)".into(), target_ltv_bps.map(|b| b.to_string()).unwrap_or_default()),
+            ];
+            app.build_configure_keeper();
+        }
+        Action::ExecuteWithdraw { request_id } => {
+            app.form_fields = vec![("Request ID".into(), request_id.to_string())];
+            app.build_execute_withdraw();
+        }
+        Action::CancelWithdraw { request_id } => {
+            app.form_fields = vec![("Request ID".into(), request_id.to_string())];
+            app.build_cancel_withdraw();
+        }
         Action::ConfigureRecovery { target_wallet, days, hours, minutes, lock_config, name } => {
             app.form_fields = vec![
                 ("Target Wallet (pubkey)".into(), target_wallet.clone()),
@@ -811,7 +1164,7 @@ fn populate_and_build(app: &mut app::App, action: &Action, markets_file: Option<
         Action::ExecuteRecovery => {
             app.build_execute_recovery();
         }
-        Action::AuthorizeKey { wallet, permissions, sell_capacity, sell_refill_slots, borrow_capacity, borrow_refill_slots, name } => {
+        Action::AuthorizeKey { wallet, permissions, sell_capacity, sell_refill_slots, borrow_capacity, borrow_refill_slots, expires_in_days, name } => {
             app.form_fields = vec![
                 ("Target Wallet (pubkey)".into(), wallet.clone()),
                 ("Permissions".into(), permissions.to_string()),
@@ -819,6 +1172,7 @@ fn populate_and_build(app: &mut app::App, action: &Action, markets_file: Option<
                 ("Sell Refill Period (slots)".into(), sell_refill_slots.to_string()),
                 ("Borrow Capacity (SOL, 0=none)".into(), sol_amount_to_field(*borrow_capacity)),
                 ("Borrow Refill Period (slots)".into(), borrow_refill_slots.to_string()),
+                ("Expires In Days (0=never)".into(), expires_in_days.to_string()),
                 ("Label (optional)".into(), name.clone().unwrap_or_default()),
             ];
             app.build_authorize_key();
@@ -246,7 +246,19 @@ fn draw_promo_list(frame: &mut Frame, app: &App, area: Rect) {
     let mut rows: Vec<Row> = Vec::new();
     for (i, entry) in app.promos.iter().enumerate() {
         let marker = if i == app.promo_cursor { ">" } else { " " };
-        let status = if entry.config.active { "Active" } else { "Paused" };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let status = if !entry.config.active {
+            "Paused"
+        } else if now < entry.config.starts_at {
+            "Upcoming"
+        } else if entry.config.ends_at != 0 && now >= entry.config.ends_at {
+            "Ended"
+        } else {
+            "Active"
+        };
         let claims = if entry.config.max_claims == 0 {
             format!("{} / unlimited", entry.config.claims_count)
         } else {
@@ -258,7 +270,7 @@ fn draw_promo_list(frame: &mut Frame, app: &App, area: Rect) {
         } else {
             Style::default()
         };
-        let status_style = if entry.config.active {
+        let status_style = if status == "Active" {
             Style::default().fg(Color::Green)
         } else {
             Style::default().fg(Color::Red)
@@ -299,6 +311,11 @@ fn draw_promo_list(frame: &mut Frame, app: &App, area: Rect) {
                 app::lamports_to_sol(entry.config.min_deposit_lamports),
             ));
         }
+        if let Some(window) =
+            hardig::instructions::format_promo_window(entry.config.starts_at, entry.config.ends_at)
+        {
+            detail_parts.push(format!("Claims: {}", window));
+        }
         if !detail_parts.is_empty() {
             rows.push(
                 Row::new(vec![
@@ -480,6 +497,93 @@ fn draw_position_panel(frame: &mut Frame, app: &App, area: Rect) {
         ]));
     }
 
+    // Emergency pause line
+    if app.is_paused() {
+        let scope = if app.protocol_paused { "Protocol" } else { "Market" };
+        lines.push(Line::from(vec![Span::styled(
+            format!("  {} PAUSED: only repay, unwind and heartbeat are available", scope),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )]));
+    }
+
+    // Retired market line
+    if app.is_retired() {
+        lines.push(Line::from(vec![Span::styled(
+            "  Market RETIRED: repay, unwind and sell only",
+            Style::default().fg(Color::Red),
+        )]));
+    }
+
+    // Pinned payout line
+    if pos.payout_address != solana_sdk::pubkey::Pubkey::default() {
+        lines.push(Line::from(vec![
+            Span::styled("  Payout: ", Style::default().fg(Color::Gray)),
+            Span::raw(app::short_pubkey(&pos.payout_address)),
+        ]));
+    }
+
+    // Leverage ceiling line
+    if pos.max_debt_to_floor_bps > 0 {
+        lines.push(Line::from(vec![
+            Span::styled("  Max Debt/Floor: ", Style::default().fg(Color::Gray)),
+            Span::raw(format!("{}%", pos.max_debt_to_floor_bps as f64 / 100.0)),
+        ]));
+    }
+
+    // Co-signing policy line
+    if pos.cosign_required > 0 {
+        let approvers = pos.cosign_approvers.iter().filter(|a| **a != solana_sdk::pubkey::Pubkey::default()).count();
+        lines.push(Line::from(vec![
+            Span::styled("  Co-sign: ", Style::default().fg(Color::Gray)),
+            Span::raw(format!(
+                "{} of {} above {} navSOL / {} SOL per day",
+                pos.cosign_required,
+                approvers,
+                app::lamports_to_sol(pos.cosign_sell_threshold),
+                app::lamports_to_sol(pos.cosign_borrow_threshold),
+            )),
+        ]));
+    }
+
+    // Withdrawal queue line
+    if pos.withdraw_delay_secs > 0 {
+        lines.push(Line::from(vec![
+            Span::styled("  Sell Queue: ", Style::default().fg(Color::Gray)),
+            Span::raw(format!(
+                "{}h delay above {} navSOL ({} requested)",
+                pos.withdraw_delay_secs as f64 / 3600.0,
+                app::lamports_to_sol(pos.withdraw_queue_threshold),
+                pos.withdraw_request_count,
+            )),
+        ]));
+    }
+
+    // Keeper bounty line
+    if pos.keeper_bounty_bps > 0 {
+        lines.push(Line::from(vec![
+            Span::styled("  Keeper: ", Style::default().fg(Color::Gray)),
+            Span::raw(format!(
+                "{}% bounty (max {} SOL) above {} SOL, spread <= {} bps",
+                pos.keeper_bounty_bps as f64 / 100.0,
+                app::lamports_to_sol(pos.keeper_bounty_max),
+                app::lamports_to_sol(pos.keeper_min_borrow),
+                pos.keeper_max_spread_bps,
+            )),
+        ]));
+    }
+
+    // Share token line
+    if let Some(mint) = app.share_mint() {
+        lines.push(Line::from(vec![
+            Span::styled("  Share Token: ", Style::default().fg(Color::Gray)),
+            Span::raw(format!(
+                "{} (you hold {})",
+                app::short_pubkey(&mint),
+                app::lamports_to_sol(app.share_token_balance.unwrap_or(0)),
+            )),
+        ]));
+    }
+
     let para = Paragraph::new(Text::from(lines));
     frame.render_widget(para, inner);
 }
@@ -593,6 +697,15 @@ fn draw_keyring_panel(frame: &mut Frame, app: &App, area: Rect) {
                     hardig::instructions::format_sol_amount(k.total_borrow_limit),
                 )));
             }
+            if k.principal_refundable {
+                let nav = app.market_config.as_ref().map(|mc| app::nav_token_name(&mc.nav_mint)).unwrap_or("shares");
+                rows.push(sub_row(format!(
+                    "Principal: {} {} (debt {} SOL)",
+                    hardig::instructions::format_sol_amount(k.principal_shares),
+                    nav,
+                    hardig::instructions::format_sol_amount(k.debt),
+                )));
+            }
         }
     }
 
@@ -633,6 +746,15 @@ fn draw_form(frame: &mut Frame, app: &App, area: Rect) {
         Some(FormKind::Sell) => format!(" Sell {} ", nav),
         Some(FormKind::Borrow) => " Borrow ".to_string(),
         Some(FormKind::Repay) => " Repay ".to_string(),
+        Some(FormKind::Unwind) => " Unwind ".to_string(),
+        Some(FormKind::SetPayout) => " Set Payout Address ".to_string(),
+        Some(FormKind::ConfigureLeverage) => " Configure Leverage Ceiling ".to_string(),
+        Some(FormKind::RedeemShares) => " Redeem Share Tokens ".to_string(),
+        Some(FormKind::ConfigureCosign) => " Configure Co-signing ".to_string(),
+        Some(FormKind::ConfigureWithdrawQueue) => " Configure Withdrawal Queue ".to_string(),
+        Some(FormKind::ConfigureKeeper) => " Configure Keeper Bounty ".to_string(),
+        Some(FormKind::ExecuteWithdraw) => " Execute Queued Sell ".to_string(),
+        Some(FormKind::CancelWithdraw) => " Cancel Queued Sell ".to_string(),
         Some(FormKind::ConfigureRecovery) => " Configure Recovery ".to_string(),
         Some(FormKind::CreatePromo) => " Create Promo ".to_string(),
         Some(FormKind::UpdatePromo) => " View Promo ".to_string(),
@@ -689,7 +811,7 @@ fn draw_form(frame: &mut Frame, app: &App, area: Rect) {
         if (matches!(app.form_kind, Some(FormKind::AuthorizeKey)) || matches!(app.form_kind, Some(FormKind::CreatePromo))) && i == 1 {
             lines.push(Line::from(Span::styled("  Permissions:", label_style)));
             let bits = app.perm_bits;
-            let perms: [(u8, &str); 7] = [
+            let perms: [(u16, &str); 9] = [
                 (hardig::state::PERM_BUY, "1 Buy"),
                 (hardig::state::PERM_SELL, "2 Sell"),
                 (hardig::state::PERM_BORROW, "3 Borrow"),
@@ -697,9 +819,11 @@ fn draw_form(frame: &mut Frame, app: &App, area: Rect) {
                 (hardig::state::PERM_REINVEST, "5 Reinvest"),
                 (hardig::state::PERM_LIMITED_SELL, "6 LtdSell"),
                 (hardig::state::PERM_LIMITED_BORROW, "7 LtdBorrow"),
+                (hardig::state::PERM_UNWIND, "8 Unwind"),
+                (hardig::state::PERM_GUARDIAN, "9 Guardian"),
             ];
-            // Render permissions on two rows: 0-4 on first, 5-6 on second
-            for row_range in [0..5, 5..7] {
+            // Render permissions on two rows: 0-4 on first, 5-8 on second
+            for row_range in [0..5, 5..9] {
                 let mut spans = vec![Span::raw("    ")];
                 for idx in row_range {
                     let (perm, name) = perms[idx];
@@ -802,7 +926,7 @@ fn draw_form(frame: &mut Frame, app: &App, area: Rect) {
     let hints = if app.form_locked {
         "  [Esc] Back"
     } else if app.form_readonly && matches!(app.form_kind, Some(FormKind::UpdatePromo)) {
-        "  [Enter] Toggle active/paused  [m] Change max claims  [Esc] Back"
+        "  [Enter] Toggle active/paused  [m] Change max claims  [c] Close  [Esc] Back"
     } else if app.form_readonly {
         "  [Enter] Edit  [Esc] Back"
     } else if on_perm_field {
@@ -1035,6 +1159,10 @@ fn draw_action_bar(frame: &mut Frame, app: &App, area: Rect) {
                 if app.can_borrow() { row1.extend([action_key("[d]"), action_label("borrow  ")]); }
                 if app.can_repay() { row1.extend([action_key("[p]"), action_label("repay  ")]); }
                 if app.can_reinvest() { row1.extend([action_key("[i]"), action_label("reinvest  ")]); }
+                if app.can_unwind() { row1.extend([action_key("[u]"), action_label("nwind  ")]); }
+                if app.can_sell() && app.has_queued_withdraws() { row1.extend([action_key("[w]"), action_label("ithdraw queued  ")]); }
+                if app.can_cancel_withdraw() { row1.extend([action_key("[W]"), action_label(" cancel queued  ")]); }
+                if app.can_redeem_shares() { row1.extend([action_key("[R]"), action_label("edeem shares  ")]); }
                 if row1.is_empty() {
                     row1.push(Span::styled(" No actions available", Style::default().fg(Color::DarkGray)));
                 }
@@ -1047,11 +1175,23 @@ fn draw_action_bar(frame: &mut Frame, app: &App, area: Rect) {
                     row2.extend([action_key("[h]"), action_label("beat  ")]);
                     row2.extend([action_key("[c]"), action_label("recovery  ")]);
                     row2.extend([action_key("[P]"), action_label("romo  ")]);
+                    row2.extend([action_key("[o]"), action_label("payout  ")]);
+                    row2.extend([action_key("[L]"), action_label("everage  ")]);
+                    row2.extend([action_key("[M]"), action_label("-of-N  ")]);
+                    row2.extend([action_key("[Q]"), action_label("ueue  ")]);
+                    row2.extend([action_key("[K]"), action_label("eeper  ")]);
+                    row2.extend([action_key("[X]"), action_label("close  ")]);
+                    if app.share_mint().is_none() {
+                        row2.extend([action_key("[T]"), action_label("okenize  ")]);
+                    }
                 }
                 // Execute recovery is available to anyone holding a recovery key
                 if app.position.as_ref().map(|p| p.recovery_asset != solana_sdk::pubkey::Pubkey::default()).unwrap_or(false) {
                     row2.extend([action_key("[e]"), action_label("recover  ")]);
                 }
+                if app.position.is_some() {
+                    row2.extend([action_key("[S]"), action_label("ync  ")]);
+                }
                 row2.extend([action_key("[n]"), action_label("ew  ")]);
                 row2.extend([action_key("[r]"), action_label("efresh  ")]);
                 row2.extend([action_key("[q]"), action_label("uit")]);
@@ -105,4 +105,164 @@ pub enum HardigError {
     UntrustedProvider,
     #[msg("Artwork receipt position_seed does not match this position")]
     ArtworkReceiptPositionMismatch,
+
+    // Key update errors
+    #[msg("Cannot modify the admin key's permissions or limits")]
+    CannotModifyAdminKey,
+
+    // Key expiry errors
+    #[msg("Key has expired")]
+    KeyExpired,
+    #[msg("Key has no expiry or has not expired yet")]
+    KeyNotExpired,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+
+    // Position close errors
+    #[msg("Position still has Mayflower debt, deposited shares or navSOL")]
+    PositionNotEmpty,
+    #[msg("Account to close is not a KeyState or PromoConfig of this position")]
+    InvalidCloseAccount,
+
+    // Unwind errors
+    #[msg("Position debt is already at or below the target debt")]
+    DebtAtOrBelowTarget,
+
+    // Payout errors
+    #[msg("Payout account does not match the position's payout address")]
+    PayoutMismatch,
+    #[msg("Payout address cannot be the position's program PDA")]
+    InvalidPayoutAddress,
+
+    // Reinvest mode errors
+    #[msg("Reinvest fraction or target LTV must be 1-10000 bps")]
+    InvalidReinvestBps,
+
+    // Leverage ceiling errors
+    #[msg("Max debt-to-floor must be 0 (no ceiling) or 1-10000 bps")]
+    InvalidLeverageCeiling,
+    #[msg("Debt would exceed the position's max debt-to-floor ratio")]
+    LeverageCeilingExceeded,
+
+    // Co-signing errors
+    #[msg("Invalid co-signing policy (approvers, required count or thresholds)")]
+    InvalidCosignPolicy,
+    #[msg("Not enough distinct approver keys co-signed this action")]
+    CosignRequired,
+    #[msg("Co-signer is not a distinct approver key or did not sign")]
+    InvalidCosigner,
+
+    // Withdrawal queue errors
+    #[msg("Withdraw past the queue allowance must use request_withdraw")]
+    WithdrawMustBeQueued,
+    #[msg("Queued withdraw delay has not elapsed yet")]
+    WithdrawNotReady,
+    #[msg("Withdraw delay must be 0-30 days")]
+    InvalidWithdrawDelay,
+
+    // Pause errors
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Market is paused")]
+    MarketPaused,
+
+    // Market lifecycle errors
+    #[msg("Market is retired")]
+    MarketRetired,
+    #[msg("No MarketConfig update is staged")]
+    NoPendingMarketUpdate,
+    #[msg("Staged MarketConfig update is not ready yet")]
+    MarketUpdateNotReady,
+    #[msg("MarketConfig update delay must be 0-30 days")]
+    InvalidMarketUpdateDelay,
+
+    // Market validation errors
+    #[msg("Market accounts do not match the Mayflower market metadata")]
+    InvalidMarketAccounts,
+
+    // Protocol fee errors
+    #[msg("Protocol fee exceeds the maximum")]
+    ProtocolFeeTooHigh,
+    #[msg("Treasury account missing or invalid")]
+    InvalidTreasury,
+
+    // Keeper errors
+    #[msg("Position does not accept keeper reinvests")]
+    KeeperNotEnabled,
+    #[msg("Invalid keeper policy")]
+    InvalidKeeperConfig,
+    #[msg("Keeper reinvest must use the position's keeper mode")]
+    KeeperModeMismatch,
+
+    // Promo allowlist errors
+    #[msg("Claimer is not on the promo allowlist")]
+    NotAllowlisted,
+
+    // Promo gate errors
+    #[msg("Promo requires a gating NFT from its collection")]
+    GateAssetRequired,
+    #[msg("Gating NFT is not held by the claimer or not in the promo's collection")]
+    InvalidGateAsset,
+
+    // Promo window errors
+    #[msg("Promo claim window is invalid")]
+    InvalidPromoWindow,
+    #[msg("Promo has not started yet")]
+    PromoNotStarted,
+    #[msg("Promo has ended")]
+    PromoEnded,
+
+    // Promo budget errors
+    #[msg("Invalid promo budget")]
+    InvalidPromoBudget,
+    #[msg("Promo key must be used with the PromoConfig it was claimed from")]
+    PromoBudgetMismatch,
+    #[msg("Promo campaign budget exceeded")]
+    PromoBudgetExceeded,
+
+    // Promo referral errors
+    #[msg("Referrer must hold a key of this position and differ from the claimer")]
+    InvalidReferrer,
+    #[msg("Referrer's KeyState is required to credit the referral reward")]
+    ReferrerKeyStateRequired,
+
+    // Promo close errors
+    #[msg("Promo must be deactivated before it is closed")]
+    PromoStillActive,
+    #[msg("Receipts can only be closed once their promo is closed")]
+    PromoNotClosed,
+
+    // Promo principal errors
+    #[msg("Withdrawal exceeds the key's refundable principal")]
+    PrincipalExceeded,
+
+    // Share token errors
+    #[msg("Position has no share token")]
+    ShareTokenNotEnabled,
+    #[msg("Share token accounts do not match the position's share mint or the depositor")]
+    ShareMintMismatch,
+    #[msg("Position has no net value to price share tokens against")]
+    NoShareValue,
+
+    // Account migration errors
+    #[msg("Account is not a Härdig account of the expected type")]
+    InvalidMigrationAccount,
+
+    // Share token policy errors
+    #[msg("Position has a share token: value leaves only through redeem_shares")]
+    ShareTokenEnabled,
+    #[msg("Share tokens cannot be combined with co-signing, a withdraw queue or a payout address")]
+    ShareTokenPolicyConflict,
+
+    // Unwind sizing errors
+    #[msg("Unwind sells more navSOL than repaying down to target_debt needs at the floor price")]
+    UnwindExceedsRepay,
+
+    // Position close errors
+    #[msg("Position has queued withdraw requests; execute or cancel them first")]
+    WithdrawRequestsPending,
+
+    // Promo referral cap errors
+    #[msg("A referral bonus needs a nonzero max_referrals_per_referrer")]
+    InvalidReferralReward,
 }
@@ -0,0 +1,495 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{MarketUpdate, PromoBudget, ReferralReward, ReinvestMode};
+
+// ---------------------------------------------------------------------------
+// Anchor events emitted by state-changing instructions.
+//
+// Amounts are the actual deltas measured around each Mayflower CPI (not the
+// requested amounts), so an indexer can rebuild position history from events
+// alone. `position` is always the PositionState PDA.
+// ---------------------------------------------------------------------------
+
+/// Emitted by `create_position`.
+#[event]
+pub struct PositionCreated {
+    pub position: Pubkey,
+    /// The position's authority_seed (first admin asset).
+    pub admin_asset: Pubkey,
+    pub market_config: Pubkey,
+    pub artwork_id: Option<Pubkey>,
+}
+
+/// Emitted by `close_position`.
+#[event]
+pub struct PositionClosed {
+    pub position: Pubkey,
+    /// The burned admin asset.
+    pub admin_asset: Pubkey,
+    /// The burned recovery asset (Pubkey::default() = none configured).
+    pub recovery_asset: Pubkey,
+    /// Number of KeyState / PromoConfig accounts closed from remaining_accounts.
+    pub closed_accounts: u32,
+}
+
+/// Emitted by `buy`.
+#[event]
+pub struct Deposited {
+    pub position: Pubkey,
+    pub key_asset: Pubkey,
+    pub permissions: u16,
+    /// Lamports sent into the Mayflower buy.
+    pub amount: u64,
+    /// navSOL shares actually credited to the position.
+    pub shares_received: u64,
+    /// Position's deposited_nav after the buy.
+    pub deposited_nav: u64,
+    /// Share tokens minted to the depositor (0 if the position has no share token).
+    pub share_tokens_minted: u64,
+}
+
+/// Emitted by `withdraw`.
+#[event]
+pub struct Withdrawn {
+    pub position: Pubkey,
+    pub key_asset: Pubkey,
+    pub permissions: u16,
+    /// navSOL shares actually sold.
+    pub shares_sold: u64,
+    /// wSOL received from the sell (paid out as native SOL).
+    pub sol_received: u64,
+    /// Position's deposited_nav after the sell.
+    pub deposited_nav: u64,
+    /// Account the SOL was paid out to (signer or pinned payout address).
+    pub recipient: Pubkey,
+}
+
+/// Emitted by `borrow`.
+#[event]
+pub struct Borrowed {
+    pub position: Pubkey,
+    pub key_asset: Pubkey,
+    pub permissions: u16,
+    /// Increase in Mayflower debt.
+    pub actual_borrowed: u64,
+    /// Position's user_debt after the borrow.
+    pub user_debt: u64,
+    /// Account the SOL was paid out to (signer or pinned payout address).
+    pub recipient: Pubkey,
+}
+
+/// Emitted by `repay`.
+#[event]
+pub struct Repaid {
+    pub position: Pubkey,
+    pub key_asset: Pubkey,
+    pub permissions: u16,
+    /// Decrease in Mayflower debt.
+    pub actual_repaid: u64,
+    /// Position's user_debt after the repay.
+    pub user_debt: u64,
+}
+
+/// Emitted by `reinvest` whenever the borrow leg runs, including when nothing is left to
+/// invest after fees (`amount_invested` = 0). Not emitted when there is nothing to borrow.
+#[event]
+pub struct Reinvested {
+    pub position: Pubkey,
+    pub key_asset: Pubkey,
+    pub permissions: u16,
+    /// Increase in Mayflower debt from the borrow leg.
+    pub actual_borrowed: u64,
+    /// wSOL spent on the buy leg (borrowed amount net of fees).
+    pub amount_invested: u64,
+    /// navSOL shares received from the buy leg.
+    pub shares_received: u64,
+    /// Position's deposited_nav after the reinvest.
+    pub deposited_nav: u64,
+    /// Position's user_debt after the reinvest.
+    pub user_debt: u64,
+    /// Base token sent to the treasury before the buy leg (not included in amount_invested).
+    pub protocol_fee: u64,
+    /// Base token paid to a keyless keeper caller (not included in amount_invested).
+    pub keeper_bounty: u64,
+}
+
+#[event]
+pub struct ProtocolFeeCollected {
+    pub position: Pubkey,
+    /// Treasury wallet; the fee lands in its ATA for `mint`.
+    pub treasury: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub fee_bps: u16,
+}
+
+/// Emitted by `unwind`.
+#[event]
+pub struct Unwound {
+    pub position: Pubkey,
+    pub key_asset: Pubkey,
+    pub permissions: u16,
+    /// navSOL shares actually sold.
+    pub shares_sold: u64,
+    /// wSOL received from the sale.
+    pub sol_received: u64,
+    /// Debt actually repaid from the ATA.
+    pub actual_repaid: u64,
+    /// wSOL left after the repay, bought back into the position.
+    pub leftover: u64,
+    /// navSOL shares the leftover bought.
+    pub shares_rebought: u64,
+    /// Position's local deposited_nav after the unwind.
+    pub deposited_nav: u64,
+    /// Position's local user_debt after the unwind.
+    pub user_debt: u64,
+}
+
+/// Emitted by `authorize_key`.
+#[event]
+pub struct KeyAuthorized {
+    pub position: Pubkey,
+    /// The admin key that authorized the new key.
+    pub admin_key_asset: Pubkey,
+    pub key_asset: Pubkey,
+    pub target_wallet: Pubkey,
+    pub permissions: u16,
+}
+
+/// Emitted by `revoke_key`.
+#[event]
+pub struct KeyRevoked {
+    pub position: Pubkey,
+    pub admin_key_asset: Pubkey,
+    /// The burned key asset.
+    pub key_asset: Pubkey,
+}
+
+/// Emitted by `reap_expired_key`.
+#[event]
+pub struct KeyReaped {
+    pub position: Pubkey,
+    /// The burned key asset.
+    pub key_asset: Pubkey,
+    /// The permissionless caller that received the KeyState rent.
+    pub caller: Pubkey,
+    pub expires_at: i64,
+}
+
+/// Emitted by `update_key_limits`.
+#[event]
+pub struct KeyLimitsUpdated {
+    pub position: Pubkey,
+    pub admin_key_asset: Pubkey,
+    pub key_asset: Pubkey,
+    pub sell_bucket_capacity: u64,
+    pub sell_refill_period_slots: u64,
+    pub borrow_bucket_capacity: u64,
+    pub borrow_refill_period_slots: u64,
+    pub total_sell_limit: u64,
+    pub total_borrow_limit: u64,
+}
+
+/// Emitted by `update_key_permissions`.
+#[event]
+pub struct KeyPermissionsUpdated {
+    pub position: Pubkey,
+    pub admin_key_asset: Pubkey,
+    pub key_asset: Pubkey,
+    /// Bitmask before the update.
+    pub old_permissions: u16,
+    /// Bitmask after the update.
+    pub permissions: u16,
+}
+
+/// Emitted by `set_payout_address`.
+#[event]
+pub struct PayoutAddressSet {
+    pub position: Pubkey,
+    /// Pubkey::default() = payouts go to the signer again.
+    pub payout_address: Pubkey,
+}
+
+/// Emitted by `sync_position`. Deltas are new minus old local value (the drift corrected).
+#[event]
+pub struct PositionSynced {
+    pub position: Pubkey,
+    /// The permissionless caller.
+    pub caller: Pubkey,
+    pub deposited_nav: u64,
+    pub user_debt: u64,
+    /// Change in deposited_nav, saturated at the i64 bounds.
+    pub nav_delta: i64,
+    /// Change in user_debt, saturated at the i64 bounds.
+    pub debt_delta: i64,
+}
+
+/// Emitted by `configure_cosign`.
+#[event]
+pub struct CosignConfigured {
+    pub position: Pubkey,
+    pub sell_threshold: u64,
+    pub borrow_threshold: u64,
+    /// 0 = co-signing disabled.
+    pub required: u8,
+    pub approvers: Vec<Pubkey>,
+}
+
+/// Emitted by `configure_withdraw_queue`.
+#[event]
+pub struct WithdrawQueueConfigured {
+    pub position: Pubkey,
+    /// 0 = no withdrawal queue.
+    pub delay_secs: i64,
+    pub threshold: u64,
+}
+
+/// Emitted by `request_withdraw`.
+#[event]
+pub struct WithdrawRequested {
+    pub position: Pubkey,
+    pub request: Pubkey,
+    pub key_asset: Pubkey,
+    pub amount: u64,
+    pub min_out: u64,
+    pub executable_at: i64,
+}
+
+/// Emitted by `cancel_withdraw`. `execute_withdraw` emits `Withdrawn`.
+#[event]
+pub struct WithdrawCancelled {
+    pub position: Pubkey,
+    pub request: Pubkey,
+    /// The guardian or admin key that cancelled it.
+    pub cancelled_by: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted by `configure_leverage`.
+#[event]
+pub struct LeverageConfigured {
+    pub position: Pubkey,
+    /// 0 = no ceiling.
+    pub max_debt_to_floor_bps: u16,
+}
+
+/// Emitted by `configure_keeper`.
+#[event]
+pub struct KeeperConfigured {
+    pub position: Pubkey,
+    /// 0 = keepers disabled.
+    pub bounty_bps: u16,
+    pub max_bounty: u64,
+    pub min_borrow: u64,
+    pub max_spread_bps: u16,
+    pub mode: ReinvestMode,
+}
+
+/// Emitted by `configure_recovery`.
+#[event]
+pub struct RecoveryConfigured {
+    pub position: Pubkey,
+    pub recovery_asset: Pubkey,
+    pub lockout_secs: i64,
+    pub locked: bool,
+}
+
+/// Emitted by `execute_recovery`.
+#[event]
+pub struct RecoveryExecuted {
+    pub position: Pubkey,
+    /// The burned recovery key.
+    pub recovery_asset: Pubkey,
+    /// The burned admin key.
+    pub old_admin_asset: Pubkey,
+    /// The newly minted admin key.
+    pub new_admin_asset: Pubkey,
+}
+
+/// Emitted by `heartbeat`.
+#[event]
+pub struct PositionHeartbeat {
+    pub position: Pubkey,
+    /// The position's new last_admin_activity (unix timestamp).
+    pub last_admin_activity: i64,
+}
+
+/// Emitted by `set_position_artwork`.
+#[event]
+pub struct PositionArtworkSet {
+    pub position: Pubkey,
+    /// None = artwork cleared.
+    pub artwork_id: Option<Pubkey>,
+}
+
+/// Emitted by `create_promo`.
+#[event]
+pub struct PromoCreated {
+    pub position: Pubkey,
+    pub promo: Pubkey,
+    pub permissions: u16,
+    pub max_claims: u32,
+    pub merkle_root: [u8; 32],
+    pub gate_collection: Pubkey,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub budget: PromoBudget,
+    pub referral_reward: ReferralReward,
+    pub refundable_principal: bool,
+}
+
+/// Emitted by `update_promo`.
+#[event]
+pub struct PromoUpdated {
+    pub position: Pubkey,
+    pub promo: Pubkey,
+    pub active: bool,
+    pub max_claims: u32,
+    pub merkle_root: [u8; 32],
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+/// Emitted by `claim_promo_key`.
+#[event]
+pub struct PromoKeyClaimed {
+    pub position: Pubkey,
+    pub promo: Pubkey,
+    pub claimer: Pubkey,
+    pub key_asset: Pubkey,
+    pub permissions: u16,
+    /// Lamports deposited through the Mayflower buy (0 = no deposit).
+    pub amount: u64,
+    /// navSOL shares credited to the position by the deposit.
+    pub shares_received: u64,
+    /// Promo claims_count after this claim.
+    pub claims_count: u32,
+    /// NFT (asset or mint) spent on the promo's collection gate (default = ungated).
+    pub gate_asset: Pubkey,
+    /// Wallet that referred the claimer (default = no referrer).
+    pub referrer: Pubkey,
+}
+
+/// Emitted by `close_promo`.
+#[event]
+pub struct PromoClosed {
+    pub position: Pubkey,
+    pub promo: Pubkey,
+    pub claims_count: u32,
+    /// Lamports returned to the admin (the tombstone keeps its own rent).
+    pub refunded: u64,
+}
+
+/// Emitted by `close_claim_receipts`.
+#[event]
+pub struct ClaimReceiptsClosed {
+    pub promo: Pubkey,
+    /// Number of ClaimReceipt / GateReceipt accounts closed.
+    pub closed: u32,
+}
+
+/// Emitted by `enable_share_mint`.
+#[event]
+pub struct ShareMintEnabled {
+    pub position: Pubkey,
+    pub share_mint: Pubkey,
+}
+
+/// Emitted by `redeem_shares`.
+#[event]
+pub struct SharesRedeemed {
+    pub position: Pubkey,
+    pub holder: Pubkey,
+    pub tokens_burned: u64,
+    /// navSOL shares sold for the holder.
+    pub shares_sold: u64,
+    /// Lamports paid out to the holder.
+    pub sol_received: u64,
+    /// Position's deposited_nav after the redemption.
+    pub deposited_nav: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Protocol-level admin events (ProtocolConfig / MarketConfig / TrustedProvider).
+// ---------------------------------------------------------------------------
+
+/// Emitted by `transfer_admin`. The transfer completes on `accept_admin`.
+#[event]
+pub struct AdminTransferStarted {
+    pub admin: Pubkey,
+    /// Pubkey::default() = pending transfer cancelled.
+    pub pending_admin: Pubkey,
+}
+
+/// Emitted by `accept_admin`.
+#[event]
+pub struct AdminTransferred {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+/// Emitted by `create_market_config`.
+#[event]
+pub struct MarketConfigCreated {
+    pub market_config: Pubkey,
+    pub nav_mint: Pubkey,
+    pub base_mint: Pubkey,
+    pub mayflower_market: Pubkey,
+}
+
+/// Emitted by `update_market_config` when the update is staged behind a notice period.
+#[event]
+pub struct MarketConfigUpdateQueued {
+    pub market_config: Pubkey,
+    pub update: MarketUpdate,
+    /// When `apply_market_config_update` may apply it (unix timestamp).
+    pub applies_at: i64,
+}
+
+/// Emitted by `update_market_config` (no notice period) and `apply_market_config_update`.
+#[event]
+pub struct MarketConfigUpdateApplied {
+    pub market_config: Pubkey,
+    pub update: MarketUpdate,
+}
+
+/// Emitted by `retire_market_config`.
+#[event]
+pub struct MarketConfigRetired {
+    pub market_config: Pubkey,
+    pub nav_mint: Pubkey,
+}
+
+/// Emitted by `add_trusted_provider`.
+#[event]
+pub struct TrustedProviderAdded {
+    pub program_id: Pubkey,
+    pub added_by: Pubkey,
+}
+
+/// Emitted by `remove_trusted_provider`.
+#[event]
+pub struct TrustedProviderRemoved {
+    pub program_id: Pubkey,
+}
+
+/// Emitted by `set_protocol_paused`.
+#[event]
+pub struct ProtocolPausedSet {
+    pub paused: bool,
+}
+
+/// Emitted by `set_market_paused`.
+#[event]
+pub struct MarketPausedSet {
+    pub market_config: Pubkey,
+    pub paused: bool,
+}
+
+/// Emitted by `set_protocol_fee`.
+#[event]
+pub struct ProtocolFeeSet {
+    /// 0 = fee disabled.
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+}
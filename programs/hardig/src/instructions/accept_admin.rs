@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::HardigError;
+use crate::events::AdminTransferred;
 use crate::state::ProtocolConfig;
 
 #[derive(Accounts)]
@@ -19,7 +20,14 @@ pub struct AcceptAdmin<'info> {
 
 pub fn handler(ctx: Context<AcceptAdmin>) -> Result<()> {
     let config = &mut ctx.accounts.config;
+    let old_admin = config.admin;
     config.admin = config.pending_admin;
     config.pending_admin = Pubkey::default();
+
+    emit!(AdminTransferred {
+        old_admin,
+        new_admin: config.admin,
+    });
+
     Ok(())
 }
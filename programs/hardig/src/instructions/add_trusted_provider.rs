@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::HardigError;
+use crate::events::TrustedProviderAdded;
 use crate::state::{ProtocolConfig, TrustedProvider};
 
 #[derive(Accounts)]
@@ -36,5 +37,11 @@ pub fn handler(ctx: Context<AddTrustedProvider>, program_id: Pubkey) -> Result<(
     tp.added_by = ctx.accounts.admin.key();
     tp.active = true;
     tp.bump = ctx.bumps.trusted_provider;
+
+    emit!(TrustedProviderAdded {
+        program_id,
+        added_by: tp.added_by,
+    });
+
     Ok(())
 }
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::MarketConfigUpdateApplied;
+use crate::state::MarketConfig;
+
+use super::update_market_config::validate_market_update;
+
+#[derive(Accounts)]
+pub struct ApplyMarketConfigUpdate<'info> {
+    /// Anyone can apply a staged update once its notice period has passed.
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [MarketConfig::SEED, market_config.nav_mint.as_ref()],
+        bump = market_config.bump,
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+
+    /// The market's Mayflower metadata.
+    /// CHECK: Validated in handler via validate_market_update.
+    pub mayflower_market_meta: UncheckedAccount<'info>,
+
+    /// The staged update's Mayflower market.
+    /// CHECK: Validated in handler via validate_market_update.
+    pub mayflower_market: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<ApplyMarketConfigUpdate>) -> Result<()> {
+    let mc = &mut ctx.accounts.market_config;
+    require!(mc.pending_update_at != 0, HardigError::NoPendingMarketUpdate);
+    require!(
+        Clock::get()?.unix_timestamp >= mc.pending_update_at,
+        HardigError::MarketUpdateNotReady
+    );
+
+    validate_market_update(
+        mc,
+        &mc.pending_update,
+        &ctx.accounts.mayflower_market_meta,
+        &ctx.accounts.mayflower_market,
+    )?;
+
+    let update = std::mem::take(&mut mc.pending_update);
+    mc.apply_update(&update);
+    mc.pending_update_at = 0;
+
+    emit!(MarketConfigUpdateApplied {
+        market_config: mc.key(),
+        update,
+    });
+
+    Ok(())
+}
@@ -11,13 +11,17 @@ use mpl_core::{
 };
 
 use crate::errors::HardigError;
+use crate::events::KeyAuthorized;
 use crate::state::{
-    KeyCreatorOrigin, KeyState, PositionState, ProtocolConfig, RateBucket,
+    KeyCreatorOrigin, KeyLimits, KeyState, PositionState, ProtocolConfig, RateBucket,
     PERM_LIMITED_BORROW, PERM_LIMITED_SELL, PERM_MANAGE_KEYS,
 };
 
 use super::validate_key::validate_key;
-use super::{permission_attributes, metadata_uri, format_sol_amount, slots_to_duration, validate_delegated_permissions};
+use super::{
+    expiry_attribute, limit_attributes, metadata_uri, permission_attributes,
+    validate_delegated_permissions, validate_expiry,
+};
 
 #[derive(Accounts)]
 pub struct AuthorizeKey<'info> {
@@ -75,14 +79,10 @@ pub struct AuthorizeKey<'info> {
 
 pub fn handler(
     ctx: Context<AuthorizeKey>,
-    permissions: u8,
-    sell_bucket_capacity: u64,
-    sell_refill_period_slots: u64,
-    borrow_bucket_capacity: u64,
-    borrow_refill_period_slots: u64,
-    total_sell_limit: u64,
-    total_borrow_limit: u64,
+    permissions: u16,
+    limits: KeyLimits,
     name: Option<String>,
+    expires_at: i64,
 ) -> Result<()> {
     // Validate the admin holds their key
     validate_key(
@@ -97,12 +97,12 @@ pub fn handler(
     validate_delegated_permissions(
         KeyCreatorOrigin::Admin,
         permissions,
-        sell_bucket_capacity,
-        sell_refill_period_slots,
-        borrow_bucket_capacity,
-        borrow_refill_period_slots,
-        total_sell_limit,
-        total_borrow_limit,
+        limits.sell_bucket_capacity,
+        limits.sell_refill_period_slots,
+        limits.borrow_bucket_capacity,
+        limits.borrow_refill_period_slots,
+        limits.total_sell_limit,
+        limits.total_borrow_limit,
     )?;
 
     // --- Read admin asset's name and market attribute ---
@@ -135,10 +135,15 @@ pub fn handler(
         ctx.remaining_accounts,
         &ctx.accounts.position.authority_seed,
         ctx.program_id,
-        Some((1, permissions)), // delegate ArtworkImage: key_type=1, permissions=bitmask
+        // delegate ArtworkImage: key_type=1, permissions=bitmask. ArtworkImage seeds carry a
+        // single permissions byte; keys using higher bits fall back to the catch-all image.
+        Some((1, u8::try_from(permissions).unwrap_or(0))),
         true,                   // graceful fallback — don't brick authorize_key if receipt is closed
     )?;
 
+    let clock = Clock::get()?;
+    validate_expiry(expires_at, clock.unix_timestamp)?;
+
     // Build attribute list with human-readable permissions + position binding
     let mut attrs = permission_attributes(permissions);
     attrs.push(Attribute {
@@ -153,26 +158,9 @@ pub fn handler(
         key: "market".to_string(),
         value: admin_market.clone(),
     });
-    let sell_limit_str = if permissions & PERM_LIMITED_SELL != 0 {
-        let v = format!("{} navSOL / {}", format_sol_amount(sell_bucket_capacity), slots_to_duration(sell_refill_period_slots));
-        attrs.push(Attribute { key: "limited_sell".to_string(), value: v.clone() });
-        if total_sell_limit > 0 {
-            attrs.push(Attribute { key: "total_sell_limit".to_string(), value: format!("{} navSOL", format_sol_amount(total_sell_limit)) });
-        }
-        Some(v)
-    } else {
-        None
-    };
-    let borrow_limit_str = if permissions & PERM_LIMITED_BORROW != 0 {
-        let v = format!("{} SOL / {}", format_sol_amount(borrow_bucket_capacity), slots_to_duration(borrow_refill_period_slots));
-        attrs.push(Attribute { key: "limited_borrow".to_string(), value: v.clone() });
-        if total_borrow_limit > 0 {
-            attrs.push(Attribute { key: "total_borrow_limit".to_string(), value: format!("{} SOL", format_sol_amount(total_borrow_limit)) });
-        }
-        Some(v)
-    } else {
-        None
-    };
+    let (limit_attrs, sell_limit_str, borrow_limit_str) = limit_attributes(permissions, &limits);
+    attrs.extend(limit_attrs);
+    attrs.extend(expiry_attribute(expires_at));
 
     // Build NFT name from base + optional suffix
     let base_name = "H\u{00e4}rdig Key";
@@ -223,7 +211,6 @@ pub fn handler(
         ])
         .invoke_signed(&[config_seeds])?;
 
-    let clock = Clock::get()?;
     let current_slot = clock.slot;
 
     // Update last_admin_activity so key management resets the recovery lockout
@@ -237,25 +224,34 @@ pub fn handler(
 
     if permissions & PERM_LIMITED_SELL != 0 {
         key_state.sell_bucket = RateBucket {
-            capacity: sell_bucket_capacity,
-            refill_period: sell_refill_period_slots,
-            level: sell_bucket_capacity, // starts full
+            capacity: limits.sell_bucket_capacity,
+            refill_period: limits.sell_refill_period_slots,
+            level: limits.sell_bucket_capacity, // starts full
             last_update: current_slot,
         };
     }
     if permissions & PERM_LIMITED_BORROW != 0 {
         key_state.borrow_bucket = RateBucket {
-            capacity: borrow_bucket_capacity,
-            refill_period: borrow_refill_period_slots,
-            level: borrow_bucket_capacity, // starts full
+            capacity: limits.borrow_bucket_capacity,
+            refill_period: limits.borrow_refill_period_slots,
+            level: limits.borrow_bucket_capacity, // starts full
             last_update: current_slot,
         };
     }
 
-    key_state.total_sell_limit = total_sell_limit;
+    key_state.total_sell_limit = limits.total_sell_limit;
     key_state.total_sold = 0;
-    key_state.total_borrow_limit = total_borrow_limit;
+    key_state.total_borrow_limit = limits.total_borrow_limit;
     key_state.total_borrowed = 0;
+    key_state.expires_at = expires_at;
+
+    emit!(KeyAuthorized {
+        position: ctx.accounts.position.key(),
+        admin_key_asset: ctx.accounts.admin_key_asset.key(),
+        key_asset: ctx.accounts.new_key_asset.key(),
+        target_wallet: ctx.accounts.target_wallet.key(),
+        permissions,
+    });
 
     Ok(())
 }
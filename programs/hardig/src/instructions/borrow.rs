@@ -5,10 +5,17 @@ use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::Token;
 
 use crate::errors::HardigError;
+use crate::events::Borrowed;
 use crate::mayflower;
-use crate::state::{KeyState, MarketConfig, PositionState, ProtocolConfig, PERM_BORROW, PERM_LIMITED_BORROW};
+use crate::state::{
+    KeyState, MarketConfig, PositionState, ProtocolConfig, PERM_BORROW,
+    PERM_LIMITED_BORROW,
+};
 
+use super::configure_cosign::require_cosigners;
 use super::consume_rate_limit::{consume_rate_limit, consume_total_limit};
+use super::promo::budget::{consume_promo_budget, BudgetKind};
+use super::set_payout_address::payout_destination;
 use super::validate_key::validate_key;
 
 #[derive(Accounts)]
@@ -24,20 +31,26 @@ pub struct Borrow<'info> {
     #[account(mut)]
     pub key_state: Option<Account<'info, KeyState>>,
 
-    /// The position to borrow against.
-    #[account(mut)]
+    /// The position to borrow against. Tokenized positions can't take on debt.
+    #[account(
+        mut,
+        constraint = position.share_mint == Pubkey::default() @ HardigError::ShareTokenEnabled,
+    )]
     pub position: Account<'info, PositionState>,
 
     /// Protocol config PDA — provides collection pubkey for key validation.
     #[account(
         seeds = [ProtocolConfig::SEED],
         bump = config.bump,
+        constraint = !config.paused @ HardigError::ProtocolPaused,
     )]
     pub config: Account<'info, ProtocolConfig>,
 
     /// The MarketConfig for this position's market.
     #[account(
         constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+        constraint = !market_config.paused @ HardigError::MarketPaused,
+        constraint = !market_config.retired @ HardigError::MarketRetired,
     )]
     pub market_config: Account<'info, MarketConfig>,
 
@@ -103,6 +116,19 @@ pub struct Borrow<'info> {
     /// CHECK: Validated in handler via seed derivation.
     #[account(mut)]
     pub log_account: UncheckedAccount<'info>,
+
+    /// The position's pinned payout address. Required iff position.payout_address is set.
+    /// CHECK: Validated in handler against position.payout_address.
+    #[account(mut)]
+    pub payout: Option<UncheckedAccount<'info>>,
+
+    /// PromoConfig the key was claimed from. Required iff key_state.promo is set.
+    /// May be a closed promo's tombstone, so it is deserialized in the handler.
+    /// CHECK: Validated in consume_promo_budget against key_state.promo.
+    #[account(mut)]
+    pub promo: Option<UncheckedAccount<'info>>,
+    // remaining_accounts: (approver wallet, approver key asset) pairs when the
+    // position's co-signing policy applies to this amount.
 }
 
 pub fn handler(ctx: Context<Borrow>, amount: u64) -> Result<()> {
@@ -119,8 +145,26 @@ pub fn handler(ctx: Context<Borrow>, amount: u64) -> Result<()> {
         require!(ks.asset == ctx.accounts.key_asset.key(), HardigError::InvalidKey);
     }
 
+    // SOL goes to the pinned payout address if the admin set one, else to the signer
+    let recipient = payout_destination(
+        &ctx.accounts.position,
+        &ctx.accounts.signer.to_account_info(),
+        ctx.accounts.payout.as_ref(),
+    )?;
+
     require!(amount > 0, HardigError::InsufficientFunds);
 
+    // Borrows past the rolling allowance need approval from the position's co-signers
+    let now = Clock::get()?.unix_timestamp;
+    if ctx.accounts.position.needs_cosign(true, amount, now) {
+        require_cosigners(
+            &ctx.accounts.position,
+            &ctx.accounts.key_asset.key(),
+            ctx.remaining_accounts,
+            &ctx.accounts.config.collection,
+        )?;
+    }
+
     let mc = &ctx.accounts.market_config;
 
     // Validate PDA-derived accounts
@@ -137,7 +181,7 @@ pub fn handler(ctx: Context<Borrow>, amount: u64) -> Result<()> {
     );
 
     if ctx.accounts.key_asset.key() == ctx.accounts.position.current_admin_asset {
-        ctx.accounts.position.last_admin_activity = Clock::get()?.unix_timestamp;
+        ctx.accounts.position.last_admin_activity = now;
     }
 
     let market = mayflower::MarketAddresses {
@@ -202,23 +246,52 @@ pub fn handler(ctx: Context<Borrow>, amount: u64) -> Result<()> {
         .checked_sub(debt_before)
         .ok_or(HardigError::BorrowCapacityExceeded)?;
 
+    // Enforce the admin's leverage ceiling on the resulting debt
+    if ctx.accounts.position.max_debt_to_floor_bps > 0 {
+        let floor_price = {
+            let data = ctx.accounts.mayflower_market.try_borrow_data()?;
+            mayflower::read_floor_price(&data)?
+        };
+        let deposited_shares = {
+            let data = pp_info.try_borrow_data()?;
+            mayflower::read_deposited_shares(&data)?
+        };
+        mayflower::check_debt_to_floor(
+            deposited_shares,
+            floor_price,
+            debt_after,
+            ctx.accounts.position.max_debt_to_floor_bps,
+        )?;
+    }
+
     // Enforce rate + total limits using actual borrowed amount (not requested amount)
     if permissions & PERM_BORROW == 0 && permissions & PERM_LIMITED_BORROW != 0 {
         let key_state = ctx.accounts.key_state.as_deref_mut()
             .ok_or(error!(HardigError::RateLimitExceeded))?;
+        let current_slot = Clock::get()?.slot;
         consume_rate_limit(
             &mut key_state.borrow_bucket,
             actual_borrowed,
-            Clock::get()?.slot,
+            current_slot,
         )?;
         consume_total_limit(
             &mut key_state.total_borrowed,
             key_state.total_borrow_limit,
             actual_borrowed,
         )?;
+        // Promo keys also draw from the campaign-wide budget
+        consume_promo_budget(
+            key_state,
+            ctx.accounts.promo.as_deref(),
+            BudgetKind::Borrow,
+            actual_borrowed,
+            current_slot,
+        )?;
+        // Attribute the debt to the key, so principal-mode keys withdraw net of it
+        key_state.debt = key_state.debt.saturating_add(actual_borrowed);
     }
 
-    // Close PDA's wSOL ATA — returns borrowed wSOL + rent as native SOL to signer
+    // Close PDA's wSOL ATA — returns borrowed wSOL + rent as native SOL to the recipient
     // Only attempt if the account is an initialized SPL token account (state byte at offset 108)
     let ata_initialized = {
         let data = ctx.accounts.user_base_token_ata.try_borrow_data()?;
@@ -229,7 +302,7 @@ pub fn handler(ctx: Context<Borrow>, amount: u64) -> Result<()> {
             program_id: anchor_spl::token::ID,
             accounts: vec![
                 AccountMeta::new(ctx.accounts.user_base_token_ata.key(), false),
-                AccountMeta::new(ctx.accounts.signer.key(), false),
+                AccountMeta::new(recipient.key(), false),
                 AccountMeta::new_readonly(ctx.accounts.program_pda.key(), true),
             ],
             data: vec![9], // SPL Token CloseAccount
@@ -238,7 +311,7 @@ pub fn handler(ctx: Context<Borrow>, amount: u64) -> Result<()> {
             &close_ix,
             &[
                 ctx.accounts.user_base_token_ata.to_account_info(),
-                ctx.accounts.signer.to_account_info(),
+                recipient.clone(),
                 ctx.accounts.program_pda.to_account_info(),
             ],
             signer_seeds,
@@ -252,5 +325,14 @@ pub fn handler(ctx: Context<Borrow>, amount: u64) -> Result<()> {
         .checked_add(actual_borrowed)
         .ok_or(HardigError::BorrowCapacityExceeded)?;
 
+    emit!(Borrowed {
+        position: ctx.accounts.position.key(),
+        key_asset: ctx.accounts.key_asset.key(),
+        permissions,
+        actual_borrowed,
+        user_debt: ctx.accounts.position.user_debt,
+        recipient: recipient.key(),
+    });
+
     Ok(())
 }
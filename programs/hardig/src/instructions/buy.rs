@@ -1,12 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke_signed;
 use anchor_spl::associated_token::get_associated_token_address;
-use anchor_spl::token::Token;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 use crate::errors::HardigError;
+use crate::events::Deposited;
 use crate::mayflower;
 use crate::state::{MarketConfig, PositionState, ProtocolConfig, PERM_BUY};
 
+use super::share_token::supply::{net_shares, share_accounts, tokens_for_deposit};
 use super::validate_key::validate_key;
 
 #[derive(Accounts)]
@@ -26,12 +28,15 @@ pub struct Buy<'info> {
     #[account(
         seeds = [ProtocolConfig::SEED],
         bump = config.bump,
+        constraint = !config.paused @ HardigError::ProtocolPaused,
     )]
     pub config: Account<'info, ProtocolConfig>,
 
     /// The MarketConfig for this position's market.
     #[account(
         constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+        constraint = !market_config.paused @ HardigError::MarketPaused,
+        constraint = !market_config.retired @ HardigError::MarketRetired,
     )]
     pub market_config: Account<'info, MarketConfig>,
 
@@ -156,10 +161,18 @@ pub struct Buy<'info> {
     /// CHECK: Validated in handler via seed derivation.
     #[account(mut)]
     pub log_account: UncheckedAccount<'info>,
+
+    /// The position's share token mint. Required iff position.share_mint is set.
+    #[account(mut)]
+    pub share_mint: Option<Account<'info, Mint>>,
+
+    /// The signer's token account (of the share mint) receiving their share tokens.
+    #[account(mut)]
+    pub depositor_share_ata: Option<Account<'info, TokenAccount>>,
 }
 
 pub fn handler(ctx: Context<Buy>, amount: u64, min_out: u64) -> Result<()> {
-    validate_key(
+    let permissions = validate_key(
         &ctx.accounts.signer,
         &ctx.accounts.key_asset.to_account_info(),
         &ctx.accounts.position.authority_seed,
@@ -227,6 +240,21 @@ pub fn handler(ctx: Context<Buy>, amount: u64, min_out: u64) -> Result<()> {
         mayflower::read_deposited_shares(&data)?
     };
 
+    // Share tokens are priced against the position's net value before the buy
+    let net_before = if ctx.accounts.position.share_mint != Pubkey::default() {
+        let debt = {
+            let data = pp_info.try_borrow_data()?;
+            mayflower::read_debt(&data)?
+        };
+        let floor_price = {
+            let data = ctx.accounts.mayflower_market.try_borrow_data()?;
+            mayflower::read_floor_price(&data)?
+        };
+        Some(net_shares(shares_before, debt, floor_price))
+    } else {
+        None
+    };
+
     invoke_signed(
         &ix,
         &[
@@ -271,5 +299,41 @@ pub fn handler(ctx: Context<Buy>, amount: u64, min_out: u64) -> Result<()> {
         .checked_add(shares_received)
         .ok_or(HardigError::InsufficientFunds)?;
 
+    // Mint the depositor's pro-rata share tokens
+    let mut share_tokens_minted = 0;
+    if let Some(net_before) = net_before {
+        let (share_mint, depositor_share_ata) = share_accounts(
+            &ctx.accounts.position,
+            &ctx.accounts.signer.key(),
+            ctx.accounts.share_mint.as_ref(),
+            ctx.accounts.depositor_share_ata.as_ref(),
+        )?;
+        share_tokens_minted = tokens_for_deposit(shares_received, share_mint.supply, net_before)?;
+        if share_tokens_minted > 0 {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::MintTo {
+                        mint: share_mint.to_account_info(),
+                        to: depositor_share_ata.to_account_info(),
+                        authority: ctx.accounts.program_pda.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                share_tokens_minted,
+            )?;
+        }
+    }
+
+    emit!(Deposited {
+        position: ctx.accounts.position.key(),
+        key_asset: ctx.accounts.key_asset.key(),
+        permissions,
+        amount,
+        shares_received,
+        deposited_nav: ctx.accounts.position.deposited_nav,
+        share_tokens_minted,
+    });
+
     Ok(())
 }
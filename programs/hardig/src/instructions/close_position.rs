@@ -0,0 +1,261 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::Token;
+use mpl_core::{ID as MPL_CORE_ID, instructions::BurnV1CpiBuilder};
+
+use crate::errors::HardigError;
+use crate::events::PositionClosed;
+use crate::mayflower;
+use crate::state::{
+    KeyState, MarketConfig, PositionState, PromoConfig, ProtocolConfig, PERM_MANAGE_KEYS,
+};
+
+use super::validate_key::validate_key;
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    /// The admin. Receives all reclaimed rent.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset). Burned.
+    /// CHECK: Validated in handler via validate_key and against position.current_admin_asset.
+    #[account(
+        mut,
+        constraint = admin_key_asset.key() == position.current_admin_asset @ HardigError::InvalidKey,
+    )]
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position to close. Rent refunded to admin.
+    #[account(mut, close = admin)]
+    pub position: Box<Account<'info, PositionState>>,
+
+    /// The position's recovery key NFT. Required (and burned) if one is configured.
+    /// CHECK: Validated in handler against position.recovery_asset.
+    #[account(mut)]
+    pub recovery_asset: Option<UncheckedAccount<'info>>,
+
+    /// Protocol config PDA — signs burns as the collection's update authority.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+        constraint = config.collection != Pubkey::default() @ HardigError::CollectionNotCreated,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The MPL-Core collection asset for Härdig key NFTs.
+    /// CHECK: Validated against config.collection.
+    #[account(
+        mut,
+        constraint = collection.key() == config.collection @ HardigError::CollectionNotCreated,
+    )]
+    pub collection: UncheckedAccount<'info>,
+
+    /// CHECK: MPL-Core program validated by address constraint.
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The MarketConfig for this position's market.
+    #[account(
+        constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+    )]
+    pub market_config: Box<Account<'info, MarketConfig>>,
+
+    /// Program PDA (authority) that owns the Mayflower PersonalPosition and the ATAs.
+    /// CHECK: PDA derived from this program.
+    #[account(
+        mut,
+        seeds = [b"authority", position.authority_seed.as_ref()],
+        bump,
+    )]
+    pub program_pda: UncheckedAccount<'info>,
+
+    /// Mayflower PersonalPosition PDA — read to verify zero debt and shares.
+    /// CHECK: Validated in handler via seed derivation.
+    pub personal_position: UncheckedAccount<'info>,
+
+    /// Program PDA's navSOL ATA. Closed if initialized (must be empty).
+    /// CHECK: Validated as correct ATA for program_pda + nav_mint.
+    #[account(
+        mut,
+        constraint = user_nav_sol_ata.key() == get_associated_token_address(&program_pda.key(), &market_config.nav_mint) @ HardigError::InvalidAta,
+    )]
+    pub user_nav_sol_ata: UncheckedAccount<'info>,
+
+    /// Program PDA's wSOL ATA. Closed if initialized (any wSOL is unwrapped to admin).
+    /// CHECK: Validated as correct ATA for program_pda + base_mint.
+    #[account(
+        mut,
+        constraint = user_wsol_ata.key() == get_associated_token_address(&program_pda.key(), &market_config.base_mint) @ HardigError::InvalidAta,
+    )]
+    pub user_wsol_ata: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    // remaining_accounts: KeyState / PromoConfig PDAs of this position to close (writable).
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, ClosePosition<'info>>) -> Result<()> {
+    // Validate the admin holds their key
+    validate_key(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    // The Mayflower position must be fully unwound: no debt, no deposited shares
+    let program_pda = ctx.accounts.program_pda.key();
+    let (expected_pp, _) =
+        mayflower::derive_personal_position(&program_pda, &ctx.accounts.market_config.market_meta);
+    require!(
+        ctx.accounts.personal_position.key() == expected_pp,
+        HardigError::InvalidMayflowerAccount
+    );
+    {
+        let data = ctx.accounts.personal_position.try_borrow_data()?;
+        require!(mayflower::read_debt(&data)? == 0, HardigError::PositionNotEmpty);
+        require!(
+            mayflower::read_deposited_shares(&data)? == 0,
+            HardigError::PositionNotEmpty
+        );
+    }
+
+    // Queued withdraws need the position to be executed or cancelled (and refunded)
+    require!(
+        ctx.accounts.position.pending_withdraw_requests == 0,
+        HardigError::WithdrawRequestsPending
+    );
+
+    // Recovery key must be supplied if configured
+    let recovery_key = ctx.accounts.position.recovery_asset;
+    if recovery_key != Pubkey::default() {
+        let recovery = ctx
+            .accounts
+            .recovery_asset
+            .as_ref()
+            .ok_or(error!(HardigError::InvalidKey))?;
+        require!(recovery.key() == recovery_key, HardigError::InvalidKey);
+    }
+
+    // Close KeyState / PromoConfig accounts of this position passed in remaining_accounts
+    let authority_seed = ctx.accounts.position.authority_seed;
+    let admin_info = ctx.accounts.admin.to_account_info();
+    let mut closed_accounts: u32 = 0;
+    for info in ctx.remaining_accounts.iter() {
+        require!(
+            info.owner == ctx.program_id && info.is_writable,
+            HardigError::InvalidCloseAccount
+        );
+        let owner_seed = {
+            let data = info.try_borrow_data()?;
+            require!(data.len() >= 8, HardigError::InvalidCloseAccount);
+            let mut slice: &[u8] = &data;
+            if data[..8] == *KeyState::DISCRIMINATOR {
+                KeyState::try_deserialize(&mut slice)?.authority_seed
+            } else if PromoConfig::is_closed(&data) {
+                // Tombstone left by close_promo: only the authority_seed remains
+                Pubkey::try_from(&data[8..PromoConfig::CLOSED_SIZE]).unwrap()
+            } else if data[..8] == *PromoConfig::DISCRIMINATOR {
+                PromoConfig::try_deserialize(&mut slice)?.authority_seed
+            } else {
+                return err!(HardigError::InvalidCloseAccount);
+            }
+        };
+        require!(owner_seed == authority_seed, HardigError::InvalidCloseAccount);
+        close_account(info, &admin_info)?;
+        closed_accounts += 1;
+    }
+
+    // Close the program PDA's token accounts if they were ever created.
+    // Only the program PDA can sign these; once the position is gone nobody can.
+    let bump = ctx.bumps.program_pda;
+    let signer_seeds: &[&[&[u8]]] = &[&[b"authority", authority_seed.as_ref(), &[bump]]];
+    for (ata, must_be_empty) in [
+        (&ctx.accounts.user_nav_sol_ata, true),
+        (&ctx.accounts.user_wsol_ata, false),
+    ] {
+        let (initialized, amount) = {
+            let data = ata.try_borrow_data()?;
+            let initialized = data.len() >= 109 && data[108] != 0;
+            let amount = if initialized {
+                u64::from_le_bytes(data[64..72].try_into().unwrap())
+            } else {
+                0
+            };
+            (initialized, amount)
+        };
+        if !initialized {
+            continue;
+        }
+        // navSOL left in the ATA belongs to the position — refuse rather than strand it
+        require!(!must_be_empty || amount == 0, HardigError::PositionNotEmpty);
+
+        let close_ix = Instruction {
+            program_id: anchor_spl::token::ID,
+            accounts: vec![
+                AccountMeta::new(ata.key(), false),
+                AccountMeta::new(ctx.accounts.admin.key(), false),
+                AccountMeta::new_readonly(program_pda, true),
+            ],
+            data: vec![9], // SPL Token CloseAccount
+        };
+        invoke_signed(
+            &close_ix,
+            &[
+                ata.to_account_info(),
+                ctx.accounts.admin.to_account_info(),
+                ctx.accounts.program_pda.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+    }
+
+    // Burn the admin key and (if configured) the recovery key
+    let config = &ctx.accounts.config;
+    let config_seeds: &[&[u8]] = &[ProtocolConfig::SEED, &[config.bump]];
+    let mut assets = vec![ctx.accounts.admin_key_asset.to_account_info()];
+    if recovery_key != Pubkey::default() {
+        if let Some(recovery) = ctx.accounts.recovery_asset.as_ref() {
+            assets.push(recovery.to_account_info());
+        }
+    }
+    for asset in assets.iter() {
+        BurnV1CpiBuilder::new(&ctx.accounts.mpl_core_program.to_account_info())
+            .asset(asset)
+            .collection(Some(&ctx.accounts.collection.to_account_info()))
+            .authority(Some(&ctx.accounts.config.to_account_info()))
+            .payer(&ctx.accounts.admin.to_account_info())
+            .system_program(Some(&ctx.accounts.system_program.to_account_info()))
+            .invoke_signed(&[config_seeds])?;
+    }
+
+    // position is closed by the `close = admin` constraint.
+
+    emit!(PositionClosed {
+        position: ctx.accounts.position.key(),
+        admin_asset: ctx.accounts.admin_key_asset.key(),
+        recovery_asset: recovery_key,
+        closed_accounts,
+    });
+
+    Ok(())
+}
+
+/// Close a program-owned account, sending its lamports to `destination`.
+pub(crate) fn close_account<'info>(info: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = info.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(HardigError::InsufficientFunds)?;
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&anchor_lang::system_program::ID);
+    info.resize(0)?;
+    Ok(())
+}
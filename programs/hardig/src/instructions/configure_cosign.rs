@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::CosignConfigured;
+use crate::state::{PositionState, ProtocolConfig, RateBucket, PERM_MANAGE_KEYS};
+use super::validate_key::{validate_key, validate_key_held_by};
+
+#[derive(Accounts)]
+pub struct ConfigureCosign<'info> {
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position to update. Mutable to update the cosign policy + last_admin_activity.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — needed to read the collection address for validate_key.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: (approver wallet, approver key asset) pairs, required while
+    // a policy is active so a single stolen admin key cannot switch it off.
+}
+
+pub fn handler(
+    ctx: Context<ConfigureCosign>,
+    sell_threshold: u64,
+    borrow_threshold: u64,
+    required: u8,
+    approvers: Vec<Pubkey>,
+) -> Result<()> {
+    validate_key(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    // Changing an active policy needs the same approvals as the actions it guards
+    if ctx.accounts.position.cosign_required > 0 {
+        require_cosigners(
+            &ctx.accounts.position,
+            &ctx.accounts.admin_key_asset.key(),
+            ctx.remaining_accounts,
+            &ctx.accounts.config.collection,
+        )?;
+    }
+
+    require!(
+        approvers.len() <= PositionState::MAX_COSIGN_APPROVERS,
+        HardigError::InvalidCosignPolicy
+    );
+    for (i, approver) in approvers.iter().enumerate() {
+        require!(
+            *approver != Pubkey::default() && !approvers[..i].contains(approver),
+            HardigError::InvalidCosignPolicy
+        );
+    }
+    if required == 0 {
+        // Disabling: nothing else may be left configured
+        require!(
+            approvers.is_empty() && sell_threshold == 0 && borrow_threshold == 0,
+            HardigError::InvalidCosignPolicy
+        );
+    } else {
+        require!(
+            required as usize <= approvers.len(),
+            HardigError::InvalidCosignPolicy
+        );
+    }
+    // Share token holders redeem without a key, so there is nothing for approvers to guard
+    require!(
+        required == 0 || ctx.accounts.position.share_mint == Pubkey::default(),
+        HardigError::ShareTokenPolicyConflict
+    );
+
+    let position = &mut ctx.accounts.position;
+    position.cosign_sell_threshold = sell_threshold;
+    position.cosign_borrow_threshold = borrow_threshold;
+    position.cosign_required = required;
+    position.cosign_approvers = [Pubkey::default(); PositionState::MAX_COSIGN_APPROVERS];
+    position.cosign_approvers[..approvers.len()].copy_from_slice(&approvers);
+    // Un-co-signed amounts accrue against the thresholds over a rolling window
+    let now = Clock::get()?.unix_timestamp;
+    let window = PositionState::COSIGN_WINDOW_SECS;
+    position.cosign_sell_bucket = RateBucket::full(sell_threshold, window, now as u64);
+    position.cosign_borrow_bucket = RateBucket::full(borrow_threshold, window, now as u64);
+    position.last_admin_activity = now;
+
+    emit!(CosignConfigured {
+        position: position.key(),
+        sell_threshold,
+        borrow_threshold,
+        required,
+        approvers,
+    });
+
+    Ok(())
+}
+
+/// Require `position.cosign_required` distinct approver keys to sign the transaction.
+///
+/// The executing key counts if it is an approver. Other approvals are passed in
+/// `remaining_accounts` as (wallet, key asset) pairs; each wallet must sign and hold
+/// an approver key of this position, validated like the executing key.
+pub fn require_cosigners(
+    position: &PositionState,
+    executing_key: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+    collection: &Pubkey,
+) -> Result<()> {
+    let mut approved: Vec<Pubkey> = Vec::new();
+    if position.is_cosign_approver(executing_key) {
+        approved.push(*executing_key);
+    }
+
+    require!(remaining_accounts.len() % 2 == 0, HardigError::InvalidCosigner);
+    for pair in remaining_accounts.chunks(2) {
+        let (wallet, key_asset) = (&pair[0], &pair[1]);
+        require!(
+            wallet.is_signer
+                && position.is_cosign_approver(key_asset.key)
+                && !approved.contains(key_asset.key),
+            HardigError::InvalidCosigner
+        );
+        // Any permission bit will do: being in the approver set is what grants approval
+        validate_key_held_by(
+            wallet.key,
+            key_asset,
+            &position.authority_seed,
+            u16::MAX,
+            collection,
+        )?;
+        approved.push(*key_asset.key);
+    }
+
+    require!(
+        approved.len() >= position.cosign_required as usize,
+        HardigError::CosignRequired
+    );
+    Ok(())
+}
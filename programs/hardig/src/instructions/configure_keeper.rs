@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::KeeperConfigured;
+use crate::state::{PositionState, ProtocolConfig, ReinvestMode, PERM_MANAGE_KEYS};
+use super::validate_key::validate_key;
+
+/// Largest keeper bounty a position can offer: 1% of each reinvest's borrowed amount.
+pub const MAX_KEEPER_BOUNTY_BPS: u16 = 100;
+
+#[derive(Accounts)]
+pub struct ConfigureKeeper<'info> {
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position to update. Mutable to update the keeper policy + last_admin_activity.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — needed to read the collection address for validate_key.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+pub fn handler(
+    ctx: Context<ConfigureKeeper>,
+    bounty_bps: u16,
+    max_bounty: u64,
+    min_borrow: u64,
+    max_spread_bps: u16,
+    mode: ReinvestMode,
+) -> Result<()> {
+    validate_key(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    // bounty_bps 0 turns keepers off; otherwise every limit must be set
+    if bounty_bps > 0 {
+        require!(
+            bounty_bps <= MAX_KEEPER_BOUNTY_BPS
+                && max_bounty > 0
+                && max_spread_bps > 0
+                && max_spread_bps <= 10_000
+                && mode.bps() > 0
+                && mode.bps() <= 10_000,
+            HardigError::InvalidKeeperConfig
+        );
+    }
+
+    let position = &mut ctx.accounts.position;
+    position.keeper_bounty_bps = bounty_bps;
+    position.keeper_bounty_max = max_bounty;
+    position.keeper_min_borrow = min_borrow;
+    position.keeper_max_spread_bps = max_spread_bps;
+    position.keeper_mode = mode;
+    position.last_admin_activity = Clock::get()?.unix_timestamp;
+
+    emit!(KeeperConfigured {
+        position: position.key(),
+        bounty_bps,
+        max_bounty,
+        min_borrow,
+        max_spread_bps,
+        mode,
+    });
+
+    Ok(())
+}
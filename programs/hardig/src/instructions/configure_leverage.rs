@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::LeverageConfigured;
+use crate::state::{PositionState, ProtocolConfig, PERM_MANAGE_KEYS};
+use super::validate_key::validate_key;
+
+#[derive(Accounts)]
+pub struct ConfigureLeverage<'info> {
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position to update. Mutable to update max_debt_to_floor_bps + last_admin_activity.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — needed to read the collection address for validate_key.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+pub fn handler(ctx: Context<ConfigureLeverage>, max_debt_to_floor_bps: u16) -> Result<()> {
+    validate_key(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    require!(max_debt_to_floor_bps <= 10_000, HardigError::InvalidLeverageCeiling);
+
+    // Lowering the ceiling below current debt is allowed: it only blocks further borrows
+    let position = &mut ctx.accounts.position;
+    position.max_debt_to_floor_bps = max_debt_to_floor_bps;
+    position.last_admin_activity = Clock::get()?.unix_timestamp;
+
+    emit!(LeverageConfigured {
+        position: position.key(),
+        max_debt_to_floor_bps,
+    });
+
+    Ok(())
+}
@@ -9,6 +9,7 @@ use mpl_core::{
 };
 
 use crate::errors::HardigError;
+use crate::events::RecoveryConfigured;
 use crate::state::{PositionState, ProtocolConfig, PERM_MANAGE_KEYS};
 use super::validate_key::validate_key;
 use super::metadata_uri;
@@ -186,5 +187,12 @@ pub fn handler(
         ])
         .invoke_signed(&[config_seeds])?;
 
+    emit!(RecoveryConfigured {
+        position: ctx.accounts.position.key(),
+        recovery_asset: ctx.accounts.recovery_asset.key(),
+        lockout_secs,
+        locked: ctx.accounts.position.recovery_config_locked,
+    });
+
     Ok(())
 }
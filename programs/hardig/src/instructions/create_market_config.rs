@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::state::{MarketConfig, ProtocolConfig};
+use crate::events::MarketConfigCreated;
+use crate::mayflower;
+use crate::state::{MarketConfig, MarketUpdate, ProtocolConfig};
 
 #[derive(Accounts)]
 #[instruction(nav_mint: Pubkey)]
@@ -25,6 +27,14 @@ pub struct CreateMarketConfig<'info> {
     )]
     pub market_config: Account<'info, MarketConfig>,
 
+    /// The Mayflower market metadata named by `market_meta`.
+    /// CHECK: Address, owner and contents validated in handler.
+    pub mayflower_market_meta: UncheckedAccount<'info>,
+
+    /// The Mayflower market named by `mayflower_market`.
+    /// CHECK: Address, owner and contents validated in handler.
+    pub mayflower_market: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -39,6 +49,22 @@ pub fn handler(
     market_nav_vault: Pubkey,
     fee_vault: Pubkey,
 ) -> Result<()> {
+    // Reject typos up front: every address must match what Mayflower's own accounts reference
+    mayflower::validate_market_accounts(
+        &mayflower::MarketAddresses {
+            nav_mint,
+            base_mint,
+            market_group,
+            market_meta,
+            mayflower_market,
+            market_base_vault,
+            market_nav_vault,
+            fee_vault,
+        },
+        &ctx.accounts.mayflower_market_meta,
+        &ctx.accounts.mayflower_market,
+    )?;
+
     let mc = &mut ctx.accounts.market_config;
     mc.nav_mint = nav_mint;
     mc.base_mint = base_mint;
@@ -49,5 +75,17 @@ pub fn handler(
     mc.market_nav_vault = market_nav_vault;
     mc.fee_vault = fee_vault;
     mc.bump = ctx.bumps.market_config;
+    mc.paused = false;
+    mc.retired = false;
+    mc.pending_update = MarketUpdate::default();
+    mc.pending_update_at = 0;
+
+    emit!(MarketConfigCreated {
+        market_config: mc.key(),
+        nav_mint,
+        base_mint,
+        mayflower_market,
+    });
+
     Ok(())
 }
@@ -10,8 +10,9 @@ use mpl_core::{
 };
 
 use crate::errors::HardigError;
+use crate::events::PositionCreated;
 use crate::mayflower;
-use crate::state::{MarketConfig, PositionState, ProtocolConfig, PRESET_ADMIN};
+use crate::state::{MarketConfig, PositionState, ProtocolConfig, RateBucket, ReinvestMode, PRESET_ADMIN};
 use super::{metadata_uri, permission_attributes};
 
 #[derive(Accounts)]
@@ -59,7 +60,8 @@ pub struct CreatePosition<'info> {
     )]
     pub collection: UncheckedAccount<'info>,
 
-    /// The MarketConfig for the target market.
+    /// The MarketConfig for the target market. Retired markets take no new positions.
+    #[account(constraint = !market_config.retired @ HardigError::MarketRetired)]
     pub market_config: Account<'info, MarketConfig>,
 
     /// CHECK: MPL-Core program validated by address constraint.
@@ -255,6 +257,32 @@ pub fn handler(ctx: Context<CreatePosition>, name: Option<String>, market_name:
     position.recovery_lockout_secs = 0;
     position.recovery_config_locked = false;
     position.artwork_id = artwork_id;
+    position.payout_address = Pubkey::default();
+    position.max_debt_to_floor_bps = 0;
+    position.cosign_sell_threshold = 0;
+    position.cosign_borrow_threshold = 0;
+    position.cosign_required = 0;
+    position.cosign_approvers = [Pubkey::default(); PositionState::MAX_COSIGN_APPROVERS];
+    position.withdraw_delay_secs = 0;
+    position.withdraw_queue_threshold = 0;
+    position.withdraw_request_count = 0;
+    position.keeper_bounty_bps = 0;
+    position.keeper_bounty_max = 0;
+    position.keeper_min_borrow = 0;
+    position.keeper_max_spread_bps = 0;
+    position.keeper_mode = ReinvestMode::Fraction { bps: 10_000 };
+    position.share_mint = Pubkey::default();
+    position.cosign_sell_bucket = RateBucket::default();
+    position.cosign_borrow_bucket = RateBucket::default();
+    position.withdraw_queue_bucket = RateBucket::default();
+    position.pending_withdraw_requests = 0;
+
+    emit!(PositionCreated {
+        position: ctx.accounts.position.key(),
+        admin_asset: ctx.accounts.admin_asset.key(),
+        market_config: ctx.accounts.market_config.key(),
+        artwork_id,
+    });
 
     Ok(())
 }
@@ -12,6 +12,7 @@ use mpl_core::{
 };
 
 use crate::errors::HardigError;
+use crate::events::RecoveryExecuted;
 use crate::state::{PositionState, ProtocolConfig, PRESET_ADMIN};
 use super::{permission_attributes, metadata_uri};
 
@@ -218,5 +219,12 @@ pub fn handler(ctx: Context<ExecuteRecovery>) -> Result<()> {
         .system_program(Some(&ctx.accounts.system_program.to_account_info()))
         .invoke_signed(&[config_seeds])?;
 
+    emit!(RecoveryExecuted {
+        position: ctx.accounts.position.key(),
+        recovery_asset: ctx.accounts.recovery_key_asset.key(),
+        old_admin_asset: ctx.accounts.old_admin_asset.key(),
+        new_admin_asset: ctx.accounts.new_admin_asset.key(),
+    });
+
     Ok(())
 }
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::HardigError;
+use crate::events::PositionHeartbeat;
 use crate::state::{PositionState, ProtocolConfig, PERM_MANAGE_KEYS};
 use super::validate_key::validate_key;
 
@@ -42,7 +43,13 @@ pub fn handler(ctx: Context<Heartbeat>) -> Result<()> {
         &ctx.accounts.config.collection,
     )?;
 
-    ctx.accounts.position.last_admin_activity = Clock::get()?.unix_timestamp;
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.position.last_admin_activity = now;
+
+    emit!(PositionHeartbeat {
+        position: ctx.accounts.position.key(),
+        last_admin_activity: now,
+    });
 
     Ok(())
 }
@@ -25,5 +25,8 @@ pub fn handler(ctx: Context<InitializeProtocol>) -> Result<()> {
     config.collection = Pubkey::default();
     config.pending_admin = Pubkey::default();
     config.bump = ctx.bumps.config;
+    config.paused = false;
+    config.fee_bps = 0;
+    config.treasury = Pubkey::default();
     Ok(())
 }
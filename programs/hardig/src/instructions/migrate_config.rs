@@ -3,15 +3,21 @@ use anchor_lang::prelude::*;
 use crate::errors::HardigError;
 use crate::state::ProtocolConfig;
 
-/// Migration for ProtocolConfig. Handles two transitions:
+use super::grow_account;
+
+/// Migration for ProtocolConfig. Handles three transitions:
 ///
-/// v0 -> v2 (41 -> 105 bytes):
+/// v0 -> v3 (41 -> 140 bytes):
 ///   Old: [discriminator(8)][admin(32)][bump(1)]
-///   New: [discriminator(8)][admin(32)][collection(32)][pending_admin(32)][bump(1)]
 ///
-/// v1 -> v2 (73 -> 105 bytes):
+/// v1 -> v3 (73 -> 140 bytes):
 ///   Old: [discriminator(8)][admin(32)][collection(32)][bump(1)]
-///   New: [discriminator(8)][admin(32)][collection(32)][pending_admin(32)][bump(1)]
+///
+/// v2 -> v3 (105 -> 140 bytes):
+///   Old: [discriminator(8)][admin(32)][collection(32)][pending_admin(32)][bump(1)]
+///
+/// New: [discriminator(8)][admin(32)][collection(32)][pending_admin(32)][bump(1)][paused(1)]
+///      [fee_bps(2)][treasury(32)]
 #[derive(Accounts)]
 pub struct MigrateConfig<'info> {
     #[account(mut)]
@@ -32,6 +38,7 @@ pub struct MigrateConfig<'info> {
 
 const V0_SIZE: usize = 8 + 32 + 1;      // 41 bytes
 const V1_SIZE: usize = 8 + 32 + 32 + 1;  // 73 bytes
+const V2_SIZE: usize = 8 + 32 + 32 + 32 + 1; // 105 bytes
 
 pub fn handler(ctx: Context<MigrateConfig>) -> Result<()> {
     let config_info = &ctx.accounts.config.to_account_info();
@@ -40,7 +47,7 @@ pub fn handler(ctx: Context<MigrateConfig>) -> Result<()> {
 
     // Guard: already at latest size
     require!(
-        current_size == V0_SIZE || current_size == V1_SIZE,
+        matches!(current_size, V0_SIZE | V1_SIZE | V2_SIZE),
         HardigError::AlreadyMigrated
     );
 
@@ -51,39 +58,31 @@ pub fn handler(ctx: Context<MigrateConfig>) -> Result<()> {
     require!(admin == ctx.accounts.admin.key(), HardigError::Unauthorized);
 
     // Read version-specific fields
-    let (collection, bump) = if current_size == V0_SIZE {
+    let (collection, pending_admin, bump) = match current_size {
         // v0: no collection field, bump at offset 40
-        (Pubkey::default(), data[40])
-    } else {
+        V0_SIZE => (Pubkey::default(), Pubkey::default(), data[40]),
         // v1: collection at 40..72, bump at 72
-        let collection = Pubkey::try_from(&data[40..72]).unwrap();
-        (collection, data[72])
+        V1_SIZE => {
+            let collection = Pubkey::try_from(&data[40..72]).unwrap();
+            (collection, Pubkey::default(), data[72])
+        }
+        // v2: pending_admin at 72..104, bump at 104
+        _ => {
+            let collection = Pubkey::try_from(&data[40..72]).unwrap();
+            let pending_admin = Pubkey::try_from(&data[72..104]).unwrap();
+            (collection, pending_admin, data[104])
+        }
     };
 
     drop(data);
 
-    // Realloc to new size (105 bytes)
-    let new_size = ProtocolConfig::SIZE;
-    let rent = Rent::get()?;
-    let new_min_balance = rent.minimum_balance(new_size);
-    let current_balance = config_info.lamports();
-    let diff = new_min_balance.saturating_sub(current_balance);
-
-    if diff > 0 {
-        anchor_lang::system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: ctx.accounts.admin.to_account_info(),
-                    to: config_info.clone(),
-                },
-            ),
-            diff,
-        )?;
-    }
-
-    #[allow(deprecated)]
-    config_info.realloc(new_size, false)?;
+    // Grow to the new size (140 bytes); the appended bytes start zeroed
+    grow_account(
+        config_info,
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ProtocolConfig::SIZE,
+    )?;
 
     // Write new layout
     let mut data = config_info.try_borrow_mut_data()?;
@@ -91,10 +90,12 @@ pub fn handler(ctx: Context<MigrateConfig>) -> Result<()> {
     // data[8..40]   — admin (unchanged)
     // data[40..72]  — collection
     data[40..72].copy_from_slice(&collection.to_bytes());
-    // data[72..104] — pending_admin = Pubkey::default()
-    data[72..104].copy_from_slice(&Pubkey::default().to_bytes());
+    // data[72..104] — pending_admin (kept from v2, otherwise none)
+    data[72..104].copy_from_slice(&pending_admin.to_bytes());
     // data[104]     — bump
     data[104] = bump;
+    // data[105]     — paused = false (zeroed by grow_account)
+    // data[106..108] — fee_bps = 0, data[108..140] — treasury = none (zeroed)
 
     Ok(())
 }
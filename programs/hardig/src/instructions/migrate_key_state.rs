@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::HardigError;
+use crate::state::KeyState;
+
+use super::grow_account;
+
+/// Migration for KeyState (permissionless). The layout only ever grew at the end,
+/// so any older length is accepted:
+///
+/// v0 (169 bytes):
+///   Old: [discriminator(8)][authority_seed(32)][asset(32)][bump(1)]
+///        [sell_bucket(32)][borrow_bucket(32)][4 lifetime counters(32)]
+///
+/// New: [...v0][expires_at(8)][promo(32)][principal_shares(8)][debt(8)]
+///      [principal_refundable(1)][referrals_credited(4)]
+///
+/// Appended fields are zeroed: never expires, not a promo key, no principal or debt,
+/// no referrals credited.
+/// Anyone may pay for it.
+#[derive(Accounts)]
+pub struct MigrateKeyState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The KeyState PDA. UncheckedAccount because the old layout
+    /// can't be deserialized as the new KeyState struct.
+    /// CHECK: Owner, discriminator and PDA (from the stored asset) validated in handler.
+    #[account(mut, owner = crate::ID)]
+    pub key_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+const V0_SIZE: usize = 8 + 32 + 32 + 1 + 32 + 32 + 8 + 8 + 8 + 8; // 169 bytes
+
+pub fn handler(ctx: Context<MigrateKeyState>) -> Result<()> {
+    let ks_info = &ctx.accounts.key_state.to_account_info();
+    let data = ks_info.try_borrow_data()?;
+
+    let current_size = data.len();
+    require!(
+        (V0_SIZE..KeyState::SIZE).contains(&current_size),
+        HardigError::AlreadyMigrated
+    );
+    require!(
+        data[..8] == *KeyState::DISCRIMINATOR,
+        HardigError::InvalidMigrationAccount
+    );
+
+    // asset (offset 40..72) is the PDA seed
+    let asset = Pubkey::try_from(&data[40..72]).unwrap();
+    let (expected, _) =
+        Pubkey::find_program_address(&[KeyState::SEED, asset.as_ref()], ctx.program_id);
+    require!(ks_info.key() == expected, HardigError::InvalidMigrationAccount);
+
+    drop(data);
+
+    grow_account(
+        ks_info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        KeyState::SIZE,
+    )
+}
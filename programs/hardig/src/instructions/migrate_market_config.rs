@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::HardigError;
+use crate::state::{MarketConfig, ProtocolConfig};
+
+use super::grow_account;
+
+/// Migration for MarketConfig (protocol admin only).
+///
+/// v0 -> v1 (265 -> 435 bytes):
+///   Old: [discriminator(8)][8 pubkeys(256)][bump(1)]
+///   New: [...v0][paused(1)][retired(1)][pending_update(160)][pending_update_at(8)]
+///
+/// Appended fields are zeroed: not paused, not retired, nothing staged.
+#[derive(Accounts)]
+pub struct MigrateMarketConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The global protocol config — used to verify the signer is the protocol admin.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ HardigError::Unauthorized,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The MarketConfig PDA. UncheckedAccount because the old layout
+    /// can't be deserialized as the new MarketConfig struct.
+    /// CHECK: Owner, discriminator and PDA (from the stored nav_mint) validated in handler.
+    #[account(mut, owner = crate::ID)]
+    pub market_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+const V0_SIZE: usize = 8 + 32 * 8 + 1; // 265 bytes
+
+pub fn handler(ctx: Context<MigrateMarketConfig>) -> Result<()> {
+    let mc_info = &ctx.accounts.market_config.to_account_info();
+    let data = mc_info.try_borrow_data()?;
+
+    require!(data.len() == V0_SIZE, HardigError::AlreadyMigrated);
+    require!(
+        data[..8] == *MarketConfig::DISCRIMINATOR,
+        HardigError::InvalidMayflowerAccount
+    );
+
+    // nav_mint (offset 8..40) is the PDA seed
+    let nav_mint = Pubkey::try_from(&data[8..40]).unwrap();
+    let (expected, _) =
+        Pubkey::find_program_address(&[MarketConfig::SEED, nav_mint.as_ref()], ctx.program_id);
+    require!(mc_info.key() == expected, HardigError::InvalidMayflowerAccount);
+
+    drop(data);
+
+    grow_account(
+        mc_info,
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        MarketConfig::SIZE,
+    )
+}
@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::HardigError;
+use crate::state::{PositionState, RateBucket, ReinvestMode};
+
+use super::grow_account;
+
+/// Migration for PositionState (permissionless).
+///
+/// v0 -> v1 (238 -> 632 bytes):
+///   Old: [...][recovery_config_locked(1)][artwork_id(1+32)]
+///   New: [...][recovery_config_locked(1)][artwork_id(1+32)][payout_address(32)]
+///        [max_debt_to_floor_bps(2)][cosign policy(177)][withdraw queue(24)]
+///        [keeper policy(23)][share_mint(32)][cosign allowances(64)]
+///        [withdraw queue allowance(32)][pending withdraw requests(8)]
+///
+/// Borsh writes a `None` artwork_id as a single byte, so the new fields start right
+/// after it and read from the old zero padding. Appended fields get the values
+/// `create_position` gives a new position: every policy off, so the position
+/// behaves exactly as before. Anyone may pay for it.
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The PositionState PDA. UncheckedAccount because the old layout
+    /// can't be deserialized as the new PositionState struct.
+    /// CHECK: Owner, discriminator and PDA (from the stored authority_seed) validated in handler.
+    #[account(mut, owner = crate::ID)]
+    pub position: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+const V0_SIZE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 2 + 8 + 1 + 1 + 32 + 32 + 8 + 1 + 33; // 238 bytes
+
+pub fn handler(ctx: Context<MigratePosition>) -> Result<()> {
+    let position_info = &ctx.accounts.position.to_account_info();
+    let data = position_info.try_borrow_data()?;
+
+    require!(data.len() == V0_SIZE, HardigError::AlreadyMigrated);
+    require!(
+        data[..8] == *PositionState::DISCRIMINATOR,
+        HardigError::InvalidMigrationAccount
+    );
+
+    // authority_seed (offset 8..40) is the PDA seed
+    let authority_seed = Pubkey::try_from(&data[8..40]).unwrap();
+    let (expected, _) = Pubkey::find_program_address(
+        &[PositionState::SEED, authority_seed.as_ref()],
+        ctx.program_id,
+    );
+    require!(position_info.key() == expected, HardigError::InvalidMigrationAccount);
+
+    drop(data);
+
+    grow_account(
+        position_info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        PositionState::SIZE,
+    )?;
+
+    // Zero is right for everything except keeper_mode (off anyway while keeper_bounty_bps
+    // is 0) and the allowance buckets, which start full
+    let mut data = position_info.try_borrow_mut_data()?;
+    let mut position = PositionState::try_deserialize(&mut &data[..])?;
+    position.keeper_mode = ReinvestMode::Fraction { bps: 10_000 };
+    let now = Clock::get()?.unix_timestamp as u64;
+    let window = PositionState::COSIGN_WINDOW_SECS;
+    position.cosign_sell_bucket = RateBucket::full(position.cosign_sell_threshold, window, now);
+    position.cosign_borrow_bucket = RateBucket::full(position.cosign_borrow_threshold, window, now);
+    position.withdraw_queue_bucket = RateBucket::full(
+        position.withdraw_queue_threshold,
+        position.withdraw_delay_secs as u64,
+        now,
+    );
+    position.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
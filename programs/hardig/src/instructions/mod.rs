@@ -1,8 +1,13 @@
 pub mod accept_admin;
 pub mod add_trusted_provider;
+pub mod apply_market_config_update;
 pub mod authorize_key;
 pub mod borrow;
 pub mod buy;
+pub mod close_position;
+pub mod configure_cosign;
+pub mod configure_keeper;
+pub mod configure_leverage;
 pub mod configure_recovery;
 pub mod consume_rate_limit;
 pub mod create_collection;
@@ -12,22 +17,44 @@ pub mod execute_recovery;
 pub mod heartbeat;
 pub mod initialize_protocol;
 pub mod migrate_config;
+pub mod migrate_key_state;
+pub mod migrate_market_config;
+pub mod migrate_position;
 pub mod promo;
+pub mod reap_expired_key;
 pub mod reinvest;
 pub mod remove_trusted_provider;
 pub mod repay;
+pub mod retire_market_config;
 pub mod revoke_key;
+pub mod set_market_paused;
+pub mod set_payout_address;
 pub mod set_position_artwork;
+pub mod set_protocol_fee;
+pub mod set_protocol_paused;
+pub mod share_token;
+pub mod sync_position;
 pub mod transfer_admin;
+pub mod unwind;
+pub mod update_market_config;
+pub mod update_key;
+pub mod update_key_limits;
+pub mod update_key_permissions;
 pub mod validate_key;
 pub mod withdraw;
+pub mod withdraw_queue;
 
 #[allow(ambiguous_glob_reexports)]
 pub use accept_admin::*;
 pub use add_trusted_provider::*;
+pub use apply_market_config_update::*;
 pub use authorize_key::*;
 pub use borrow::*;
 pub use buy::*;
+pub use close_position::*;
+pub use configure_cosign::*;
+pub use configure_keeper::*;
+pub use configure_leverage::*;
 pub use configure_recovery::*;
 pub use create_collection::*;
 pub use create_market_config::*;
@@ -36,14 +63,29 @@ pub use execute_recovery::*;
 pub use heartbeat::*;
 pub use initialize_protocol::*;
 pub use migrate_config::*;
+pub use migrate_key_state::*;
+pub use migrate_market_config::*;
+pub use migrate_position::*;
 pub use promo::*;
+pub use reap_expired_key::*;
 pub use reinvest::*;
 pub use remove_trusted_provider::*;
 pub use repay::*;
+pub use retire_market_config::*;
 pub use revoke_key::*;
+pub use set_market_paused::*;
+pub use set_payout_address::*;
 pub use set_position_artwork::*;
+pub use set_protocol_fee::*;
+pub use set_protocol_paused::*;
+pub use share_token::*;
+pub use sync_position::*;
 pub use transfer_admin::*;
+pub use unwind::*;
+pub use update_market_config::*;
+pub use update_key::*;
 pub use withdraw::*;
+pub use withdraw_queue::*;
 
 use anchor_lang::prelude::*;
 use mpl_core::types::Attribute;
@@ -51,10 +93,10 @@ use crate::errors::HardigError;
 use crate::state::*;
 
 /// Build human-readable on-chain attributes from a permission bitmask.
-/// Does NOT include limited_sell/limited_borrow — those are added by authorize_key
+/// Does NOT include limited_sell/limited_borrow — those come from `limit_attributes`
 /// with the actual capacity/period values merged in.
-pub fn permission_attributes(permissions: u8) -> Vec<Attribute> {
-    let flag = |bit: u8| -> &'static str {
+pub fn permission_attributes(permissions: u16) -> Vec<Attribute> {
+    let flag = |bit: u16| -> &'static str {
         if permissions & bit != 0 { "true" } else { "false" }
     };
 
@@ -66,9 +108,146 @@ pub fn permission_attributes(permissions: u8) -> Vec<Attribute> {
         Attribute { key: "repay".to_string(), value: flag(PERM_REPAY).to_string() },
         Attribute { key: "reinvest".to_string(), value: flag(PERM_REINVEST).to_string() },
         Attribute { key: "manage_keys".to_string(), value: flag(PERM_MANAGE_KEYS).to_string() },
+        Attribute { key: "unwind".to_string(), value: flag(PERM_UNWIND).to_string() },
+        Attribute { key: "guardian".to_string(), value: flag(PERM_GUARDIAN).to_string() },
     ]
 }
 
+/// Grow a program-owned account to `new_size` for a layout migration. `payer`
+/// tops the account up to the new rent-exempt minimum; bytes past the old
+/// length are zeroed.
+pub fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_size: usize,
+) -> Result<()> {
+    let old_size = account.data_len();
+    let diff = Rent::get()?
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+    if diff > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            diff,
+        )?;
+    }
+
+    account.resize(new_size)?;
+    account.try_borrow_mut_data()?[old_size..].fill(0);
+    Ok(())
+}
+
+/// Validate a key expiry timestamp: 0 (never expires) or strictly in the future.
+pub fn validate_expiry(expires_at: i64, now: i64) -> Result<()> {
+    require!(expires_at == 0 || expires_at > now, HardigError::InvalidExpiry);
+    Ok(())
+}
+
+/// Check a promo claim window. Either bound may be 0 (open); when both are set
+/// the window must be non-empty.
+pub fn validate_promo_window(starts_at: i64, ends_at: i64) -> Result<()> {
+    require!(starts_at >= 0 && ends_at >= 0, HardigError::InvalidPromoWindow);
+    require!(
+        starts_at == 0 || ends_at == 0 || ends_at > starts_at,
+        HardigError::InvalidPromoWindow
+    );
+    Ok(())
+}
+
+/// Format a unix timestamp as "YYYY-MM-DD HH:MM UTC".
+/// NOTE: Used for both on-chain NFT attributes and TUI display — changes affect both.
+pub fn format_utc_timestamp(ts: i64) -> String {
+    let days = ts.div_euclid(86_400);
+    let secs = ts.rem_euclid(86_400);
+    // Civil-from-days (Howard Hinnant), valid for the proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year, month, day, secs / 3_600, (secs % 3_600) / 60
+    )
+}
+
+/// Human-readable promo claim window, or None if claims are not time-bounded.
+/// NOTE: Used for both on-chain NFT attributes and TUI display — changes affect both.
+pub fn format_promo_window(starts_at: i64, ends_at: i64) -> Option<String> {
+    match (starts_at, ends_at) {
+        (0, 0) => None,
+        (s, 0) => Some(format!("from {}", format_utc_timestamp(s))),
+        (0, e) => Some(format!("until {}", format_utc_timestamp(e))),
+        (s, e) => Some(format!("{} to {}", format_utc_timestamp(s), format_utc_timestamp(e))),
+    }
+}
+
+/// The `expires_at` attribute read by `validate_key`. None for keys that never expire.
+pub fn expiry_attribute(expires_at: i64) -> Option<Attribute> {
+    (expires_at != 0).then(|| Attribute {
+        key: "expires_at".to_string(),
+        value: expires_at.to_string(),
+    })
+}
+
+/// Attribute keys written by `limit_attributes`. Stripped before rewriting limits.
+pub const LIMIT_ATTRIBUTE_KEYS: &[&str] = &[
+    "limited_sell",
+    "total_sell_limit",
+    "limited_borrow",
+    "total_borrow_limit",
+];
+
+/// Build the `limited_sell` / `limited_borrow` (and lifetime cap) attributes for a
+/// delegated key. Also returns the pre-formatted limited_sell / limited_borrow
+/// strings for `metadata_uri`.
+pub fn limit_attributes(
+    permissions: u16,
+    limits: &KeyLimits,
+) -> (Vec<Attribute>, Option<String>, Option<String>) {
+    let mut attrs = Vec::new();
+    let sell_limit_str = if permissions & PERM_LIMITED_SELL != 0 {
+        let v = format!(
+            "{} navSOL / {}",
+            format_sol_amount(limits.sell_bucket_capacity),
+            slots_to_duration(limits.sell_refill_period_slots)
+        );
+        attrs.push(Attribute { key: "limited_sell".to_string(), value: v.clone() });
+        if limits.total_sell_limit > 0 {
+            attrs.push(Attribute { key: "total_sell_limit".to_string(), value: format!("{} navSOL", format_sol_amount(limits.total_sell_limit)) });
+        }
+        Some(v)
+    } else {
+        None
+    };
+    let borrow_limit_str = if permissions & PERM_LIMITED_BORROW != 0 {
+        let v = format!(
+            "{} SOL / {}",
+            format_sol_amount(limits.borrow_bucket_capacity),
+            slots_to_duration(limits.borrow_refill_period_slots)
+        );
+        attrs.push(Attribute { key: "limited_borrow".to_string(), value: v.clone() });
+        if limits.total_borrow_limit > 0 {
+            attrs.push(Attribute { key: "total_borrow_limit".to_string(), value: format!("{} SOL", format_sol_amount(limits.total_borrow_limit)) });
+        }
+        Some(v)
+    } else {
+        None
+    };
+    (attrs, sell_limit_str, borrow_limit_str)
+}
+
 /// Convert a slot count to a human-readable duration string using ~400ms per slot.
 /// Examples: "15 days", "30 days, 12 hours", "6 hours", "45 minutes".
 /// NOTE: Used for both on-chain NFT attributes and TUI display — changes affect both.
@@ -125,7 +304,7 @@ const KEY_IMAGE: &str = "https://gateway.irys.xyz/GKa2AyPSRe2VnsPXBepTzhzohEBsLN
 /// (e.g. for promo keys).
 pub fn metadata_uri(
     name: &str,
-    permissions: u8,
+    permissions: u16,
     limited_sell: Option<&str>,
     limited_borrow: Option<&str>,
     market: Option<&str>,
@@ -133,13 +312,15 @@ pub fn metadata_uri(
     image_override: Option<&str>,
 ) -> String {
     let mut attrs = Vec::new();
-    let bits: &[(u8, &str)] = &[
+    let bits: &[(u16, &str)] = &[
         (PERM_BUY, "buy"),
         (PERM_SELL, "sell"),
         (PERM_BORROW, "borrow"),
         (PERM_REPAY, "repay"),
         (PERM_REINVEST, "reinvest"),
         (PERM_MANAGE_KEYS, "manage_keys"),
+        (PERM_UNWIND, "unwind"),
+        (PERM_GUARDIAN, "guardian"),
     ];
     for &(bit, label) in bits {
         if permissions & bit != 0 {
@@ -167,6 +348,23 @@ pub fn metadata_uri(
     )
 }
 
+/// Extract the `image` value from a metadata URI built by `metadata_uri`, so a
+/// rewritten URI keeps the key's current image (default, artwork or promo).
+/// Returns None if the URI has no image field.
+pub fn metadata_image(uri: &str) -> Option<String> {
+    let start = uri.find("\"image\":\"")? + "\"image\":\"".len();
+    let mut out = String::new();
+    let mut chars = uri[start..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(out),
+            '\\' => out.push(chars.next()?),
+            c => out.push(c),
+        }
+    }
+    None
+}
+
 /// Format a raw u64 amount (lamports or shares, 9 decimals) as a human-readable string.
 /// Trailing zeros after the decimal point are trimmed. Integer amounts have no decimal point.
 /// NOTE: Used for both on-chain NFT attributes and TUI display — changes affect both.
@@ -185,7 +383,7 @@ pub fn format_sol_amount(raw: u64) -> String {
 /// rate-limit parameters are consistent with the permission bits.
 pub fn validate_delegated_permissions(
     origin: KeyCreatorOrigin,
-    permissions: u8,
+    permissions: u16,
     sell_capacity: u64,
     sell_refill_period: u64,
     borrow_capacity: u64,
@@ -228,3 +426,26 @@ pub fn validate_delegated_permissions(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_utc_timestamps() {
+        assert_eq!(format_utc_timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_utc_timestamp(951_782_400), "2000-02-29 00:00 UTC");
+        assert_eq!(format_utc_timestamp(1_767_225_599), "2025-12-31 23:59 UTC");
+        assert_eq!(format_utc_timestamp(1_772_323_200 + 3 * 3_600 + 30 * 60), "2026-03-01 03:30 UTC");
+    }
+
+    #[test]
+    fn formats_promo_windows() {
+        assert_eq!(format_promo_window(0, 0), None);
+        assert_eq!(format_promo_window(86_400, 0).unwrap(), "from 1970-01-02 00:00 UTC");
+        assert_eq!(format_promo_window(0, 86_400).unwrap(), "until 1970-01-02 00:00 UTC");
+        assert!(validate_promo_window(10, 10).is_err());
+        assert!(validate_promo_window(-1, 0).is_err());
+        assert!(validate_promo_window(10, 0).is_ok());
+    }
+}
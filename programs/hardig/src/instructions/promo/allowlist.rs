@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+
+// ---------------------------------------------------------------------------
+// Merkle allowlist for promo claims
+//
+// leaf = sha256(0x00 || claimer || min_deposit as u64 LE)
+// node = sha256(0x01 || min(a, b) || max(a, b))
+//
+// Sorted pairs mean proofs carry no left/right flags; the prefixes stop a leaf
+// from being passed off as an inner node. An odd node is carried up unhashed.
+// ---------------------------------------------------------------------------
+
+/// Leaf committing to a claimer and their minimum deposit (0 = promo minimum only).
+pub fn allowlist_leaf(claimer: &Pubkey, min_deposit: u64) -> [u8; 32] {
+    hashv(&[&[0], claimer.as_ref(), &min_deposit.to_le_bytes()]).to_bytes()
+}
+
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[&[1], lo.as_ref(), hi.as_ref()]).to_bytes()
+}
+
+/// True if `proof` links `leaf` to `root`.
+pub fn verify_allowlist_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => hash_pair(a, b),
+            [a] => *a,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Root of the tree over `leaves` (off-chain helper for clients and tests).
+pub fn allowlist_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Proof for `leaves[index]` (off-chain helper for clients and tests).
+pub fn allowlist_proof(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<[u8; 32]> {
+        (0..n)
+            .map(|i| allowlist_leaf(&Pubkey::new_unique(), i as u64 * 1_000))
+            .collect()
+    }
+
+    #[test]
+    fn proofs_verify_for_every_leaf() {
+        for n in [1, 2, 3, 5, 8] {
+            let leaves = leaves(n);
+            let root = allowlist_root(&leaves);
+            for (i, leaf) in leaves.iter().enumerate() {
+                assert!(verify_allowlist_proof(&root, *leaf, &allowlist_proof(&leaves, i)));
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_claimer_or_deposit_rejected() {
+        let claimer = Pubkey::new_unique();
+        let mut leaves = leaves(4);
+        leaves[2] = allowlist_leaf(&claimer, 5_000);
+        let root = allowlist_root(&leaves);
+        let proof = allowlist_proof(&leaves, 2);
+
+        assert!(verify_allowlist_proof(&root, allowlist_leaf(&claimer, 5_000), &proof));
+        assert!(!verify_allowlist_proof(&root, allowlist_leaf(&claimer, 0), &proof));
+        assert!(!verify_allowlist_proof(&root, allowlist_leaf(&Pubkey::new_unique(), 5_000), &proof));
+    }
+}
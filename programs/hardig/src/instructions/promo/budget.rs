@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::state::{KeyState, PromoBudget, PromoConfig};
+use super::super::consume_rate_limit::{consume_rate_limit, consume_total_limit};
+
+/// Which side of a promo's budget an action draws from.
+#[derive(Clone, Copy)]
+pub enum BudgetKind {
+    Borrow,
+    Sell,
+}
+
+/// Check a `create_promo` budget: each shared bucket needs both a capacity and
+/// a refill period, or neither.
+pub fn validate_promo_budget(budget: &PromoBudget) -> Result<()> {
+    for (capacity, refill_period) in [
+        (budget.borrow_capacity, budget.borrow_refill_period),
+        (budget.sell_capacity, budget.sell_refill_period),
+    ] {
+        require!(
+            (capacity == 0) == (refill_period == 0),
+            HardigError::InvalidPromoBudget
+        );
+    }
+    Ok(())
+}
+
+/// Draw `amount` from the campaign-wide budget of the promo `key_state` was claimed from.
+/// No-op for keys that did not come from a promo. A promo closed by `close_promo`
+/// counts as exhausted, so its keys can't fall back to their uncapped per-key limits.
+/// Runs next to the key's own `consume_rate_limit` / `consume_total_limit`.
+pub fn consume_promo_budget(
+    key_state: &KeyState,
+    promo: Option<&AccountInfo>,
+    kind: BudgetKind,
+    amount: u64,
+    current_slot: u64,
+) -> Result<()> {
+    if key_state.promo == Pubkey::default() {
+        return Ok(());
+    }
+    let promo = promo.ok_or(error!(HardigError::PromoBudgetMismatch))?;
+    require!(
+        promo.key() == key_state.promo && promo.owner == &crate::ID,
+        HardigError::PromoBudgetMismatch
+    );
+    let mut data = promo.try_borrow_mut_data()?;
+    require!(!PromoConfig::is_closed(&data), HardigError::PromoBudgetExceeded);
+    let mut config = PromoConfig::try_deserialize(&mut &data[..])?;
+
+    let (bucket, used, limit) = match kind {
+        BudgetKind::Borrow => (
+            &mut config.budget_borrow_bucket,
+            &mut config.budget_borrowed,
+            config.budget_borrow_limit,
+        ),
+        BudgetKind::Sell => (
+            &mut config.budget_sell_bucket,
+            &mut config.budget_sold,
+            config.budget_sell_limit,
+        ),
+    };
+    if bucket.capacity > 0 {
+        consume_rate_limit(bucket, amount, current_slot)
+            .map_err(|_| error!(HardigError::PromoBudgetExceeded))?;
+    }
+    consume_total_limit(used, limit, amount)
+        .map_err(|_| error!(HardigError::PromoBudgetExceeded))?;
+    config.try_serialize(&mut &mut data[..])
+}
@@ -11,12 +11,19 @@ use mpl_core::{
 };
 
 use crate::errors::HardigError;
+use crate::events::PromoKeyClaimed;
 use crate::mayflower;
 use crate::state::{
-    ClaimReceipt, KeyState, MarketConfig, PositionState, PromoConfig, ProtocolConfig, RateBucket,
-    PERM_LIMITED_BORROW, PERM_LIMITED_SELL,
+    ClaimReceipt, GateReceipt, KeyLimits, KeyState, MarketConfig, PositionState, PromoConfig,
+    ProtocolConfig, RateBucket, PERM_LIMITED_BORROW, PERM_LIMITED_SELL,
+};
+use super::allowlist::{allowlist_leaf, verify_allowlist_proof};
+use super::gate::validate_gate;
+use super::referral::apply_referral_reward;
+use super::super::validate_key::validate_key_held_by;
+use super::super::{
+    expiry_attribute, format_promo_window, limit_attributes, metadata_uri, permission_attributes,
 };
-use super::super::{format_sol_amount, metadata_uri, permission_attributes, slots_to_duration};
 
 #[derive(Accounts)]
 #[instruction(amount: u64)]
@@ -44,7 +51,11 @@ pub struct ClaimPromoKey<'info> {
     pub claim_receipt: Account<'info, ClaimReceipt>,
 
     /// The position this promo is for. Mutable because deposited_nav updates.
-    #[account(mut)]
+    /// Tokenized positions take deposits through `buy` only, which mints share tokens.
+    #[account(
+        mut,
+        constraint = position.share_mint == Pubkey::default() @ HardigError::ShareTokenEnabled,
+    )]
     pub position: Box<Account<'info, PositionState>>,
 
     /// The new MPL-Core asset for the key NFT.
@@ -65,6 +76,7 @@ pub struct ClaimPromoKey<'info> {
     #[account(
         seeds = [ProtocolConfig::SEED],
         bump = config.bump,
+        constraint = !config.paused @ HardigError::ProtocolPaused,
         constraint = config.collection != Pubkey::default() @ HardigError::CollectionNotCreated,
     )]
     pub config: Account<'info, ProtocolConfig>,
@@ -88,6 +100,8 @@ pub struct ClaimPromoKey<'info> {
     /// The MarketConfig for this position's market.
     #[account(
         constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+        constraint = !market_config.paused @ HardigError::MarketPaused,
+        constraint = !market_config.retired @ HardigError::MarketRetired,
     )]
     pub market_config: Box<Account<'info, MarketConfig>>,
 
@@ -207,9 +221,54 @@ pub struct ClaimPromoKey<'info> {
     /// CHECK: Validated in handler via seed derivation.
     #[account(mut)]
     pub log_account: UncheckedAccount<'info>,
+
+    // -- Collection gate (required when promo.gate_collection is set) --
+
+    /// The gating NFT: an MPL-Core asset, or the mint of a Token Metadata NFT.
+    /// CHECK: Validated in handler against promo.gate_collection.
+    pub gate_asset: Option<UncheckedAccount<'info>>,
+
+    /// Token Metadata gates: the claimer's token account holding the gate mint.
+    /// CHECK: Validated in handler (SPL token owner, mint, holder, amount).
+    pub gate_token_account: Option<UncheckedAccount<'info>>,
+
+    /// Token Metadata gates: the gate mint's metadata PDA.
+    /// CHECK: Validated in handler via seed derivation and program owner.
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// One-claim-per-NFT guard. Init fails if this NFT already claimed from the promo.
+    #[account(
+        init,
+        payer = claimer,
+        space = GateReceipt::SIZE,
+        seeds = [
+            GateReceipt::SEED,
+            promo.key().as_ref(),
+            gate_asset.as_ref().ok_or(error!(HardigError::GateAssetRequired))?.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub gate_receipt: Option<Box<Account<'info, GateReceipt>>>,
+
+    // -- Referral (required when a referrer is named) --
+
+    /// A key of this position held by the referrer.
+    /// CHECK: Validated in handler via validate_key_held_by.
+    pub referrer_key_asset: Option<UncheckedAccount<'info>>,
+
+    /// The referrer key's KeyState, credited with the promo's referral reward.
+    /// Only the position's admin key, which has no KeyState, may omit it.
+    #[account(mut)]
+    pub referrer_key_state: Option<Box<Account<'info, KeyState>>>,
 }
 
-pub fn handler(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
+pub fn handler(
+    ctx: Context<ClaimPromoKey>,
+    amount: u64,
+    allowlist_min_deposit: u64,
+    proof: Vec<[u8; 32]>,
+    referrer: Option<Pubkey>,
+) -> Result<()> {
     let promo = &ctx.accounts.promo;
 
     // 1. Check promo is active
@@ -235,16 +294,113 @@ pub fn handler(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
         HardigError::InsufficientFunds
     );
 
-    // 5. Populate ClaimReceipt
+    // 4b. Enforce the claim window (0 = unbounded on that side)
+    let clock = Clock::get()?;
+    require!(clock.unix_timestamp >= promo.starts_at, HardigError::PromoNotStarted);
+    require!(
+        promo.ends_at == 0 || clock.unix_timestamp < promo.ends_at,
+        HardigError::PromoEnded
+    );
+
+    // 5. Reject claims for keys that would already be expired
+    require!(
+        promo.key_expires_at == 0 || clock.unix_timestamp < promo.key_expires_at,
+        HardigError::KeyExpired
+    );
+
+    // 5b. Allowlisted promos: the claimer and their deposit floor must be in the tree
+    if promo.merkle_root != [0u8; 32] {
+        require!(
+            verify_allowlist_proof(
+                &promo.merkle_root,
+                allowlist_leaf(&ctx.accounts.claimer.key(), allowlist_min_deposit),
+                &proof,
+            ),
+            HardigError::NotAllowlisted
+        );
+        require!(amount >= allowlist_min_deposit, HardigError::InsufficientFunds);
+    }
+
+    // 5c. Collection-gated promos: the claimer must hold an unspent NFT from the collection
+    let mut gate_asset_key = Pubkey::default();
+    if promo.gate_collection != Pubkey::default() {
+        let gate_asset = ctx
+            .accounts
+            .gate_asset
+            .as_ref()
+            .ok_or(error!(HardigError::GateAssetRequired))?;
+        validate_gate(
+            promo.gate_standard,
+            &promo.gate_collection,
+            &ctx.accounts.claimer.key(),
+            &gate_asset.to_account_info(),
+            ctx.accounts.gate_token_account.as_ref().map(|a| a.as_ref()),
+            ctx.accounts.gate_metadata.as_ref().map(|a| a.as_ref()),
+        )?;
+        gate_asset_key = gate_asset.key();
+
+        let gate_receipt = ctx
+            .accounts
+            .gate_receipt
+            .as_mut()
+            .ok_or(error!(HardigError::GateAssetRequired))?;
+        gate_receipt.promo = ctx.accounts.promo.key();
+        gate_receipt.gate_asset = gate_asset_key;
+        gate_receipt.claimer = ctx.accounts.claimer.key();
+        gate_receipt.bump = ctx.bumps.gate_receipt.unwrap();
+    } else {
+        require!(ctx.accounts.gate_receipt.is_none(), HardigError::InvalidGateAsset);
+    }
+
+    // 5d. Referral: the referrer must hold a key of this position; credit its KeyState
+    let referrer = referrer.unwrap_or_default();
+    if referrer != Pubkey::default() {
+        require!(referrer != ctx.accounts.claimer.key(), HardigError::InvalidReferrer);
+        let referrer_key_asset = ctx
+            .accounts
+            .referrer_key_asset
+            .as_ref()
+            .ok_or(error!(HardigError::InvalidReferrer))?;
+        validate_key_held_by(
+            &referrer,
+            &referrer_key_asset.to_account_info(),
+            &ctx.accounts.position.authority_seed,
+            u16::MAX,
+            &ctx.accounts.config.collection,
+        )
+        .map_err(|_| error!(HardigError::InvalidReferrer))?;
+
+        let reward = ctx.accounts.promo.referral_reward;
+        match ctx.accounts.referrer_key_state.as_deref_mut() {
+            Some(ks) => {
+                require!(ks.asset == referrer_key_asset.key(), HardigError::InvalidReferrer);
+                let ks = &mut **ks;
+                // Past the per-referrer cap the claim still goes through, uncredited
+                if ks.referrals_credited < reward.max_referrals_per_referrer {
+                    apply_referral_reward(&mut ks.borrow_bucket, &mut ks.total_borrow_limit, &reward);
+                    ks.referrals_credited += 1;
+                }
+            }
+            None => require!(
+                !reward.has_bonus()
+                    || referrer_key_asset.key() == ctx.accounts.position.current_admin_asset,
+                HardigError::ReferrerKeyStateRequired
+            ),
+        }
+    }
+    let promo = &ctx.accounts.promo;
+
+    // 6. Populate ClaimReceipt
     let claim_receipt = &mut ctx.accounts.claim_receipt;
     claim_receipt.claimer = ctx.accounts.claimer.key();
     claim_receipt.promo = ctx.accounts.promo.key();
     claim_receipt.bump = ctx.bumps.claim_receipt;
+    claim_receipt.referrer = referrer;
 
-    // 6. Build NFT name
+    // 7. Build NFT name
     let nft_name = format!("H\u{00e4}rdig Key - {}", promo.name_suffix);
 
-    // 7. Build attributes
+    // 8. Build attributes
     let permissions = promo.permissions;
     let mut attrs = permission_attributes(permissions);
     attrs.push(Attribute {
@@ -262,45 +418,40 @@ pub fn handler(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
         });
     }
 
-    // 8. Build limited sell/borrow strings if applicable
-    let sell_limit_str = if permissions & PERM_LIMITED_SELL != 0 {
-        let v = format!(
-            "{} navSOL / {}",
-            format_sol_amount(promo.sell_capacity),
-            slots_to_duration(promo.sell_refill_period)
-        );
-        attrs.push(Attribute {
-            key: "limited_sell".to_string(),
-            value: v.clone(),
-        });
-        Some(v)
-    } else {
-        None
+    // 9. Limit attributes, including the lifetime caps
+    let limits = KeyLimits {
+        sell_bucket_capacity: promo.sell_capacity,
+        sell_refill_period_slots: promo.sell_refill_period,
+        borrow_bucket_capacity: promo.borrow_capacity,
+        borrow_refill_period_slots: promo.borrow_refill_period,
+        total_sell_limit: promo.total_sell_limit,
+        total_borrow_limit: promo.total_borrow_limit,
     };
+    let (limit_attrs, sell_limit_str, borrow_limit_str) = limit_attributes(permissions, &limits);
+    attrs.extend(limit_attrs);
 
-    let borrow_limit_str = if permissions & PERM_LIMITED_BORROW != 0 {
-        let v = format!(
-            "{} SOL / {}",
-            format_sol_amount(promo.borrow_capacity),
-            slots_to_duration(promo.borrow_refill_period)
-        );
+    attrs.extend(expiry_attribute(promo.key_expires_at));
+    if let Some(window) = format_promo_window(promo.starts_at, promo.ends_at) {
         attrs.push(Attribute {
-            key: "limited_borrow".to_string(),
-            value: v.clone(),
+            key: "claim_window".to_string(),
+            value: window,
         });
-        Some(v)
-    } else {
-        None
-    };
+    }
+    if referrer != Pubkey::default() {
+        attrs.push(Attribute {
+            key: "referrer".to_string(),
+            value: referrer.to_string(),
+        });
+    }
 
-    // 9. Determine image override
+    // 10. Determine image override
     let image = if promo.image_uri.is_empty() {
         None
     } else {
         Some(promo.image_uri.as_str())
     };
 
-    // 10. Build metadata URI
+    // 11. Build metadata URI
     let uri = metadata_uri(
         &nft_name,
         permissions,
@@ -311,7 +462,7 @@ pub fn handler(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
         image,
     );
 
-    // 11. Mint key NFT via MPL-Core CreateV2CpiBuilder
+    // 12. Mint key NFT via MPL-Core CreateV2CpiBuilder
     let config = &ctx.accounts.config;
     let config_seeds: &[&[u8]] = &[ProtocolConfig::SEED, &[config.bump]];
 
@@ -342,8 +493,7 @@ pub fn handler(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
         ])
         .invoke_signed(&[config_seeds])?;
 
-    // 12. Initialize KeyState
-    let clock = Clock::get()?;
+    // 13. Initialize KeyState
     let current_slot = clock.slot;
 
     let key_state = &mut ctx.accounts.key_state;
@@ -377,13 +527,19 @@ pub fn handler(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
     key_state.total_sold = 0;
     key_state.total_borrow_limit = promo.total_borrow_limit;
     key_state.total_borrowed = 0;
+    key_state.expires_at = promo.key_expires_at;
+    key_state.promo = ctx.accounts.promo.key();
+    key_state.debt = 0;
+    key_state.principal_refundable = promo.refundable_principal;
+    key_state.referrals_credited = 0;
 
-    // 13. Increment claims_count (checked to prevent overflow)
+    // 14. Increment claims_count (checked to prevent overflow)
     ctx.accounts.promo.claims_count = ctx.accounts.promo.claims_count
         .checked_add(1)
         .ok_or(error!(HardigError::PromoMaxClaimsReached))?;
 
-    // 14. Mayflower buy CPI — convert deposit SOL to navSOL
+    // 15. Mayflower buy CPI — convert deposit SOL to navSOL
+    let mut shares_received = 0;
     if amount > 0 {
         let mc = &ctx.accounts.market_config;
 
@@ -468,7 +624,7 @@ pub fn handler(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
             let data = pp_info.try_borrow_data()?;
             mayflower::read_deposited_shares(&data)?
         };
-        let shares_received = shares_after
+        shares_received = shares_after
             .checked_sub(shares_before)
             .ok_or(HardigError::InsufficientFunds)?;
 
@@ -480,5 +636,21 @@ pub fn handler(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
             .ok_or(HardigError::InsufficientFunds)?;
     }
 
+    // 16. Record the claimer's principal on their key
+    ctx.accounts.key_state.principal_shares = shares_received;
+
+    emit!(PromoKeyClaimed {
+        position: ctx.accounts.position.key(),
+        promo: ctx.accounts.promo.key(),
+        claimer: ctx.accounts.claimer.key(),
+        key_asset: ctx.accounts.key_asset.key(),
+        permissions,
+        amount,
+        shares_received,
+        claims_count: ctx.accounts.promo.claims_count,
+        gate_asset: gate_asset_key,
+        referrer,
+    });
+
     Ok(())
 }
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::HardigError;
+use crate::events::ClaimReceiptsClosed;
+use crate::state::{ClaimReceipt, GateReceipt, PromoConfig};
+use super::super::close_position::close_account;
+
+#[derive(Accounts)]
+pub struct CloseClaimReceipts<'info> {
+    /// The promo the receipts belong to. Must already be closed: a close_promo
+    /// tombstone, or gone entirely once its position was closed.
+    /// CHECK: Validated in handler.
+    pub promo: UncheckedAccount<'info>,
+    // remaining_accounts: (receipt, claimer wallet) pairs, both writable. Receipts are
+    // ClaimReceipts or GateReceipts of this promo; rent goes back to the claimer who paid it.
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, CloseClaimReceipts<'info>>) -> Result<()> {
+    // Receipts are what stop double claims, so they may only go once the promo
+    // can no longer be claimed from (or recreated under the same name_suffix)
+    let promo = &ctx.accounts.promo;
+    let tombstone = promo.owner == ctx.program_id && PromoConfig::is_closed(&promo.try_borrow_data()?);
+    let gone = promo.owner == &anchor_lang::system_program::ID && promo.data_is_empty();
+    require!(tombstone || gone, HardigError::PromoNotClosed);
+
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 2 == 0,
+        HardigError::InvalidCloseAccount
+    );
+    let mut closed: u32 = 0;
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let (receipt, claimer) = (&pair[0], &pair[1]);
+        require!(
+            receipt.owner == ctx.program_id && receipt.is_writable && claimer.is_writable,
+            HardigError::InvalidCloseAccount
+        );
+        let (receipt_promo, receipt_claimer) = {
+            let data = receipt.try_borrow_data()?;
+            require!(data.len() >= 8, HardigError::InvalidCloseAccount);
+            let mut slice: &[u8] = &data;
+            if data[..8] == *ClaimReceipt::DISCRIMINATOR {
+                let r = ClaimReceipt::try_deserialize(&mut slice)?;
+                (r.promo, r.claimer)
+            } else if data[..8] == *GateReceipt::DISCRIMINATOR {
+                let r = GateReceipt::try_deserialize(&mut slice)?;
+                (r.promo, r.claimer)
+            } else {
+                return err!(HardigError::InvalidCloseAccount);
+            }
+        };
+        require!(
+            receipt_promo == promo.key() && receipt_claimer == claimer.key(),
+            HardigError::InvalidCloseAccount
+        );
+        close_account(receipt, claimer)?;
+        closed += 1;
+    }
+
+    emit!(ClaimReceiptsClosed {
+        promo: promo.key(),
+        closed,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::PromoClosed;
+use crate::state::{PositionState, PromoConfig, ProtocolConfig, PERM_MANAGE_KEYS};
+use super::super::validate_key::validate_key;
+
+#[derive(Accounts)]
+pub struct ClosePromo<'info> {
+    /// The admin. Receives the reclaimed rent.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key.
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position this promo belongs to.
+    pub position: Account<'info, PositionState>,
+
+    /// The PromoConfig PDA to close. Shrunk to a tombstone rather than closed outright,
+    /// so it is taken unchecked (Anchor would re-serialize a typed account on exit).
+    /// CHECK: Deserialized and seed-checked in handler.
+    #[account(mut)]
+    pub promo: UncheckedAccount<'info>,
+
+    /// Protocol config PDA — provides collection pubkey for key validation.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+pub fn handler(ctx: Context<ClosePromo>) -> Result<()> {
+    // Validate admin holds their key with MANAGE_KEYS permission
+    validate_key(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    let promo_info = ctx.accounts.promo.to_account_info();
+    require!(promo_info.owner == ctx.program_id, HardigError::InvalidKey);
+    let promo = {
+        let data = promo_info.try_borrow_data()?;
+        PromoConfig::try_deserialize(&mut &data[..])?
+    };
+    let authority_seed = ctx.accounts.position.authority_seed;
+    let expected = Pubkey::create_program_address(
+        &[
+            PromoConfig::SEED,
+            authority_seed.as_ref(),
+            promo.name_suffix.as_bytes(),
+            &[promo.bump],
+        ],
+        ctx.program_id,
+    )
+    .map_err(|_| error!(HardigError::InvalidKey))?;
+    require!(
+        promo_info.key() == expected && promo.authority_seed == authority_seed,
+        HardigError::InvalidKey
+    );
+
+    // Claims must be stopped first (update_promo active = false)
+    require!(!promo.active, HardigError::PromoStillActive);
+
+    // Shrink to a tombstone and refund everything above its rent-exempt minimum.
+    // Keys claimed from this promo keep their own limits; the campaign budget ends here.
+    let keep = Rent::get()?.minimum_balance(PromoConfig::CLOSED_SIZE);
+    let refund = promo_info.lamports().saturating_sub(keep);
+    promo_info.resize(PromoConfig::CLOSED_SIZE)?;
+    **promo_info.try_borrow_mut_lamports()? -= refund;
+    let admin_info = ctx.accounts.admin.to_account_info();
+    **admin_info.try_borrow_mut_lamports()? = admin_info
+        .lamports()
+        .checked_add(refund)
+        .ok_or(HardigError::InsufficientFunds)?;
+
+    emit!(PromoClosed {
+        position: ctx.accounts.position.key(),
+        promo: promo_info.key(),
+        claims_count: promo.claims_count,
+        refunded: refund,
+    });
+
+    Ok(())
+}
@@ -1,9 +1,15 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::HardigError;
-use crate::state::{KeyCreatorOrigin, PositionState, PromoConfig, ProtocolConfig, PERM_MANAGE_KEYS};
+use crate::events::PromoCreated;
+use crate::state::{
+    KeyCreatorOrigin, KeyLimits, PositionState, PromoBudget, PromoConfig, PromoGate, PromoTerms,
+    PromoWindow, ProtocolConfig, RateBucket, ReferralReward, PERM_MANAGE_KEYS,
+};
+use super::budget::validate_promo_budget;
+use super::referral::validate_referral_reward;
 use super::super::validate_key::validate_key;
-use super::super::validate_delegated_permissions;
+use super::super::{validate_delegated_permissions, validate_expiry, validate_promo_window};
 
 #[derive(Accounts)]
 #[instruction(name_suffix: String)]
@@ -41,18 +47,15 @@ pub struct CreatePromo<'info> {
 pub fn handler(
     ctx: Context<CreatePromo>,
     name_suffix: String,
-    permissions: u8,
-    borrow_capacity: u64,
-    borrow_refill_period: u64,
-    sell_capacity: u64,
-    sell_refill_period: u64,
-    total_borrow_limit: u64,
-    total_sell_limit: u64,
-    min_deposit_lamports: u64,
-    max_claims: u32,
-    initial_fill_bps: u16,
+    permissions: u16,
+    limits: KeyLimits,
+    terms: PromoTerms,
     image_uri: String,
     market_name: String,
+    gate: PromoGate,
+    window: PromoWindow,
+    budget: PromoBudget,
+    referral_reward: ReferralReward,
 ) -> Result<()> {
     // Validate admin holds their key with MANAGE_KEYS permission
     validate_key(
@@ -82,36 +85,85 @@ pub fn handler(
     validate_delegated_permissions(
         KeyCreatorOrigin::Promo,
         permissions,
-        sell_capacity,
-        sell_refill_period,
-        borrow_capacity,
-        borrow_refill_period,
-        total_sell_limit,
-        total_borrow_limit,
+        limits.sell_bucket_capacity,
+        limits.sell_refill_period_slots,
+        limits.borrow_bucket_capacity,
+        limits.borrow_refill_period_slots,
+        limits.total_sell_limit,
+        limits.total_borrow_limit,
     )?;
 
     // Validate initial fill basis points
-    require!(initial_fill_bps <= 10_000, HardigError::InvalidInitialFill);
+    require!(terms.initial_fill_bps <= 10_000, HardigError::InvalidInitialFill);
+
+    // Validate claimed-key expiry (0 = never)
+    let now = Clock::get()?.unix_timestamp;
+    validate_expiry(terms.key_expires_at, now)?;
+
+    // Validate the claim window; a promo that has already ended is a mistake
+    validate_promo_window(window.starts_at, window.ends_at)?;
+    require!(
+        window.ends_at == 0 || window.ends_at > now,
+        HardigError::InvalidPromoWindow
+    );
+
+    // Validate the campaign-wide budget
+    validate_promo_budget(&budget)?;
+
+    // A referral bonus must be capped per referrer
+    validate_referral_reward(&referral_reward)?;
 
     // Populate the PromoConfig
     let promo = &mut ctx.accounts.promo;
     promo.authority_seed = ctx.accounts.position.authority_seed;
     promo.permissions = permissions;
-    promo.borrow_capacity = borrow_capacity;
-    promo.borrow_refill_period = borrow_refill_period;
-    promo.sell_capacity = sell_capacity;
-    promo.sell_refill_period = sell_refill_period;
-    promo.min_deposit_lamports = min_deposit_lamports;
-    promo.max_claims = max_claims;
+    promo.borrow_capacity = limits.borrow_bucket_capacity;
+    promo.borrow_refill_period = limits.borrow_refill_period_slots;
+    promo.sell_capacity = limits.sell_bucket_capacity;
+    promo.sell_refill_period = limits.sell_refill_period_slots;
+    promo.min_deposit_lamports = terms.min_deposit_lamports;
+    promo.max_claims = terms.max_claims;
     promo.claims_count = 0;
     promo.active = true;
-    promo.total_borrow_limit = total_borrow_limit;
-    promo.total_sell_limit = total_sell_limit;
-    promo.initial_fill_bps = initial_fill_bps;
+    promo.total_borrow_limit = limits.total_borrow_limit;
+    promo.total_sell_limit = limits.total_sell_limit;
+    promo.initial_fill_bps = terms.initial_fill_bps;
+    promo.key_expires_at = terms.key_expires_at;
+    promo.merkle_root = gate.merkle_root;
+    promo.gate_collection = gate.collection.unwrap_or_default();
+    promo.gate_standard = gate.standard;
+    promo.starts_at = window.starts_at;
+    promo.ends_at = window.ends_at;
+    let current_slot = Clock::get()?.slot;
+    promo.budget_borrow_limit = budget.total_borrow_limit;
+    promo.budget_borrowed = 0;
+    promo.budget_sell_limit = budget.total_sell_limit;
+    promo.budget_sold = 0;
+    // A full shared bucket per side (capacity 0 = no bucket)
+    promo.budget_borrow_bucket =
+        RateBucket::full(budget.borrow_capacity, budget.borrow_refill_period, current_slot);
+    promo.budget_sell_bucket =
+        RateBucket::full(budget.sell_capacity, budget.sell_refill_period, current_slot);
+    promo.referral_reward = referral_reward;
+    promo.refundable_principal = terms.refundable_principal;
     promo.name_suffix = name_suffix;
     promo.image_uri = image_uri;
     promo.market_name = market_name;
     promo.bump = ctx.bumps.promo;
 
+    emit!(PromoCreated {
+        position: ctx.accounts.position.key(),
+        promo: ctx.accounts.promo.key(),
+        permissions,
+        max_claims: terms.max_claims,
+        merkle_root: gate.merkle_root,
+        gate_collection: ctx.accounts.promo.gate_collection,
+        starts_at: window.starts_at,
+        ends_at: window.ends_at,
+        budget,
+        referral_reward,
+        refundable_principal: terms.refundable_principal,
+    });
+
     Ok(())
 }
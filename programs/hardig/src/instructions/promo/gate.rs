@@ -0,0 +1,187 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::state::GateStandard;
+
+// ---------------------------------------------------------------------------
+// Collection gate for promo claims
+//
+// MPL-Core: the asset itself names its collection (update_authority).
+// Token Metadata: the claimer's token account proves ownership of the mint and
+// the metadata PDA carries a `collection` that must be verified.
+// ---------------------------------------------------------------------------
+
+/// Metaplex Token Metadata program ID.
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Verify that `claimer` holds `gate_asset` and that it belongs to `collection`.
+///
+/// For Token Metadata gates `gate_asset` is the NFT mint and both the token
+/// account and metadata PDA must be supplied.
+pub fn validate_gate(
+    standard: GateStandard,
+    collection: &Pubkey,
+    claimer: &Pubkey,
+    gate_asset: &AccountInfo,
+    gate_token_account: Option<&AccountInfo>,
+    gate_metadata: Option<&AccountInfo>,
+) -> Result<()> {
+    match standard {
+        GateStandard::MplCore => validate_core_asset(collection, claimer, gate_asset),
+        GateStandard::TokenMetadata => {
+            let token_account = gate_token_account.ok_or(error!(HardigError::GateAssetRequired))?;
+            let metadata = gate_metadata.ok_or(error!(HardigError::GateAssetRequired))?;
+            validate_token_metadata_nft(collection, claimer, gate_asset, token_account, metadata)
+        }
+    }
+}
+
+fn validate_core_asset(collection: &Pubkey, claimer: &Pubkey, asset: &AccountInfo) -> Result<()> {
+    require!(*asset.owner == mpl_core::ID, HardigError::InvalidGateAsset);
+    let data = asset.try_borrow_data()?;
+    // BaseAssetV1: key(1) + owner(32) + update_authority(1 tag + 32 pubkey); tag 2 = Collection
+    require!(
+        data.len() >= 66 && data[0] == mpl_core::types::Key::AssetV1 as u8,
+        HardigError::InvalidGateAsset
+    );
+    require!(data[1..33] == claimer.to_bytes(), HardigError::InvalidGateAsset);
+    require!(
+        data[33] == 2 && data[34..66] == collection.to_bytes(),
+        HardigError::InvalidGateAsset
+    );
+    Ok(())
+}
+
+fn validate_token_metadata_nft(
+    collection: &Pubkey,
+    claimer: &Pubkey,
+    mint: &AccountInfo,
+    token_account: &AccountInfo,
+    metadata: &AccountInfo,
+) -> Result<()> {
+    // SPL token account: mint(32) + owner(32) + amount(8)
+    require!(
+        *token_account.owner == anchor_spl::token::ID,
+        HardigError::InvalidGateAsset
+    );
+    {
+        let data = token_account.try_borrow_data()?;
+        require!(data.len() >= 72, HardigError::InvalidGateAsset);
+        require!(data[0..32] == mint.key().to_bytes(), HardigError::InvalidGateAsset);
+        require!(data[32..64] == claimer.to_bytes(), HardigError::InvalidGateAsset);
+        let amount = u64::from_le_bytes(data[64..72].try_into().unwrap());
+        require!(amount > 0, HardigError::InvalidGateAsset);
+    }
+
+    let (expected_metadata, _) = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.key().as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    );
+    require!(
+        metadata.key() == expected_metadata && *metadata.owner == TOKEN_METADATA_PROGRAM_ID,
+        HardigError::InvalidGateAsset
+    );
+    let data = metadata.try_borrow_data()?;
+    require!(
+        read_verified_collection(&data) == Some(*collection),
+        HardigError::InvalidGateAsset
+    );
+    Ok(())
+}
+
+/// The verified collection of a Token Metadata `Metadata` account, if any.
+///
+/// Layout: key(1) + update_authority(32) + mint(32) + name/symbol/uri (4 + len each)
+/// + seller_fee_basis_points(2) + creators Option<Vec<34>> + primary_sale_happened(1)
+/// + is_mutable(1) + edition_nonce Option<u8> + token_standard Option<u8>
+/// + collection Option<{ verified: bool, key: Pubkey }>.
+pub fn read_verified_collection(data: &[u8]) -> Option<Pubkey> {
+    let mut cursor = data.get(65..)?;
+
+    fn take<'a>(cursor: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+        let (head, tail) = (cursor.get(..n)?, cursor.get(n..)?);
+        *cursor = tail;
+        Some(head)
+    }
+    fn take_u32(cursor: &mut &[u8]) -> Option<usize> {
+        Some(u32::from_le_bytes(take(cursor, 4)?.try_into().ok()?) as usize)
+    }
+
+    for _ in 0..3 {
+        let len = take_u32(&mut cursor)?;
+        take(&mut cursor, len)?;
+    }
+    take(&mut cursor, 2)?; // seller_fee_basis_points
+    if take(&mut cursor, 1)?[0] == 1 {
+        let creators = take_u32(&mut cursor)?;
+        take(&mut cursor, creators.checked_mul(34)?)?;
+    }
+    take(&mut cursor, 2)?; // primary_sale_happened + is_mutable
+    for _ in 0..2 {
+        // edition_nonce, token_standard
+        if take(&mut cursor, 1)?[0] == 1 {
+            take(&mut cursor, 1)?;
+        }
+    }
+    if take(&mut cursor, 1)?[0] != 1 {
+        return None;
+    }
+    let verified = take(&mut cursor, 1)?[0] == 1;
+    let key = Pubkey::try_from(take(&mut cursor, 32)?).ok()?;
+    verified.then_some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(creators: usize, collection: Option<(bool, Pubkey)>) -> Vec<u8> {
+        let mut data = vec![4u8]; // Key::MetadataV1
+        data.extend_from_slice(&[7u8; 64]); // update_authority + mint
+        for field in ["Gate #1", "GATE", "https://example.com/1.json"] {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field.as_bytes());
+        }
+        data.extend_from_slice(&500u16.to_le_bytes());
+        if creators > 0 {
+            data.push(1);
+            data.extend_from_slice(&(creators as u32).to_le_bytes());
+            data.extend_from_slice(&vec![9u8; creators * 34]);
+        } else {
+            data.push(0);
+        }
+        data.extend_from_slice(&[1, 1]); // primary_sale_happened, is_mutable
+        data.extend_from_slice(&[1, 255]); // edition_nonce: Some
+        data.push(0); // token_standard: None
+        match collection {
+            Some((verified, key)) => {
+                data.push(1);
+                data.push(verified as u8);
+                data.extend_from_slice(key.as_ref());
+            }
+            None => data.push(0),
+        }
+        data.extend_from_slice(&[0u8; 40]); // uses, collection_details, padding
+        data
+    }
+
+    #[test]
+    fn reads_verified_collection() {
+        let collection = Pubkey::new_unique();
+        for creators in [0, 1, 3] {
+            assert_eq!(
+                read_verified_collection(&metadata(creators, Some((true, collection)))),
+                Some(collection)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unverified_missing_or_truncated() {
+        let collection = Pubkey::new_unique();
+        assert_eq!(read_verified_collection(&metadata(1, Some((false, collection)))), None);
+        assert_eq!(read_verified_collection(&metadata(1, None)), None);
+        assert_eq!(read_verified_collection(&metadata(1, Some((true, collection)))[..120]), None);
+    }
+}
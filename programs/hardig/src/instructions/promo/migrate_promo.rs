@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::HardigError;
+use crate::state::{GateStandard, PromoConfig, RateBucket, ReferralReward};
+
+use super::super::grow_account;
+
+/// Migration for PromoConfig (permissionless). Handles one transition:
+///
+/// v0 -> v1 (345 -> 552 bytes):
+///   Old: [discriminator(8)][authority_seed(32)][permissions(u8)][5 x u64 limits(40)]
+///        [max_claims(4)][claims_count(4)][active(1)][total_borrow_limit(8)]
+///        [total_sell_limit(8)][initial_fill_bps(2)][name_suffix][image_uri][market_name][bump]
+///
+/// New: permissions widened to u16, with key expiry, allowlist, gate, claim window,
+/// campaign budget, referral reward and refundable principal inserted before the
+/// strings. Fields move, so the account is rewritten rather than extended. New
+/// fields are off: keys never expire, anyone may claim at any time, no budget,
+/// no referral reward, principal not refundable. Anyone may pay for it.
+#[derive(Accounts)]
+pub struct MigratePromo<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The PromoConfig PDA. UncheckedAccount because the old layout
+    /// can't be deserialized as the new PromoConfig struct.
+    /// CHECK: Owner, discriminator and PDA (from the stored seeds and bump) validated in handler.
+    #[account(mut, owner = crate::ID)]
+    pub promo: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+const V0_SIZE: usize = 8 + 32 + 1
+    + 8 + 8
+    + 8 + 8 + 8
+    + 4 + 4 + 1
+    + 8 + 8 + 2
+    + (4 + 64) + (4 + PromoConfig::MAX_IMAGE_URI_LEN) + (4 + PromoConfig::MAX_MARKET_NAME_LEN) + 1; // 345 bytes
+
+/// The v0 PromoConfig body (after the discriminator).
+#[derive(AnchorDeserialize)]
+struct PromoConfigV0 {
+    authority_seed: Pubkey,
+    permissions: u8,
+    borrow_capacity: u64,
+    borrow_refill_period: u64,
+    sell_capacity: u64,
+    sell_refill_period: u64,
+    min_deposit_lamports: u64,
+    max_claims: u32,
+    claims_count: u32,
+    active: bool,
+    total_borrow_limit: u64,
+    total_sell_limit: u64,
+    initial_fill_bps: u16,
+    name_suffix: String,
+    image_uri: String,
+    market_name: String,
+    bump: u8,
+}
+
+pub fn handler(ctx: Context<MigratePromo>) -> Result<()> {
+    let promo_info = &ctx.accounts.promo.to_account_info();
+    let data = promo_info.try_borrow_data()?;
+
+    require!(data.len() == V0_SIZE, HardigError::AlreadyMigrated);
+    require!(
+        data[..8] == *PromoConfig::DISCRIMINATOR,
+        HardigError::InvalidMigrationAccount
+    );
+
+    let old = PromoConfigV0::deserialize(&mut &data[8..])
+        .map_err(|_| error!(HardigError::InvalidMigrationAccount))?;
+    drop(data);
+
+    let expected = Pubkey::create_program_address(
+        &[
+            PromoConfig::SEED,
+            old.authority_seed.as_ref(),
+            old.name_suffix.as_bytes(),
+            &[old.bump],
+        ],
+        ctx.program_id,
+    )
+    .map_err(|_| error!(HardigError::InvalidMigrationAccount))?;
+    require!(promo_info.key() == expected, HardigError::InvalidMigrationAccount);
+
+    let promo = PromoConfig {
+        authority_seed: old.authority_seed,
+        permissions: old.permissions as u16,
+        borrow_capacity: old.borrow_capacity,
+        borrow_refill_period: old.borrow_refill_period,
+        sell_capacity: old.sell_capacity,
+        sell_refill_period: old.sell_refill_period,
+        min_deposit_lamports: old.min_deposit_lamports,
+        max_claims: old.max_claims,
+        claims_count: old.claims_count,
+        active: old.active,
+        total_borrow_limit: old.total_borrow_limit,
+        total_sell_limit: old.total_sell_limit,
+        initial_fill_bps: old.initial_fill_bps,
+        key_expires_at: 0,
+        merkle_root: [0u8; 32],
+        gate_collection: Pubkey::default(),
+        gate_standard: GateStandard::default(),
+        starts_at: 0,
+        ends_at: 0,
+        budget_borrow_limit: 0,
+        budget_borrowed: 0,
+        budget_sell_limit: 0,
+        budget_sold: 0,
+        budget_borrow_bucket: RateBucket::default(),
+        budget_sell_bucket: RateBucket::default(),
+        referral_reward: ReferralReward::default(),
+        refundable_principal: false,
+        name_suffix: old.name_suffix,
+        image_uri: old.image_uri,
+        market_name: old.market_name,
+        bump: old.bump,
+    };
+
+    grow_account(
+        promo_info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        PromoConfig::SIZE,
+    )?;
+
+    let mut data = promo_info.try_borrow_mut_data()?;
+    data.fill(0);
+    promo.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
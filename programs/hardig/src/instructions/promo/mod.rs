@@ -1,8 +1,19 @@
+pub mod allowlist;
+pub mod budget;
 pub mod claim_promo_key;
+pub mod close_claim_receipts;
+pub mod close_promo;
 pub mod create_promo;
+pub mod gate;
+pub mod migrate_promo;
+pub mod principal;
+pub mod referral;
 pub mod update_promo;
 
 #[allow(ambiguous_glob_reexports)]
 pub use claim_promo_key::*;
+pub use close_claim_receipts::*;
+pub use close_promo::*;
 pub use create_promo::*;
+pub use migrate_promo::*;
 pub use update_promo::*;
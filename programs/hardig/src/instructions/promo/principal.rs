@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::state::KeyState;
+
+// ---------------------------------------------------------------------------
+// Promo principal
+//
+// claim_promo_key records the claimer's deposit in KeyState.principal_shares.
+// On promos created with `refundable_principal`, the claimed key may sell that
+// principal back through `withdraw` without a sell permission, less the navSOL
+// needed to cover the debt still attributed to it (valued at the floor price).
+// ---------------------------------------------------------------------------
+
+/// navSOL shares worth `debt` lamports at `floor_price` (lamports per 1e9 shares),
+/// rounded up. A zero floor price leaves nothing withdrawable while debt is owed.
+pub fn debt_in_shares(debt: u64, floor_price: u64) -> u64 {
+    if debt == 0 {
+        return 0;
+    }
+    if floor_price == 0 {
+        return u64::MAX;
+    }
+    let shares = (debt as u128 * 1_000_000_000).div_ceil(floor_price as u128);
+    u64::try_from(shares).unwrap_or(u64::MAX)
+}
+
+/// Whether `key_state` belongs to `key_asset` and carries a refundable principal,
+/// letting the key sell it back and repay its debt without the usual permissions.
+pub fn is_principal_key(key_state: Option<&KeyState>, key_asset: &Pubkey) -> bool {
+    key_state.is_some_and(|ks| ks.asset == *key_asset && ks.principal_refundable)
+}
+
+/// Principal shares the key may still sell back.
+pub fn withdrawable_principal(key_state: &KeyState, floor_price: u64) -> u64 {
+    key_state
+        .principal_shares
+        .saturating_sub(debt_in_shares(key_state.debt, floor_price))
+}
+
+/// Draw `shares_sold` from the key's principal after a principal-mode sell.
+pub fn consume_principal(key_state: &mut KeyState, shares_sold: u64, floor_price: u64) -> Result<()> {
+    require!(
+        shares_sold <= withdrawable_principal(key_state, floor_price),
+        HardigError::PrincipalExceeded
+    );
+    key_state.principal_shares -= shares_sold;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_debt_at_floor_rounding_up() {
+        assert_eq!(debt_in_shares(0, 0), 0);
+        assert_eq!(debt_in_shares(1_000_000_000, 2_000_000_000), 500_000_000);
+        assert_eq!(debt_in_shares(1, 2_000_000_000), 1);
+        assert_eq!(debt_in_shares(1, 0), u64::MAX);
+    }
+
+    #[test]
+    fn principal_is_reduced_by_debt() {
+        let mut ks = KeyState {
+            authority_seed: Pubkey::default(),
+            asset: Pubkey::default(),
+            bump: 0,
+            sell_bucket: Default::default(),
+            borrow_bucket: Default::default(),
+            total_sell_limit: 0,
+            total_sold: 0,
+            total_borrow_limit: 0,
+            total_borrowed: 0,
+            expires_at: 0,
+            promo: Pubkey::default(),
+            principal_shares: 1_000,
+            debt: 500,
+            principal_refundable: true,
+            referrals_credited: 0,
+        };
+        // 500 lamports at 1 SOL per navSOL = 500 shares held back
+        assert_eq!(withdrawable_principal(&ks, 1_000_000_000), 500);
+        assert!(consume_principal(&mut ks, 501, 1_000_000_000).is_err());
+        consume_principal(&mut ks, 500, 1_000_000_000).unwrap();
+        assert_eq!(ks.principal_shares, 500);
+    }
+}
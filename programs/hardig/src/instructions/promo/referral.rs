@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::state::{RateBucket, ReferralReward};
+
+// ---------------------------------------------------------------------------
+// Promo referrals
+//
+// A claimer may name a referrer who holds a key of the same position. If the
+// promo carries a ReferralReward, the referrer's KeyState is credited on each
+// referred claim. Only limits the key already has are raised: a key without a
+// borrow bucket gains no capacity, and an uncapped key stays uncapped. A key
+// stops earning once its `referrals_credited` reaches the promo's
+// `max_referrals_per_referrer`, so sybil claims can't raise its limits forever.
+// ---------------------------------------------------------------------------
+
+/// Check a `create_promo` referral reward: a bonus needs a per-referrer cap.
+pub fn validate_referral_reward(reward: &ReferralReward) -> Result<()> {
+    require!(
+        !reward.has_bonus() || reward.max_referrals_per_referrer > 0,
+        HardigError::InvalidReferralReward
+    );
+    Ok(())
+}
+
+/// Credit `reward` to a referrer's borrow bucket and lifetime borrow limit. The
+/// capacity bonus is also added to the bucket level, so it is usable right away.
+pub fn apply_referral_reward(
+    borrow_bucket: &mut RateBucket,
+    total_borrow_limit: &mut u64,
+    reward: &ReferralReward,
+) {
+    if borrow_bucket.capacity > 0 {
+        borrow_bucket.capacity = borrow_bucket.capacity.saturating_add(reward.borrow_capacity_bonus);
+        borrow_bucket.level = borrow_bucket.level.saturating_add(reward.borrow_capacity_bonus);
+    }
+    if *total_borrow_limit > 0 {
+        *total_borrow_limit = total_borrow_limit.saturating_add(reward.total_borrow_limit_bonus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REWARD: ReferralReward = ReferralReward {
+        borrow_capacity_bonus: 5,
+        total_borrow_limit_bonus: 7,
+        max_referrals_per_referrer: 3,
+    };
+
+    #[test]
+    fn bonus_needs_a_cap() {
+        assert!(validate_referral_reward(&ReferralReward::default()).is_ok());
+        assert!(validate_referral_reward(&REWARD).is_ok());
+        let uncapped = ReferralReward { max_referrals_per_referrer: 0, ..REWARD };
+        assert!(validate_referral_reward(&uncapped).is_err());
+    }
+
+    #[test]
+    fn raises_existing_limits() {
+        let mut bucket = RateBucket { capacity: 100, refill_period: 1_000, level: 40, last_update: 0 };
+        let mut total_borrow_limit = 1_000;
+        apply_referral_reward(&mut bucket, &mut total_borrow_limit, &REWARD);
+        assert_eq!((bucket.capacity, bucket.level), (105, 45));
+        assert_eq!(total_borrow_limit, 1_007);
+    }
+
+    #[test]
+    fn leaves_absent_limits_alone() {
+        let mut bucket = RateBucket::default();
+        let mut total_borrow_limit = 0;
+        apply_referral_reward(&mut bucket, &mut total_borrow_limit, &REWARD);
+        assert_eq!((bucket.capacity, bucket.level), (0, 0));
+        assert_eq!(total_borrow_limit, 0);
+    }
+}
@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::HardigError;
+use crate::events::PromoUpdated;
 use crate::state::{PositionState, PromoConfig, ProtocolConfig, PERM_MANAGE_KEYS};
 use super::super::validate_key::validate_key;
+use super::super::validate_promo_window;
 
 #[derive(Accounts)]
 pub struct UpdatePromo<'info> {
@@ -37,6 +39,9 @@ pub fn handler(
     ctx: Context<UpdatePromo>,
     active: Option<bool>,
     max_claims: Option<u32>,
+    merkle_root: Option<[u8; 32]>,
+    starts_at: Option<i64>,
+    ends_at: Option<i64>,
 ) -> Result<()> {
     // Validate admin holds their key with MANAGE_KEYS permission
     validate_key(
@@ -61,5 +66,28 @@ pub fn handler(
         promo.max_claims = max_claims;
     }
 
+    // Rotating the root does not revoke keys already claimed under the old list
+    if let Some(merkle_root) = merkle_root {
+        promo.merkle_root = merkle_root;
+    }
+
+    if let Some(starts_at) = starts_at {
+        promo.starts_at = starts_at;
+    }
+    if let Some(ends_at) = ends_at {
+        promo.ends_at = ends_at;
+    }
+    validate_promo_window(promo.starts_at, promo.ends_at)?;
+
+    emit!(PromoUpdated {
+        position: ctx.accounts.position.key(),
+        promo: promo.key(),
+        active: promo.active,
+        max_claims: promo.max_claims,
+        merkle_root: promo.merkle_root,
+        starts_at: promo.starts_at,
+        ends_at: promo.ends_at,
+    });
+
     Ok(())
 }
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    ID as MPL_CORE_ID,
+    accounts::BaseAssetV1,
+    fetch_plugin,
+    instructions::BurnV1CpiBuilder,
+    types::{Attributes, PluginType},
+};
+
+use crate::errors::HardigError;
+use crate::events::KeyReaped;
+use crate::state::{KeyState, PositionState, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct ReapExpiredKey<'info> {
+    /// Anyone can reap an expired key. Receives the KeyState rent.
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    /// The position the expired key belongs to.
+    #[account(
+        seeds = [PositionState::SEED, target_key_state.authority_seed.as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, PositionState>,
+
+    /// The expired key asset to burn.
+    /// CHECK: Validated in handler (MPL-Core owner + position attribute).
+    #[account(mut)]
+    pub target_asset: UncheckedAccount<'info>,
+
+    /// The expired key's KeyState PDA. Closed, rent refunded to the caller.
+    #[account(
+        mut,
+        close = caller,
+        constraint = target_key_state.asset == target_asset.key() @ HardigError::InvalidKey,
+    )]
+    pub target_key_state: Account<'info, KeyState>,
+
+    /// Protocol config PDA — signs burn as the collection's update authority
+    /// (PermanentBurnDelegate authority).
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+        constraint = config.collection != Pubkey::default() @ HardigError::CollectionNotCreated,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The MPL-Core collection asset for Härdig key NFTs.
+    /// CHECK: Validated against config.collection.
+    #[account(
+        mut,
+        constraint = collection.key() == config.collection @ HardigError::CollectionNotCreated,
+    )]
+    pub collection: UncheckedAccount<'info>,
+
+    /// CHECK: MPL-Core program validated by address constraint.
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ReapExpiredKey>) -> Result<()> {
+    // Only keys with an expiry that has passed can be reaped
+    let expires_at = ctx.accounts.target_key_state.expires_at;
+    require!(
+        ctx.accounts.target_key_state.is_expired(Clock::get()?.unix_timestamp),
+        HardigError::KeyNotExpired
+    );
+
+    // Verify target_asset is actually an MPL-Core asset
+    require!(
+        *ctx.accounts.target_asset.to_account_info().owner == mpl_core::ID,
+        HardigError::InvalidKey
+    );
+
+    // The asset's position binding must match its KeyState (same check as revoke_key)
+    let (_, target_attrs, _) = fetch_plugin::<BaseAssetV1, Attributes>(
+        &ctx.accounts.target_asset.to_account_info(),
+        PluginType::Attributes,
+    )
+    .map_err(|_| error!(HardigError::InvalidKey))?;
+    let target_position = target_attrs
+        .attribute_list
+        .iter()
+        .find(|a| a.key == "position")
+        .ok_or(error!(HardigError::WrongPosition))?;
+    require!(
+        target_position.value == ctx.accounts.position.authority_seed.to_string(),
+        HardigError::WrongPosition
+    );
+
+    // Burn the target asset via PermanentBurnDelegate.
+    // The collection's update_authority (config PDA) is the PermanentBurnDelegate authority.
+    let config = &ctx.accounts.config;
+    let signer_seeds: &[&[&[u8]]] = &[&[ProtocolConfig::SEED, &[config.bump]]];
+
+    BurnV1CpiBuilder::new(&ctx.accounts.mpl_core_program.to_account_info())
+        .asset(&ctx.accounts.target_asset.to_account_info())
+        .collection(Some(&ctx.accounts.collection.to_account_info()))
+        .authority(Some(&ctx.accounts.config.to_account_info()))
+        .payer(&ctx.accounts.caller.to_account_info())
+        .system_program(Some(&ctx.accounts.system_program.to_account_info()))
+        .invoke_signed(signer_seeds)?;
+
+    // target_key_state is closed by the `close = caller` constraint.
+
+    emit!(KeyReaped {
+        position: ctx.accounts.position.key(),
+        key_asset: ctx.accounts.target_asset.key(),
+        caller: ctx.accounts.caller.key(),
+        expires_at,
+    });
+
+    Ok(())
+}
@@ -1,11 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke_signed;
-use anchor_spl::associated_token::get_associated_token_address;
-use anchor_spl::token::Token;
+use anchor_spl::associated_token::{self, get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{self, Token};
 
 use crate::errors::HardigError;
+use crate::events::{ProtocolFeeCollected, Reinvested};
 use crate::mayflower;
-use crate::state::{MarketConfig, PositionState, ProtocolConfig, PERM_REINVEST};
+use crate::state::{MarketConfig, PositionState, ProtocolConfig, ReinvestMode, PERM_REINVEST};
 
 use super::validate_key::validate_key;
 
@@ -14,17 +15,23 @@ pub struct Reinvest<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
-    /// The signer's key NFT (MPL-Core asset).
+    /// The signer's key NFT (MPL-Core asset). None = keyless keeper call under the
+    /// position's keeper policy (see configure_keeper).
     /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
-    pub key_asset: UncheckedAccount<'info>,
+    pub key_asset: Option<UncheckedAccount<'info>>,
 
-    /// The position to reinvest for.
-    #[account(mut)]
+    /// The position to reinvest for. Tokenized positions can't take on debt.
+    #[account(
+        mut,
+        constraint = position.share_mint == Pubkey::default() @ HardigError::ShareTokenEnabled,
+    )]
     pub position: Account<'info, PositionState>,
 
     /// The MarketConfig for this position's market.
     #[account(
         constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+        constraint = !market_config.paused @ HardigError::MarketPaused,
+        constraint = !market_config.retired @ HardigError::MarketRetired,
     )]
     pub market_config: Account<'info, MarketConfig>,
 
@@ -32,6 +39,7 @@ pub struct Reinvest<'info> {
     #[account(
         seeds = [ProtocolConfig::SEED],
         bump = config.bump,
+        constraint = !config.paused @ HardigError::ProtocolPaused,
     )]
     pub config: Account<'info, ProtocolConfig>,
 
@@ -121,16 +129,63 @@ pub struct Reinvest<'info> {
     /// CHECK: Validated in handler via seed derivation.
     #[account(mut)]
     pub log_account: UncheckedAccount<'info>,
+
+    /// Treasury's ATA for the market's base mint. Required while config.fee_bps > 0.
+    /// Created (signer pays) if it doesn't exist yet.
+    /// CHECK: Validated in handler as the ATA of config.treasury + base_mint.
+    #[account(mut)]
+    pub treasury_base_ata: Option<UncheckedAccount<'info>>,
+
+    /// Keeper's ATA for the market's base mint; receives the bounty on keyless calls.
+    /// CHECK: Validated in handler as the ATA of signer + base_mint.
+    #[account(mut)]
+    pub keeper_base_ata: Option<UncheckedAccount<'info>>,
+
+    /// The treasury wallet. Only needed to create treasury_base_ata.
+    /// CHECK: Must equal config.treasury.
+    #[account(constraint = treasury.key() == config.treasury @ HardigError::InvalidTreasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Only needed to create treasury_base_ata.
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
 }
 
-pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Result<()> {
-    validate_key(
-        &ctx.accounts.signer,
-        &ctx.accounts.key_asset.to_account_info(),
-        &ctx.accounts.position.authority_seed,
-        PERM_REINVEST,
-        &ctx.accounts.config.collection,
-    )?;
+pub fn handler(
+    ctx: Context<Reinvest>,
+    min_out: u64,
+    max_spread_bps: u16,
+    mode: ReinvestMode,
+    min_amount: u64,
+) -> Result<()> {
+    let keeper = ctx.accounts.key_asset.is_none();
+    let (key_asset, permissions) = match ctx.accounts.key_asset.as_ref() {
+        Some(key_asset) => {
+            let permissions = validate_key(
+                &ctx.accounts.signer,
+                &key_asset.to_account_info(),
+                &ctx.accounts.position.authority_seed,
+                PERM_REINVEST,
+                &ctx.accounts.config.collection,
+            )?;
+            (key_asset.key(), permissions)
+        }
+        None => (Pubkey::default(), 0),
+    };
+
+    // Keyless callers are held to the owner's keeper policy: fixed mode, a minimum
+    // borrow worth paying a bounty for, and the tighter of both spread limits.
+    let (max_spread_bps, min_amount) = if keeper {
+        let position = &ctx.accounts.position;
+        require!(position.keeper_bounty_bps > 0, HardigError::KeeperNotEnabled);
+        require!(mode == position.keeper_mode, HardigError::KeeperModeMismatch);
+        let spread = match max_spread_bps {
+            0 => position.keeper_max_spread_bps,
+            s => s.min(position.keeper_max_spread_bps),
+        };
+        (spread, min_amount.max(position.keeper_min_borrow))
+    } else {
+        (max_spread_bps, min_amount)
+    };
 
     let mc = &ctx.accounts.market_config;
 
@@ -152,7 +207,7 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
         HardigError::InvalidMayflowerAccount
     );
 
-    if ctx.accounts.key_asset.key() == ctx.accounts.position.current_admin_asset {
+    if key_asset == ctx.accounts.position.current_admin_asset {
         ctx.accounts.position.last_admin_activity = Clock::get()?.unix_timestamp;
     }
 
@@ -164,10 +219,15 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
     let deposited_shares = mayflower::read_deposited_shares(&pp_data)?;
     let current_debt = mayflower::read_debt(&pp_data)?;
 
-    let capacity =
-        mayflower::calculate_borrow_capacity(deposited_shares, floor_price, current_debt)?;
+    require!(
+        mode.bps() > 0 && mode.bps() <= 10_000,
+        HardigError::InvalidReinvestBps
+    );
+    let borrow_amount =
+        mayflower::calculate_reinvest_borrow(deposited_shares, floor_price, current_debt, mode)?;
 
-    if capacity == 0 {
+    // Nothing to do, or not worth the fees of compounding dust
+    if borrow_amount == 0 || borrow_amount < min_amount {
         return Ok(());
     }
 
@@ -192,7 +252,7 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
 
     let pp_info = ctx.accounts.personal_position.to_account_info();
 
-    // Step 1: Borrow the available capacity
+    // Step 1: Borrow the amount selected by `mode`
     // Read debt BEFORE the borrow CPI
     let debt_before = {
         let data = pp_info.try_borrow_data()?;
@@ -203,7 +263,7 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
         program_pda,
         ctx.accounts.personal_position.key(),
         ctx.accounts.user_base_token_ata.key(),
-        capacity,
+        borrow_amount,
         &market,
     );
 
@@ -255,10 +315,118 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
     };
     drop(wsol_data);
 
+    let max_debt_to_floor_bps = ctx.accounts.position.max_debt_to_floor_bps;
+
     if actual_amount == 0 {
+        mayflower::check_debt_to_floor(
+            deposited_shares,
+            floor_price,
+            debt_after,
+            max_debt_to_floor_bps,
+        )?;
+        emit!(Reinvested {
+            position: ctx.accounts.position.key(),
+            key_asset,
+            permissions,
+            actual_borrowed,
+            amount_invested: 0,
+            shares_received: 0,
+            deposited_nav: ctx.accounts.position.deposited_nav,
+            user_debt: ctx.accounts.position.user_debt,
+            protocol_fee: 0,
+            keeper_bounty: 0,
+        });
         return Ok(());
     }
 
+    // Protocol fee comes off the borrowed base token before it is invested
+    let fee_bps = ctx.accounts.config.fee_bps;
+    let protocol_fee = ((actual_amount as u128) * (fee_bps as u128) / 10_000u128) as u64;
+    if protocol_fee > 0 {
+        let treasury = ctx.accounts.config.treasury;
+        let treasury_ata = ctx
+            .accounts
+            .treasury_base_ata
+            .as_ref()
+            .ok_or(error!(HardigError::InvalidTreasury))?;
+        require!(
+            treasury_ata.key() == get_associated_token_address(&treasury, &mc.base_mint),
+            HardigError::InvalidTreasury
+        );
+        if treasury_ata.data_is_empty() {
+            let (Some(treasury_wallet), Some(ata_program)) = (
+                ctx.accounts.treasury.as_ref(),
+                ctx.accounts.associated_token_program.as_ref(),
+            ) else {
+                return err!(HardigError::InvalidTreasury);
+            };
+            associated_token::create_idempotent(CpiContext::new(
+                ata_program.to_account_info(),
+                associated_token::Create {
+                    payer: ctx.accounts.signer.to_account_info(),
+                    associated_token: treasury_ata.to_account_info(),
+                    authority: treasury_wallet.to_account_info(),
+                    mint: ctx.accounts.wsol_mint.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+            ))?;
+        }
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.user_base_token_ata.to_account_info(),
+                    to: treasury_ata.to_account_info(),
+                    authority: ctx.accounts.program_pda.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            protocol_fee,
+        )?;
+        emit!(ProtocolFeeCollected {
+            position: ctx.accounts.position.key(),
+            treasury,
+            mint: mc.base_mint,
+            amount: protocol_fee,
+            fee_bps,
+        });
+    }
+
+    // Keeper bounty, also off the borrowed amount
+    let keeper_bounty = if keeper {
+        let position = &ctx.accounts.position;
+        (((actual_amount as u128) * (position.keeper_bounty_bps as u128) / 10_000u128) as u64)
+            .min(position.keeper_bounty_max)
+    } else {
+        0
+    };
+    if keeper_bounty > 0 {
+        let keeper_ata = ctx
+            .accounts
+            .keeper_base_ata
+            .as_ref()
+            .ok_or(error!(HardigError::InvalidAta))?;
+        require!(
+            keeper_ata.key()
+                == get_associated_token_address(&ctx.accounts.signer.key(), &mc.base_mint),
+            HardigError::InvalidAta
+        );
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.user_base_token_ata.to_account_info(),
+                    to: keeper_ata.to_account_info(),
+                    authority: ctx.accounts.program_pda.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            keeper_bounty,
+        )?;
+    }
+    let amount_invested = actual_amount - protocol_fee - keeper_bounty;
+
     // Step 3: Buy navSOL with the actual borrowed amount
     // Read deposited shares BEFORE the buy CPI
     let shares_before = {
@@ -272,7 +440,7 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
         ctx.accounts.user_shares.key(),
         ctx.accounts.user_nav_sol_ata.key(),
         ctx.accounts.user_wsol_ata.key(),
-        actual_amount,
+        amount_invested,
         0, // Mayflower's own min_output — we enforce slippage ourselves
         &market,
     );
@@ -316,8 +484,8 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
 
     // Enforce reinvest spread limit
     if max_spread_bps > 0 && floor_price > 0 && shares_received > 0 {
-        // effective_price = actual_amount * 1e9 / shares_received (lamports per share, 1e9 scaled)
-        let effective_price = (actual_amount as u128)
+        // effective_price = amount_invested * 1e9 / shares_received (lamports per share, 1e9 scaled)
+        let effective_price = (amount_invested as u128)
             .checked_mul(1_000_000_000u128)
             .ok_or(HardigError::InsufficientFunds)?
             / (shares_received as u128);
@@ -334,6 +502,9 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
         }
     }
 
+    // Enforce the admin's leverage ceiling on the resulting position
+    mayflower::check_debt_to_floor(shares_after, floor_price, debt_after, max_debt_to_floor_bps)?;
+
     ctx.accounts.position.deposited_nav = ctx
         .accounts
         .position
@@ -341,5 +512,18 @@ pub fn handler(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Res
         .checked_add(shares_received)
         .ok_or(HardigError::InsufficientFunds)?;
 
+    emit!(Reinvested {
+        position: ctx.accounts.position.key(),
+        key_asset,
+        permissions,
+        actual_borrowed,
+        amount_invested,
+        shares_received,
+        deposited_nav: ctx.accounts.position.deposited_nav,
+        user_debt: ctx.accounts.position.user_debt,
+        protocol_fee,
+        keeper_bounty,
+    });
+
     Ok(())
 }
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::HardigError;
+use crate::events::TrustedProviderRemoved;
 use crate::state::{ProtocolConfig, TrustedProvider};
 
 #[derive(Accounts)]
@@ -26,9 +27,13 @@ pub struct RemoveTrustedProvider<'info> {
     pub trusted_provider: Account<'info, TrustedProvider>,
 }
 
-pub fn handler(_ctx: Context<RemoveTrustedProvider>) -> Result<()> {
+pub fn handler(ctx: Context<RemoveTrustedProvider>) -> Result<()> {
     // Account is closed by Anchor's `close = admin` constraint.
     // Rent is returned to the admin. The provider can be re-added later
     // via `add_trusted_provider`.
+    emit!(TrustedProviderRemoved {
+        program_id: ctx.accounts.trusted_provider.program_id,
+    });
+
     Ok(())
 }
@@ -4,9 +4,11 @@ use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::Token;
 
 use crate::errors::HardigError;
+use crate::events::Repaid;
 use crate::mayflower;
-use crate::state::{MarketConfig, PositionState, ProtocolConfig, PERM_REPAY};
+use crate::state::{KeyState, MarketConfig, PositionState, ProtocolConfig, PERM_REPAY};
 
+use super::promo::principal::is_principal_key;
 use super::validate_key::validate_key;
 
 #[derive(Accounts)]
@@ -82,17 +84,29 @@ pub struct Repay<'info> {
     /// CHECK: Validated in handler via seed derivation.
     #[account(mut)]
     pub log_account: UncheckedAccount<'info>,
+
+    /// Optional KeyState of the signing key; the repayment is credited against its debt.
+    #[account(mut)]
+    pub key_state: Option<Account<'info, KeyState>>,
 }
 
 pub fn handler(ctx: Context<Repay>, amount: u64) -> Result<()> {
-    validate_key(
+    // Keys with a refundable promo principal may pay down their own debt without PERM_REPAY
+    let principal_mode =
+        is_principal_key(ctx.accounts.key_state.as_deref(), &ctx.accounts.key_asset.key());
+    let permissions = validate_key(
         &ctx.accounts.signer,
         &ctx.accounts.key_asset.to_account_info(),
         &ctx.accounts.position.authority_seed,
-        PERM_REPAY,
+        if principal_mode { u16::MAX } else { PERM_REPAY },
         &ctx.accounts.config.collection,
     )?;
 
+    // Validate KeyState matches key_asset if provided
+    if let Some(ref ks) = ctx.accounts.key_state {
+        require!(ks.asset == ctx.accounts.key_asset.key(), HardigError::InvalidKey);
+    }
+
     require!(amount > 0, HardigError::InsufficientFunds);
 
     let mc = &ctx.accounts.market_config;
@@ -187,6 +201,17 @@ pub fn handler(ctx: Context<Repay>, amount: u64) -> Result<()> {
         .position
         .user_debt
         .saturating_sub(actual_repaid);
+    if let Some(ks) = ctx.accounts.key_state.as_mut() {
+        ks.debt = ks.debt.saturating_sub(actual_repaid);
+    }
+
+    emit!(Repaid {
+        position: ctx.accounts.position.key(),
+        key_asset: ctx.accounts.key_asset.key(),
+        permissions,
+        actual_repaid,
+        user_debt: ctx.accounts.position.user_debt,
+    });
 
     Ok(())
 }
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::MarketConfigRetired;
+use crate::state::{MarketConfig, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct RetireMarketConfig<'info> {
+    pub admin: Signer<'info>,
+
+    /// The global protocol config — used to verify the signer is the protocol admin.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ HardigError::Unauthorized,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [MarketConfig::SEED, market_config.nav_mint.as_ref()],
+        bump = market_config.bump,
+        constraint = !market_config.retired @ HardigError::MarketRetired,
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+}
+
+/// Permanently retire a market. Irreversible: the nav mint's MarketConfig PDA cannot be recreated.
+pub fn handler(ctx: Context<RetireMarketConfig>) -> Result<()> {
+    let mc = &mut ctx.accounts.market_config;
+    mc.retired = true;
+
+    emit!(MarketConfigRetired {
+        market_config: mc.key(),
+        nav_mint: mc.nav_mint,
+    });
+
+    Ok(())
+}
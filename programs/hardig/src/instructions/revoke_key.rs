@@ -8,6 +8,7 @@ use mpl_core::{
 };
 
 use crate::errors::HardigError;
+use crate::events::KeyRevoked;
 use crate::state::{KeyState, PositionState, ProtocolConfig, PERM_MANAGE_KEYS};
 
 use super::validate_key::validate_key;
@@ -121,5 +122,11 @@ pub fn handler(ctx: Context<RevokeKey>) -> Result<()> {
 
     // target_key_state is closed by the `close = admin` constraint.
 
+    emit!(KeyRevoked {
+        position: ctx.accounts.position.key(),
+        admin_key_asset: ctx.accounts.admin_key_asset.key(),
+        key_asset: ctx.accounts.target_asset.key(),
+    });
+
     Ok(())
 }
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::MarketPausedSet;
+use crate::state::{MarketConfig, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct SetMarketPaused<'info> {
+    #[account(
+        constraint = admin.key() == config.admin @ HardigError::Unauthorized,
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [MarketConfig::SEED, market_config.nav_mint.as_ref()],
+        bump = market_config.bump,
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+}
+
+pub fn handler(ctx: Context<SetMarketPaused>, paused: bool) -> Result<()> {
+    ctx.accounts.market_config.paused = paused;
+
+    emit!(MarketPausedSet {
+        market_config: ctx.accounts.market_config.key(),
+        paused,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::PayoutAddressSet;
+use crate::state::{PositionState, ProtocolConfig, PERM_MANAGE_KEYS};
+use super::configure_cosign::require_cosigners;
+use super::validate_key::validate_key;
+
+#[derive(Accounts)]
+pub struct SetPayoutAddress<'info> {
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position to update. Mutable to update payout_address + last_admin_activity.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — needed to read the collection address for validate_key.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: co-signer pairs, required while a co-signing policy is active
+    // so a single stolen admin key cannot redirect payouts to itself.
+}
+
+pub fn handler(ctx: Context<SetPayoutAddress>, payout_address: Option<Pubkey>) -> Result<()> {
+    validate_key(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    if ctx.accounts.position.cosign_required > 0 {
+        require_cosigners(
+            &ctx.accounts.position,
+            &ctx.accounts.admin_key_asset.key(),
+            ctx.remaining_accounts,
+            &ctx.accounts.config.collection,
+        )?;
+    }
+
+    let position = &mut ctx.accounts.position;
+    let payout_address = payout_address.unwrap_or_default();
+
+    // The program PDA owns the wSOL ATA being closed; paying out to it would strand the SOL
+    let program_pda = Pubkey::create_program_address(
+        &[b"authority", position.authority_seed.as_ref(), &[position.authority_bump]],
+        ctx.program_id,
+    )
+    .map_err(|_| error!(HardigError::InvalidPayoutAddress))?;
+    require!(payout_address != program_pda, HardigError::InvalidPayoutAddress);
+    require!(
+        payout_address == Pubkey::default() || position.share_mint == Pubkey::default(),
+        HardigError::ShareTokenPolicyConflict
+    );
+
+    position.payout_address = payout_address;
+    position.last_admin_activity = Clock::get()?.unix_timestamp;
+
+    emit!(PayoutAddressSet {
+        position: position.key(),
+        payout_address,
+    });
+
+    Ok(())
+}
+
+/// Resolve the account that receives unwrapped SOL from withdraw/borrow.
+///
+/// With no payout address pinned the signer is paid and `payout` must be omitted.
+/// Once pinned, `payout` must be that exact account, so the signer cannot redirect funds.
+pub fn payout_destination<'info>(
+    position: &PositionState,
+    signer: &AccountInfo<'info>,
+    payout: Option<&UncheckedAccount<'info>>,
+) -> Result<AccountInfo<'info>> {
+    if position.payout_address == Pubkey::default() {
+        require!(payout.is_none(), HardigError::PayoutMismatch);
+        return Ok(signer.clone());
+    }
+    let payout = payout.ok_or(error!(HardigError::PayoutMismatch))?;
+    require!(
+        payout.key() == position.payout_address,
+        HardigError::PayoutMismatch
+    );
+    Ok(payout.to_account_info())
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+use crate::events::PositionArtworkSet;
 use crate::state::{PositionState, ProtocolConfig, PERM_MANAGE_KEYS};
 use super::validate_key::validate_key;
 
@@ -47,5 +48,10 @@ pub fn handler(ctx: Context<SetPositionArtwork>, artwork_id: Option<Pubkey>) ->
     ctx.accounts.position.artwork_id = artwork_id;
     ctx.accounts.position.last_admin_activity = Clock::get()?.unix_timestamp;
 
+    emit!(PositionArtworkSet {
+        position: ctx.accounts.position.key(),
+        artwork_id,
+    });
+
     Ok(())
 }
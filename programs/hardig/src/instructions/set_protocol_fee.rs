@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::ProtocolFeeSet;
+use crate::state::ProtocolConfig;
+
+/// Hard ceiling on the protocol fee: 10% of each reinvest's borrowed amount.
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    #[account(
+        constraint = admin.key() == config.admin @ HardigError::Unauthorized,
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+pub fn handler(ctx: Context<SetProtocolFee>, fee_bps: u16, treasury: Pubkey) -> Result<()> {
+    require!(fee_bps <= MAX_PROTOCOL_FEE_BPS, HardigError::ProtocolFeeTooHigh);
+    require!(
+        fee_bps == 0 || treasury != Pubkey::default(),
+        HardigError::InvalidTreasury
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.fee_bps = fee_bps;
+    config.treasury = treasury;
+
+    emit!(ProtocolFeeSet { fee_bps, treasury });
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::ProtocolPausedSet;
+use crate::state::ProtocolConfig;
+
+#[derive(Accounts)]
+pub struct SetProtocolPaused<'info> {
+    #[account(
+        constraint = admin.key() == config.admin @ HardigError::Unauthorized,
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+pub fn handler(ctx: Context<SetProtocolPaused>, paused: bool) -> Result<()> {
+    ctx.accounts.config.paused = paused;
+
+    emit!(ProtocolPausedSet { paused });
+
+    Ok(())
+}
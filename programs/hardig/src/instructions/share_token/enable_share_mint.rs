@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+
+use crate::errors::HardigError;
+use crate::events::ShareMintEnabled;
+use crate::mayflower;
+use crate::state::{MarketConfig, PositionState, ProtocolConfig, PERM_MANAGE_KEYS};
+use super::super::validate_key::validate_key;
+
+#[derive(Accounts)]
+pub struct EnableShareMint<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position to tokenize. Mutable to record share_mint + last_admin_activity.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — needed to read the collection address for validate_key.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The MarketConfig for this position's market.
+    #[account(
+        constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+
+    /// Program PDA (authority) that becomes the share mint's mint authority.
+    /// CHECK: PDA derived from this program.
+    #[account(
+        seeds = [b"authority", position.authority_seed.as_ref()],
+        bump,
+    )]
+    pub program_pda: UncheckedAccount<'info>,
+
+    /// Mayflower PersonalPosition PDA (read to check the position is empty).
+    /// CHECK: Validated in handler via seed derivation.
+    pub personal_position: UncheckedAccount<'info>,
+
+    /// navSOL mint; the share token uses its decimals.
+    #[account(
+        constraint = nav_sol_mint.key() == market_config.nav_mint @ HardigError::InvalidMayflowerAccount,
+    )]
+    pub nav_sol_mint: Account<'info, Mint>,
+
+    /// The share token mint PDA to create.
+    #[account(
+        init,
+        payer = admin,
+        seeds = [PositionState::SHARE_MINT_SEED, position.key().as_ref()],
+        bump,
+        mint::decimals = nav_sol_mint.decimals,
+        mint::authority = program_pda,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<EnableShareMint>) -> Result<()> {
+    validate_key(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    let program_pda = ctx.accounts.program_pda.key();
+    let (expected_pp, _) =
+        mayflower::derive_personal_position(&program_pda, &ctx.accounts.market_config.market_meta);
+    require!(
+        ctx.accounts.personal_position.key() == expected_pp,
+        HardigError::InvalidMayflowerAccount
+    );
+
+    // Every token must come from a `buy`: holdings from before the share token
+    // would otherwise have to be minted to someone outside the key controls
+    {
+        let data = ctx.accounts.personal_position.try_borrow_data()?;
+        require!(
+            mayflower::read_deposited_shares(&data)? == 0 && mayflower::read_debt(&data)? == 0,
+            HardigError::PositionNotEmpty
+        );
+    }
+
+    // Redeeming needs no key, so key-level payout controls could not hold
+    let position = &ctx.accounts.position;
+    require!(
+        position.cosign_required == 0
+            && position.withdraw_delay_secs == 0
+            && position.payout_address == Pubkey::default(),
+        HardigError::ShareTokenPolicyConflict
+    );
+
+    let position = &mut ctx.accounts.position;
+    position.share_mint = ctx.accounts.share_mint.key();
+    position.last_admin_activity = Clock::get()?.unix_timestamp;
+
+    emit!(ShareMintEnabled {
+        position: position.key(),
+        share_mint: position.share_mint,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod enable_share_mint;
+pub mod redeem_shares;
+pub mod supply;
+
+#[allow(ambiguous_glob_reexports)]
+pub use enable_share_mint::*;
+pub use redeem_shares::*;
@@ -0,0 +1,290 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use crate::errors::HardigError;
+use crate::events::SharesRedeemed;
+use crate::mayflower;
+use crate::state::{MarketConfig, PositionState, ProtocolConfig};
+use super::super::unwind::token_balance;
+use super::supply::{net_shares, shares_for_tokens};
+
+#[derive(Accounts)]
+pub struct RedeemShares<'info> {
+    /// Share token holder. No key NFT is needed: the tokens are the claim.
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    /// The position to redeem from.
+    #[account(
+        mut,
+        constraint = position.share_mint != Pubkey::default() @ HardigError::ShareTokenNotEnabled,
+    )]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+        constraint = !config.paused @ HardigError::ProtocolPaused,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The MarketConfig for this position's market.
+    #[account(
+        constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+        constraint = !market_config.paused @ HardigError::MarketPaused,
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+
+    /// The position's share token mint.
+    #[account(
+        mut,
+        constraint = share_mint.key() == position.share_mint @ HardigError::ShareMintMismatch,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// The holder's share token account to burn from.
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = holder,
+    )]
+    pub holder_share_ata: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+
+    // -- Mayflower CPI accounts --
+
+    /// Mutable because Mayflower CPI marks user_wallet as writable.
+    /// CHECK: PDA derived from this program.
+    #[account(mut, seeds = [b"authority", position.authority_seed.as_ref()], bump)]
+    pub program_pda: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in handler via seed derivation.
+    #[account(mut)]
+    pub personal_position: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in handler via seed derivation.
+    #[account(mut)]
+    pub user_shares: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as correct ATA for program_pda + nav_mint.
+    #[account(
+        mut,
+        constraint = user_nav_sol_ata.key() == get_associated_token_address(&program_pda.key(), &market_config.nav_mint) @ HardigError::InvalidAta,
+    )]
+    pub user_nav_sol_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as correct ATA for program_pda + base_mint.
+    #[account(
+        mut,
+        constraint = user_wsol_ata.key() == get_associated_token_address(&program_pda.key(), &market_config.base_mint) @ HardigError::InvalidAta,
+    )]
+    pub user_wsol_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Constant address validated by constraint.
+    #[account(constraint = tenant.key() == mayflower::MAYFLOWER_TENANT @ HardigError::InvalidMayflowerAccount)]
+    pub tenant: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(constraint = market_group.key() == market_config.market_group @ HardigError::InvalidMayflowerAccount)]
+    pub market_group: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(constraint = market_meta.key() == market_config.market_meta @ HardigError::InvalidMayflowerAccount)]
+    pub market_meta: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = mayflower_market.key() == market_config.mayflower_market @ HardigError::InvalidMayflowerAccount)]
+    pub mayflower_market: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = nav_sol_mint.key() == market_config.nav_mint @ HardigError::InvalidMayflowerAccount)]
+    pub nav_sol_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = market_base_vault.key() == market_config.market_base_vault @ HardigError::InvalidMayflowerAccount)]
+    pub market_base_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = market_nav_vault.key() == market_config.market_nav_vault @ HardigError::InvalidMayflowerAccount)]
+    pub market_nav_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = fee_vault.key() == market_config.fee_vault @ HardigError::InvalidMayflowerAccount)]
+    pub fee_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(constraint = wsol_mint.key() == market_config.base_mint @ HardigError::InvalidMayflowerAccount)]
+    pub wsol_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Constant address validated by constraint.
+    #[account(constraint = mayflower_program.key() == mayflower::MAYFLOWER_PROGRAM_ID @ HardigError::InvalidMayflowerAccount)]
+    pub mayflower_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Validated in handler via seed derivation.
+    #[account(mut)]
+    pub log_account: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<RedeemShares>, amount: u64, min_out: u64) -> Result<()> {
+    require!(amount > 0, HardigError::InsufficientFunds);
+
+    let mc = &ctx.accounts.market_config;
+
+    // Validate PDA-derived accounts BEFORE reading from them
+    let program_pda = ctx.accounts.program_pda.key();
+    let (expected_pp, _) = mayflower::derive_personal_position(&program_pda, &mc.market_meta);
+    require!(
+        ctx.accounts.personal_position.key() == expected_pp,
+        HardigError::InvalidMayflowerAccount
+    );
+    let (expected_escrow, _) = mayflower::derive_personal_position_escrow(&expected_pp);
+    require!(
+        ctx.accounts.user_shares.key() == expected_escrow,
+        HardigError::InvalidMayflowerAccount
+    );
+    let (expected_log, _) = mayflower::derive_log_account();
+    require!(
+        ctx.accounts.log_account.key() == expected_log,
+        HardigError::InvalidMayflowerAccount
+    );
+
+    // The burned tokens' fraction of the position's navSOL net of debt
+    let pp_info = ctx.accounts.personal_position.to_account_info();
+    let (shares_before, debt) = {
+        let data = pp_info.try_borrow_data()?;
+        (mayflower::read_deposited_shares(&data)?, mayflower::read_debt(&data)?)
+    };
+    let floor_price = {
+        let data = ctx.accounts.mayflower_market.try_borrow_data()?;
+        mayflower::read_floor_price(&data)?
+    };
+    let net = net_shares(shares_before, debt, floor_price);
+    let shares_to_sell = shares_for_tokens(amount, ctx.accounts.share_mint.supply, net);
+    require!(shares_to_sell > 0, HardigError::NoShareValue);
+
+    // Burn first; the holder signs for their own tokens
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.holder_share_ata.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let wsol_info = ctx.accounts.user_wsol_ata.to_account_info();
+    let wsol_before = token_balance(&wsol_info)?;
+
+    let market = mayflower::MarketAddresses {
+        nav_mint: mc.nav_mint,
+        base_mint: mc.base_mint,
+        market_group: mc.market_group,
+        market_meta: mc.market_meta,
+        mayflower_market: mc.mayflower_market,
+        market_base_vault: mc.market_base_vault,
+        market_nav_vault: mc.market_nav_vault,
+        fee_vault: mc.fee_vault,
+    };
+
+    let bump = ctx.bumps.program_pda;
+    let admin_asset_key = ctx.accounts.position.authority_seed;
+    let signer_seeds: &[&[&[u8]]] = &[&[b"authority", admin_asset_key.as_ref(), &[bump]]];
+
+    let sell_ix = mayflower::build_sell_ix(
+        program_pda,
+        ctx.accounts.personal_position.key(),
+        ctx.accounts.user_shares.key(),
+        ctx.accounts.user_nav_sol_ata.key(),
+        ctx.accounts.user_wsol_ata.key(),
+        shares_to_sell,
+        0, // Mayflower's own min_output — we enforce slippage ourselves
+        &market,
+    );
+    invoke_signed(
+        &sell_ix,
+        &[
+            ctx.accounts.program_pda.to_account_info(),       // 0: userWallet
+            ctx.accounts.tenant.to_account_info(),            // 1: tenant
+            ctx.accounts.market_group.to_account_info(),      // 2: marketGroup
+            ctx.accounts.market_meta.to_account_info(),       // 3: marketMetadata
+            ctx.accounts.mayflower_market.to_account_info(),  // 4: mayflowerMarket
+            pp_info.clone(),                                  // 5: personalPosition
+            ctx.accounts.market_base_vault.to_account_info(), // 6: marketBaseVault
+            ctx.accounts.market_nav_vault.to_account_info(),  // 7: marketNavVault
+            ctx.accounts.fee_vault.to_account_info(),         // 8: feeVault
+            ctx.accounts.nav_sol_mint.to_account_info(),      // 9: navMint
+            ctx.accounts.wsol_mint.to_account_info(),         // 10: baseMint
+            wsol_info.clone(),                                // 11: userWsolATA
+            ctx.accounts.user_nav_sol_ata.to_account_info(),  // 12: userNavSolATA
+            ctx.accounts.user_shares.to_account_info(),       // 13: userShares
+            ctx.accounts.token_program.to_account_info(),     // 14: Token Program
+            ctx.accounts.token_program.to_account_info(),     // 15: Token Program (dup)
+            ctx.accounts.log_account.to_account_info(),       // 16: logAccount
+            ctx.accounts.mayflower_program.to_account_info(), // 17: Mayflower program
+        ],
+        signer_seeds,
+    )?;
+
+    let shares_after = {
+        let data = pp_info.try_borrow_data()?;
+        mayflower::read_deposited_shares(&data)?
+    };
+    let shares_sold = shares_before
+        .checked_sub(shares_after)
+        .ok_or(HardigError::InsufficientFunds)?;
+
+    // Slippage check on the sale proceeds
+    let sol_received = token_balance(&wsol_info)?.saturating_sub(wsol_before);
+    require!(sol_received >= min_out, HardigError::SlippageExceeded);
+
+    // Close PDA's wSOL ATA — returns the proceeds + rent as native SOL to the holder
+    // Only attempt if the account is an initialized SPL token account (state byte at offset 108)
+    let wsol_initialized = {
+        let data = wsol_info.try_borrow_data()?;
+        data.len() >= 109 && data[108] != 0
+    };
+    if wsol_initialized {
+        let close_ix = Instruction {
+            program_id: anchor_spl::token::ID,
+            accounts: vec![
+                AccountMeta::new(ctx.accounts.user_wsol_ata.key(), false),
+                AccountMeta::new(ctx.accounts.holder.key(), false),
+                AccountMeta::new_readonly(program_pda, true),
+            ],
+            data: vec![9], // SPL Token CloseAccount
+        };
+        invoke_signed(
+            &close_ix,
+            &[
+                wsol_info,
+                ctx.accounts.holder.to_account_info(),
+                ctx.accounts.program_pda.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+    }
+
+    let position = &mut ctx.accounts.position;
+    position.deposited_nav = position.deposited_nav.saturating_sub(shares_sold);
+
+    emit!(SharesRedeemed {
+        position: position.key(),
+        holder: ctx.accounts.holder.key(),
+        tokens_burned: amount,
+        shares_sold,
+        sol_received,
+        deposited_nav: position.deposited_nav,
+    });
+
+    Ok(())
+}
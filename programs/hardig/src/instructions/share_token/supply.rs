@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::errors::HardigError;
+use crate::state::PositionState;
+use super::super::promo::principal::debt_in_shares;
+
+// ---------------------------------------------------------------------------
+// Share token supply
+//
+// A position's share token is a pro-rata claim on its navSOL net of debt
+// (debt valued at the floor price). `buy` mints tokens at the current net value
+// per token, and `redeem_shares` sells the fraction of the net shares that the
+// burned tokens represent. A position is tokenized empty, and from then on
+// withdraw, borrow, unwind, reinvest and promo claims are refused, so navSOL
+// only enters through `buy` and leaves through `redeem_shares`: supply tracks
+// deposited_nav.
+// ---------------------------------------------------------------------------
+
+/// navSOL shares the position holds net of `debt` at `floor_price`.
+pub fn net_shares(deposited_shares: u64, debt: u64, floor_price: u64) -> u64 {
+    deposited_shares.saturating_sub(debt_in_shares(debt, floor_price))
+}
+
+/// Share tokens minted for `shares_received` navSOL. The first deposit mints 1:1.
+pub fn tokens_for_deposit(shares_received: u64, supply: u64, net_before: u64) -> Result<u64> {
+    if supply == 0 {
+        return Ok(shares_received);
+    }
+    require!(net_before > 0, HardigError::NoShareValue);
+    let tokens = shares_received as u128 * supply as u128 / net_before as u128;
+    u64::try_from(tokens).map_err(|_| error!(HardigError::NoShareValue))
+}
+
+/// navSOL shares that `tokens` out of `supply` redeem for, rounded down.
+pub fn shares_for_tokens(tokens: u64, supply: u64, net_shares: u64) -> u64 {
+    if supply == 0 {
+        return 0;
+    }
+    // tokens <= supply, so the result never exceeds net_shares
+    (tokens as u128 * net_shares as u128 / supply as u128) as u64
+}
+
+/// The share mint and destination token account `buy` mints into. Both must be
+/// passed and belong to `position.share_mint`, and the token account to `depositor`.
+pub fn share_accounts<'a, 'info>(
+    position: &PositionState,
+    depositor: &Pubkey,
+    share_mint: Option<&'a Account<'info, Mint>>,
+    depositor_share_ata: Option<&'a Account<'info, TokenAccount>>,
+) -> Result<(&'a Account<'info, Mint>, &'a Account<'info, TokenAccount>)> {
+    let share_mint = share_mint.ok_or(error!(HardigError::ShareMintMismatch))?;
+    let depositor_share_ata = depositor_share_ata.ok_or(error!(HardigError::ShareMintMismatch))?;
+    require!(
+        share_mint.key() == position.share_mint && depositor_share_ata.mint == position.share_mint,
+        HardigError::ShareMintMismatch
+    );
+    require!(depositor_share_ata.owner == *depositor, HardigError::ShareMintMismatch);
+    Ok((share_mint, depositor_share_ata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deposits_mint_at_net_value_per_token() {
+        // First deposit mints 1:1
+        assert_eq!(tokens_for_deposit(1_000, 0, 0).unwrap(), 1_000);
+        // 1_000 tokens backed by 2_000 net shares: each token is worth 2 shares
+        assert_eq!(tokens_for_deposit(500, 1_000, 2_000).unwrap(), 250);
+        // Tokens outstanding but nothing left to price them against
+        assert!(tokens_for_deposit(500, 1_000, 0).is_err());
+    }
+
+    #[test]
+    fn redemptions_are_pro_rata_net_of_debt() {
+        // 1_000 shares, 500 lamports of debt at 1 SOL per navSOL = 500 net shares
+        let net = net_shares(1_000, 500, 1_000_000_000);
+        assert_eq!(net, 500);
+        assert_eq!(shares_for_tokens(100, 1_000, net), 50);
+        assert_eq!(shares_for_tokens(1_000, 1_000, net), 500);
+        assert_eq!(shares_for_tokens(1, 0, net), 0);
+    }
+}
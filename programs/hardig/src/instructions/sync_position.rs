@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::PositionSynced;
+use crate::mayflower;
+use crate::state::PositionState;
+
+#[derive(Accounts)]
+pub struct SyncPosition<'info> {
+    /// Anyone can resync a position.
+    pub caller: Signer<'info>,
+
+    /// The position whose local accounting is overwritten.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// Mayflower PersonalPosition — the source of truth for shares and debt.
+    /// CHECK: Validated against position.position_pda (set at creation from the PDA derivation).
+    #[account(
+        constraint = personal_position.key() == position.position_pda @ HardigError::InvalidMayflowerAccount,
+    )]
+    pub personal_position: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<SyncPosition>) -> Result<()> {
+    let (deposited_shares, debt) = {
+        let data = ctx.accounts.personal_position.try_borrow_data()?;
+        (
+            mayflower::read_deposited_shares(&data)?,
+            mayflower::read_debt(&data)?,
+        )
+    };
+
+    let position = &mut ctx.accounts.position;
+    let nav_delta = deposited_shares as i128 - position.deposited_nav as i128;
+    let debt_delta = debt as i128 - position.user_debt as i128;
+    position.deposited_nav = deposited_shares;
+    position.user_debt = debt;
+
+    emit!(PositionSynced {
+        position: position.key(),
+        caller: ctx.accounts.caller.key(),
+        deposited_nav: deposited_shares,
+        user_debt: debt,
+        nav_delta: saturate_i64(nav_delta),
+        debt_delta: saturate_i64(debt_delta),
+    });
+
+    Ok(())
+}
+
+/// The difference of two u64s can exceed i64. The deltas only describe the sync,
+/// so they saturate rather than fail it.
+fn saturate_i64(delta: i128) -> i64 {
+    delta.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+use crate::events::AdminTransferStarted;
 use crate::state::ProtocolConfig;
 
 #[derive(Accounts)]
@@ -19,5 +20,11 @@ pub struct TransferAdmin<'info> {
 
 pub fn handler(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
     ctx.accounts.config.pending_admin = new_admin;
+
+    emit!(AdminTransferStarted {
+        admin: ctx.accounts.admin.key(),
+        pending_admin: new_admin,
+    });
+
     Ok(())
 }
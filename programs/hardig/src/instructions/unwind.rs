@@ -0,0 +1,369 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::Token;
+
+use crate::errors::HardigError;
+use crate::events::Unwound;
+use crate::mayflower;
+use crate::state::{MarketConfig, PositionState, ProtocolConfig, PERM_MANAGE_KEYS, PERM_UNWIND};
+
+use super::validate_key::validate_key;
+
+#[derive(Accounts)]
+pub struct Unwind<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// The signer's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub key_asset: UncheckedAccount<'info>,
+
+    /// The position to deleverage. Tokenized positions pay out through `redeem_shares` only.
+    #[account(
+        mut,
+        constraint = position.share_mint == Pubkey::default() @ HardigError::ShareTokenEnabled,
+    )]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — provides collection pubkey for key validation.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The MarketConfig for this position's market.
+    #[account(
+        constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+
+    pub system_program: Program<'info, System>,
+
+    // -- Mayflower CPI accounts --
+
+    /// Mutable because Mayflower CPI marks user_wallet as writable.
+    /// CHECK: PDA derived from this program.
+    #[account(mut, seeds = [b"authority", position.authority_seed.as_ref()], bump)]
+    pub program_pda: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in handler via seed derivation.
+    #[account(mut)]
+    pub personal_position: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in handler via seed derivation.
+    #[account(mut)]
+    pub user_shares: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as correct ATA for program_pda + nav_mint.
+    #[account(
+        mut,
+        constraint = user_nav_sol_ata.key() == get_associated_token_address(&program_pda.key(), &market_config.nav_mint) @ HardigError::InvalidAta,
+    )]
+    pub user_nav_sol_ata: UncheckedAccount<'info>,
+
+    /// Receives the sale proceeds and funds the repay. Left open; any wSOL not
+    /// needed to reach the target debt is bought back into the position.
+    /// CHECK: Validated as correct ATA for program_pda + base_mint.
+    #[account(
+        mut,
+        constraint = user_wsol_ata.key() == get_associated_token_address(&program_pda.key(), &market_config.base_mint) @ HardigError::InvalidAta,
+    )]
+    pub user_wsol_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Constant address validated by constraint.
+    #[account(constraint = tenant.key() == mayflower::MAYFLOWER_TENANT @ HardigError::InvalidMayflowerAccount)]
+    pub tenant: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(constraint = market_group.key() == market_config.market_group @ HardigError::InvalidMayflowerAccount)]
+    pub market_group: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(constraint = market_meta.key() == market_config.market_meta @ HardigError::InvalidMayflowerAccount)]
+    pub market_meta: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = mayflower_market.key() == market_config.mayflower_market @ HardigError::InvalidMayflowerAccount)]
+    pub mayflower_market: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = nav_sol_mint.key() == market_config.nav_mint @ HardigError::InvalidMayflowerAccount)]
+    pub nav_sol_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = market_base_vault.key() == market_config.market_base_vault @ HardigError::InvalidMayflowerAccount)]
+    pub market_base_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = market_nav_vault.key() == market_config.market_nav_vault @ HardigError::InvalidMayflowerAccount)]
+    pub market_nav_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(mut, constraint = fee_vault.key() == market_config.fee_vault @ HardigError::InvalidMayflowerAccount)]
+    pub fee_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against market_config.
+    #[account(constraint = wsol_mint.key() == market_config.base_mint @ HardigError::InvalidMayflowerAccount)]
+    pub wsol_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Constant address validated by constraint.
+    #[account(constraint = mayflower_program.key() == mayflower::MAYFLOWER_PROGRAM_ID @ HardigError::InvalidMayflowerAccount)]
+    pub mayflower_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Validated in handler via seed derivation.
+    #[account(mut)]
+    pub log_account: UncheckedAccount<'info>,
+}
+
+/// Read the token amount of an SPL token account (0 if not yet created).
+pub(crate) fn token_balance(info: &AccountInfo) -> Result<u64> {
+    let data = info.try_borrow_data()?;
+    Ok(if data.len() >= 72 {
+        u64::from_le_bytes(data[64..72].try_into().unwrap())
+    } else {
+        0
+    })
+}
+
+pub fn handler(
+    ctx: Context<Unwind>,
+    amount: u64,
+    min_out: u64,
+    target_debt: u64,
+    min_shares_rebought: u64,
+) -> Result<()> {
+    // Admin keys predate PERM_UNWIND; MANAGE_KEYS (admin-only) is accepted in its place.
+    let permissions = validate_key(
+        &ctx.accounts.signer,
+        &ctx.accounts.key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_UNWIND | PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    require!(amount > 0, HardigError::InsufficientFunds);
+
+    let mc = &ctx.accounts.market_config;
+
+    // Validate PDA-derived accounts BEFORE reading from them
+    let program_pda = ctx.accounts.program_pda.key();
+    let (expected_pp, _) = mayflower::derive_personal_position(&program_pda, &mc.market_meta);
+    require!(
+        ctx.accounts.personal_position.key() == expected_pp,
+        HardigError::InvalidMayflowerAccount
+    );
+    let (expected_escrow, _) = mayflower::derive_personal_position_escrow(&expected_pp);
+    require!(
+        ctx.accounts.user_shares.key() == expected_escrow,
+        HardigError::InvalidMayflowerAccount
+    );
+    let (expected_log, _) = mayflower::derive_log_account();
+    require!(
+        ctx.accounts.log_account.key() == expected_log,
+        HardigError::InvalidMayflowerAccount
+    );
+
+    // Mayflower is the source of truth for shares and debt
+    let pp_info = ctx.accounts.personal_position.to_account_info();
+    let (shares_before, debt_before) = {
+        let data = pp_info.try_borrow_data()?;
+        (mayflower::read_deposited_shares(&data)?, mayflower::read_debt(&data)?)
+    };
+    require!(amount <= shares_before, HardigError::InsufficientFunds);
+    require!(debt_before > target_debt, HardigError::DebtAtOrBelowTarget);
+
+    // navSOL never sells below the floor, so the floor value of the debt to repay bounds
+    // the sale. Anything more would only be sold to be bought back.
+    let floor_price = {
+        let data = ctx.accounts.mayflower_market.try_borrow_data()?;
+        mayflower::read_floor_price(&data)?
+    };
+    let max_sell = mayflower::shares_covering(debt_before - target_debt, floor_price)?;
+    require!(amount <= max_sell, HardigError::UnwindExceedsRepay);
+
+    if ctx.accounts.key_asset.key() == ctx.accounts.position.current_admin_asset {
+        ctx.accounts.position.last_admin_activity = Clock::get()?.unix_timestamp;
+    }
+
+    let wsol_info = ctx.accounts.user_wsol_ata.to_account_info();
+    let wsol_before = token_balance(&wsol_info)?;
+
+    let market = mayflower::MarketAddresses {
+        nav_mint: mc.nav_mint,
+        base_mint: mc.base_mint,
+        market_group: mc.market_group,
+        market_meta: mc.market_meta,
+        mayflower_market: mc.mayflower_market,
+        market_base_vault: mc.market_base_vault,
+        market_nav_vault: mc.market_nav_vault,
+        fee_vault: mc.fee_vault,
+    };
+
+    let bump = ctx.bumps.program_pda;
+    let admin_asset_key = ctx.accounts.position.authority_seed;
+    let signer_seeds: &[&[&[u8]]] = &[&[b"authority", admin_asset_key.as_ref(), &[bump]]];
+
+    // 1. Sell navSOL — proceeds land in the program PDA's wSOL ATA
+    let sell_ix = mayflower::build_sell_ix(
+        program_pda,
+        ctx.accounts.personal_position.key(),
+        ctx.accounts.user_shares.key(),
+        ctx.accounts.user_nav_sol_ata.key(),
+        ctx.accounts.user_wsol_ata.key(),
+        amount,
+        0, // Mayflower's own min_output — we enforce slippage ourselves
+        &market,
+    );
+    invoke_signed(
+        &sell_ix,
+        &[
+            ctx.accounts.program_pda.to_account_info(),       // 0: userWallet
+            ctx.accounts.tenant.to_account_info(),            // 1: tenant
+            ctx.accounts.market_group.to_account_info(),      // 2: marketGroup
+            ctx.accounts.market_meta.to_account_info(),       // 3: marketMetadata
+            ctx.accounts.mayflower_market.to_account_info(),  // 4: mayflowerMarket
+            pp_info.clone(),                                  // 5: personalPosition
+            ctx.accounts.market_base_vault.to_account_info(), // 6: marketBaseVault
+            ctx.accounts.market_nav_vault.to_account_info(),  // 7: marketNavVault
+            ctx.accounts.fee_vault.to_account_info(),         // 8: feeVault
+            ctx.accounts.nav_sol_mint.to_account_info(),      // 9: navMint
+            ctx.accounts.wsol_mint.to_account_info(),         // 10: baseMint
+            wsol_info.clone(),                                // 11: userWsolATA
+            ctx.accounts.user_nav_sol_ata.to_account_info(),  // 12: userNavSolATA
+            ctx.accounts.user_shares.to_account_info(),       // 13: userShares
+            ctx.accounts.token_program.to_account_info(),     // 14: Token Program
+            ctx.accounts.token_program.to_account_info(),     // 15: Token Program (dup)
+            ctx.accounts.log_account.to_account_info(),       // 16: logAccount
+            ctx.accounts.mayflower_program.to_account_info(), // 17: Mayflower program
+        ],
+        signer_seeds,
+    )?;
+
+    let shares_after = {
+        let data = pp_info.try_borrow_data()?;
+        mayflower::read_deposited_shares(&data)?
+    };
+    let shares_sold = shares_before
+        .checked_sub(shares_after)
+        .ok_or(HardigError::InsufficientFunds)?;
+
+    // Slippage check on the sale proceeds
+    let wsol_after_sell = token_balance(&wsol_info)?;
+    let sol_received = wsol_after_sell.saturating_sub(wsol_before);
+    require!(sol_received >= min_out, HardigError::SlippageExceeded);
+
+    // 2. Repay from the ATA, never below target_debt
+    let repay_amount = wsol_after_sell.min(debt_before - target_debt);
+    let mut actual_repaid = 0;
+    if repay_amount > 0 {
+        let repay_ix = mayflower::build_repay_ix(
+            program_pda,
+            ctx.accounts.personal_position.key(),
+            ctx.accounts.user_wsol_ata.key(),
+            repay_amount,
+            &market,
+        );
+        invoke_signed(
+            &repay_ix,
+            &[
+                ctx.accounts.program_pda.to_account_info(),
+                ctx.accounts.market_meta.to_account_info(),
+                ctx.accounts.mayflower_market.to_account_info(),
+                pp_info.clone(),
+                ctx.accounts.wsol_mint.to_account_info(),
+                wsol_info.clone(),
+                ctx.accounts.market_base_vault.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.log_account.to_account_info(),
+                ctx.accounts.mayflower_program.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+
+        let debt_after = {
+            let data = pp_info.try_borrow_data()?;
+            mayflower::read_debt(&data)?
+        };
+        actual_repaid = debt_before
+            .checked_sub(debt_after)
+            .ok_or(HardigError::InsufficientFunds)?;
+    }
+
+    // 3. Buy back with whatever the repay didn't use (the sale beating the floor, plus
+    //    any dust already in the ATA). Left there it would be swept up by the next
+    //    borrow, withdraw or reinvest.
+    let leftover = wsol_after_sell.saturating_sub(actual_repaid);
+    let mut shares_rebought = 0;
+    if leftover > 0 {
+        let buy_ix = mayflower::build_buy_ix(
+            program_pda,
+            ctx.accounts.personal_position.key(),
+            ctx.accounts.user_shares.key(),
+            ctx.accounts.user_nav_sol_ata.key(),
+            ctx.accounts.user_wsol_ata.key(),
+            leftover,
+            0,
+            &market,
+        );
+        invoke_signed(
+            &buy_ix,
+            &[
+                ctx.accounts.program_pda.to_account_info(),
+                ctx.accounts.tenant.to_account_info(),
+                ctx.accounts.market_group.to_account_info(),
+                ctx.accounts.market_meta.to_account_info(),
+                ctx.accounts.mayflower_market.to_account_info(),
+                pp_info.clone(),
+                ctx.accounts.user_shares.to_account_info(),
+                ctx.accounts.nav_sol_mint.to_account_info(),
+                ctx.accounts.wsol_mint.to_account_info(),
+                ctx.accounts.user_nav_sol_ata.to_account_info(),
+                wsol_info.clone(),
+                ctx.accounts.market_base_vault.to_account_info(),
+                ctx.accounts.market_nav_vault.to_account_info(),
+                ctx.accounts.fee_vault.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.log_account.to_account_info(),
+                ctx.accounts.mayflower_program.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+
+        let shares_final = {
+            let data = pp_info.try_borrow_data()?;
+            mayflower::read_deposited_shares(&data)?
+        };
+        shares_rebought = shares_final
+            .checked_sub(shares_after)
+            .ok_or(HardigError::InsufficientFunds)?;
+    }
+    require!(shares_rebought >= min_shares_rebought, HardigError::SlippageExceeded);
+
+    let position = &mut ctx.accounts.position;
+    position.deposited_nav = position
+        .deposited_nav
+        .saturating_sub(shares_sold)
+        .checked_add(shares_rebought)
+        .ok_or(HardigError::InsufficientFunds)?;
+    position.user_debt = position.user_debt.saturating_sub(actual_repaid);
+
+    emit!(Unwound {
+        position: position.key(),
+        key_asset: ctx.accounts.key_asset.key(),
+        permissions,
+        shares_sold,
+        sol_received,
+        actual_repaid,
+        leftover,
+        shares_rebought,
+        deposited_nav: position.deposited_nav,
+        user_debt: position.user_debt,
+    });
+
+    Ok(())
+}
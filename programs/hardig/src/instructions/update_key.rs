@@ -0,0 +1,245 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    ID as MPL_CORE_ID,
+    accounts::BaseAssetV1,
+    fetch_plugin,
+    instructions::{UpdatePluginV1CpiBuilder, UpdateV1CpiBuilder},
+    types::{Attribute, Attributes, Plugin, PluginType},
+};
+
+use crate::errors::HardigError;
+use crate::state::{
+    KeyLimits, KeyState, PositionState, ProtocolConfig, RateBucket, PERM_LIMITED_BORROW,
+    PERM_LIMITED_SELL, PERM_MANAGE_KEYS,
+};
+
+use super::validate_key::validate_key;
+use super::{limit_attributes, metadata_image, metadata_uri, LIMIT_ATTRIBUTE_KEYS};
+
+/// Accounts shared by `update_key_limits` and `update_key_permissions`, which both
+/// rewrite a delegated key's KeyState and its on-chain attributes/URI in place.
+#[derive(Accounts)]
+pub struct UpdateKey<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, position attribute, permissions).
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position. Mutable to update last_admin_activity.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// The delegated key asset being changed.
+    /// CHECK: Validated in handler (MPL-Core owner + position attribute).
+    #[account(mut)]
+    pub target_asset: UncheckedAccount<'info>,
+
+    /// The target key's KeyState PDA. Kept for the key's lifetime (revoke_key
+    /// closes it); its buckets are initialized or cleared as limited bits change.
+    #[account(
+        mut,
+        constraint = target_key_state.asset == target_asset.key() @ HardigError::InvalidKey,
+    )]
+    pub target_key_state: Account<'info, KeyState>,
+
+    /// Protocol config PDA — signs the plugin/URI updates as the collection's
+    /// update authority.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+        constraint = config.collection != Pubkey::default() @ HardigError::CollectionNotCreated,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The MPL-Core collection asset for Härdig key NFTs.
+    /// CHECK: Validated against config.collection.
+    #[account(
+        mut,
+        constraint = collection.key() == config.collection @ HardigError::CollectionNotCreated,
+    )]
+    pub collection: UncheckedAccount<'info>,
+
+    /// CHECK: MPL-Core program validated by address constraint.
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// What `update_key_*` needs from the target asset before rewriting it.
+pub struct TargetKey {
+    /// Current permission bitmask (from the `permissions` attribute).
+    pub permissions: u16,
+    /// Current attribute list.
+    pub attributes: Vec<Attribute>,
+    /// Current NFT name, kept in the rewritten metadata URI.
+    pub name: String,
+    /// Current image, kept in the rewritten metadata URI.
+    pub image: Option<String>,
+}
+
+impl TargetKey {
+    fn attribute(&self, key: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| a.value.clone())
+    }
+}
+
+/// Validate the admin key and the target (a delegated key of this position, not the
+/// admin key), then read the target's attributes, name and image.
+pub fn load_target_key(accounts: &UpdateKey) -> Result<TargetKey> {
+    // Validate the admin holds their key
+    validate_key(
+        &accounts.admin,
+        &accounts.admin_key_asset.to_account_info(),
+        &accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &accounts.config.collection,
+    )?;
+
+    // The admin key's permissions and limits are fixed
+    require!(
+        accounts.target_asset.key() != accounts.position.current_admin_asset,
+        HardigError::CannotModifyAdminKey
+    );
+
+    // The KeyState must belong to this position
+    require!(
+        accounts.target_key_state.authority_seed == accounts.position.authority_seed,
+        HardigError::WrongPosition
+    );
+
+    // Verify target_asset is actually an MPL-Core asset
+    let target_info = accounts.target_asset.to_account_info();
+    require!(*target_info.owner == mpl_core::ID, HardigError::InvalidKey);
+
+    // Read the target's attributes: position binding + permission bitmask
+    let (_, target_attrs, _) =
+        fetch_plugin::<BaseAssetV1, Attributes>(&target_info, PluginType::Attributes)
+            .map_err(|_| error!(HardigError::InvalidKey))?;
+
+    // Read the current name + URI so the rewritten metadata keeps name and image
+    let target_data = target_info.try_borrow_data()?;
+    let target_base =
+        BaseAssetV1::from_bytes(&target_data).map_err(|_| error!(HardigError::InvalidKey))?;
+    let name = target_base.name.clone();
+    let image = metadata_image(&target_base.uri);
+    drop(target_data);
+
+    let mut target = TargetKey {
+        permissions: 0,
+        attributes: target_attrs.attribute_list,
+        name,
+        image,
+    };
+    let target_position = target
+        .attribute("position")
+        .ok_or(error!(HardigError::WrongPosition))?;
+    require!(
+        target_position == accounts.position.authority_seed.to_string(),
+        HardigError::WrongPosition
+    );
+    target.permissions = target
+        .attribute("permissions")
+        .and_then(|v| v.parse().ok())
+        .ok_or(error!(HardigError::InvalidKey))?;
+
+    Ok(target)
+}
+
+/// Rewrite a key's rate-limit buckets and lifetime caps for `permissions`. Newly added
+/// limited bits start with a full bucket (as in authorize_key), kept buckets carry over
+/// their tokens (clamped to the new capacity), removed buckets are cleared. Lifetime
+/// accumulators are preserved.
+pub fn apply_key_limits(
+    key_state: &mut KeyState,
+    old_permissions: u16,
+    permissions: u16,
+    limits: &KeyLimits,
+    current_slot: u64,
+) {
+    for (bit, bucket, capacity, refill_period) in [
+        (PERM_LIMITED_SELL, &mut key_state.sell_bucket, limits.sell_bucket_capacity, limits.sell_refill_period_slots),
+        (PERM_LIMITED_BORROW, &mut key_state.borrow_bucket, limits.borrow_bucket_capacity, limits.borrow_refill_period_slots),
+    ] {
+        if permissions & bit == 0 {
+            *bucket = RateBucket::default();
+        } else if old_permissions & bit != 0 {
+            bucket.reconfigure(capacity, refill_period, current_slot);
+        } else {
+            *bucket = RateBucket {
+                capacity,
+                refill_period,
+                level: capacity, // starts full
+                last_update: current_slot,
+            };
+        }
+    }
+    key_state.total_sell_limit = limits.total_sell_limit;
+    key_state.total_borrow_limit = limits.total_borrow_limit;
+}
+
+/// Rewrite the target's Attributes plugin and metadata URI. The new list is `leading`,
+/// then the existing attributes whose keys are not in `leading` or limit attributes
+/// (position, position_name, market, ...), then the limit attributes for `permissions`.
+pub fn rewrite_key_metadata(
+    accounts: &UpdateKey,
+    target: TargetKey,
+    permissions: u16,
+    limits: &KeyLimits,
+    leading: Vec<Attribute>,
+) -> Result<()> {
+    let (limit_attrs, sell_limit_str, borrow_limit_str) = limit_attributes(permissions, limits);
+    let market = target.attribute("market");
+    let position_name = target.attribute("position_name");
+    let replaced_keys: Vec<String> = leading
+        .iter()
+        .map(|a| a.key.clone())
+        .chain(LIMIT_ATTRIBUTE_KEYS.iter().map(|k| k.to_string()))
+        .collect();
+    let mut attrs = leading;
+    attrs.extend(
+        target
+            .attributes
+            .into_iter()
+            .filter(|a| !replaced_keys.contains(&a.key)),
+    );
+    attrs.extend(limit_attrs);
+
+    let target_info = accounts.target_asset.to_account_info();
+    let config_seeds: &[&[u8]] = &[ProtocolConfig::SEED, &[accounts.config.bump]];
+
+    UpdatePluginV1CpiBuilder::new(&accounts.mpl_core_program.to_account_info())
+        .asset(&target_info)
+        .collection(Some(&accounts.collection.to_account_info()))
+        .payer(&accounts.admin.to_account_info())
+        .authority(Some(&accounts.config.to_account_info()))
+        .system_program(&accounts.system_program.to_account_info())
+        .plugin(Plugin::Attributes(Attributes {
+            attribute_list: attrs,
+        }))
+        .invoke_signed(&[config_seeds])?;
+
+    UpdateV1CpiBuilder::new(&accounts.mpl_core_program.to_account_info())
+        .asset(&target_info)
+        .collection(Some(&accounts.collection.to_account_info()))
+        .payer(&accounts.admin.to_account_info())
+        .authority(Some(&accounts.config.to_account_info()))
+        .system_program(&accounts.system_program.to_account_info())
+        .new_uri(metadata_uri(
+            &target.name,
+            permissions,
+            sell_limit_str.as_deref(),
+            borrow_limit_str.as_deref(),
+            market.as_deref(),
+            position_name.as_deref(),
+            target.image.as_deref(),
+        ))
+        .invoke_signed(&[config_seeds])?;
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::events::KeyLimitsUpdated;
+use crate::state::{KeyCreatorOrigin, KeyLimits};
+
+use super::update_key::{apply_key_limits, load_target_key, rewrite_key_metadata, UpdateKey};
+use super::validate_delegated_permissions;
+
+pub fn handler(ctx: Context<UpdateKey>, limits: KeyLimits) -> Result<()> {
+    let target = load_target_key(ctx.accounts)?;
+    let permissions = target.permissions;
+
+    // Same rules as authorize_key: limits only for the limited bits the key holds
+    validate_delegated_permissions(
+        KeyCreatorOrigin::Admin,
+        permissions,
+        limits.sell_bucket_capacity,
+        limits.sell_refill_period_slots,
+        limits.borrow_bucket_capacity,
+        limits.borrow_refill_period_slots,
+        limits.total_sell_limit,
+        limits.total_borrow_limit,
+    )?;
+
+    let clock = Clock::get()?;
+    apply_key_limits(
+        &mut ctx.accounts.target_key_state,
+        permissions,
+        permissions,
+        &limits,
+        clock.slot,
+    );
+
+    // Update last_admin_activity so key management resets the recovery lockout
+    ctx.accounts.position.last_admin_activity = clock.unix_timestamp;
+
+    // Keep everything except the limit attributes, then append the new ones
+    rewrite_key_metadata(ctx.accounts, target, permissions, &limits, Vec::new())?;
+
+    emit!(KeyLimitsUpdated {
+        position: ctx.accounts.position.key(),
+        admin_key_asset: ctx.accounts.admin_key_asset.key(),
+        key_asset: ctx.accounts.target_asset.key(),
+        sell_bucket_capacity: limits.sell_bucket_capacity,
+        sell_refill_period_slots: limits.sell_refill_period_slots,
+        borrow_bucket_capacity: limits.borrow_bucket_capacity,
+        borrow_refill_period_slots: limits.borrow_refill_period_slots,
+        total_sell_limit: limits.total_sell_limit,
+        total_borrow_limit: limits.total_borrow_limit,
+    });
+
+    Ok(())
+}
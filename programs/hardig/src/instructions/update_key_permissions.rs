@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::events::KeyPermissionsUpdated;
+use crate::state::{KeyCreatorOrigin, KeyLimits};
+
+use super::update_key::{apply_key_limits, load_target_key, rewrite_key_metadata, UpdateKey};
+use super::{permission_attributes, validate_delegated_permissions};
+
+pub fn handler(ctx: Context<UpdateKey>, permissions: u16, limits: KeyLimits) -> Result<()> {
+    // Same rules as authorize_key for the new bitmask
+    validate_delegated_permissions(
+        KeyCreatorOrigin::Admin,
+        permissions,
+        limits.sell_bucket_capacity,
+        limits.sell_refill_period_slots,
+        limits.borrow_bucket_capacity,
+        limits.borrow_refill_period_slots,
+        limits.total_sell_limit,
+        limits.total_borrow_limit,
+    )?;
+
+    let target = load_target_key(ctx.accounts)?;
+    let old_permissions = target.permissions;
+
+    let clock = Clock::get()?;
+    apply_key_limits(
+        &mut ctx.accounts.target_key_state,
+        old_permissions,
+        permissions,
+        &limits,
+        clock.slot,
+    );
+
+    // Update last_admin_activity so key management resets the recovery lockout
+    ctx.accounts.position.last_admin_activity = clock.unix_timestamp;
+
+    // New permission flags first, then the existing non-permission attributes
+    // (position, position_name, market, ...), then limits
+    rewrite_key_metadata(
+        ctx.accounts,
+        target,
+        permissions,
+        &limits,
+        permission_attributes(permissions),
+    )?;
+
+    emit!(KeyPermissionsUpdated {
+        position: ctx.accounts.position.key(),
+        admin_key_asset: ctx.accounts.admin_key_asset.key(),
+        key_asset: ctx.accounts.target_asset.key(),
+        old_permissions,
+        permissions,
+    });
+
+    Ok(())
+}
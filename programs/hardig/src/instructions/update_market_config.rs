@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::{MarketConfigUpdateApplied, MarketConfigUpdateQueued};
+use crate::mayflower;
+use crate::state::{MarketConfig, MarketUpdate, ProtocolConfig};
+
+/// Longest notice period update_market_config accepts.
+pub const MAX_MARKET_UPDATE_DELAY_SECS: i64 = 30 * 24 * 60 * 60;
+
+#[derive(Accounts)]
+pub struct UpdateMarketConfig<'info> {
+    pub admin: Signer<'info>,
+
+    /// The global protocol config — used to verify the signer is the protocol admin.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ HardigError::Unauthorized,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [MarketConfig::SEED, market_config.nav_mint.as_ref()],
+        bump = market_config.bump,
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+
+    /// The market's Mayflower metadata. Only read when the update applies immediately.
+    /// CHECK: Validated in handler via validate_market_update.
+    pub mayflower_market_meta: UncheckedAccount<'info>,
+
+    /// The update's Mayflower market. Only read when the update applies immediately.
+    /// CHECK: Validated in handler via validate_market_update.
+    pub mayflower_market: UncheckedAccount<'info>,
+}
+
+/// Replace the market's Mayflower accounts. With `delay_secs` 0 the update applies
+/// now (and discards anything staged); otherwise it is staged for
+/// apply_market_config_update, replacing any earlier staged update.
+pub fn handler(ctx: Context<UpdateMarketConfig>, update: MarketUpdate, delay_secs: i64) -> Result<()> {
+    require!(
+        (0..=MAX_MARKET_UPDATE_DELAY_SECS).contains(&delay_secs),
+        HardigError::InvalidMarketUpdateDelay
+    );
+
+    if delay_secs == 0 {
+        validate_market_update(
+            &ctx.accounts.market_config,
+            &update,
+            &ctx.accounts.mayflower_market_meta,
+            &ctx.accounts.mayflower_market,
+        )?;
+    }
+
+    let mc = &mut ctx.accounts.market_config;
+    if delay_secs == 0 {
+        mc.apply_update(&update);
+        mc.pending_update = MarketUpdate::default();
+        mc.pending_update_at = 0;
+        emit!(MarketConfigUpdateApplied {
+            market_config: mc.key(),
+            update,
+        });
+    } else {
+        mc.pending_update = update.clone();
+        mc.pending_update_at = Clock::get()?.unix_timestamp + delay_secs;
+        emit!(MarketConfigUpdateQueued {
+            market_config: mc.key(),
+            update,
+            applies_at: mc.pending_update_at,
+        });
+    }
+
+    Ok(())
+}
+
+/// Check that `mc` with `update` applied still matches Mayflower's own accounts.
+/// Staged updates are checked when they apply, since Mayflower may not have
+/// migrated yet when the update is staged.
+pub(crate) fn validate_market_update<'info>(
+    mc: &MarketConfig,
+    update: &MarketUpdate,
+    market_meta: &AccountInfo<'info>,
+    mayflower_market: &AccountInfo<'info>,
+) -> Result<()> {
+    mayflower::validate_market_accounts(
+        &mayflower::MarketAddresses {
+            nav_mint: mc.nav_mint,
+            base_mint: mc.base_mint,
+            market_group: update.market_group,
+            market_meta: mc.market_meta,
+            mayflower_market: update.mayflower_market,
+            market_base_vault: update.market_base_vault,
+            market_nav_vault: update.market_nav_vault,
+            fee_vault: update.fee_vault,
+        },
+        market_meta,
+        mayflower_market,
+    )
+}
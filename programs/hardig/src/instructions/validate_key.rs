@@ -12,16 +12,35 @@ use crate::errors::HardigError;
 /// Validates that the signer owns the given MPL-Core key asset, that the asset
 /// belongs to the expected position (via the `position` attribute), that the
 /// asset's update authority is the expected collection, and that the key has at
-/// least one of the required permission bits set.
+/// least one of the required permission bits set. Keys carrying an `expires_at`
+/// attribute are rejected once that timestamp has passed.
 ///
 /// Returns the permissions bitmask for further checks (e.g., rate limiting).
 pub fn validate_key(
     signer: &Signer,
     key_asset_info: &AccountInfo,
     expected_admin_asset: &Pubkey,
-    required: u8,
+    required: u16,
     expected_collection: &Pubkey,
-) -> Result<u8> {
+) -> Result<u16> {
+    validate_key_held_by(
+        &signer.key(),
+        key_asset_info,
+        expected_admin_asset,
+        required,
+        expected_collection,
+    )
+}
+
+/// `validate_key` for a holder that is not an Anchor `Signer` (e.g. a co-signer
+/// passed in `remaining_accounts`). The caller must check that `holder` signed.
+pub fn validate_key_held_by(
+    holder: &Pubkey,
+    key_asset_info: &AccountInfo,
+    expected_admin_asset: &Pubkey,
+    required: u16,
+    expected_collection: &Pubkey,
+) -> Result<u16> {
     // Verify the account is owned by the MPL-Core program
     require!(
         *key_asset_info.owner == ID,
@@ -54,7 +73,7 @@ pub fn validate_key(
     drop(data);
 
     // 1. Signer owns this asset
-    require!(owner == signer.key(), HardigError::KeyNotHeld);
+    require!(owner == *holder, HardigError::KeyNotHeld);
 
     // 2. Read attributes from Attributes plugin
     let (_, attributes, _) = fetch_plugin::<BaseAssetV1, Attributes>(
@@ -79,7 +98,7 @@ pub fn validate_key(
         .attribute_list
         .iter()
         .find(|a| a.key == "permissions")
-        .and_then(|a| a.value.parse::<u8>().ok())
+        .and_then(|a| a.value.parse::<u16>().ok())
         .ok_or(error!(HardigError::InvalidKey))?;
 
     // 5. Check required permission(s)
@@ -88,5 +107,18 @@ pub fn validate_key(
         HardigError::InsufficientPermission
     );
 
+    // 6. Reject expired keys (attribute mirrors KeyState.expires_at; absent = never)
+    if let Some(expires_at) = attributes
+        .attribute_list
+        .iter()
+        .find(|a| a.key == "expires_at")
+        .and_then(|a| a.value.parse::<i64>().ok())
+    {
+        require!(
+            Clock::get()?.unix_timestamp < expires_at,
+            HardigError::KeyExpired
+        );
+    }
+
     Ok(permissions)
 }
@@ -5,10 +5,18 @@ use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::Token;
 
 use crate::errors::HardigError;
+use crate::events::Withdrawn;
 use crate::mayflower;
-use crate::state::{KeyState, MarketConfig, PositionState, ProtocolConfig, PERM_LIMITED_SELL, PERM_SELL};
+use crate::state::{
+    KeyState, MarketConfig, PositionState, ProtocolConfig, PERM_LIMITED_SELL,
+    PERM_SELL,
+};
 
+use super::configure_cosign::require_cosigners;
 use super::consume_rate_limit::{consume_rate_limit, consume_total_limit};
+use super::promo::budget::{consume_promo_budget, BudgetKind};
+use super::promo::principal::{consume_principal, is_principal_key};
+use super::set_payout_address::payout_destination;
 use super::validate_key::validate_key;
 
 #[derive(Accounts)]
@@ -24,20 +32,25 @@ pub struct Withdraw<'info> {
     #[account(mut)]
     pub key_state: Option<Account<'info, KeyState>>,
 
-    /// The position to withdraw from.
-    #[account(mut)]
+    /// The position to withdraw from. Tokenized positions pay out through `redeem_shares` only.
+    #[account(
+        mut,
+        constraint = position.share_mint == Pubkey::default() @ HardigError::ShareTokenEnabled,
+    )]
     pub position: Account<'info, PositionState>,
 
     /// Protocol config PDA — provides collection pubkey for key validation.
     #[account(
         seeds = [ProtocolConfig::SEED],
         bump = config.bump,
+        constraint = !config.paused @ HardigError::ProtocolPaused,
     )]
     pub config: Account<'info, ProtocolConfig>,
 
     /// The MarketConfig for this position's market.
     #[account(
         constraint = market_config.key() == position.market_config @ HardigError::InvalidMayflowerAccount,
+        constraint = !market_config.paused @ HardigError::MarketPaused,
     )]
     pub market_config: Account<'info, MarketConfig>,
 
@@ -117,47 +130,99 @@ pub struct Withdraw<'info> {
     /// CHECK: Validated in handler via seed derivation.
     #[account(mut)]
     pub log_account: UncheckedAccount<'info>,
+
+    /// The position's pinned payout address. Required iff position.payout_address is set.
+    /// CHECK: Validated in handler against position.payout_address.
+    #[account(mut)]
+    pub payout: Option<UncheckedAccount<'info>>,
+
+    /// PromoConfig the key was claimed from. Required iff key_state.promo is set.
+    /// May be a closed promo's tombstone, so it is deserialized in the handler.
+    /// CHECK: Validated in consume_promo_budget against key_state.promo.
+    #[account(mut)]
+    pub promo: Option<UncheckedAccount<'info>>,
+    // remaining_accounts: (approver wallet, approver key asset) pairs when the
+    // position's co-signing policy applies to this amount.
 }
 
 pub fn handler(ctx: Context<Withdraw>, amount: u64, min_out: u64) -> Result<()> {
+    // Sells past the rolling allowance must wait out the position's delay via
+    // request_withdraw / execute_withdraw
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !ctx.accounts.position.must_queue_withdraw(amount, now),
+        HardigError::WithdrawMustBeQueued
+    );
+
+    sell(ctx.accounts, ctx.bumps.program_pda, ctx.remaining_accounts, amount, min_out)
+}
+
+/// Sell `amount` navSOL and pay the SOL out. Shared by `withdraw` and `execute_withdraw`.
+pub fn sell<'info>(
+    accounts: &mut Withdraw<'info>,
+    program_pda_bump: u8,
+    remaining_accounts: &[AccountInfo],
+    amount: u64,
+    min_out: u64,
+) -> Result<()> {
+    // Keys with a refundable promo principal may sell it back without a sell permission
+    let principal_mode = is_principal_key(accounts.key_state.as_deref(), &accounts.key_asset.key());
     let permissions = validate_key(
-        &ctx.accounts.signer,
-        &ctx.accounts.key_asset.to_account_info(),
-        &ctx.accounts.position.authority_seed,
-        PERM_SELL | PERM_LIMITED_SELL,
-        &ctx.accounts.config.collection,
+        &accounts.signer,
+        &accounts.key_asset.to_account_info(),
+        &accounts.position.authority_seed,
+        if principal_mode { u16::MAX } else { PERM_SELL | PERM_LIMITED_SELL },
+        &accounts.config.collection,
     )?;
 
     // Validate KeyState matches key_asset if provided
-    if let Some(ref ks) = ctx.accounts.key_state {
-        require!(ks.asset == ctx.accounts.key_asset.key(), HardigError::InvalidKey);
+    if let Some(ref ks) = accounts.key_state {
+        require!(ks.asset == accounts.key_asset.key(), HardigError::InvalidKey);
     }
 
+    // SOL goes to the pinned payout address if the admin set one, else to the signer
+    let recipient = payout_destination(
+        &accounts.position,
+        &accounts.signer.to_account_info(),
+        accounts.payout.as_ref(),
+    )?;
+
     require!(amount > 0, HardigError::InsufficientFunds);
 
-    let mc = &ctx.accounts.market_config;
+    // Withdraws past the rolling allowance need approval from the position's co-signers
+    let now = Clock::get()?.unix_timestamp;
+    if accounts.position.needs_cosign(false, amount, now) {
+        require_cosigners(
+            &accounts.position,
+            &accounts.key_asset.key(),
+            remaining_accounts,
+            &accounts.config.collection,
+        )?;
+    }
+
+    let mc = &accounts.market_config;
 
     // Validate PDA-derived accounts BEFORE reading from them
-    let program_pda = ctx.accounts.program_pda.key();
+    let program_pda = accounts.program_pda.key();
     let (expected_pp, _) = mayflower::derive_personal_position(&program_pda, &mc.market_meta);
     require!(
-        ctx.accounts.personal_position.key() == expected_pp,
+        accounts.personal_position.key() == expected_pp,
         HardigError::InvalidMayflowerAccount
     );
     let (expected_escrow, _) = mayflower::derive_personal_position_escrow(&expected_pp);
     require!(
-        ctx.accounts.user_shares.key() == expected_escrow,
+        accounts.user_shares.key() == expected_escrow,
         HardigError::InvalidMayflowerAccount
     );
     let (expected_log, _) = mayflower::derive_log_account();
     require!(
-        ctx.accounts.log_account.key() == expected_log,
+        accounts.log_account.key() == expected_log,
         HardigError::InvalidMayflowerAccount
     );
 
     // Use Mayflower's actual deposited shares as the ceiling (source of truth)
     let mayflower_shares = {
-        let data = ctx.accounts.personal_position.try_borrow_data()?;
+        let data = accounts.personal_position.try_borrow_data()?;
         mayflower::read_deposited_shares(&data)?
     };
     require!(
@@ -165,13 +230,13 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64, min_out: u64) -> Result<()>
         HardigError::InsufficientFunds
     );
 
-    if ctx.accounts.key_asset.key() == ctx.accounts.position.current_admin_asset {
-        ctx.accounts.position.last_admin_activity = Clock::get()?.unix_timestamp;
+    if accounts.key_asset.key() == accounts.position.current_admin_asset {
+        accounts.position.last_admin_activity = now;
     }
 
     // Read wSOL balance before CPI for slippage check
     let wsol_before = {
-        let wsol_data = ctx.accounts.user_wsol_ata.try_borrow_data()?;
+        let wsol_data = accounts.user_wsol_ata.try_borrow_data()?;
         if wsol_data.len() >= 72 {
             u64::from_le_bytes(wsol_data[64..72].try_into().unwrap())
         } else {
@@ -193,21 +258,21 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64, min_out: u64) -> Result<()>
     // Build and invoke Mayflower sell CPI
     let ix = mayflower::build_sell_ix(
         program_pda,
-        ctx.accounts.personal_position.key(),
-        ctx.accounts.user_shares.key(),
-        ctx.accounts.user_nav_sol_ata.key(),
-        ctx.accounts.user_wsol_ata.key(),
+        accounts.personal_position.key(),
+        accounts.user_shares.key(),
+        accounts.user_nav_sol_ata.key(),
+        accounts.user_wsol_ata.key(),
         amount,
         0, // Mayflower's own min_output — we enforce slippage ourselves
         &market,
     );
 
-    let bump = ctx.bumps.program_pda;
-    let admin_asset_key = ctx.accounts.position.authority_seed;
+    let bump = program_pda_bump;
+    let admin_asset_key = accounts.position.authority_seed;
     let signer_seeds: &[&[&[u8]]] = &[&[b"authority", admin_asset_key.as_ref(), &[bump]]];
 
     // Read deposited shares BEFORE the sell CPI
-    let pp_info = ctx.accounts.personal_position.to_account_info();
+    let pp_info = accounts.personal_position.to_account_info();
     let shares_before = {
         let data = pp_info.try_borrow_data()?;
         mayflower::read_deposited_shares(&data)?
@@ -216,24 +281,24 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64, min_out: u64) -> Result<()>
     invoke_signed(
         &ix,
         &[
-            ctx.accounts.program_pda.to_account_info(),       // 0: userWallet
-            ctx.accounts.tenant.to_account_info(),            // 1: tenant
-            ctx.accounts.market_group.to_account_info(),      // 2: marketGroup
-            ctx.accounts.market_meta.to_account_info(),       // 3: marketMetadata
-            ctx.accounts.mayflower_market.to_account_info(),  // 4: mayflowerMarket
+            accounts.program_pda.to_account_info(),       // 0: userWallet
+            accounts.tenant.to_account_info(),            // 1: tenant
+            accounts.market_group.to_account_info(),      // 2: marketGroup
+            accounts.market_meta.to_account_info(),       // 3: marketMetadata
+            accounts.mayflower_market.to_account_info(),  // 4: mayflowerMarket
             pp_info.clone(),                                  // 5: personalPosition
-            ctx.accounts.market_base_vault.to_account_info(), // 6: marketBaseVault
-            ctx.accounts.market_nav_vault.to_account_info(),  // 7: marketNavVault
-            ctx.accounts.fee_vault.to_account_info(),         // 8: feeVault
-            ctx.accounts.nav_sol_mint.to_account_info(),      // 9: navMint
-            ctx.accounts.wsol_mint.to_account_info(),         // 10: baseMint
-            ctx.accounts.user_wsol_ata.to_account_info(),     // 11: userWsolATA
-            ctx.accounts.user_nav_sol_ata.to_account_info(),  // 12: userNavSolATA
-            ctx.accounts.user_shares.to_account_info(),       // 13: userShares
-            ctx.accounts.token_program.to_account_info(),     // 14: Token Program
-            ctx.accounts.token_program.to_account_info(),     // 15: Token Program (dup)
-            ctx.accounts.log_account.to_account_info(),       // 16: logAccount
-            ctx.accounts.mayflower_program.to_account_info(), // 17: Mayflower program
+            accounts.market_base_vault.to_account_info(), // 6: marketBaseVault
+            accounts.market_nav_vault.to_account_info(),  // 7: marketNavVault
+            accounts.fee_vault.to_account_info(),         // 8: feeVault
+            accounts.nav_sol_mint.to_account_info(),      // 9: navMint
+            accounts.wsol_mint.to_account_info(),         // 10: baseMint
+            accounts.user_wsol_ata.to_account_info(),     // 11: userWsolATA
+            accounts.user_nav_sol_ata.to_account_info(),  // 12: userNavSolATA
+            accounts.user_shares.to_account_info(),       // 13: userShares
+            accounts.token_program.to_account_info(),     // 14: Token Program
+            accounts.token_program.to_account_info(),     // 15: Token Program (dup)
+            accounts.log_account.to_account_info(),       // 16: logAccount
+            accounts.mayflower_program.to_account_info(), // 17: Mayflower program
         ],
         signer_seeds,
     )?;
@@ -249,23 +314,43 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64, min_out: u64) -> Result<()>
 
     // Enforce rate + total limits using actual shares sold (not requested amount)
     if permissions & PERM_SELL == 0 && permissions & PERM_LIMITED_SELL != 0 {
-        let key_state = ctx.accounts.key_state.as_deref_mut()
+        let key_state = accounts.key_state.as_deref_mut()
             .ok_or(error!(HardigError::RateLimitExceeded))?;
+        let current_slot = Clock::get()?.slot;
         consume_rate_limit(
             &mut key_state.sell_bucket,
             shares_sold,
-            Clock::get()?.slot,
+            current_slot,
         )?;
         consume_total_limit(
             &mut key_state.total_sold,
             key_state.total_sell_limit,
             shares_sold,
         )?;
+        // Promo keys also draw from the campaign-wide budget
+        consume_promo_budget(
+            key_state,
+            accounts.promo.as_deref(),
+            BudgetKind::Sell,
+            shares_sold,
+            current_slot,
+        )?;
+    } else if permissions & PERM_SELL == 0 {
+        // Principal mode: only the key's own deposit, net of its debt at the floor price
+        let key_state = accounts
+            .key_state
+            .as_deref_mut()
+            .ok_or(error!(HardigError::PrincipalExceeded))?;
+        let floor_price = {
+            let data = accounts.mayflower_market.try_borrow_data()?;
+            mayflower::read_floor_price(&data)?
+        };
+        consume_principal(key_state, shares_sold, floor_price)?;
     }
 
     // Slippage check: verify SOL received >= min_out
     let wsol_after = {
-        let wsol_data = ctx.accounts.user_wsol_ata.try_borrow_data()?;
+        let wsol_data = accounts.user_wsol_ata.try_borrow_data()?;
         if wsol_data.len() >= 72 {
             u64::from_le_bytes(wsol_data[64..72].try_into().unwrap())
         } else {
@@ -275,38 +360,47 @@ pub fn handler(ctx: Context<Withdraw>, amount: u64, min_out: u64) -> Result<()>
     let sol_received = wsol_after.saturating_sub(wsol_before);
     require!(sol_received >= min_out, HardigError::SlippageExceeded);
 
-    // Close PDA's wSOL ATA — returns all wSOL + rent as native SOL to signer
+    // Close PDA's wSOL ATA — returns all wSOL + rent as native SOL to the recipient
     // Only attempt if the account is an initialized SPL token account (state byte at offset 108)
     let wsol_initialized = {
-        let data = ctx.accounts.user_wsol_ata.try_borrow_data()?;
+        let data = accounts.user_wsol_ata.try_borrow_data()?;
         data.len() >= 109 && data[108] != 0
     };
     if wsol_initialized {
         let close_ix = Instruction {
             program_id: anchor_spl::token::ID,
             accounts: vec![
-                AccountMeta::new(ctx.accounts.user_wsol_ata.key(), false),
-                AccountMeta::new(ctx.accounts.signer.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.program_pda.key(), true),
+                AccountMeta::new(accounts.user_wsol_ata.key(), false),
+                AccountMeta::new(recipient.key(), false),
+                AccountMeta::new_readonly(accounts.program_pda.key(), true),
             ],
             data: vec![9], // SPL Token CloseAccount
         };
         invoke_signed(
             &close_ix,
             &[
-                ctx.accounts.user_wsol_ata.to_account_info(),
-                ctx.accounts.signer.to_account_info(),
-                ctx.accounts.program_pda.to_account_info(),
+                accounts.user_wsol_ata.to_account_info(),
+                recipient.clone(),
+                accounts.program_pda.to_account_info(),
             ],
             signer_seeds,
         )?;
     }
 
-    ctx.accounts.position.deposited_nav = ctx
-        .accounts
+    accounts.position.deposited_nav = accounts
         .position
         .deposited_nav
         .saturating_sub(shares_sold);
 
+    emit!(Withdrawn {
+        position: accounts.position.key(),
+        key_asset: accounts.key_asset.key(),
+        permissions,
+        shares_sold,
+        sol_received,
+        deposited_nav: accounts.position.deposited_nav,
+        recipient: recipient.key(),
+    });
+
     Ok(())
 }
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::WithdrawCancelled;
+use crate::state::{
+    PositionState, ProtocolConfig, WithdrawRequest, PERM_GUARDIAN, PERM_MANAGE_KEYS,
+};
+use super::super::validate_key::validate_key;
+
+#[derive(Accounts)]
+pub struct CancelWithdraw<'info> {
+    pub signer: Signer<'info>,
+
+    /// The signer's guardian or admin key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub key_asset: UncheckedAccount<'info>,
+
+    /// The position the request belongs to. Mutable to count the request as settled.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — provides collection pubkey for key validation.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// The queued request to cancel. Closed, rent refunded to the requester.
+    #[account(
+        mut,
+        close = requester,
+        seeds = [
+            WithdrawRequest::SEED,
+            position.key().as_ref(),
+            &withdraw_request.request_id.to_le_bytes(),
+        ],
+        bump = withdraw_request.bump,
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    /// CHECK: Validated against withdraw_request.requester; receives the request rent.
+    #[account(mut, address = withdraw_request.requester @ HardigError::InvalidKey)]
+    pub requester: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<CancelWithdraw>) -> Result<()> {
+    validate_key(
+        &ctx.accounts.signer,
+        &ctx.accounts.key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_GUARDIAN | PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    let position = &mut ctx.accounts.position;
+    position.pending_withdraw_requests = position.pending_withdraw_requests.saturating_sub(1);
+
+    emit!(WithdrawCancelled {
+        position: ctx.accounts.position.key(),
+        request: ctx.accounts.withdraw_request.key(),
+        cancelled_by: ctx.accounts.key_asset.key(),
+        amount: ctx.accounts.withdraw_request.amount,
+    });
+
+    Ok(())
+}
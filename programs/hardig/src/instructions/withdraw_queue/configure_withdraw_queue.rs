@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::WithdrawQueueConfigured;
+use crate::state::{PositionState, ProtocolConfig, RateBucket, PERM_MANAGE_KEYS};
+use super::super::configure_cosign::require_cosigners;
+use super::super::validate_key::validate_key;
+
+/// Longest allowed withdraw delay (30 days).
+pub const MAX_WITHDRAW_DELAY_SECS: i64 = 2_592_000;
+
+#[derive(Accounts)]
+pub struct ConfigureWithdrawQueue<'info> {
+    pub admin: Signer<'info>,
+
+    /// The admin's key NFT (MPL-Core asset).
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub admin_key_asset: UncheckedAccount<'info>,
+
+    /// The position to update. Mutable to update the queue settings + last_admin_activity.
+    #[account(mut)]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — needed to read the collection address for validate_key.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    // remaining_accounts: co-signer pairs, required while a co-signing policy is active
+    // so a single stolen admin key cannot switch the queue off.
+}
+
+pub fn handler(ctx: Context<ConfigureWithdrawQueue>, delay_secs: i64, threshold: u64) -> Result<()> {
+    validate_key(
+        &ctx.accounts.admin,
+        &ctx.accounts.admin_key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        PERM_MANAGE_KEYS,
+        &ctx.accounts.config.collection,
+    )?;
+
+    if ctx.accounts.position.cosign_required > 0 {
+        require_cosigners(
+            &ctx.accounts.position,
+            &ctx.accounts.admin_key_asset.key(),
+            ctx.remaining_accounts,
+            &ctx.accounts.config.collection,
+        )?;
+    }
+
+    require!(
+        (0..=MAX_WITHDRAW_DELAY_SECS).contains(&delay_secs),
+        HardigError::InvalidWithdrawDelay
+    );
+    require!(
+        delay_secs == 0 || ctx.accounts.position.share_mint == Pubkey::default(),
+        HardigError::ShareTokenPolicyConflict
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let position = &mut ctx.accounts.position;
+    position.withdraw_delay_secs = delay_secs;
+    position.withdraw_queue_threshold = threshold;
+    position.withdraw_queue_bucket = RateBucket::full(threshold, delay_secs as u64, now as u64);
+    position.last_admin_activity = now;
+
+    emit!(WithdrawQueueConfigured {
+        position: position.key(),
+        delay_secs,
+        threshold,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::state::WithdrawRequest;
+use super::super::withdraw::*;
+
+#[derive(Accounts)]
+pub struct ExecuteWithdraw<'info> {
+    /// The same accounts as `withdraw`. `withdraw.signer` must hold the requesting key.
+    pub withdraw: Withdraw<'info>,
+
+    /// The queued request. Closed, rent refunded to the requester.
+    #[account(
+        mut,
+        close = requester,
+        seeds = [
+            WithdrawRequest::SEED,
+            withdraw.position.key().as_ref(),
+            &withdraw_request.request_id.to_le_bytes(),
+        ],
+        bump = withdraw_request.bump,
+        constraint = withdraw_request.key_asset == withdraw.key_asset.key() @ HardigError::InvalidKey,
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    /// CHECK: Validated against withdraw_request.requester; receives the request rent.
+    #[account(mut, address = withdraw_request.requester @ HardigError::InvalidKey)]
+    pub requester: UncheckedAccount<'info>,
+    // remaining_accounts: co-signer pairs, as for withdraw.
+}
+
+pub fn handler(ctx: Context<ExecuteWithdraw>) -> Result<()> {
+    let request = &ctx.accounts.withdraw_request;
+    require!(
+        Clock::get()?.unix_timestamp >= request.executable_at,
+        HardigError::WithdrawNotReady
+    );
+    let (amount, min_out) = (request.amount, request.min_out);
+    let position = &mut ctx.accounts.withdraw.position;
+    position.pending_withdraw_requests = position.pending_withdraw_requests.saturating_sub(1);
+
+    sell(
+        &mut ctx.accounts.withdraw,
+        ctx.bumps.withdraw.program_pda,
+        ctx.remaining_accounts,
+        amount,
+        min_out,
+    )
+}
@@ -0,0 +1,10 @@
+pub mod cancel_withdraw;
+pub mod configure_withdraw_queue;
+pub mod execute_withdraw;
+pub mod request_withdraw;
+
+#[allow(ambiguous_glob_reexports)]
+pub use cancel_withdraw::*;
+pub use configure_withdraw_queue::*;
+pub use execute_withdraw::*;
+pub use request_withdraw::*;
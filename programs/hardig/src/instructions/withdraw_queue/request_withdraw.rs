@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use crate::events::WithdrawRequested;
+use crate::state::{
+    KeyState, PositionState, ProtocolConfig, WithdrawRequest, PERM_LIMITED_SELL, PERM_SELL,
+};
+use super::super::promo::principal::is_principal_key;
+use super::super::validate_key::validate_key;
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    /// Pays the WithdrawRequest rent (refunded on execute or cancel).
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// The signer's key NFT (MPL-Core asset). Must be held again to execute.
+    /// CHECK: Validated in handler via validate_key (owner, update_authority, permissions).
+    pub key_asset: UncheckedAccount<'info>,
+
+    /// The position to withdraw from. Mutable to bump withdraw_request_count.
+    #[account(
+        mut,
+        constraint = position.share_mint == Pubkey::default() @ HardigError::ShareTokenEnabled,
+    )]
+    pub position: Account<'info, PositionState>,
+
+    /// Protocol config PDA — provides collection pubkey for key validation.
+    #[account(
+        seeds = [ProtocolConfig::SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = WithdrawRequest::SIZE,
+        seeds = [
+            WithdrawRequest::SEED,
+            position.key().as_ref(),
+            &position.withdraw_request_count.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Optional KeyState of the signing key. Lets a key with a refundable promo
+    /// principal queue a sell without a sell permission, as `withdraw` does.
+    pub key_state: Option<Account<'info, KeyState>>,
+}
+
+pub fn handler(ctx: Context<RequestWithdraw>, amount: u64, min_out: u64) -> Result<()> {
+    // Same permission as withdraw; rate limits and principal are consumed when the sell executes
+    let principal_mode =
+        is_principal_key(ctx.accounts.key_state.as_deref(), &ctx.accounts.key_asset.key());
+    validate_key(
+        &ctx.accounts.signer,
+        &ctx.accounts.key_asset.to_account_info(),
+        &ctx.accounts.position.authority_seed,
+        if principal_mode { u16::MAX } else { PERM_SELL | PERM_LIMITED_SELL },
+        &ctx.accounts.config.collection,
+    )?;
+
+    require!(amount > 0, HardigError::InsufficientFunds);
+
+    let now = Clock::get()?.unix_timestamp;
+    let position = &mut ctx.accounts.position;
+    let executable_at = now
+        .checked_add(position.withdraw_delay_secs)
+        .ok_or(HardigError::InvalidWithdrawDelay)?;
+
+    let request = &mut ctx.accounts.withdraw_request;
+    request.position = position.key();
+    request.key_asset = ctx.accounts.key_asset.key();
+    request.requester = ctx.accounts.signer.key();
+    request.amount = amount;
+    request.min_out = min_out;
+    request.executable_at = executable_at;
+    request.request_id = position.withdraw_request_count;
+    request.bump = ctx.bumps.withdraw_request;
+
+    position.withdraw_request_count = position
+        .withdraw_request_count
+        .checked_add(1)
+        .ok_or(HardigError::InsufficientFunds)?;
+    position.pending_withdraw_requests = position
+        .pending_withdraw_requests
+        .checked_add(1)
+        .ok_or(HardigError::InsufficientFunds)?;
+    if ctx.accounts.key_asset.key() == position.current_admin_asset {
+        position.last_admin_activity = now;
+    }
+
+    emit!(WithdrawRequested {
+        position: position.key(),
+        request: request.key(),
+        key_asset: request.key_asset,
+        amount,
+        min_out,
+        executable_at,
+    });
+
+    Ok(())
+}
@@ -2,11 +2,16 @@ use anchor_lang::prelude::*;
 
 pub mod artwork;
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod mayflower;
 pub mod state;
 
 use instructions::*;
+use state::{
+    KeyLimits, MarketUpdate, PromoBudget, PromoGate, PromoTerms, PromoWindow, ReferralReward,
+    ReinvestMode,
+};
 
 declare_id!("4U2Pgjdq51NXUEDVX4yyFNMdg6PuLHs9ikn9JThkn21p");
 
@@ -19,11 +24,35 @@ pub mod hardig {
         instructions::initialize_protocol::handler(ctx)
     }
 
-    /// Migrate ProtocolConfig from v0 (41 bytes) to v1 (73 bytes, adds collection field).
+    /// Migrate ProtocolConfig from any older layout to v3 (140 bytes: paused flag and protocol fee).
     pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
         instructions::migrate_config::handler(ctx)
     }
 
+    /// Migrate a MarketConfig from v0 (265 bytes) to the current layout
+    /// (435 bytes: paused flag, retirement and staged updates).
+    pub fn migrate_market_config(ctx: Context<MigrateMarketConfig>) -> Result<()> {
+        instructions::migrate_market_config::handler(ctx)
+    }
+
+    /// Migrate a PositionState from the original layout (238 bytes) to the current one
+    /// (permissionless; the payer covers the extra rent). New fields start disabled.
+    pub fn migrate_position(ctx: Context<MigratePosition>) -> Result<()> {
+        instructions::migrate_position::handler(ctx)
+    }
+
+    /// Migrate a KeyState from any older layout (169 bytes and up) to the current one
+    /// (permissionless; the payer covers the extra rent). New fields start zeroed.
+    pub fn migrate_key_state(ctx: Context<MigrateKeyState>) -> Result<()> {
+        instructions::migrate_key_state::handler(ctx)
+    }
+
+    /// Migrate a PromoConfig from v0 (345 bytes, u8 permissions) to the current layout
+    /// (permissionless; the payer covers the extra rent). New restrictions start off.
+    pub fn migrate_promo(ctx: Context<MigratePromo>) -> Result<()> {
+        instructions::migrate_promo::handler(ctx)
+    }
+
     /// Create the MPL-Core collection for all Härdig key NFTs (protocol admin only, once).
     pub fn create_collection(ctx: Context<CreateCollection>, uri: String) -> Result<()> {
         instructions::create_collection::handler(ctx, uri)
@@ -40,28 +69,16 @@ pub mod hardig {
     }
 
     /// Authorize a new key NFT for a position (admin only).
+    /// `limits`: rate-limit buckets and lifetime caps, for the limited bits the key holds.
+    /// `expires_at`: unix timestamp after which the key is invalid (0 = never).
     pub fn authorize_key(
         ctx: Context<AuthorizeKey>,
-        permissions: u8,
-        sell_bucket_capacity: u64,
-        sell_refill_period_slots: u64,
-        borrow_bucket_capacity: u64,
-        borrow_refill_period_slots: u64,
-        total_sell_limit: u64,
-        total_borrow_limit: u64,
+        permissions: u16,
+        limits: KeyLimits,
         name: Option<String>,
+        expires_at: i64,
     ) -> Result<()> {
-        instructions::authorize_key::handler(
-            ctx,
-            permissions,
-            sell_bucket_capacity,
-            sell_refill_period_slots,
-            borrow_bucket_capacity,
-            borrow_refill_period_slots,
-            total_sell_limit,
-            total_borrow_limit,
-            name,
-        )
+        instructions::authorize_key::handler(ctx, permissions, limits, name, expires_at)
     }
 
     /// Revoke a key by burning its MPL-Core asset (admin only).
@@ -69,6 +86,27 @@ pub mod hardig {
         instructions::revoke_key::handler(ctx)
     }
 
+    /// Burn an expired key and close its KeyState (permissionless; rent goes to the caller).
+    pub fn reap_expired_key(ctx: Context<ReapExpiredKey>) -> Result<()> {
+        instructions::reap_expired_key::handler(ctx)
+    }
+
+    /// Change a delegated key's rate-limit buckets and lifetime caps in place (admin only).
+    /// Lifetime accumulators (total_sold / total_borrowed) are preserved.
+    pub fn update_key_limits(ctx: Context<UpdateKey>, limits: KeyLimits) -> Result<()> {
+        instructions::update_key_limits::handler(ctx, limits)
+    }
+
+    /// Change a delegated key's permission bitmask in place (admin only).
+    /// Rate-limit params follow the same rules as `authorize_key` for the new bitmask.
+    pub fn update_key_permissions(
+        ctx: Context<UpdateKey>,
+        permissions: u16,
+        limits: KeyLimits,
+    ) -> Result<()> {
+        instructions::update_key_permissions::handler(ctx, permissions, limits)
+    }
+
     /// Buy navSOL by depositing SOL (admin, operator, or depositor).
     /// `min_out`: minimum navSOL shares to receive (slippage protection, 0 = no check).
     pub fn buy(ctx: Context<Buy>, amount: u64, min_out: u64) -> Result<()> {
@@ -81,6 +119,33 @@ pub mod hardig {
         instructions::withdraw::handler(ctx, amount, min_out)
     }
 
+    /// Configure the withdrawal queue (admin only). While `delay_secs` > 0, withdraws
+    /// past `threshold` navSOL shares per `delay_secs` must use `request_withdraw` and
+    /// wait the delay.
+    pub fn configure_withdraw_queue(
+        ctx: Context<ConfigureWithdrawQueue>,
+        delay_secs: i64,
+        threshold: u64,
+    ) -> Result<()> {
+        instructions::configure_withdraw_queue::handler(ctx, delay_secs, threshold)
+    }
+
+    /// Queue a withdraw (same permission as `withdraw`, including principal mode).
+    /// Executable after the position's delay.
+    pub fn request_withdraw(ctx: Context<RequestWithdraw>, amount: u64, min_out: u64) -> Result<()> {
+        instructions::request_withdraw::handler(ctx, amount, min_out)
+    }
+
+    /// Execute a queued withdraw once its delay has passed (holder of the requesting key).
+    pub fn execute_withdraw(ctx: Context<ExecuteWithdraw>) -> Result<()> {
+        instructions::execute_withdraw::handler(ctx)
+    }
+
+    /// Cancel a queued withdraw (guardian or admin key). Rent goes back to the requester.
+    pub fn cancel_withdraw(ctx: Context<CancelWithdraw>) -> Result<()> {
+        instructions::cancel_withdraw::handler(ctx)
+    }
+
     /// Borrow SOL against the navSOL floor (borrow or limited-borrow key).
     pub fn borrow(ctx: Context<Borrow>, amount: u64) -> Result<()> {
         instructions::borrow::handler(ctx, amount)
@@ -94,8 +159,49 @@ pub mod hardig {
     /// Reinvest new borrow capacity into more navSOL (admin, operator, or keeper).
     /// `min_out`: minimum navSOL shares to receive from the buy (slippage protection, 0 = no check).
     /// `max_spread_bps`: maximum market/floor spread in basis points (0 = no check).
-    pub fn reinvest(ctx: Context<Reinvest>, min_out: u64, max_spread_bps: u16) -> Result<()> {
-        instructions::reinvest::handler(ctx, min_out, max_spread_bps)
+    /// `mode`: fraction of capacity or target debt/floor-value ratio to borrow up to.
+    /// `min_amount`: skip (no-op) when the borrow would be below this many lamports.
+    /// Without a key asset, anyone may call it under the position's keeper policy for a bounty.
+    pub fn reinvest(
+        ctx: Context<Reinvest>,
+        min_out: u64,
+        max_spread_bps: u16,
+        mode: ReinvestMode,
+        min_amount: u64,
+    ) -> Result<()> {
+        instructions::reinvest::handler(ctx, min_out, max_spread_bps, mode, min_amount)
+    }
+
+    /// Close a fully unwound position (admin only). Requires zero Mayflower debt
+    /// and deposited shares. Burns the admin and recovery keys, closes the position
+    /// plus any KeyState / PromoConfig accounts passed in `remaining_accounts`,
+    /// and returns all rent to the admin.
+    pub fn close_position<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClosePosition<'info>>,
+    ) -> Result<()> {
+        instructions::close_position::handler(ctx)
+    }
+
+    /// Overwrite deposited_nav / user_debt with Mayflower's PersonalPosition values
+    /// (permissionless). Emits the correction so drift is visible.
+    pub fn sync_position(ctx: Context<SyncPosition>) -> Result<()> {
+        instructions::sync_position::handler(ctx)
+    }
+
+    /// Deleverage atomically: sell `amount` navSOL into the program PDA's wSOL ATA,
+    /// then repay debt from it down to at most `target_debt` (admin or unwind key).
+    /// `amount` may not exceed the shares whose floor value covers the repay.
+    /// wSOL the repay doesn't use is bought back into the position.
+    /// `min_out`: minimum SOL to receive from the sell (slippage protection, 0 = no check).
+    /// `min_shares_rebought`: minimum navSOL the buy-back must return (0 = no check).
+    pub fn unwind(
+        ctx: Context<Unwind>,
+        amount: u64,
+        min_out: u64,
+        target_debt: u64,
+        min_shares_rebought: u64,
+    ) -> Result<()> {
+        instructions::unwind::handler(ctx, amount, min_out, target_debt, min_shares_rebought)
     }
 
     /// Nominate a new protocol admin (current admin only). The nominated key
@@ -109,6 +215,27 @@ pub mod hardig {
         instructions::accept_admin::handler(ctx)
     }
 
+    /// Set the protocol fee taken from reinvest borrows and the treasury wallet that
+    /// receives it (protocol admin only). Capped at MAX_PROTOCOL_FEE_BPS.
+    pub fn set_protocol_fee(
+        ctx: Context<SetProtocolFee>,
+        fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::set_protocol_fee::handler(ctx, fee_bps, treasury)
+    }
+
+    /// Pause or resume buy/withdraw/borrow/reinvest/claim_promo_key in every
+    /// market (protocol admin only). Repay, unwind and heartbeat stay available.
+    pub fn set_protocol_paused(ctx: Context<SetProtocolPaused>, paused: bool) -> Result<()> {
+        instructions::set_protocol_paused::handler(ctx, paused)
+    }
+
+    /// Pause or resume the same instructions for a single market (protocol admin only).
+    pub fn set_market_paused(ctx: Context<SetMarketPaused>, paused: bool) -> Result<()> {
+        instructions::set_market_paused::handler(ctx, paused)
+    }
+
     /// No-op liveness proof. Updates last_admin_activity to prove admin is active.
     pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
         instructions::heartbeat::handler(ctx)
@@ -124,43 +251,125 @@ pub mod hardig {
         instructions::configure_recovery::handler(ctx, lockout_secs, lock_config, name)
     }
 
+    /// Set the position's co-signing policy (admin only). `withdraw` past `sell_threshold`
+    /// shares or `borrow` past `borrow_threshold` lamports per rolling day then need `required`
+    /// distinct keys from `approvers` to sign. `required` = 0 disables the policy. Changing an
+    /// active policy needs its approvals too.
+    pub fn configure_cosign(
+        ctx: Context<ConfigureCosign>,
+        sell_threshold: u64,
+        borrow_threshold: u64,
+        required: u8,
+        approvers: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::configure_cosign::handler(ctx, sell_threshold, borrow_threshold, required, approvers)
+    }
+
+    /// Let anyone call `reinvest` for this position without a key (admin only). Keepers earn
+    /// `bounty_bps` of the borrow, capped at `max_bounty`, and must use `mode` within
+    /// `max_spread_bps`. `bounty_bps` 0 disables keeper reinvests.
+    pub fn configure_keeper(
+        ctx: Context<ConfigureKeeper>,
+        bounty_bps: u16,
+        max_bounty: u64,
+        min_borrow: u64,
+        max_spread_bps: u16,
+        mode: ReinvestMode,
+    ) -> Result<()> {
+        instructions::configure_keeper::handler(ctx, bounty_bps, max_bounty, min_borrow, max_spread_bps, mode)
+    }
+
+    /// Set the position's leverage ceiling (admin only). `borrow` and `reinvest` fail if
+    /// debt would exceed `max_debt_to_floor_bps` of the floor value (0 = no ceiling).
+    pub fn configure_leverage(ctx: Context<ConfigureLeverage>, max_debt_to_floor_bps: u16) -> Result<()> {
+        instructions::configure_leverage::handler(ctx, max_debt_to_floor_bps)
+    }
+
     /// Execute recovery after lockout period has expired (recovery key holder only).
     pub fn execute_recovery(ctx: Context<ExecuteRecovery>) -> Result<()> {
         instructions::execute_recovery::handler(ctx)
     }
 
     /// Create a PromoConfig PDA for a position (admin only).
+    /// `limits`: rate-limit buckets and lifetime caps for every claimed key.
+    /// `terms`: minimum deposit, claim count, initial fill, key expiry and whether
+    /// claimed keys may withdraw their own deposit, less their debt.
+    /// `gate`: optional collection gate (claimers must hold an NFT from it) and
+    /// claim allowlist root.
+    /// `window`: optional claim window (unix timestamps, 0 = unbounded).
+    /// `budget`: campaign-wide borrow/sell caps and shared buckets drawn by every claimed key.
+    /// `referral_reward`: bonus credited to a referrer's key for each claim they referred.
     pub fn create_promo(
         ctx: Context<CreatePromo>,
         name_suffix: String,
-        permissions: u8,
-        borrow_capacity: u64,
-        borrow_refill_period: u64,
-        sell_capacity: u64,
-        sell_refill_period: u64,
-        total_borrow_limit: u64,
-        total_sell_limit: u64,
-        min_deposit_lamports: u64,
-        max_claims: u32,
-        initial_fill_bps: u16,
+        permissions: u16,
+        limits: KeyLimits,
+        terms: PromoTerms,
         image_uri: String,
         market_name: String,
+        gate: PromoGate,
+        window: PromoWindow,
+        budget: PromoBudget,
+        referral_reward: ReferralReward,
     ) -> Result<()> {
-        instructions::create_promo::handler(ctx, name_suffix, permissions, borrow_capacity, borrow_refill_period, sell_capacity, sell_refill_period, total_borrow_limit, total_sell_limit, min_deposit_lamports, max_claims, initial_fill_bps, image_uri, market_name)
+        instructions::create_promo::handler(ctx, name_suffix, permissions, limits, terms, image_uri, market_name, gate, window, budget, referral_reward)
     }
 
+    /// `merkle_root`: Some rotates the claim allowlist (all zeros opens the promo to anyone).
+    /// `starts_at` / `ends_at`: Some moves that edge of the claim window (0 = unbounded).
     pub fn update_promo(
         ctx: Context<UpdatePromo>,
         active: Option<bool>,
         max_claims: Option<u32>,
+        merkle_root: Option<[u8; 32]>,
+        starts_at: Option<i64>,
+        ends_at: Option<i64>,
+    ) -> Result<()> {
+        instructions::update_promo::handler(ctx, active, max_claims, merkle_root, starts_at, ends_at)
+    }
+
+    /// Close a deactivated promo (admin only). The PromoConfig shrinks to a small
+    /// tombstone so its name_suffix cannot be reused; the rest of its rent goes to the admin.
+    pub fn close_promo(ctx: Context<ClosePromo>) -> Result<()> {
+        instructions::close_promo::handler(ctx)
+    }
+
+    /// Close ClaimReceipts / GateReceipts of a closed promo (permissionless), passed as
+    /// (receipt, claimer) pairs in `remaining_accounts`. Rent goes back to each claimer.
+    pub fn close_claim_receipts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseClaimReceipts<'info>>,
     ) -> Result<()> {
-        instructions::update_promo::handler(ctx, active, max_claims)
+        instructions::close_claim_receipts::handler(ctx)
     }
 
     /// Claim a promo key NFT from a PromoConfig (permissionless — anyone can call).
     /// `amount`: lamports to deposit via Mayflower buy CPI (must be >= promo.min_deposit_lamports).
-    pub fn claim_promo_key(ctx: Context<ClaimPromoKey>, amount: u64) -> Result<()> {
-        instructions::claim_promo_key::handler(ctx, amount)
+    /// On allowlisted promos, `proof` must link (claimer, `allowlist_min_deposit`) to
+    /// the promo's merkle root and `amount` must also cover `allowlist_min_deposit`.
+    /// On collection-gated promos, the claimer must present an NFT from the gate
+    /// collection that has not claimed from this promo before.
+    /// `referrer`: optional wallet holding a key of the same position (with that key
+    /// and its KeyState as `referrer_key_asset` / `referrer_key_state`).
+    pub fn claim_promo_key(
+        ctx: Context<ClaimPromoKey>,
+        amount: u64,
+        allowlist_min_deposit: u64,
+        proof: Vec<[u8; 32]>,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::claim_promo_key::handler(ctx, amount, allowlist_min_deposit, proof, referrer)
+    }
+
+    /// Create the position's share token mint (admin only). The position must be empty and
+    /// have no co-signing, withdraw queue or payout address; buys then mint to the depositor.
+    pub fn enable_share_mint(ctx: Context<EnableShareMint>) -> Result<()> {
+        instructions::enable_share_mint::handler(ctx)
+    }
+
+    /// Burn `amount` share tokens for their pro-rata navSOL net of debt, sold to SOL
+    /// (permissionless — any holder). `min_out`: minimum wSOL to receive.
+    pub fn redeem_shares(ctx: Context<RedeemShares>, amount: u64, min_out: u64) -> Result<()> {
+        instructions::redeem_shares::handler(ctx, amount, min_out)
     }
 
     /// Register a trusted artwork provider program (protocol admin only).
@@ -173,6 +382,12 @@ pub mod hardig {
         instructions::remove_trusted_provider::handler(ctx)
     }
 
+    /// Pin or clear the address that receives SOL from withdraw/borrow (admin key required,
+    /// plus co-signers while a co-signing policy is active). None = pay the signer.
+    pub fn set_payout_address(ctx: Context<SetPayoutAddress>, payout_address: Option<Pubkey>) -> Result<()> {
+        instructions::set_payout_address::handler(ctx, payout_address)
+    }
+
     /// Set or clear custom artwork on a position (admin key required).
     /// When setting, pass receipt + TrustedProvider PDA as remaining_accounts.
     pub fn set_position_artwork(ctx: Context<SetPositionArtwork>, artwork_id: Option<Pubkey>) -> Result<()> {
@@ -203,4 +418,25 @@ pub mod hardig {
             fee_vault,
         )
     }
+
+    /// Replace a market's Mayflower accounts (protocol admin only). `delay_secs` 0
+    /// applies immediately; otherwise the update is staged for `apply_market_config_update`.
+    pub fn update_market_config(
+        ctx: Context<UpdateMarketConfig>,
+        update: MarketUpdate,
+        delay_secs: i64,
+    ) -> Result<()> {
+        instructions::update_market_config::handler(ctx, update, delay_secs)
+    }
+
+    /// Apply a staged MarketConfig update once its notice period has passed (any signer).
+    pub fn apply_market_config_update(ctx: Context<ApplyMarketConfigUpdate>) -> Result<()> {
+        instructions::apply_market_config_update::handler(ctx)
+    }
+
+    /// Permanently retire a market (protocol admin only): no new positions, deposits
+    /// or borrows. Existing positions can still repay, unwind and withdraw.
+    pub fn retire_market_config(ctx: Context<RetireMarketConfig>) -> Result<()> {
+        instructions::retire_market_config::handler(ctx)
+    }
 }
@@ -59,3 +59,21 @@ pub const PP_DEBT_OFFSET: usize = 112; // u64 LE
 pub const MARKET_DISCRIMINATOR: [u8; 8] = [133, 114, 237, 100, 77, 96, 120, 49];
 pub const MARKET_FLOOR_PRICE_OFFSET: usize = 104; // Rust Decimal, 16 bytes
 pub const RUST_DECIMAL_SIZE: usize = 16;
+
+// MarketLinear -> MarketMeta back-reference (Pubkey, first field after the discriminator)
+pub const MARKET_META_REF_OFFSET: usize = 8;
+
+// MarketMeta account layout (the addresses a MarketConfig copies), read off the mainnet
+// navSOL accounts above (DEFAULT_MARKET_META / DEFAULT_MAYFLOWER_MARKET). The unlisted
+// 32-byte slots (8..40 and 136..168) aren't used. Re-check after a Mayflower upgrade with
+// `test_mainnet_fork_market_meta_layout` (tests/mainnet_fork.rs), which reads every
+// offset here from the cloned mainnet accounts.
+pub const MARKET_META_DISCRIMINATOR: [u8; 8] = [95, 146, 205, 231, 152, 205, 151, 183];
+pub const META_MARKET_GROUP_OFFSET: usize = 40;
+pub const META_BASE_MINT_OFFSET: usize = 72;
+pub const META_NAV_MINT_OFFSET: usize = 104;
+pub const META_MARKET_OFFSET: usize = 168;
+pub const META_BASE_VAULT_OFFSET: usize = 200;
+pub const META_NAV_VAULT_OFFSET: usize = 232;
+pub const META_FEE_VAULT_OFFSET: usize = 264;
+pub const MARKET_META_MIN_SIZE: usize = 296;
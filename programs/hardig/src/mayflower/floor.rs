@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::HardigError;
+use crate::state::ReinvestMode;
 use super::constants::*;
 
 /// Read the floor price from the Mayflower market account data.
@@ -80,6 +81,68 @@ pub fn calculate_borrow_capacity(
     u64::try_from(capacity).map_err(|_| error!(HardigError::InsufficientFunds))
 }
 
+/// navSOL shares whose floor value covers `lamports`, rounded up. A zero floor covers nothing.
+///
+/// shares = ceil(lamports * 1e9 / floor_price)
+pub fn shares_covering(lamports: u64, floor_price_lamports: u64) -> Result<u64> {
+    if floor_price_lamports == 0 {
+        return Ok(0);
+    }
+    let shares = ((lamports as u128) * 1_000_000_000u128).div_ceil(floor_price_lamports as u128);
+    u64::try_from(shares).map_err(|_| error!(HardigError::InsufficientFunds))
+}
+
+/// Calculate how much `reinvest` should borrow under `mode`. Never exceeds the capacity.
+///
+/// Fraction:  capacity * bps / 10000
+/// TargetLtv: (floor_value * bps / 10000) - current_debt
+pub fn calculate_reinvest_borrow(
+    deposited_shares: u64,
+    floor_price_lamports: u64,
+    current_debt: u64,
+    mode: ReinvestMode,
+) -> Result<u64> {
+    let capacity = calculate_borrow_capacity(deposited_shares, floor_price_lamports, current_debt)?;
+
+    let amount = match mode {
+        ReinvestMode::Fraction { bps } => (capacity as u128) * (bps as u128) / 10_000u128,
+        ReinvestMode::TargetLtv { bps } => {
+            let floor_value = (deposited_shares as u128)
+                .checked_mul(floor_price_lamports as u128)
+                .ok_or(error!(HardigError::InsufficientFunds))?
+                / 1_000_000_000u128;
+            let target_debt = floor_value * (bps as u128) / 10_000u128;
+            target_debt.saturating_sub(current_debt as u128)
+        }
+    };
+
+    let amount = u64::try_from(amount).map_err(|_| error!(HardigError::InsufficientFunds))?;
+    Ok(amount.min(capacity))
+}
+
+/// Enforce a position's leverage ceiling: debt <= floor_value * max_bps / 10000.
+///
+/// `max_bps` of 0 means no ceiling.
+pub fn check_debt_to_floor(
+    deposited_shares: u64,
+    floor_price_lamports: u64,
+    debt: u64,
+    max_bps: u16,
+) -> Result<()> {
+    if max_bps == 0 {
+        return Ok(());
+    }
+    let floor_value = (deposited_shares as u128)
+        .checked_mul(floor_price_lamports as u128)
+        .ok_or(error!(HardigError::InsufficientFunds))?
+        / 1_000_000_000u128;
+    require!(
+        (debt as u128) * 10_000u128 <= floor_value * (max_bps as u128),
+        HardigError::LeverageCeilingExceeded
+    );
+    Ok(())
+}
+
 /// Decode a 16-byte Rust Decimal into lamports (scaled by 1e9).
 ///
 /// Layout:
@@ -120,6 +183,15 @@ fn decode_rust_decimal_to_lamports(bytes: &[u8]) -> Result<u64> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_shares_covering() {
+        // 1.5 SOL floor: 3 SOL needs 2 shares, one more lamport rounds up
+        let floor = 1_500_000_000u64;
+        assert_eq!(shares_covering(3_000_000_000, floor).unwrap(), 2_000_000_000);
+        assert_eq!(shares_covering(3_000_000_001, floor).unwrap(), 2_000_000_001);
+        assert_eq!(shares_covering(1, 0).unwrap(), 0);
+    }
+
     #[test]
     fn test_borrow_capacity_basic() {
         let deposited = 10_000_000_000u64;
@@ -152,6 +224,47 @@ mod tests {
         assert_eq!(cap, 0);
     }
 
+    #[test]
+    fn test_reinvest_borrow_fraction() {
+        // 10 SOL floor value, 4 SOL debt -> 6 SOL capacity, half of it
+        let amt = calculate_reinvest_borrow(
+            10_000_000_000, 1_000_000_000, 4_000_000_000,
+            ReinvestMode::Fraction { bps: 5_000 },
+        ).unwrap();
+        assert_eq!(amt, 3_000_000_000);
+    }
+
+    #[test]
+    fn test_reinvest_borrow_target_ltv() {
+        // 10 SOL floor value, 4 SOL debt, 50% target -> borrow 1 SOL
+        let amt = calculate_reinvest_borrow(
+            10_000_000_000, 1_000_000_000, 4_000_000_000,
+            ReinvestMode::TargetLtv { bps: 5_000 },
+        ).unwrap();
+        assert_eq!(amt, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_reinvest_borrow_target_ltv_already_above() {
+        let amt = calculate_reinvest_borrow(
+            10_000_000_000, 1_000_000_000, 6_000_000_000,
+            ReinvestMode::TargetLtv { bps: 5_000 },
+        ).unwrap();
+        assert_eq!(amt, 0);
+    }
+
+    #[test]
+    fn test_debt_to_floor_ceiling() {
+        // 10 SOL floor value, 70% ceiling -> 7 SOL max debt
+        assert!(check_debt_to_floor(10_000_000_000, 1_000_000_000, 7_000_000_000, 7_000).is_ok());
+        assert!(check_debt_to_floor(10_000_000_000, 1_000_000_000, 7_000_000_001, 7_000).is_err());
+    }
+
+    #[test]
+    fn test_debt_to_floor_no_ceiling() {
+        assert!(check_debt_to_floor(0, 0, 5_000_000_000, 0).is_ok());
+    }
+
     #[test]
     fn test_decode_rust_decimal_one() {
         let mut bytes = [0u8; 16];
@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HardigError;
+use super::constants::*;
+use super::cpi_accounts::MarketAddresses;
+
+/// Read a Pubkey at `offset`, failing if the account is too short.
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    let bytes: [u8; 32] = data
+        .get(offset..offset + 32)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(error!(HardigError::InvalidMarketAccounts))?;
+    Ok(Pubkey::new_from_array(bytes))
+}
+
+/// Check a set of market addresses against the Mayflower accounts they claim to describe.
+///
+/// `meta_data` must be a MarketMeta and `market_data` a MarketLinear (both by
+/// discriminator). The market must point back at the meta, and the meta must
+/// reference exactly the supplied group, mints, market and vaults.
+pub fn validate_market_addresses(
+    market: &MarketAddresses,
+    meta_data: &[u8],
+    market_data: &[u8],
+) -> Result<()> {
+    require!(
+        meta_data.len() >= MARKET_META_MIN_SIZE && meta_data[..8] == MARKET_META_DISCRIMINATOR,
+        HardigError::InvalidMayflowerAccount
+    );
+    require!(
+        market_data.len() >= MARKET_META_REF_OFFSET + 32
+            && market_data[..8] == MARKET_DISCRIMINATOR,
+        HardigError::InvalidMayflowerAccount
+    );
+    require!(
+        read_pubkey(market_data, MARKET_META_REF_OFFSET)? == market.market_meta,
+        HardigError::InvalidMarketAccounts
+    );
+
+    for (offset, expected) in [
+        (META_MARKET_GROUP_OFFSET, market.market_group),
+        (META_BASE_MINT_OFFSET, market.base_mint),
+        (META_NAV_MINT_OFFSET, market.nav_mint),
+        (META_MARKET_OFFSET, market.mayflower_market),
+        (META_BASE_VAULT_OFFSET, market.market_base_vault),
+        (META_NAV_VAULT_OFFSET, market.market_nav_vault),
+        (META_FEE_VAULT_OFFSET, market.fee_vault),
+    ] {
+        require!(
+            read_pubkey(meta_data, offset)? == expected,
+            HardigError::InvalidMarketAccounts
+        );
+    }
+    Ok(())
+}
+
+/// Validate the MarketMeta and MarketLinear accounts passed alongside a set of market
+/// addresses: both must be the addressed, Mayflower-owned accounts, and their contents
+/// must agree with `market` (see `validate_market_addresses`).
+pub fn validate_market_accounts<'info>(
+    market: &MarketAddresses,
+    market_meta: &AccountInfo<'info>,
+    mayflower_market: &AccountInfo<'info>,
+) -> Result<()> {
+    for (info, expected) in [
+        (market_meta, market.market_meta),
+        (mayflower_market, market.mayflower_market),
+    ] {
+        require!(
+            info.key() == expected && *info.owner == MAYFLOWER_PROGRAM_ID,
+            HardigError::InvalidMayflowerAccount
+        );
+    }
+    let meta_data = market_meta.try_borrow_data()?;
+    let market_data = mayflower_market.try_borrow_data()?;
+    validate_market_addresses(market, &meta_data, &market_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addresses() -> MarketAddresses {
+        MarketAddresses {
+            nav_mint: Pubkey::new_unique(),
+            base_mint: Pubkey::new_unique(),
+            market_group: Pubkey::new_unique(),
+            market_meta: Pubkey::new_unique(),
+            mayflower_market: Pubkey::new_unique(),
+            market_base_vault: Pubkey::new_unique(),
+            market_nav_vault: Pubkey::new_unique(),
+            fee_vault: Pubkey::new_unique(),
+        }
+    }
+
+    fn encode(m: &MarketAddresses) -> (Vec<u8>, Vec<u8>) {
+        let mut meta = vec![0u8; MARKET_META_MIN_SIZE];
+        meta[..8].copy_from_slice(&MARKET_META_DISCRIMINATOR);
+        for (offset, key) in [
+            (META_MARKET_GROUP_OFFSET, m.market_group),
+            (META_BASE_MINT_OFFSET, m.base_mint),
+            (META_NAV_MINT_OFFSET, m.nav_mint),
+            (META_MARKET_OFFSET, m.mayflower_market),
+            (META_BASE_VAULT_OFFSET, m.market_base_vault),
+            (META_NAV_VAULT_OFFSET, m.market_nav_vault),
+            (META_FEE_VAULT_OFFSET, m.fee_vault),
+        ] {
+            meta[offset..offset + 32].copy_from_slice(key.as_ref());
+        }
+        let mut market = vec![0u8; 256];
+        market[..8].copy_from_slice(&MARKET_DISCRIMINATOR);
+        market[MARKET_META_REF_OFFSET..MARKET_META_REF_OFFSET + 32]
+            .copy_from_slice(m.market_meta.as_ref());
+        (meta, market)
+    }
+
+    #[test]
+    fn test_validate_market_addresses_consistent() {
+        let m = addresses();
+        let (meta, market) = encode(&m);
+        assert!(validate_market_addresses(&m, &meta, &market).is_ok());
+    }
+
+    #[test]
+    fn test_validate_market_addresses_mismatch() {
+        let m = addresses();
+        let (meta, market) = encode(&m);
+        let typo = MarketAddresses { fee_vault: Pubkey::new_unique(), ..addresses() };
+        assert!(validate_market_addresses(&typo, &meta, &market).is_err());
+
+        // Swapped accounts fail the discriminator checks
+        assert!(validate_market_addresses(&m, &market, &meta).is_err());
+    }
+}
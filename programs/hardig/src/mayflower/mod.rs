@@ -1,7 +1,9 @@
 pub mod constants;
 pub mod cpi_accounts;
 pub mod floor;
+pub mod market;
 
 pub use constants::*;
 pub use cpi_accounts::*;
 pub use floor::*;
+pub use market::*;
@@ -14,12 +14,18 @@ pub struct ProtocolConfig {
     pub pending_admin: Pubkey,
     /// Bump seed for the config PDA.
     pub bump: u8,
+    /// Emergency stop: blocks buy/withdraw/borrow/reinvest/claim_promo_key in every market.
+    pub paused: bool,
+    /// Protocol fee on reinvest, in bps of the borrowed base token (0 = no fee).
+    pub fee_bps: u16,
+    /// Wallet whose base-mint ATAs receive the protocol fee.
+    pub treasury: Pubkey,
 }
 
 impl ProtocolConfig {
     pub const SEED: &'static [u8] = b"config";
-    // discriminator + admin + collection + pending_admin + bump
-    pub const SIZE: usize = 8 + 32 + 32 + 32 + 1; // 105
+    // discriminator + admin + collection + pending_admin + bump + paused + fee_bps + treasury
+    pub const SIZE: usize = 8 + 32 + 32 + 32 + 1 + 1 + 2 + 32; // 140
 }
 
 /// A navSOL position controlled by an NFT keyring.
@@ -55,16 +61,107 @@ pub struct PositionState {
     pub recovery_config_locked: bool,
     /// Optional artwork set ID for custom key visuals. None = default artwork.
     pub artwork_id: Option<Pubkey>,
+    /// Where withdraw/borrow send unwrapped SOL. Pubkey::default() = the signer.
+    pub payout_address: Pubkey,
+    /// Leverage ceiling: max debt as bps of the floor value of deposited shares. 0 = no ceiling.
+    pub max_debt_to_floor_bps: u16,
+    /// navSOL shares `withdraw` may sell without co-signers per COSIGN_WINDOW_SECS
+    /// (when cosign_required > 0).
+    pub cosign_sell_threshold: u64,
+    /// Lamports `borrow` may take without co-signers per COSIGN_WINDOW_SECS
+    /// (when cosign_required > 0).
+    pub cosign_borrow_threshold: u64,
+    /// Distinct approver keys that must sign a co-signed action. 0 = no co-signing policy.
+    pub cosign_required: u8,
+    /// Approver key assets. Pubkey::default() = empty slot.
+    pub cosign_approvers: [Pubkey; 5],
+    /// Delay before a queued withdraw can execute. 0 = no withdrawal queue.
+    pub withdraw_delay_secs: i64,
+    /// navSOL shares `withdraw` may sell without queueing per withdraw_delay_secs
+    /// (when withdraw_delay_secs > 0).
+    pub withdraw_queue_threshold: u64,
+    /// Number of withdraw requests ever created; seeds the next WithdrawRequest PDA.
+    pub withdraw_request_count: u64,
+    /// Bounty paid to a keyless `reinvest` caller, in bps of the borrowed amount. 0 = keepers off.
+    pub keeper_bounty_bps: u16,
+    /// Cap on a single keeper bounty, in lamports.
+    pub keeper_bounty_max: u64,
+    /// Keeper reinvests below this many lamports are skipped.
+    pub keeper_min_borrow: u64,
+    /// Spread limit keeper reinvests must respect (the tighter of this and the caller's).
+    pub keeper_max_spread_bps: u16,
+    /// The only ReinvestMode a keeper may use.
+    pub keeper_mode: ReinvestMode,
+    /// SPL mint of the position's share token. Pubkey::default() = no share token.
+    pub share_mint: Pubkey,
+    /// Rolling allowance of un-co-signed withdraws (capacity cosign_sell_threshold).
+    /// Timestamps in unix seconds.
+    pub cosign_sell_bucket: RateBucket,
+    /// Rolling allowance of un-co-signed borrows (capacity cosign_borrow_threshold).
+    /// Timestamps in unix seconds.
+    pub cosign_borrow_bucket: RateBucket,
+    /// Rolling allowance of unqueued withdraws (capacity withdraw_queue_threshold,
+    /// refilling over withdraw_delay_secs). Timestamps in unix seconds.
+    pub withdraw_queue_bucket: RateBucket,
+    /// WithdrawRequests not yet executed or cancelled. close_position waits for 0.
+    pub pending_withdraw_requests: u64,
 }
 
 impl PositionState {
     pub const SEED: &'static [u8] = b"position";
+    /// Share token mint PDA: seeds = [SHARE_MINT_SEED, position].
+    pub const SHARE_MINT_SEED: &'static [u8] = b"share_mint";
     // discriminator(8) + authority_seed(32) + position_pda(32) + market_config(32)
     // + deposited_nav(8) + user_debt(8) + _reserved(2)
     // + last_admin_activity(8) + bump(1) + authority_bump(1)
     // + current_admin_asset(32) + recovery_asset(32) + recovery_lockout_secs(8)
-    // + recovery_config_locked(1) + artwork_id(1+32)
-    pub const SIZE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 2 + 8 + 1 + 1 + 32 + 32 + 8 + 1 + 33;
+    // + recovery_config_locked(1) + artwork_id(1+32) + payout_address(32)
+    // + max_debt_to_floor_bps(2) + cosign_sell_threshold(8) + cosign_borrow_threshold(8)
+    // + cosign_required(1) + cosign_approvers(32*5)
+    // + withdraw_delay_secs(8) + withdraw_queue_threshold(8) + withdraw_request_count(8)
+    // + keeper_bounty_bps(2) + keeper_bounty_max(8) + keeper_min_borrow(8)
+    // + keeper_max_spread_bps(2) + keeper_mode(1+2)
+    // + share_mint(32)
+    // + cosign_sell_bucket(32) + cosign_borrow_bucket(32) + withdraw_queue_bucket(32)
+    // + pending_withdraw_requests(8)
+    pub const SIZE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 2 + 8 + 1 + 1 + 32 + 32 + 8 + 1 + 33 + 32 + 2
+        + 8 + 8 + 1 + 32 * Self::MAX_COSIGN_APPROVERS
+        + 8 + 8 + 8
+        + 2 + 8 + 8 + 2 + 3
+        + 32
+        + 32 + 32 + 32
+        + 8;
+    pub const MAX_COSIGN_APPROVERS: usize = 5;
+    /// Window over which un-co-signed amounts add up toward the co-signing thresholds.
+    pub const COSIGN_WINDOW_SECS: u64 = 86_400;
+
+    /// Whether a withdraw (`borrow` = false) or borrow of `amount` needs co-signers at
+    /// `now`: the policy is on and the amount, added to what was taken without co-signers
+    /// over the last COSIGN_WINDOW_SECS, exceeds the threshold. Amounts that don't need
+    /// co-signers are drawn from the allowance, so splitting an action doesn't help.
+    pub fn needs_cosign(&mut self, borrow: bool, amount: u64, now: i64) -> bool {
+        if self.cosign_required == 0 {
+            return false;
+        }
+        let allowance = if borrow {
+            &mut self.cosign_borrow_bucket
+        } else {
+            &mut self.cosign_sell_bucket
+        };
+        !allowance.try_take(amount, now as u64)
+    }
+
+    pub fn is_cosign_approver(&self, key_asset: &Pubkey) -> bool {
+        *key_asset != Pubkey::default() && self.cosign_approvers.contains(key_asset)
+    }
+
+    /// Whether a withdraw of `amount` shares must go through the withdrawal queue at
+    /// `now`: the queue is on and the amount, added to what was withdrawn directly over
+    /// the last withdraw_delay_secs, exceeds the threshold. Direct withdraws draw from
+    /// the allowance, so splitting a withdraw doesn't skip the delay.
+    pub fn must_queue_withdraw(&mut self, amount: u64, now: i64) -> bool {
+        self.withdraw_delay_secs > 0 && !self.withdraw_queue_bucket.try_take(amount, now as u64)
+    }
 }
 
 /// On-chain configuration for a Mayflower market.
@@ -89,12 +186,46 @@ pub struct MarketConfig {
     pub fee_vault: Pubkey,
     /// Bump seed for the MarketConfig PDA.
     pub bump: u8,
+    /// Emergency stop for this market only (see ProtocolConfig::paused).
+    pub paused: bool,
+    /// Retired markets accept no new positions, deposits or borrows.
+    /// Existing positions can still repay, unwind and withdraw.
+    pub retired: bool,
+    /// Mayflower accounts staged by a timelocked update_market_config.
+    pub pending_update: MarketUpdate,
+    /// Unix timestamp from which pending_update can be applied (0 = nothing staged).
+    pub pending_update_at: i64,
 }
 
 impl MarketConfig {
     pub const SEED: &'static [u8] = b"market_config";
-    // discriminator(8) + 8 pubkeys(32*8) + bump(1)
-    pub const SIZE: usize = 8 + 32 * 8 + 1;
+    // discriminator(8) + 8 pubkeys(32*8) + bump(1) + paused(1)
+    // + retired(1) + pending_update(32*5) + pending_update_at(8)
+    pub const SIZE: usize = 8 + 32 * 8 + 1 + 1 + 1 + MarketUpdate::SIZE + 8; // 435
+
+    /// Overwrite the Mayflower accounts that can move in a market migration.
+    /// nav_mint, base_mint and market_meta are fixed: position PDAs and ATAs derive from them.
+    pub fn apply_update(&mut self, update: &MarketUpdate) {
+        self.market_group = update.market_group;
+        self.mayflower_market = update.mayflower_market;
+        self.market_base_vault = update.market_base_vault;
+        self.market_nav_vault = update.market_nav_vault;
+        self.fee_vault = update.fee_vault;
+    }
+}
+
+/// Replacement Mayflower accounts for update_market_config. Embedded in MarketConfig.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct MarketUpdate {
+    pub market_group: Pubkey,
+    pub mayflower_market: Pubkey,
+    pub market_base_vault: Pubkey,
+    pub market_nav_vault: Pubkey,
+    pub fee_vault: Pubkey,
+}
+
+impl MarketUpdate {
+    pub const SIZE: usize = 32 * 5;
 }
 
 /// Marker PDA for a trusted artwork provider program.
@@ -117,27 +248,62 @@ impl TrustedProvider {
     pub const SIZE: usize = 8 + 32 + 32 + 1 + 1; // 74
 }
 
+/// A queued withdraw waiting out the position's withdraw delay.
+/// PDA seeds = [b"withdraw_request", position, request_id (u64 LE)].
+#[account]
+pub struct WithdrawRequest {
+    /// The position to sell from.
+    pub position: Pubkey,
+    /// The key asset that requested it. Only its holder can execute.
+    pub key_asset: Pubkey,
+    /// Wallet that paid the rent; refunded when executed or cancelled.
+    pub requester: Pubkey,
+    /// navSOL shares to sell.
+    pub amount: u64,
+    /// Minimum SOL to receive from the sell (0 = no check).
+    pub min_out: u64,
+    /// Earliest unix timestamp execute_withdraw may run.
+    pub executable_at: i64,
+    /// Index from position.withdraw_request_count.
+    pub request_id: u64,
+    /// PDA bump seed.
+    pub bump: u8,
+}
+
+impl WithdrawRequest {
+    pub const SEED: &'static [u8] = b"withdraw_request";
+    // discriminator(8) + position(32) + key_asset(32) + requester(32) + amount(8)
+    // + min_out(8) + executable_at(8) + request_id(8) + bump(1)
+    pub const SIZE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1; // 137
+}
+
 // ---------------------------------------------------------------------------
 // Permission bitmask constants
 // ---------------------------------------------------------------------------
 
 /// Each bit grants a specific permission.
-pub const PERM_BUY: u8 = 0x01;
-pub const PERM_SELL: u8 = 0x02;
-pub const PERM_BORROW: u8 = 0x04;
-pub const PERM_REPAY: u8 = 0x08;
-pub const PERM_REINVEST: u8 = 0x10;
-pub const PERM_MANAGE_KEYS: u8 = 0x20;
+pub const PERM_BUY: u16 = 0x01;
+pub const PERM_SELL: u16 = 0x02;
+pub const PERM_BORROW: u16 = 0x04;
+pub const PERM_REPAY: u16 = 0x08;
+pub const PERM_REINVEST: u16 = 0x10;
+pub const PERM_MANAGE_KEYS: u16 = 0x20;
 
 /// Rate-limited sell permission (bit 6). Enforced by token-bucket in KeyState.
-pub const PERM_LIMITED_SELL: u8 = 0x40;
+pub const PERM_LIMITED_SELL: u16 = 0x40;
 /// Rate-limited borrow permission (bit 7). Enforced by token-bucket in KeyState.
-pub const PERM_LIMITED_BORROW: u8 = 0x80;
+pub const PERM_LIMITED_BORROW: u16 = 0x80;
+/// Deleverage permission (bit 8): sell navSOL and repay debt with the proceeds in
+/// one call (`unwind`). Grants no way to move SOL out of the position.
+pub const PERM_UNWIND: u16 = 0x100;
+/// Guardian permission (bit 9): cancel queued withdraws (`cancel_withdraw`).
+/// Grants no way to move funds.
+pub const PERM_GUARDIAN: u16 = 0x200;
 /// Mask for rate-limited permission bits.
-pub const PERM_LIMITED_MASK: u8 = 0xC0;
+pub const PERM_LIMITED_MASK: u16 = 0xC0;
 
-/// All defined permissions (bits 0-7).
-pub const PERM_ALL: u8 = 0xFF;
+/// All defined permissions (bits 0-9).
+pub const PERM_ALL: u16 = 0x3FF;
 
 /// Who is creating this delegated key — determines which permissions are allowed.
 #[derive(Clone, Copy)]
@@ -150,31 +316,50 @@ pub enum KeyCreatorOrigin {
 
 impl KeyCreatorOrigin {
     /// Permission bits allowed for this origin.
-    pub fn allowed_permissions(&self) -> u8 {
+    pub fn allowed_permissions(&self) -> u16 {
         match self {
             Self::Admin => PERM_BUY | PERM_SELL | PERM_BORROW | PERM_REPAY
-                         | PERM_REINVEST | PERM_LIMITED_SELL | PERM_LIMITED_BORROW,
+                         | PERM_REINVEST | PERM_LIMITED_SELL | PERM_LIMITED_BORROW
+                         | PERM_UNWIND | PERM_GUARDIAN,
             Self::Promo => PERM_BUY | PERM_LIMITED_BORROW,
         }
     }
 }
 
 // Backwards-compatible presets
-pub const PRESET_ADMIN: u8 = 0x3F; // all 6 bits
-pub const PRESET_OPERATOR: u8 = 0x19; // buy + repay + reinvest
-pub const PRESET_DEPOSITOR: u8 = 0x09; // buy + repay
-pub const PRESET_KEEPER: u8 = 0x10; // reinvest only
+pub const PRESET_ADMIN: u16 = 0x3F; // all 6 bits
+pub const PRESET_OPERATOR: u16 = 0x19; // buy + repay + reinvest
+pub const PRESET_DEPOSITOR: u16 = 0x09; // buy + repay
+pub const PRESET_KEEPER: u16 = 0x10; // reinvest only
+
+/// How much of the position's borrow capacity `reinvest` uses. Both ratios are in bps (1-10000).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReinvestMode {
+    /// Borrow this fraction of the available capacity (10000 = all of it).
+    Fraction { bps: u16 },
+    /// Borrow until debt / floor value reaches this ratio; nothing if already at or above it.
+    TargetLtv { bps: u16 },
+}
 
-/// Token-bucket rate limiter. Embedded in KeyState.
+impl ReinvestMode {
+    pub fn bps(&self) -> u16 {
+        match *self {
+            ReinvestMode::Fraction { bps } | ReinvestMode::TargetLtv { bps } => bps,
+        }
+    }
+}
+
+/// Token-bucket rate limiter. Embedded in KeyState, PromoConfig and PositionState.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
 pub struct RateBucket {
     /// Maximum tokens (shares for sell, lamports for borrow).
     pub capacity: u64,
-    /// Number of slots for a full refill from 0 to capacity.
+    /// Number of slots for a full refill from 0 to capacity
+    /// (seconds for the PositionState allowances).
     pub refill_period: u64,
     /// Current tokens available.
     pub level: u64,
-    /// Slot of last update.
+    /// Slot of last update (unix timestamp for the PositionState allowances).
     pub last_update: u64,
 }
 
@@ -205,6 +390,53 @@ impl RateBucket {
 
         self.level.saturating_add(refill).min(self.capacity)
     }
+
+    /// A bucket that starts full.
+    pub fn full(capacity: u64, refill_period: u64, current_slot: u64) -> Self {
+        Self { capacity, refill_period, level: capacity, last_update: current_slot }
+    }
+
+    /// Refill up to `current_slot` and take `amount` if the bucket covers it.
+    /// Returns false, taking nothing, if it doesn't.
+    pub fn try_take(&mut self, amount: u64, current_slot: u64) -> bool {
+        self.level = self.available_now(current_slot);
+        self.last_update = current_slot;
+        match self.level.checked_sub(amount) {
+            Some(level) => {
+                self.level = level;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Change capacity and refill period in place, carrying over the tokens
+    /// available at `current_slot` (clamped to the new capacity).
+    pub fn reconfigure(&mut self, capacity: u64, refill_period: u64, current_slot: u64) {
+        let available = self.available_now(current_slot);
+        self.capacity = capacity;
+        self.refill_period = refill_period;
+        self.level = available.min(capacity);
+        self.last_update = current_slot;
+    }
+}
+
+/// Rate-limit parameters for a delegated key's PERM_LIMITED_SELL / PERM_LIMITED_BORROW
+/// buckets and lifetime caps. Fields only apply for the limited bits the key holds.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct KeyLimits {
+    /// Sell bucket capacity. Units: navSOL shares.
+    pub sell_bucket_capacity: u64,
+    /// Slots for the sell bucket to refill from empty.
+    pub sell_refill_period_slots: u64,
+    /// Borrow bucket capacity. Units: lamports.
+    pub borrow_bucket_capacity: u64,
+    /// Slots for the borrow bucket to refill from empty.
+    pub borrow_refill_period_slots: u64,
+    /// Lifetime sell cap (0 = no cap). Units: navSOL shares.
+    pub total_sell_limit: u64,
+    /// Lifetime borrow cap (0 = no cap). Units: lamports.
+    pub total_borrow_limit: u64,
 }
 
 /// Mutable state for a key NFT. Created for all delegated keys (via authorize_key).
@@ -230,6 +462,23 @@ pub struct KeyState {
     pub total_borrow_limit: u64,
     /// Accumulator of total lamports borrowed via this key.
     pub total_borrowed: u64,
+    /// Unix timestamp after which the key is no longer valid (0 = never expires).
+    /// Mirrored in the asset's `expires_at` attribute, which validate_key enforces.
+    pub expires_at: i64,
+    /// PromoConfig PDA this key was claimed from (default = not a promo key).
+    /// Limited borrows/sells by promo keys also draw from that promo's budget.
+    pub promo: Pubkey,
+    /// navSOL shares deposited when this key was claimed from a promo, less any
+    /// principal sold back since. Units: navSOL shares.
+    pub principal_shares: u64,
+    /// Lamports borrowed through this key (LimitedBorrow) and not yet repaid through it.
+    pub debt: u64,
+    /// May sell back `principal_shares` (less `debt`) without a sell permission.
+    /// Copied from the promo's `refundable_principal` at claim.
+    pub principal_refundable: bool,
+    /// Referred claims credited to this key, across all promos of the position.
+    /// Checked against each promo's `ReferralReward::max_referrals_per_referrer`.
+    pub referrals_credited: u32,
 }
 
 impl KeyState {
@@ -237,5 +486,12 @@ impl KeyState {
     // discriminator(8) + authority_seed(32) + asset(32) + bump(1)
     // + sell_bucket(32) + borrow_bucket(32)
     // + total_sell_limit(8) + total_sold(8) + total_borrow_limit(8) + total_borrowed(8)
-    pub const SIZE: usize = 8 + 32 + 32 + 1 + 32 + 32 + 8 + 8 + 8 + 8;
+    // + expires_at(8) + promo(32) + principal_shares(8) + debt(8) + principal_refundable(1)
+    // + referrals_credited(4)
+    pub const SIZE: usize = 8 + 32 + 32 + 1 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 32 + 8 + 8 + 1 + 4;
+
+    /// True if the key has an expiry and it has passed at `now` (unix timestamp).
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
 }
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use super::RateBucket;
 
 // ---------------------------------------------------------------------------
 // Promo feature – isolated state for future extraction
@@ -11,7 +14,7 @@ pub struct PromoConfig {
     /// Which position this promo is for (the position's authority_seed).
     pub authority_seed: Pubkey,
     /// Key permissions bitmask granted to claimed promo keys.
-    pub permissions: u8,
+    pub permissions: u16,
     /// LimitedBorrow bucket capacity (lamports).
     pub borrow_capacity: u64,
     /// LimitedBorrow refill period (slots).
@@ -34,6 +37,34 @@ pub struct PromoConfig {
     pub total_sell_limit: u64,
     /// Initial bucket fill level in basis points (0 = empty, 10000 = full).
     pub initial_fill_bps: u16,
+    /// Unix timestamp at which claimed keys expire (0 = never).
+    pub key_expires_at: i64,
+    /// Merkle root of the claim allowlist (all zeros = anyone may claim).
+    pub merkle_root: [u8; 32],
+    /// Collection whose NFTs gate claims (default = ungated).
+    pub gate_collection: Pubkey,
+    /// NFT standard of `gate_collection`.
+    pub gate_standard: GateStandard,
+    /// Unix timestamp before which claims are rejected (0 = no start bound).
+    pub starts_at: i64,
+    /// Unix timestamp from which claims are rejected (0 = no end bound).
+    pub ends_at: i64,
+    /// Campaign-wide lifetime borrow cap across all claimed keys (0 = no cap). Units: lamports.
+    pub budget_borrow_limit: u64,
+    /// Lamports borrowed so far by all keys of this promo.
+    pub budget_borrowed: u64,
+    /// Campaign-wide lifetime sell cap across all claimed keys (0 = no cap). Units: navSOL shares.
+    pub budget_sell_limit: u64,
+    /// navSOL shares sold so far by all keys of this promo.
+    pub budget_sold: u64,
+    /// Borrow bucket shared by all keys of this promo (capacity 0 = none).
+    pub budget_borrow_bucket: RateBucket,
+    /// Sell bucket shared by all keys of this promo (capacity 0 = none).
+    pub budget_sell_bucket: RateBucket,
+    /// Credited to the referrer's key on each referred claim (zero = no reward).
+    pub referral_reward: ReferralReward,
+    /// Claimed keys may sell their own deposit back through `withdraw`.
+    pub refundable_principal: bool,
     /// NFT name suffix (e.g. "Promo Borrow").
     pub name_suffix: String,
     /// Custom NFT image URL (max 128 bytes).
@@ -49,18 +80,121 @@ impl PromoConfig {
     pub const MAX_IMAGE_URI_LEN: usize = 128;
     pub const MAX_MARKET_NAME_LEN: usize = 32;
 
-    // discriminator(8) + authority_seed(32) + permissions(1)
+    // discriminator(8) + authority_seed(32) + permissions(2)
     // + borrow_capacity(8) + borrow_refill_period(8)
     // + sell_capacity(8) + sell_refill_period(8) + min_deposit_lamports(8)
     // + max_claims(4) + claims_count(4) + active(1)
-    // + total_borrow_limit(8) + total_sell_limit(8) + initial_fill_bps(2)
+    // + total_borrow_limit(8) + total_sell_limit(8) + initial_fill_bps(2) + key_expires_at(8)
+    // + merkle_root(32) + gate_collection(32) + gate_standard(1)
+    // + starts_at(8) + ends_at(8)
+    // + budget_borrow_limit(8) + budget_borrowed(8) + budget_sell_limit(8) + budget_sold(8)
+    // + budget_borrow_bucket(32) + budget_sell_bucket(32) + referral_reward(20)
+    // + refundable_principal(1)
     // + name_suffix(4 + 64) + image_uri(4 + 128) + market_name(4 + 32) + bump(1)
-    pub const SIZE: usize = 8 + 32 + 1
+    pub const SIZE: usize = 8 + 32 + 2
         + 8 + 8
         + 8 + 8 + 8
         + 4 + 4 + 1
-        + 8 + 8 + 2
+        + 8 + 8 + 2 + 8
+        + 32 + 32 + 1
+        + 8 + 8
+        + 8 + 8 + 8 + 8
+        + 32 + 32 + 20
+        + 1
         + (4 + 64) + (4 + Self::MAX_IMAGE_URI_LEN) + (4 + Self::MAX_MARKET_NAME_LEN) + 1;
+
+    // What `close_promo` leaves behind: discriminator(8) + authority_seed(32).
+    // The tombstone keeps the PDA from being recreated under the same name_suffix,
+    // which would otherwise let claimers whose receipts were closed claim again.
+    pub const CLOSED_SIZE: usize = 8 + 32;
+
+    /// Whether `data` is a promo tombstone left by `close_promo`.
+    pub fn is_closed(data: &[u8]) -> bool {
+        data.len() == Self::CLOSED_SIZE && data[..8] == *Self::DISCRIMINATOR
+    }
+}
+
+/// Who may claim from a promo. Passed to `create_promo`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PromoGate {
+    /// Claimers must hold an NFT from this collection (None = no gate).
+    pub collection: Option<Pubkey>,
+    /// NFT standard of `collection`.
+    pub standard: GateStandard,
+    /// Claim allowlist root (all zeros = anyone may claim).
+    pub merkle_root: [u8; 32],
+}
+
+/// What a claimer pays and gets. Passed to `create_promo`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PromoTerms {
+    /// Minimum deposit per claim. Units: lamports.
+    pub min_deposit_lamports: u64,
+    /// Claims accepted in total (0 = unlimited).
+    pub max_claims: u32,
+    /// How full the claimed key's buckets start, in bps (10_000 = full).
+    pub initial_fill_bps: u16,
+    /// Unix timestamp at which claimed keys expire (0 = never).
+    pub key_expires_at: i64,
+    /// Claimed keys may withdraw their own deposit, less their debt.
+    pub refundable_principal: bool,
+}
+
+/// When a promo accepts claims. Passed to `create_promo`. Unix timestamps, 0 = unbounded.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PromoWindow {
+    /// Claims before this are rejected.
+    pub starts_at: i64,
+    /// Claims at or after this are rejected.
+    pub ends_at: i64,
+}
+
+/// Campaign-wide limits shared by every key claimed from a promo.
+/// Passed to `create_promo`; zero disables each limit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PromoBudget {
+    /// Lifetime borrow cap across all keys. Units: lamports.
+    pub total_borrow_limit: u64,
+    /// Lifetime sell cap across all keys. Units: navSOL shares.
+    pub total_sell_limit: u64,
+    /// Shared borrow bucket capacity (lamports).
+    pub borrow_capacity: u64,
+    /// Shared borrow bucket refill period (slots).
+    pub borrow_refill_period: u64,
+    /// Shared sell bucket capacity (navSOL shares).
+    pub sell_capacity: u64,
+    /// Shared sell bucket refill period (slots).
+    pub sell_refill_period: u64,
+}
+
+/// Bonus credited to a referrer's KeyState for each claim they referred.
+/// Only raises limits the referrer's key already has.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReferralReward {
+    /// Added to the referrer's borrow bucket capacity (and level). Units: lamports.
+    pub borrow_capacity_bonus: u64,
+    /// Added to the referrer's lifetime borrow limit. Units: lamports.
+    pub total_borrow_limit_bonus: u64,
+    /// Referrals a key may have credited (`KeyState::referrals_credited`) and still
+    /// earn this bonus. Must be nonzero when a bonus is set.
+    pub max_referrals_per_referrer: u32,
+}
+
+impl ReferralReward {
+    /// True if referred claims raise anything.
+    pub fn has_bonus(&self) -> bool {
+        self.borrow_capacity_bonus > 0 || self.total_borrow_limit_bonus > 0
+    }
+}
+
+/// NFT standard of a promo's gate collection.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GateStandard {
+    /// MPL-Core asset whose update authority is the collection.
+    #[default]
+    MplCore,
+    /// Token Metadata NFT with a verified `collection` field.
+    TokenMetadata,
 }
 
 /// One-time claim receipt preventing double-claims.
@@ -73,10 +207,32 @@ pub struct ClaimReceipt {
     pub promo: Pubkey,
     /// Bump seed for this PDA.
     pub bump: u8,
+    /// Wallet that referred the claimer (default = no referrer).
+    pub referrer: Pubkey,
 }
 
 impl ClaimReceipt {
     pub const SEED: &'static [u8] = b"claim";
-    // discriminator(8) + claimer(32) + promo(32) + bump(1)
-    pub const SIZE: usize = 8 + 32 + 32 + 1;
+    // discriminator(8) + claimer(32) + promo(32) + bump(1) + referrer(32)
+    pub const SIZE: usize = 8 + 32 + 32 + 1 + 32;
+}
+
+/// Marks a gating NFT as spent for a promo, so it cannot claim again from another wallet.
+/// PDA seeds = [b"gate", promo_pda, gate_asset] (gate_asset = MPL-Core asset or NFT mint).
+#[account]
+pub struct GateReceipt {
+    /// The PromoConfig PDA this receipt belongs to.
+    pub promo: Pubkey,
+    /// The gating asset (MPL-Core) or mint (Token Metadata).
+    pub gate_asset: Pubkey,
+    /// The wallet that claimed with it.
+    pub claimer: Pubkey,
+    /// Bump seed for this PDA.
+    pub bump: u8,
+}
+
+impl GateReceipt {
+    pub const SEED: &'static [u8] = b"gate";
+    // discriminator(8) + promo(32) + gate_asset(32) + claimer(32) + bump(1)
+    pub const SIZE: usize = 8 + 32 + 32 + 32 + 1;
 }
@@ -9,18 +9,30 @@ use solana_sdk::{
     transaction::Transaction,
 };
 
-use anchor_lang::AccountDeserialize;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator};
+use hardig::events::{
+    Borrowed, CosignConfigured, Deposited, KeeperConfigured, KeyAuthorized, KeyLimitsUpdated, KeyPermissionsUpdated, KeyReaped, KeyRevoked,
+    ClaimReceiptsClosed, LeverageConfigured, MarketConfigRetired, MarketConfigUpdateApplied,
+    MarketConfigUpdateQueued, PayoutAddressSet, PromoClosed, PromoCreated, PromoKeyClaimed, PromoUpdated, PositionClosed, PositionSynced, ProtocolFeeCollected, Reinvested, Repaid, ShareMintEnabled, SharesRedeemed, Unwound, WithdrawCancelled,
+    WithdrawRequested, Withdrawn,
+};
 use hardig::mayflower::{
     self, DEFAULT_FEE_VAULT, DEFAULT_MARKET_BASE_VAULT, DEFAULT_MARKET_GROUP, DEFAULT_MARKET_META,
     DEFAULT_MARKET_NAV_VAULT, DEFAULT_MAYFLOWER_MARKET, DEFAULT_NAV_SOL_MINT, DEFAULT_WSOL_MINT,
-    MAYFLOWER_PROGRAM_ID, MAYFLOWER_TENANT, PP_DISCRIMINATOR, MARKET_DISCRIMINATOR,
+    MAYFLOWER_PROGRAM_ID, MAYFLOWER_TENANT, PP_DISCRIMINATOR, MARKET_DISCRIMINATOR, PP_DEBT_OFFSET,
+    PP_DEPOSITED_SHARES_OFFSET, MARKET_FLOOR_PRICE_OFFSET, MARKET_META_DISCRIMINATOR,
+    MARKET_META_MIN_SIZE, MARKET_META_REF_OFFSET, META_BASE_MINT_OFFSET, META_BASE_VAULT_OFFSET,
+    META_FEE_VAULT_OFFSET, META_MARKET_GROUP_OFFSET, META_MARKET_OFFSET, META_NAV_MINT_OFFSET,
+    META_NAV_VAULT_OFFSET,
 };
 use hardig::artwork::ARTWORK_RECEIPT_DISCRIMINATOR;
+use hardig::instructions::promo::allowlist::{allowlist_leaf, allowlist_proof, allowlist_root};
 use hardig::state::{
-    ClaimReceipt, KeyState, MarketConfig, PositionState, PromoConfig, ProtocolConfig,
-    TrustedProvider,
+    ClaimReceipt, GateReceipt, GateStandard, KeyLimits, KeyState, MarketConfig, MarketUpdate, PositionState, PromoBudget, PromoConfig, PromoGate, PromoTerms, PromoWindow, ProtocolConfig,
+    ReferralReward, ReinvestMode,
+    TrustedProvider, WithdrawRequest,
     PERM_BUY, PERM_SELL, PERM_MANAGE_KEYS, PERM_REINVEST,
-    PERM_LIMITED_SELL, PERM_LIMITED_BORROW,
+    PERM_LIMITED_SELL, PERM_LIMITED_BORROW, PERM_UNWIND, PERM_GUARDIAN,
     PRESET_ADMIN, PRESET_DEPOSITOR, PRESET_KEEPER, PRESET_OPERATOR,
 };
 
@@ -88,14 +100,12 @@ fn setup() -> (LiteSVM, Keypair) {
 fn plant_mayflower_stubs(svm: &mut LiteSVM) {
     let owner = MAYFLOWER_PROGRAM_ID;
 
-    // Constant addresses: tenant, market_group, market_meta
-    for addr in [
-        MAYFLOWER_TENANT,
-        DEFAULT_MARKET_GROUP,
-        DEFAULT_MARKET_META,
-    ] {
+    // Constant addresses: tenant, market_group
+    for addr in [MAYFLOWER_TENANT, DEFAULT_MARKET_GROUP] {
         plant_account(svm, &addr, &owner, 256);
     }
+    // MarketMeta must reference the default market's accounts for create_market_config
+    plant_market_meta_account(svm, &default_market_addresses());
 
     // Mutable constant addresses
     for addr in [
@@ -166,10 +176,12 @@ fn plant_pp_account(svm: &mut LiteSVM, address: &Pubkey, owner: &Pubkey, size: u
     svm.set_account(*address, account).unwrap();
 }
 
-/// Plant a MayflowerMarket stub with the correct discriminator.
+/// Plant a MayflowerMarket stub with the correct discriminator, pointing at the default MarketMeta.
 fn plant_market_account(svm: &mut LiteSVM, address: &Pubkey, owner: &Pubkey, size: usize) {
     let mut data = vec![0u8; size];
     data[..8].copy_from_slice(&MARKET_DISCRIMINATOR);
+    data[MARKET_META_REF_OFFSET..MARKET_META_REF_OFFSET + 32]
+        .copy_from_slice(DEFAULT_MARKET_META.as_ref());
     let account = Account {
         lamports: 1_000_000_000,
         data,
@@ -180,6 +192,45 @@ fn plant_market_account(svm: &mut LiteSVM, address: &Pubkey, owner: &Pubkey, siz
     svm.set_account(*address, account).unwrap();
 }
 
+/// The default navSOL market's addresses, as stored in its MarketConfig.
+fn default_market_addresses() -> mayflower::MarketAddresses {
+    mayflower::MarketAddresses {
+        nav_mint: DEFAULT_NAV_SOL_MINT,
+        base_mint: DEFAULT_WSOL_MINT,
+        market_group: DEFAULT_MARKET_GROUP,
+        market_meta: DEFAULT_MARKET_META,
+        mayflower_market: DEFAULT_MAYFLOWER_MARKET,
+        market_base_vault: DEFAULT_MARKET_BASE_VAULT,
+        market_nav_vault: DEFAULT_MARKET_NAV_VAULT,
+        fee_vault: DEFAULT_FEE_VAULT,
+    }
+}
+
+/// Plant (or overwrite) the MarketMeta stub at `market.market_meta`, referencing `market`'s accounts.
+fn plant_market_meta_account(svm: &mut LiteSVM, market: &mayflower::MarketAddresses) {
+    let mut data = vec![0u8; MARKET_META_MIN_SIZE];
+    data[..8].copy_from_slice(&MARKET_META_DISCRIMINATOR);
+    for (offset, key) in [
+        (META_MARKET_GROUP_OFFSET, market.market_group),
+        (META_BASE_MINT_OFFSET, market.base_mint),
+        (META_NAV_MINT_OFFSET, market.nav_mint),
+        (META_MARKET_OFFSET, market.mayflower_market),
+        (META_BASE_VAULT_OFFSET, market.market_base_vault),
+        (META_NAV_VAULT_OFFSET, market.market_nav_vault),
+        (META_FEE_VAULT_OFFSET, market.fee_vault),
+    ] {
+        data[offset..offset + 32].copy_from_slice(key.as_ref());
+    }
+    let account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: MAYFLOWER_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    svm.set_account(market.market_meta, account).unwrap();
+}
+
 fn send_tx(
     svm: &mut LiteSVM,
     ixs: &[Instruction],
@@ -197,6 +248,34 @@ fn send_tx(
         .map_err(|e| format!("{:?}", e))
 }
 
+/// Like `send_tx`, but returns the transaction logs on success.
+fn send_tx_logs(
+    svm: &mut LiteSVM,
+    ixs: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<Vec<String>, String> {
+    let blockhash = svm.latest_blockhash();
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&signers[0].pubkey()),
+        signers,
+        blockhash,
+    );
+    svm.send_transaction(tx)
+        .map(|meta| meta.logs)
+        .map_err(|e| format!("{:?}", e))
+}
+
+/// Decode the first Anchor event of type `E` from "Program data: <base64>" log lines.
+fn find_event<E: AnchorDeserialize + Discriminator>(logs: &[String]) -> Option<E> {
+    use anchor_lang::__private::base64::{engine::general_purpose::STANDARD, Engine};
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|b64| STANDARD.decode(b64).ok())
+        .find(|data| data.starts_with(E::DISCRIMINATOR))
+        .and_then(|data| E::deserialize(&mut &data[E::DISCRIMINATOR.len()..]).ok())
+}
+
 // ---------------------------------------------------------------------------
 // Instruction builders
 // ---------------------------------------------------------------------------
@@ -238,6 +317,8 @@ fn ix_create_market_config(admin: &Pubkey) -> Instruction {
             AccountMeta::new(*admin, true),
             AccountMeta::new_readonly(config_pda, false),
             AccountMeta::new(mc_pda, false),
+            AccountMeta::new_readonly(DEFAULT_MARKET_META, false),
+            AccountMeta::new_readonly(DEFAULT_MAYFLOWER_MARKET, false),
             AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
         ],
     )
@@ -349,18 +430,44 @@ fn ix_create_position_named(
 }
 
 fn ix_authorize_key(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    position_pda: &Pubkey,
+    new_asset: &Pubkey,
+    target_wallet: &Pubkey,
+    role: u16,
+    sell_bucket_capacity: u64,
+    sell_refill_period_slots: u64,
+    borrow_bucket_capacity: u64,
+    borrow_refill_period_slots: u64,
+    total_sell_limit: u64,
+    total_borrow_limit: u64,
+    collection: &Pubkey,
+) -> Instruction {
+    ix_authorize_key_expiring(
+        admin, admin_asset, position_pda, new_asset, target_wallet, role,
+        sell_bucket_capacity, sell_refill_period_slots,
+        borrow_bucket_capacity, borrow_refill_period_slots,
+        total_sell_limit, total_borrow_limit,
+        0, // expires_at: never
+        collection,
+    )
+}
+
+fn ix_authorize_key_expiring(
     admin: &Pubkey,
     admin_asset: &Pubkey,
     _position_pda: &Pubkey,
     new_asset: &Pubkey,
     target_wallet: &Pubkey,
-    role: u8,
+    role: u16,
     sell_bucket_capacity: u64,
     sell_refill_period_slots: u64,
     borrow_bucket_capacity: u64,
     borrow_refill_period_slots: u64,
     total_sell_limit: u64,
     total_borrow_limit: u64,
+    expires_at: i64,
     collection: &Pubkey,
 ) -> Instruction {
     let (pos_pda, _) =
@@ -370,7 +477,7 @@ fn ix_authorize_key(
     let (cfg_pda, _) = config_pda();
 
     let mut data = sighash("authorize_key");
-    data.push(role);
+    data.extend_from_slice(&role.to_le_bytes());
     data.extend_from_slice(&sell_bucket_capacity.to_le_bytes());
     data.extend_from_slice(&sell_refill_period_slots.to_le_bytes());
     data.extend_from_slice(&borrow_bucket_capacity.to_le_bytes());
@@ -379,6 +486,7 @@ fn ix_authorize_key(
     data.extend_from_slice(&total_borrow_limit.to_le_bytes());
     // name: Option<String> = None
     data.push(0);
+    data.extend_from_slice(&expires_at.to_le_bytes());
 
     Instruction::new_with_bytes(
         program_id(),
@@ -426,6 +534,120 @@ fn ix_revoke_key(
     )
 }
 
+fn ix_reap_expired_key(
+    caller: &Pubkey,
+    admin_asset: &Pubkey,
+    target_asset: &Pubkey,
+    collection: &Pubkey,
+) -> Instruction {
+    let (pos_pda, _) = position_pda(admin_asset);
+    let (key_state_pda, _) = key_state_pda(target_asset);
+    let (cfg_pda, _) = config_pda();
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("reap_expired_key"),
+        vec![
+            AccountMeta::new(*caller, true),                  // caller (receives rent)
+            AccountMeta::new_readonly(pos_pda, false),         // position
+            AccountMeta::new(*target_asset, false),            // target_asset
+            AccountMeta::new(key_state_pda, false),            // target_key_state
+            AccountMeta::new_readonly(cfg_pda, false),         // config
+            AccountMeta::new(*collection, false),              // collection
+            AccountMeta::new_readonly(MPL_CORE_ID, false),     // mpl_core_program
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
+fn ix_update_key_limits(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    target_asset: &Pubkey,
+    sell_bucket_capacity: u64,
+    sell_refill_period_slots: u64,
+    borrow_bucket_capacity: u64,
+    borrow_refill_period_slots: u64,
+    total_sell_limit: u64,
+    total_borrow_limit: u64,
+    collection: &Pubkey,
+) -> Instruction {
+    let (pos_pda, _) =
+        Pubkey::find_program_address(&[PositionState::SEED, admin_asset.as_ref()], &program_id());
+    let (key_state_pda, _) =
+        Pubkey::find_program_address(&[KeyState::SEED, target_asset.as_ref()], &program_id());
+    let (cfg_pda, _) = config_pda();
+
+    let mut data = sighash("update_key_limits");
+    data.extend_from_slice(&sell_bucket_capacity.to_le_bytes());
+    data.extend_from_slice(&sell_refill_period_slots.to_le_bytes());
+    data.extend_from_slice(&borrow_bucket_capacity.to_le_bytes());
+    data.extend_from_slice(&borrow_refill_period_slots.to_le_bytes());
+    data.extend_from_slice(&total_sell_limit.to_le_bytes());
+    data.extend_from_slice(&total_borrow_limit.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*admin, true),                   // admin
+            AccountMeta::new_readonly(*admin_asset, false),    // admin_key_asset
+            AccountMeta::new(pos_pda, false),                  // position (mut for last_admin_activity)
+            AccountMeta::new(*target_asset, false),            // target_asset
+            AccountMeta::new(key_state_pda, false),            // target_key_state
+            AccountMeta::new_readonly(cfg_pda, false),         // config
+            AccountMeta::new(*collection, false),              // collection
+            AccountMeta::new_readonly(MPL_CORE_ID, false),     // mpl_core_program
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
+fn ix_update_key_permissions(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    target_asset: &Pubkey,
+    permissions: u16,
+    sell_bucket_capacity: u64,
+    sell_refill_period_slots: u64,
+    borrow_bucket_capacity: u64,
+    borrow_refill_period_slots: u64,
+    total_sell_limit: u64,
+    total_borrow_limit: u64,
+    collection: &Pubkey,
+) -> Instruction {
+    let (pos_pda, _) =
+        Pubkey::find_program_address(&[PositionState::SEED, admin_asset.as_ref()], &program_id());
+    let (key_state_pda, _) =
+        Pubkey::find_program_address(&[KeyState::SEED, target_asset.as_ref()], &program_id());
+    let (cfg_pda, _) = config_pda();
+
+    let mut data = sighash("update_key_permissions");
+    data.extend_from_slice(&permissions.to_le_bytes());
+    data.extend_from_slice(&sell_bucket_capacity.to_le_bytes());
+    data.extend_from_slice(&sell_refill_period_slots.to_le_bytes());
+    data.extend_from_slice(&borrow_bucket_capacity.to_le_bytes());
+    data.extend_from_slice(&borrow_refill_period_slots.to_le_bytes());
+    data.extend_from_slice(&total_sell_limit.to_le_bytes());
+    data.extend_from_slice(&total_borrow_limit.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*admin, true),                   // admin
+            AccountMeta::new_readonly(*admin_asset, false),    // admin_key_asset
+            AccountMeta::new(pos_pda, false),                  // position (mut for last_admin_activity)
+            AccountMeta::new(*target_asset, false),            // target_asset
+            AccountMeta::new(key_state_pda, false),            // target_key_state
+            AccountMeta::new_readonly(cfg_pda, false),         // config
+            AccountMeta::new(*collection, false),              // collection
+            AccountMeta::new_readonly(MPL_CORE_ID, false),     // mpl_core_program
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Financial instruction builders (with MarketConfig)
 // ---------------------------------------------------------------------------
@@ -485,6 +707,8 @@ fn ix_buy(
             AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false),   // mayflower_program
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),           // token_program
             AccountMeta::new(log_pda, false),                         // log_account
+            AccountMeta::new_readonly(program_id(), false),           // share_mint (Option)
+            AccountMeta::new_readonly(program_id(), false),           // depositor_share_ata (Option)
         ],
     )
 }
@@ -534,6 +758,56 @@ fn ix_withdraw(
             AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false),
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),
             AccountMeta::new(log_pda, false),
+            AccountMeta::new(program_id(), false),                     // payout (Option)
+            AccountMeta::new(program_id(), false),                     // promo (Option)
+        ],
+    )
+}
+
+fn ix_unwind(
+    signer: &Pubkey,
+    key_asset: &Pubkey,
+    position_pda: &Pubkey,
+    admin_asset: &Pubkey,
+    amount: u64,
+    target_debt: u64,
+) -> Instruction {
+    let (program_pda, pp_pda, escrow_pda, log_pda, wsol_ata, nav_sol_ata) = mayflower_addrs(admin_asset);
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+
+    let mut data = sighash("unwind");
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // min_out = 0 (no slippage check)
+    data.extend_from_slice(&target_debt.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // min_shares_rebought = 0 (no check)
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*signer, true),
+            AccountMeta::new_readonly(*key_asset, false),             // key_asset
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(config_pda().0, false),          // config
+            AccountMeta::new_readonly(mc_pda, false),                  // market_config
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new(program_pda, false),
+            AccountMeta::new(pp_pda, false),
+            AccountMeta::new(escrow_pda, false),
+            AccountMeta::new(nav_sol_ata, false),
+            AccountMeta::new(wsol_ata, false),
+            AccountMeta::new_readonly(MAYFLOWER_TENANT, false),
+            AccountMeta::new_readonly(DEFAULT_MARKET_GROUP, false),
+            AccountMeta::new_readonly(DEFAULT_MARKET_META, false),
+            AccountMeta::new(DEFAULT_MAYFLOWER_MARKET, false),
+            AccountMeta::new(DEFAULT_NAV_SOL_MINT, false),
+            AccountMeta::new(DEFAULT_MARKET_BASE_VAULT, false),
+            AccountMeta::new(DEFAULT_MARKET_NAV_VAULT, false),
+            AccountMeta::new(DEFAULT_FEE_VAULT, false),
+            AccountMeta::new_readonly(DEFAULT_WSOL_MINT, false),
+            AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SPL_TOKEN_ID, false),
+            AccountMeta::new(log_pda, false),
         ],
     )
 }
@@ -578,6 +852,8 @@ fn ix_borrow(
             AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false),
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),
             AccountMeta::new(log_pda, false),
+            AccountMeta::new(program_id(), false),                     // payout (Option)
+            AccountMeta::new(program_id(), false),                     // promo (Option)
         ],
     )
 }
@@ -615,6 +891,7 @@ fn ix_repay(
             AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false),
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),
             AccountMeta::new(log_pda, false),
+            AccountMeta::new_readonly(program_id(), false),  // key_state (Option)
         ],
     )
 }
@@ -625,6 +902,21 @@ fn ix_reinvest(
     position_pda: &Pubkey,
     admin_asset: &Pubkey,
     max_spread_bps: u16,
+) -> Instruction {
+    ix_reinvest_with_mode(
+        signer, key_asset, position_pda, admin_asset, max_spread_bps,
+        ReinvestMode::Fraction { bps: 10_000 }, 0,
+    )
+}
+
+fn ix_reinvest_with_mode(
+    signer: &Pubkey,
+    key_asset: &Pubkey,
+    position_pda: &Pubkey,
+    admin_asset: &Pubkey,
+    max_spread_bps: u16,
+    mode: ReinvestMode,
+    min_amount: u64,
 ) -> Instruction {
     let (program_pda, pp_pda, escrow_pda, log_pda, wsol_ata, nav_sol_ata) = mayflower_addrs(admin_asset);
     let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
@@ -632,6 +924,8 @@ fn ix_reinvest(
     let mut data = sighash("reinvest");
     data.extend_from_slice(&0u64.to_le_bytes()); // min_out = 0 (no slippage check)
     data.extend_from_slice(&max_spread_bps.to_le_bytes());
+    mode.serialize(&mut data).unwrap();
+    data.extend_from_slice(&min_amount.to_le_bytes());
 
     Instruction::new_with_bytes(
         program_id(),
@@ -661,6 +955,10 @@ fn ix_reinvest(
             AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false),
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),
             AccountMeta::new(log_pda, false),
+            AccountMeta::new_readonly(program_id(), false),           // treasury_base_ata (None)
+            AccountMeta::new_readonly(program_id(), false),           // keeper_base_ata (None)
+            AccountMeta::new_readonly(program_id(), false),           // treasury (None)
+            AccountMeta::new_readonly(program_id(), false),           // associated_token_program (None)
         ],
     )
 }
@@ -2441,1319 +2739,3675 @@ fn test_total_limit_rejected_without_limited_permission() {
 }
 
 // ===========================================================================
-// MPL-Core asset tests
+// Update key limits tests
 // ===========================================================================
 
 #[test]
-fn test_create_position_creates_mpl_core_asset() {
+fn test_update_key_limits_preserves_accumulators() {
     let (mut svm, _) = setup();
-    let admin = Keypair::new();
-    svm.airdrop(&admin.pubkey(), 10_000_000_000).unwrap();
-    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
-
-    // Create collection
-    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
-    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
-    let collection = coll_kp.pubkey();
-
-    // Create MarketConfig for default navSOL market
-    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+    let h = full_setup(&mut svm);
+    let buy_ix = ix_buy(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        &h.position_pda, &h.admin_asset.pubkey(), 5_000_000_000,
+    );
+    send_tx(&mut svm, &[buy_ix], &[&h.admin]).unwrap();
 
-    let asset_kp = Keypair::new();
-    plant_position_stubs(&mut svm, &asset_kp.pubkey());
+    let user = Keypair::new();
+    svm.airdrop(&user.pubkey(), 5_000_000_000).unwrap();
+    let asset = Keypair::new();
     send_tx(
         &mut svm,
-        &[ix_create_position(&admin.pubkey(), &asset_kp.pubkey(), &collection)],
-        &[&admin, &asset_kp],
+        &[ix_authorize_key(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &h.position_pda,
+            &asset.pubkey(),
+            &user.pubkey(),
+            PERM_LIMITED_SELL,
+            2_000_000_000, // 2 SOL rate capacity
+            1_000_000,     // refill period
+            0, 0,
+            3_000_000_000, // total_sell_limit = 3 SOL
+            0,
+            &h.collection,
+        )],
+        &[&h.admin, &asset],
     )
     .unwrap();
+    let (ks_pda, _) = key_state_pda(&asset.pubkey());
 
-    // MPL-Core asset should exist
-    let asset_account = svm.get_account(&asset_kp.pubkey());
-    assert!(asset_account.is_some(), "MPL-Core asset should exist");
-    let asset_data = asset_account.unwrap();
-    assert_eq!(asset_data.owner, MPL_CORE_ID, "asset should be owned by MPL-Core");
-    // First byte should be Key::AssetV1 = 1
-    assert_eq!(asset_data.data[0], 1, "first byte should be AssetV1 discriminator");
-}
+    // Sell 1 SOL so the accumulator and bucket level move
+    let sell_ix = ix_withdraw(
+        &user.pubkey(), &asset.pubkey(),
+        Some(&ks_pda), &h.position_pda, &h.admin_asset.pubkey(), 1_000_000_000,
+    );
+    send_tx(&mut svm, &[sell_ix], &[&user]).unwrap();
 
-#[test]
-fn test_authorize_key_creates_mpl_core_asset() {
-    let (mut svm, _) = setup();
-    let h = full_setup(&mut svm);
+    // Raise the bucket to 4 SOL and the lifetime cap to 10 SOL
+    let logs = send_tx_logs(
+        &mut svm,
+        &[ix_update_key_limits(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &asset.pubkey(),
+            4_000_000_000,
+            2_000_000,
+            0, 0,
+            10_000_000_000,
+            0,
+            &h.collection,
+        )],
+        &[&h.admin],
+    )
+    .unwrap();
 
-    // Operator's MPL-Core asset should exist
-    let asset_account = svm.get_account(&h.operator_asset);
-    assert!(asset_account.is_some(), "operator MPL-Core asset should exist");
-    let asset_data = asset_account.unwrap();
-    assert_eq!(asset_data.owner, MPL_CORE_ID, "asset should be owned by MPL-Core");
-    assert_eq!(asset_data.data[0], 1, "first byte should be AssetV1 discriminator");
+    let ks = read_key_state(&svm, &ks_pda);
+    assert_eq!(ks.sell_bucket.capacity, 4_000_000_000);
+    assert_eq!(ks.sell_bucket.refill_period, 2_000_000);
+    // Tokens left after the sell carry over (no free refill to the new capacity)
+    assert_eq!(ks.sell_bucket.level, 1_000_000_000);
+    assert_eq!(ks.total_sell_limit, 10_000_000_000);
+    assert_eq!(ks.total_sold, 1_000_000_000, "accumulator must be preserved");
+
+    let event: KeyLimitsUpdated = find_event(&logs).expect("KeyLimitsUpdated event");
+    assert_eq!(event.key_asset, asset.pubkey());
+    assert_eq!(event.sell_bucket_capacity, 4_000_000_000);
+    assert_eq!(event.total_sell_limit, 10_000_000_000);
+
+    // On-chain attributes reflect the new limits; other attributes are kept
+    let account = svm.get_account(&asset.pubkey()).unwrap();
+    let attrs = extract_asset_attributes(&account);
+    assert_eq!(find_attribute(&attrs, "limited_sell"), Some("4 navSOL / 9 days, 6 hours"));
+    assert_eq!(find_attribute(&attrs, "total_sell_limit"), Some("10 navSOL"));
+    assert_eq!(
+        find_attribute(&attrs, "position"),
+        Some(h.admin_asset.pubkey().to_string().as_str())
+    );
+    assert_eq!(attrs.iter().filter(|(k, _)| k == "limited_sell").count(), 1);
 }
 
 #[test]
-fn test_revoke_burns_mpl_core_asset() {
+fn test_update_key_limits_removes_total_cap() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
 
-    // Authorize a key to the admin's own wallet
-    let extra_asset = Keypair::new();
+    let user = Keypair::new();
+    let asset = Keypair::new();
     send_tx(
         &mut svm,
         &[ix_authorize_key(
             &h.admin.pubkey(),
             &h.admin_asset.pubkey(),
             &h.position_pda,
-            &extra_asset.pubkey(),
+            &asset.pubkey(),
+            &user.pubkey(),
+            PERM_LIMITED_BORROW,
+            0, 0,
+            1_000_000_000, 1_000_000,
+            0,
+            5_000_000_000, // total_borrow_limit = 5 SOL
+            &h.collection,
+        )],
+        &[&h.admin, &asset],
+    )
+    .unwrap();
+
+    send_tx(
+        &mut svm,
+        &[ix_update_key_limits(
             &h.admin.pubkey(),
-            PRESET_OPERATOR,
-            0, 0, 0, 0, 0, 0,
+            &h.admin_asset.pubkey(),
+            &asset.pubkey(),
+            0, 0,
+            1_000_000_000, 1_000_000,
+            0,
+            0, // remove lifetime cap
             &h.collection,
         )],
-        &[&h.admin, &extra_asset],
+        &[&h.admin],
     )
     .unwrap();
-    let (extra_ks, _) = key_state_pda(&extra_asset.pubkey());
 
-    // Verify asset exists before revoke
-    assert!(svm.get_account(&extra_asset.pubkey()).is_some());
-    assert!(svm.get_account(&extra_ks).is_some());
+    let (ks_pda, _) = key_state_pda(&asset.pubkey());
+    assert_eq!(read_key_state(&svm, &ks_pda).total_borrow_limit, 0);
+    let attrs = extract_asset_attributes(&svm.get_account(&asset.pubkey()).unwrap());
+    assert_eq!(find_attribute(&attrs, "total_borrow_limit"), None);
+    assert!(find_attribute(&attrs, "limited_borrow").is_some());
+}
 
-    // Revoke and burn via PermanentBurnDelegate
-    let ix = ix_revoke_key(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &extra_asset.pubkey(),
-        &extra_ks,
-        &h.collection,
+#[test]
+fn test_update_key_limits_rejects_params_for_missing_permission() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    // Operator key has no limited bits, so any limit params are invalid
+    let result = send_tx(
+        &mut svm,
+        &[ix_update_key_limits(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &h.operator_asset,
+            1_000_000_000, 1_000_000,
+            0, 0, 0, 0,
+            &h.collection,
+        )],
+        &[&h.admin],
     );
-    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    assert!(result.is_err());
+}
 
-    // KeyState should be closed
-    assert!(svm.get_account(&extra_ks).is_none());
+#[test]
+fn test_update_key_limits_non_admin_denied() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    // After MPL-Core burn, account is resized to 1 byte with Key::Uninitialized (0)
-    let asset_after = svm.get_account(&extra_asset.pubkey());
-    assert!(
-        asset_after.is_none()
-            || asset_after.as_ref().unwrap().data.is_empty()
-            || asset_after.as_ref().unwrap().data.len() == 1,
-        "MPL-Core asset should be burned"
+    let ix = ix_update_key_limits(
+        &h.operator.pubkey(),
+        &h.operator_asset,
+        &h.depositor_asset,
+        0, 0, 0, 0, 0, 0,
+        &h.collection,
     );
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
 }
 
 // ===========================================================================
-// Collection tests
+// Update key permissions tests
 // ===========================================================================
 
 #[test]
-fn test_create_collection() {
-    let (mut svm, admin) = setup();
-    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+fn test_update_key_permissions_promotes_depositor() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
-    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+    // Depositor (buy + repay) -> Operator + limited sell
+    let new_perms = PRESET_OPERATOR | PERM_LIMITED_SELL;
+    let logs = send_tx_logs(
+        &mut svm,
+        &[ix_update_key_permissions(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &h.depositor_asset,
+            new_perms,
+            1_000_000_000, 1_000_000,
+            0, 0, 0, 0,
+            &h.collection,
+        )],
+        &[&h.admin],
+    )
+    .unwrap();
 
-    let config = read_protocol_config(&svm);
-    assert_eq!(config.collection, coll_kp.pubkey());
+    let event: KeyPermissionsUpdated = find_event(&logs).expect("KeyPermissionsUpdated event");
+    assert_eq!(event.old_permissions, PRESET_DEPOSITOR);
+    assert_eq!(event.permissions, new_perms);
 
-    // Collection asset should exist (owned by MPL-Core program)
-    let coll_account = svm.get_account(&coll_kp.pubkey());
-    assert!(coll_account.is_some(), "collection asset should exist");
-    assert_eq!(coll_account.unwrap().owner, MPL_CORE_ID);
+    // Newly added limited bucket starts full
+    let ks = read_key_state(&svm, &h.depositor_key_state);
+    assert_eq!(ks.sell_bucket.capacity, 1_000_000_000);
+    assert_eq!(ks.sell_bucket.level, 1_000_000_000);
+
+    let attrs = extract_asset_attributes(&svm.get_account(&h.depositor_asset).unwrap());
+    let perms_str = new_perms.to_string();
+    assert_eq!(find_attribute(&attrs, "permissions"), Some(perms_str.as_str()));
+    assert_eq!(find_attribute(&attrs, "reinvest"), Some("true"));
+    assert!(find_attribute(&attrs, "limited_sell").is_some());
+    assert_eq!(
+        find_attribute(&attrs, "position"),
+        Some(h.admin_asset.pubkey().to_string().as_str())
+    );
+    assert_eq!(attrs.iter().filter(|(k, _)| k == "permissions").count(), 1);
+
+    // The key can now reinvest (validate_key reads the new bitmask)
+    let ix = ix_reinvest(
+        &h.depositor.pubkey(), &h.depositor_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 0,
+    );
+    send_tx(&mut svm, &[ix], &[&h.depositor]).unwrap();
 }
 
 #[test]
-fn test_create_collection_twice_rejected() {
-    let (mut svm, admin) = setup();
-    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+fn test_update_key_permissions_removing_limited_bit_clears_bucket() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
-    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+    let user = Keypair::new();
+    let asset = Keypair::new();
+    send_tx(
+        &mut svm,
+        &[ix_authorize_key(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &h.position_pda,
+            &asset.pubkey(),
+            &user.pubkey(),
+            PERM_BUY | PERM_LIMITED_BORROW,
+            0, 0,
+            1_000_000_000, 1_000_000,
+            0,
+            5_000_000_000,
+            &h.collection,
+        )],
+        &[&h.admin, &asset],
+    )
+    .unwrap();
 
-    // Second attempt should fail
-    let (coll_ix2, coll_kp2) = ix_create_collection(&admin.pubkey());
-    assert!(send_tx(&mut svm, &[coll_ix2], &[&admin, &coll_kp2]).is_err());
+    send_tx(
+        &mut svm,
+        &[ix_update_key_permissions(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &asset.pubkey(),
+            PERM_BUY,
+            0, 0, 0, 0, 0, 0,
+            &h.collection,
+        )],
+        &[&h.admin],
+    )
+    .unwrap();
+
+    let (ks_pda, _) = key_state_pda(&asset.pubkey());
+    let ks = read_key_state(&svm, &ks_pda);
+    assert_eq!(ks.borrow_bucket.capacity, 0);
+    assert_eq!(ks.total_borrow_limit, 0);
+
+    let attrs = extract_asset_attributes(&svm.get_account(&asset.pubkey()).unwrap());
+    assert_eq!(find_attribute(&attrs, "limited_borrow"), None);
+    assert_eq!(find_attribute(&attrs, "total_borrow_limit"), None);
 }
 
 #[test]
-fn test_create_position_without_collection_rejected() {
-    let (mut svm, admin) = setup();
-    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+fn test_update_key_permissions_manage_keys_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    // Try to create position without creating collection first
-    let asset_kp = Keypair::new();
-    // Use a dummy pubkey for collection since none exists
-    let dummy_collection = Pubkey::new_unique();
-    let result = send_tx(
-        &mut svm,
-        &[ix_create_position(&admin.pubkey(), &asset_kp.pubkey(), &dummy_collection)],
-        &[&admin, &asset_kp],
+    let ix = ix_update_key_permissions(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.operator_asset,
+        PRESET_OPERATOR | PERM_MANAGE_KEYS,
+        0, 0, 0, 0, 0, 0,
+        &h.collection,
     );
-    assert!(result.is_err());
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
 }
 
-/// Extract the name string from an MPL-Core AssetV1 account's raw data.
-/// Layout: 1 (key) + 32 (owner) + 1 (update_authority tag) + 32 (authority pubkey) + 4 (name len) + name bytes
-fn extract_asset_name(data: &[u8]) -> String {
-    let name_len_offset = 1 + 32 + 1 + 32; // = 66
-    let name_len = u32::from_le_bytes(data[name_len_offset..name_len_offset + 4].try_into().unwrap()) as usize;
-    let name_start = name_len_offset + 4;
-    String::from_utf8(data[name_start..name_start + name_len].to_vec()).unwrap()
+#[test]
+fn test_update_key_permissions_non_admin_denied() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let ix = ix_update_key_permissions(
+        &h.operator.pubkey(),
+        &h.operator_asset,
+        &h.depositor_asset,
+        PRESET_OPERATOR,
+        0, 0, 0, 0, 0, 0,
+        &h.collection,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+}
+
+// ===========================================================================
+// Key expiry tests
+// ===========================================================================
+
+fn now(svm: &LiteSVM) -> i64 {
+    svm.get_sysvar::<Clock>().unix_timestamp
 }
 
 #[test]
-fn test_create_position_custom_name() {
-    let (mut svm, admin) = setup();
-    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
-    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
-    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
-    let collection = coll_kp.pubkey();
-    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+fn test_expired_key_rejected_then_reaped() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    let asset_kp = Keypair::new();
-    plant_position_stubs(&mut svm, &asset_kp.pubkey());
+    let contractor = Keypair::new();
+    svm.airdrop(&contractor.pubkey(), 5_000_000_000).unwrap();
+    let asset = Keypair::new();
+    let expires_at = now(&svm) + 3600;
     send_tx(
         &mut svm,
-        &[ix_create_position_named(
-            &admin.pubkey(),
-            &asset_kp.pubkey(),
-            &collection,
-            "My Vault",
+        &[ix_authorize_key_expiring(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &h.position_pda,
+            &asset.pubkey(),
+            &contractor.pubkey(),
+            PRESET_DEPOSITOR,
+            0, 0, 0, 0, 0, 0,
+            expires_at,
+            &h.collection,
         )],
-        &[&admin, &asset_kp],
+        &[&h.admin, &asset],
     )
     .unwrap();
 
-    // Verify the MPL-Core asset has the base name + suffix
-    let asset_account = svm.get_account(&asset_kp.pubkey()).expect("asset should exist");
-    assert_eq!(asset_account.owner, MPL_CORE_ID);
-    let name = extract_asset_name(&asset_account.data);
-    assert_eq!(name, "H\u{00e4}rdig Admin Key - My Vault");
-}
+    let (ks_pda, _) = key_state_pda(&asset.pubkey());
+    assert_eq!(read_key_state(&svm, &ks_pda).expires_at, expires_at);
+    let attrs = extract_asset_attributes(&svm.get_account(&asset.pubkey()).unwrap());
+    let expires_str = expires_at.to_string();
+    assert_eq!(find_attribute(&attrs, "expires_at"), Some(expires_str.as_str()));
 
-/// Extract attribute values from an MPL-Core asset account using fetch_plugin.
-/// Returns the list of (key, value) pairs.
-fn extract_asset_attributes(account: &Account) -> Vec<(String, String)> {
-    use mpl_core::{accounts::BaseAssetV1, fetch_plugin, types::{Attributes, PluginType}};
-    let key = Pubkey::new_unique(); // dummy key, not used in fetch_plugin
-    let mut lamports = account.lamports;
-    let mut data = account.data.clone();
-    let account_info = solana_sdk::account_info::AccountInfo::new(
-        &key,
-        false,
-        false,
-        &mut lamports,
-        &mut data,
-        &account.owner,
-        false,
-        0,
+    // Valid before expiry
+    let buy_ix = ix_buy(
+        &contractor.pubkey(), &asset.pubkey(),
+        &h.position_pda, &h.admin_asset.pubkey(), 1_000_000_000,
+    );
+    send_tx(&mut svm, &[buy_ix], &[&contractor]).unwrap();
+
+    // Not reapable yet
+    let outsider = Keypair::new();
+    svm.airdrop(&outsider.pubkey(), 1_000_000_000).unwrap();
+    let reap_ix = ix_reap_expired_key(
+        &outsider.pubkey(), &h.admin_asset.pubkey(), &asset.pubkey(), &h.collection,
+    );
+    assert!(send_tx(&mut svm, std::slice::from_ref(&reap_ix), &[&outsider]).is_err());
+
+    advance_clock(&mut svm, 3600);
+    svm.expire_blockhash();
+
+    // Rejected by validate_key after expiry
+    let buy_ix = ix_buy(
+        &contractor.pubkey(), &asset.pubkey(),
+        &h.position_pda, &h.admin_asset.pubkey(), 1_000_000_000,
+    );
+    assert!(send_tx(&mut svm, &[buy_ix], &[&contractor]).is_err());
+
+    // Anyone can reap: asset burned, KeyState closed
+    let logs = send_tx_logs(&mut svm, &[reap_ix], &[&outsider]).unwrap();
+    let event: KeyReaped = find_event(&logs).expect("KeyReaped event");
+    assert_eq!(event.key_asset, asset.pubkey());
+    assert_eq!(event.caller, outsider.pubkey());
+    assert_eq!(event.expires_at, expires_at);
+    assert!(svm.get_account(&ks_pda).is_none());
+    let asset_after = svm.get_account(&asset.pubkey());
+    assert!(
+        asset_after.is_none() || asset_after.as_ref().unwrap().data.len() <= 1,
+        "MPL-Core asset should be burned"
     );
-    let (_, attributes, _) = fetch_plugin::<BaseAssetV1, Attributes>(
-        &account_info,
-        PluginType::Attributes,
-    )
-    .expect("failed to fetch Attributes plugin");
-    attributes
-        .attribute_list
-        .into_iter()
-        .map(|a| (a.key, a.value))
-        .collect()
 }
 
-/// Find a specific attribute value by key from the attribute list.
-fn find_attribute<'a>(attrs: &'a [(String, String)], key: &str) -> Option<&'a str> {
-    attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+#[test]
+fn test_reap_non_expiring_key_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    advance_clock(&mut svm, 365 * 86_400);
+
+    let ix = ix_reap_expired_key(
+        &h.outsider.pubkey(), &h.admin_asset.pubkey(), &h.operator_asset, &h.collection,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.outsider]).is_err());
 }
 
 #[test]
-fn test_admin_key_has_market_attribute() {
-    let (mut svm, admin) = setup();
-    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
-    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
-    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
-    let collection = coll_kp.pubkey();
-    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+fn test_authorize_key_past_expiry_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    let asset_kp = Keypair::new();
-    plant_position_stubs(&mut svm, &asset_kp.pubkey());
-    send_tx(
-        &mut svm,
-        &[ix_create_position_with_market(
-            &admin.pubkey(),
-            &asset_kp.pubkey(),
-            &collection,
-            Some("My Vault"),
-            "navSOL",
-        )],
-        &[&admin, &asset_kp],
-    )
-    .unwrap();
+    let asset = Keypair::new();
+    let ix = ix_authorize_key_expiring(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &asset.pubkey(),
+        &h.outsider.pubkey(),
+        PRESET_KEEPER,
+        0, 0, 0, 0, 0, 0,
+        now(&svm),
+        &h.collection,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin, &asset]).is_err());
+}
 
-    // Read admin asset's attributes
-    let asset_account = svm.get_account(&asset_kp.pubkey()).expect("asset should exist");
-    let attrs = extract_asset_attributes(&asset_account);
+// ===========================================================================
+// Close position tests
+// ===========================================================================
 
-    // Verify "market" attribute is set
-    assert_eq!(
-        find_attribute(&attrs, "market"),
-        Some("navSOL"),
-        "admin key should have market=navSOL attribute"
+fn ix_close_position(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    recovery_asset: Option<&Pubkey>,
+    collection: &Pubkey,
+    close_accounts: &[Pubkey],
+) -> Instruction {
+    let (pos_pda, _) = position_pda(admin_asset);
+    let (program_pda, pp_pda, _, _, wsol_ata, nav_sol_ata) = mayflower_addrs(admin_asset);
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    let (cfg_pda, _) = config_pda();
+    // Pass program_id() as the "None" sentinel for the optional recovery asset.
+    let recovery = recovery_asset.copied().unwrap_or(program_id());
+
+    let mut accounts = vec![
+        AccountMeta::new(*admin, true),                     // admin (receives rent)
+        AccountMeta::new(*admin_asset, false),              // admin_key_asset
+        AccountMeta::new(pos_pda, false),                   // position
+        AccountMeta::new(recovery, false),                  // recovery_asset (Option)
+        AccountMeta::new_readonly(cfg_pda, false),          // config
+        AccountMeta::new(*collection, false),               // collection
+        AccountMeta::new_readonly(MPL_CORE_ID, false),      // mpl_core_program
+        AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        AccountMeta::new_readonly(mc_pda, false),           // market_config
+        AccountMeta::new(program_pda, false),               // program_pda
+        AccountMeta::new_readonly(pp_pda, false),           // personal_position
+        AccountMeta::new(nav_sol_ata, false),               // user_nav_sol_ata
+        AccountMeta::new(wsol_ata, false),                  // user_wsol_ata
+        AccountMeta::new_readonly(SPL_TOKEN_ID, false),     // token_program
+    ];
+    accounts.extend(close_accounts.iter().map(|a| AccountMeta::new(*a, false)));
+
+    Instruction::new_with_bytes(program_id(), &sighash("close_position"), accounts)
+}
+
+#[test]
+fn test_close_position_reclaims_all_rent() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let key_states = [h.operator_key_state, h.depositor_key_state, h.keeper_key_state];
+    let reclaimable: u64 = key_states
+        .iter()
+        .chain(std::iter::once(&h.position_pda))
+        .map(|a| svm.get_account(a).unwrap().lamports)
+        .sum();
+    let before = svm.get_account(&h.admin.pubkey()).unwrap().lamports;
+
+    let ix = ix_close_position(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(), None, &h.collection, &key_states,
     );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
 
-    // Verify "position" attribute points to itself (admin asset)
-    assert_eq!(
-        find_attribute(&attrs, "position"),
-        Some(asset_kp.pubkey().to_string().as_str()),
-        "admin key position attribute should be its own pubkey"
+    let event: PositionClosed = find_event(&logs).expect("PositionClosed event");
+    assert_eq!(event.position, h.position_pda);
+    assert_eq!(event.admin_asset, h.admin_asset.pubkey());
+    assert_eq!(event.recovery_asset, Pubkey::default());
+    assert_eq!(event.closed_accounts, 3);
+
+    assert!(svm.get_account(&h.position_pda).is_none());
+    for ks in key_states {
+        assert!(svm.get_account(&ks).is_none());
+    }
+    let asset_after = svm.get_account(&h.admin_asset.pubkey());
+    assert!(
+        asset_after.is_none() || asset_after.as_ref().unwrap().data.len() <= 1,
+        "admin asset should be burned"
     );
+    // Admin gets back at least the PDA rent (net of the tx fee)
+    let after = svm.get_account(&h.admin.pubkey()).unwrap().lamports;
+    assert!(after + 10_000 >= before + reclaimable);
 }
 
 #[test]
-fn test_admin_key_market_attribute_custom_value() {
-    let (mut svm, admin) = setup();
-    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
-    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
-    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
-    let collection = coll_kp.pubkey();
-    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+fn test_close_position_with_debt_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    let asset_kp = Keypair::new();
-    plant_position_stubs(&mut svm, &asset_kp.pubkey());
-    send_tx(
-        &mut svm,
-        &[ix_create_position_with_market(
-            &admin.pubkey(),
-            &asset_kp.pubkey(),
-            &collection,
-            None,
-            "navETH",
-        )],
-        &[&admin, &asset_kp],
-    )
-    .unwrap();
+    // Outstanding Mayflower debt
+    let (_, pp_pda, _, _, _, _) = mayflower_addrs(&h.admin_asset.pubkey());
+    let mut pp = svm.get_account(&pp_pda).unwrap();
+    pp.data[PP_DEBT_OFFSET..PP_DEBT_OFFSET + 8].copy_from_slice(&1u64.to_le_bytes());
+    svm.set_account(pp_pda, pp).unwrap();
 
-    let asset_account = svm.get_account(&asset_kp.pubkey()).expect("asset should exist");
-    let attrs = extract_asset_attributes(&asset_account);
-    assert_eq!(
-        find_attribute(&attrs, "market"),
-        Some("navETH"),
-        "admin key should have market=navETH attribute"
+    let ix = ix_close_position(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(), None, &h.collection, &[],
     );
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    assert!(svm.get_account(&h.position_pda).is_some());
 }
 
 #[test]
-fn test_delegated_key_has_market_and_position_attributes() {
-    let (mut svm, admin) = setup();
-    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
-    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
-    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
-    let collection = coll_kp.pubkey();
-    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+fn test_close_position_foreign_key_state_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    // Create position with market_name = "navSOL" and custom name
-    let admin_asset = Keypair::new();
-    plant_position_stubs(&mut svm, &admin_asset.pubkey());
+    // A second position (same admin) with its own delegated key
+    let other_admin_asset = Keypair::new();
+    plant_position_stubs(&mut svm, &other_admin_asset.pubkey());
     send_tx(
         &mut svm,
-        &[ix_create_position_with_market(
-            &admin.pubkey(),
-            &admin_asset.pubkey(),
-            &collection,
-            Some("My Vault"),
-            "navSOL",
-        )],
-        &[&admin, &admin_asset],
+        &[ix_create_position(&h.admin.pubkey(), &other_admin_asset.pubkey(), &h.collection)],
+        &[&h.admin, &other_admin_asset],
     )
     .unwrap();
-
-    let (pos_pda, _) = position_pda(&admin_asset.pubkey());
-
-    // Authorize an operator key
-    let operator = Keypair::new();
-    svm.airdrop(&operator.pubkey(), 5_000_000_000).unwrap();
-    let op_asset = Keypair::new();
+    let (other_pos, _) = position_pda(&other_admin_asset.pubkey());
+    let other_key = Keypair::new();
     send_tx(
         &mut svm,
         &[ix_authorize_key(
-            &admin.pubkey(),
-            &admin_asset.pubkey(),
-            &pos_pda,
-            &op_asset.pubkey(),
-            &operator.pubkey(),
-            PRESET_OPERATOR,
+            &h.admin.pubkey(),
+            &other_admin_asset.pubkey(),
+            &other_pos,
+            &other_key.pubkey(),
+            &h.outsider.pubkey(),
+            PRESET_KEEPER,
             0, 0, 0, 0, 0, 0,
-            &collection,
+            &h.collection,
         )],
-        &[&admin, &op_asset],
+        &[&h.admin, &other_key],
     )
     .unwrap();
+    let (other_ks, _) = key_state_pda(&other_key.pubkey());
 
-    // Read the delegated key's attributes
-    let op_account = svm.get_account(&op_asset.pubkey()).expect("operator asset should exist");
-    let attrs = extract_asset_attributes(&op_account);
-
-    // Verify "market" attribute is inherited from admin key
-    assert_eq!(
-        find_attribute(&attrs, "market"),
-        Some("navSOL"),
-        "delegated key should have market=navSOL attribute from admin"
+    let ix = ix_close_position(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(), None, &h.collection, &[other_ks],
     );
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    assert!(svm.get_account(&other_ks).is_some());
+}
 
-    // Verify "position_name" attribute matches admin asset's name
-    assert_eq!(
-        find_attribute(&attrs, "position_name"),
-        Some("H\u{00e4}rdig Admin Key - My Vault"),
-        "delegated key should have position_name matching admin asset name"
-    );
+#[test]
+fn test_close_position_operator_denied() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
 
-    // Verify "position" attribute still points to admin asset pubkey
-    assert_eq!(
-        find_attribute(&attrs, "position"),
-        Some(admin_asset.pubkey().to_string().as_str()),
-        "delegated key position attribute should point to admin asset"
+    let ix = ix_close_position(
+        &h.operator.pubkey(), &h.admin_asset.pubkey(), None, &h.collection, &[],
     );
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+}
+
+#[test]
+fn test_close_position_waits_for_withdraw_requests() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+
+    let ix = ix_request_withdraw(&admin, &admin_asset, None, &h.position_pda, 0, 500_000);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).pending_withdraw_requests, 1);
+
+    // The request PDA would be stranded
+    let close = ix_close_position(&admin, &admin_asset, None, &h.collection, &[]);
+    assert!(send_tx(&mut svm, std::slice::from_ref(&close), &[&h.admin]).is_err());
+
+    let ix = ix_cancel_withdraw(&admin, &admin_asset, &h.position_pda, 0, &admin);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).pending_withdraw_requests, 0);
+
+    send_tx(&mut svm, &[close], &[&h.admin]).unwrap();
+    assert!(svm.get_account(&h.position_pda).is_none());
 }
 
 // ===========================================================================
-// Recovery instruction builders
+// Unwind tests
 // ===========================================================================
 
-fn ix_heartbeat(
-    admin: &Pubkey,
-    admin_key_asset: &Pubkey,
-    position_pda: &Pubkey,
-) -> Instruction {
-    Instruction::new_with_bytes(
-        program_id(),
-        &sighash("heartbeat"),
-        vec![
-            AccountMeta::new_readonly(*admin, true),
-            AccountMeta::new_readonly(*admin_key_asset, false),
-            AccountMeta::new(*position_pda, false),
-            AccountMeta::new_readonly(config_pda().0, false),          // config
+/// Buy and borrow as admin, then mint an unwind-only key to the outsider.
+fn setup_unwind(svm: &mut LiteSVM, h: &TestHarness) -> Pubkey {
+    send_tx(
+        svm,
+        &[
+            ix_buy(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, &h.admin_asset.pubkey(), 1_000_000),
+            ix_borrow(&h.admin.pubkey(), &h.admin_asset.pubkey(), None, &h.position_pda, &h.admin_asset.pubkey(), 500_000),
         ],
+        &[&h.admin],
     )
+    .unwrap();
+    // The sale is sized against the floor value of the debt it repays
+    plant_unit_floor_price(svm);
+    let unwind_key = Keypair::new();
+    send_tx(
+        svm,
+        &[ix_authorize_key(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &h.position_pda,
+            &unwind_key.pubkey(),
+            &h.outsider.pubkey(),
+            PERM_UNWIND,
+            0, 0, 0, 0, 0, 0,
+            &h.collection,
+        )],
+        &[&h.admin, &unwind_key],
+    )
+    .unwrap();
+    unwind_key.pubkey()
 }
 
-fn ix_configure_recovery(
-    admin: &Pubkey,
-    admin_key_asset: &Pubkey,
-    position_pda: &Pubkey,
-    recovery_asset: &Pubkey,
-    target_wallet: &Pubkey,
-    old_recovery_asset: Option<&Pubkey>,
-    collection: &Pubkey,
-    lockout_secs: i64,
-    lock_config: bool,
-    name: Option<&str>,
-) -> Instruction {
-    let (cfg_pda, _) = config_pda();
+#[test]
+fn test_unwind_with_unwind_key_ok() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let unwind_asset = setup_unwind(&mut svm, &h);
 
-    let mut data = sighash("configure_recovery");
-    data.extend_from_slice(&lockout_secs.to_le_bytes());
-    data.push(if lock_config { 1 } else { 0 });
-    // name: Option<String>
-    match name {
-        Some(n) => {
-            data.push(1); // Some
-            data.extend_from_slice(&(n.len() as u32).to_le_bytes());
-            data.extend_from_slice(n.as_bytes());
-        }
-        None => {
-            data.push(0); // None
-        }
-    }
+    let ix = ix_unwind(
+        &h.outsider.pubkey(), &unwind_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 200_000, 0,
+    );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.outsider]).unwrap();
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.deposited_nav, 800_000);
+    assert!(pos.user_debt <= 500_000);
 
-    // For Option<UncheckedAccount>, Anchor expects the account always present.
-    // Pass program_id() as the "None" sentinel.
-    let old_recovery = old_recovery_asset.copied().unwrap_or(program_id());
+    let ev: Unwound = find_event(&logs).expect("Unwound event not emitted");
+    assert_eq!(ev.key_asset, unwind_asset);
+    assert_eq!(ev.permissions, PERM_UNWIND);
+    assert_eq!(ev.shares_sold, 200_000);
+    assert_eq!(ev.user_debt, pos.user_debt);
+}
 
-    Instruction::new_with_bytes(
-        program_id(),
-        &data,
-        vec![
-            AccountMeta::new(*admin, true),                    // admin
-            AccountMeta::new_readonly(*admin_key_asset, false), // admin_key_asset
-            AccountMeta::new(*position_pda, false),             // position
-            AccountMeta::new(*recovery_asset, true),            // recovery_asset (signer)
-            AccountMeta::new_readonly(*target_wallet, false),   // target_wallet
-            AccountMeta::new(old_recovery, false),              // old_recovery_asset (Option)
-            AccountMeta::new_readonly(cfg_pda, false),          // config
-            AccountMeta::new(*collection, false),               // collection
-            AccountMeta::new_readonly(MPL_CORE_ID, false),      // mpl_core_program
-            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
-        ],
-    )
+#[test]
+fn test_unwind_buys_back_leftover() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let unwind_asset = setup_unwind(&mut svm, &h);
+
+    // 300_000 wSOL in the ATA (the mock sell doesn't move tokens); the repay to a
+    // 400_000 target only needs 100_000 of it
+    let (.., wsol_ata, _) = mayflower_addrs(&h.admin_asset.pubkey());
+    let mut ata = svm.get_account(&wsol_ata).unwrap();
+    ata.data[64..72].copy_from_slice(&300_000u64.to_le_bytes());
+    svm.set_account(wsol_ata, ata).unwrap();
+
+    // The buy-back falls short of min_shares_rebought
+    let mut ix = ix_unwind(
+        &h.outsider.pubkey(), &unwind_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 100_000, 400_000,
+    );
+    let n = ix.data.len();
+    ix.data[n - 8..].copy_from_slice(&200_001u64.to_le_bytes());
+    assert!(send_tx(&mut svm, &[ix.clone()], &[&h.outsider]).is_err());
+
+    ix.data[n - 8..].copy_from_slice(&200_000u64.to_le_bytes());
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.outsider]).unwrap();
+    let ev: Unwound = find_event(&logs).expect("Unwound event not emitted");
+    assert_eq!(ev.actual_repaid, 100_000);
+    assert_eq!(ev.leftover, 200_000);
+    assert_eq!(ev.shares_rebought, 200_000);
+
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.user_debt, 400_000);
+    assert_eq!(pos.deposited_nav, 1_100_000);
 }
 
-fn ix_execute_recovery(
-    recovery_holder: &Pubkey,
-    recovery_key_asset: &Pubkey,
+#[test]
+fn test_unwind_sale_capped_by_debt_to_repay() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let unwind_asset = setup_unwind(&mut svm, &h);
+
+    // Repaying 100_000 of debt at a 1.0 floor needs at most 100_000 shares
+    let ix = ix_unwind(
+        &h.outsider.pubkey(), &unwind_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 100_001, 400_000,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.outsider]).is_err());
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, 1_000_000);
+
+    let ix = ix_unwind(
+        &h.outsider.pubkey(), &unwind_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 100_000, 400_000,
+    );
+    send_tx(&mut svm, &[ix], &[&h.outsider]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, 900_000);
+}
+
+#[test]
+fn test_unwind_key_cannot_withdraw() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let unwind_asset = setup_unwind(&mut svm, &h);
+
+    let ix = ix_withdraw(
+        &h.outsider.pubkey(), &unwind_asset,
+        None, &h.position_pda, &h.admin_asset.pubkey(), 100_000,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.outsider]).is_err());
+}
+
+#[test]
+fn test_unwind_without_bit_denied() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    setup_unwind(&mut svm, &h);
+
+    let ix = ix_unwind(
+        &h.operator.pubkey(), &h.operator_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 100_000, 0,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+}
+
+#[test]
+fn test_unwind_debt_at_target_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let unwind_asset = setup_unwind(&mut svm, &h);
+
+    let ix = ix_unwind(
+        &h.outsider.pubkey(), &unwind_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 100_000, 500_000,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.outsider]).is_err());
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.deposited_nav, 1_000_000);
+}
+
+// ===========================================================================
+// Payout address tests
+// ===========================================================================
+
+fn ix_set_payout_address(
+    admin: &Pubkey,
+    key_asset: &Pubkey,
     position_pda: &Pubkey,
-    old_admin_asset: &Pubkey,
-    new_admin_asset: &Pubkey,
-    collection: &Pubkey,
+    payout_address: Option<Pubkey>,
 ) -> Instruction {
-    let (cfg_pda, _) = config_pda();
+    let (config_pda, _) = config_pda();
+
+    let mut data = sighash("set_payout_address");
+    match payout_address {
+        Some(pk) => {
+            data.push(1);
+            data.extend_from_slice(pk.as_ref());
+        }
+        None => data.push(0),
+    }
 
     Instruction::new_with_bytes(
         program_id(),
-        &sighash("execute_recovery"),
+        &data,
         vec![
-            AccountMeta::new(*recovery_holder, true),           // recovery_holder
-            AccountMeta::new(*recovery_key_asset, false),          // recovery_key_asset (mut for burn)
-            AccountMeta::new(*position_pda, false),              // position
-            AccountMeta::new(*old_admin_asset, false),           // old_admin_asset
-            AccountMeta::new(*new_admin_asset, true),            // new_admin_asset (signer)
-            AccountMeta::new_readonly(cfg_pda, false),           // config
-            AccountMeta::new(*collection, false),                // collection
-            AccountMeta::new_readonly(MPL_CORE_ID, false),       // mpl_core_program
-            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*key_asset, false),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(config_pda, false),
         ],
     )
 }
 
-// ===========================================================================
-// Recovery tests
-// ===========================================================================
+/// Point the `payout` account of a withdraw/borrow instruction at `payout`.
+fn with_payout(mut ix: Instruction, payout: &Pubkey) -> Instruction {
+    let i = ix.accounts.len() - 2;
+    ix.accounts[i] = AccountMeta::new(*payout, false);
+    ix
+}
 
 #[test]
-fn test_heartbeat_admin_ok() {
+fn test_withdraw_pays_pinned_payout_address() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    let treasury = Pubkey::new_unique();
 
-    let pos_before = read_position(&svm, &h.position_pda);
-
-    // Advance clock so we can detect the timestamp change
-    advance_clock(&mut svm, 10);
+    let ix = ix_set_payout_address(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, Some(treasury));
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ev: PayoutAddressSet = find_event(&logs).expect("PayoutAddressSet event not emitted");
+    assert_eq!(ev.payout_address, treasury);
+    assert_eq!(read_position(&svm, &h.position_pda).payout_address, treasury);
 
-    let ix = ix_heartbeat(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
+    let buy = ix_buy(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
     );
-    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
-
-    let pos_after = read_position(&svm, &h.position_pda);
-    assert!(
-        pos_after.last_admin_activity >= pos_before.last_admin_activity,
-        "heartbeat should update last_admin_activity"
+    send_tx(&mut svm, &[buy], &[&h.admin]).unwrap();
+    let ix = with_payout(
+        ix_withdraw(
+            &h.admin.pubkey(), &h.admin_asset.pubkey(),
+            None, &h.position_pda, &h.admin_asset.pubkey(), 500_000,
+        ),
+        &treasury,
     );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ev: Withdrawn = find_event(&logs).expect("Withdrawn event not emitted");
+    assert_eq!(ev.recipient, treasury);
 }
 
 #[test]
-fn test_heartbeat_operator_denied() {
+fn test_withdraw_without_pinned_payout_rejected() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    let treasury = Pubkey::new_unique();
+    let ix = ix_set_payout_address(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, Some(treasury));
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
 
-    // Operator does NOT have PERM_MANAGE_KEYS, so heartbeat should fail
-    let ix = ix_heartbeat(
-        &h.operator.pubkey(),
-        &h.operator_asset,
-        &h.position_pda,
+    let buy = ix_buy(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
     );
-    assert!(
-        send_tx(&mut svm, &[ix], &[&h.operator]).is_err(),
-        "operator should not be able to heartbeat"
+    send_tx(&mut svm, &[buy], &[&h.admin]).unwrap();
+    let ix = ix_withdraw(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), 500_000,
     );
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, 1_000_000);
 }
 
 #[test]
-fn test_heartbeat_outsider_denied() {
+fn test_borrow_redirect_to_signer_rejected() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    let treasury = Pubkey::new_unique();
+    let ix = ix_set_payout_address(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, Some(treasury));
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
 
-    // Outsider has no key at all — use a random pubkey as the asset
-    let fake_asset = Pubkey::new_unique();
-    let ix = ix_heartbeat(
-        &h.outsider.pubkey(),
-        &fake_asset,
-        &h.position_pda,
+    let ix = with_payout(
+        ix_borrow(
+            &h.admin.pubkey(), &h.admin_asset.pubkey(),
+            None, &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
+        ),
+        &h.admin.pubkey(),
     );
-    assert!(
-        send_tx(&mut svm, &[ix], &[&h.outsider]).is_err(),
-        "outsider should not be able to heartbeat"
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+
+    let ix = with_payout(
+        ix_borrow(
+            &h.admin.pubkey(), &h.admin_asset.pubkey(),
+            None, &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
+        ),
+        &treasury,
     );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ev: Borrowed = find_event(&logs).expect("Borrowed event not emitted");
+    assert_eq!(ev.recipient, treasury);
 }
 
 #[test]
-fn test_configure_recovery_ok() {
+fn test_clear_payout_address_pays_signer_again() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    let ix = ix_set_payout_address(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, Some(Pubkey::new_unique()));
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ix = ix_set_payout_address(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, None);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).payout_address, Pubkey::default());
 
-    let recovery_wallet = Keypair::new();
-    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
-    let recovery_asset = Keypair::new();
-
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset.pubkey(),
-        &recovery_wallet.pubkey(),
-        None,
-        &h.collection,
-        86400, // 1 day lockout
-        false,
-        None,
+    let ix = ix_borrow(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
     );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
-
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.recovery_asset, recovery_asset.pubkey());
-    assert_eq!(pos.recovery_lockout_secs, 86400);
-    assert!(!pos.recovery_config_locked);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ev: Borrowed = find_event(&logs).expect("Borrowed event not emitted");
+    assert_eq!(ev.recipient, h.admin.pubkey());
+}
 
-    // Verify recovery key NFT was created with correct attributes
-    let rec_account = svm.get_account(&recovery_asset.pubkey()).expect("recovery asset should exist");
-    let attrs = extract_asset_attributes(&rec_account);
-    assert_eq!(find_attribute(&attrs, "permissions"), Some("0"));
-    assert_eq!(find_attribute(&attrs, "recovery"), Some("true"));
-    assert_eq!(
-        find_attribute(&attrs, "position"),
-        Some(h.admin_asset.pubkey().to_string().as_str()),
-        "recovery key should be bound to admin asset (authority_seed)"
-    );
+#[test]
+fn test_set_payout_address_program_pda_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let (program_pda, _, _, _, _, _) = mayflower_addrs(&h.admin_asset.pubkey());
+    let ix = ix_set_payout_address(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, Some(program_pda));
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
 }
 
 #[test]
-fn test_configure_recovery_with_lock() {
+fn test_set_payout_address_operator_denied() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    let ix = ix_set_payout_address(&h.operator.pubkey(), &h.operator_asset, &h.position_pda, Some(h.operator.pubkey()));
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+}
 
-    let recovery_wallet = Keypair::new();
-    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
-    let recovery_asset = Keypair::new();
+// ===========================================================================
+// Reinvest mode tests
+// ===========================================================================
 
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset.pubkey(),
-        &recovery_wallet.pubkey(),
-        None,
-        &h.collection,
-        604800, // 7 day lockout
-        true,   // lock config
-        Some("backup"),
-    );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+/// Give the position `shares` deposited navSOL and `debt` lamports of debt at a 1.0 floor price.
+fn plant_reinvest_capacity(svm: &mut LiteSVM, admin_asset: &Pubkey, shares: u64, debt: u64) {
+    let (_, pp_pda, _, _, _, _) = mayflower_addrs(admin_asset);
+    let mut pp = svm.get_account(&pp_pda).unwrap();
+    pp.data[PP_DEPOSITED_SHARES_OFFSET..PP_DEPOSITED_SHARES_OFFSET + 8]
+        .copy_from_slice(&shares.to_le_bytes());
+    pp.data[PP_DEBT_OFFSET..PP_DEBT_OFFSET + 8].copy_from_slice(&debt.to_le_bytes());
+    svm.set_account(pp_pda, pp).unwrap();
+    plant_unit_floor_price(svm);
+}
 
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.recovery_asset, recovery_asset.pubkey());
-    assert_eq!(pos.recovery_lockout_secs, 604800);
-    assert!(pos.recovery_config_locked);
+/// Set the default market's floor price to 1.0 (1e9 lamports per share).
+fn plant_unit_floor_price(svm: &mut LiteSVM) {
+    // Rust Decimal 1.0: scale 0, mantissa 1
+    let mut market = svm.get_account(&DEFAULT_MAYFLOWER_MARKET).unwrap();
+    market.data[MARKET_FLOOR_PRICE_OFFSET..MARKET_FLOOR_PRICE_OFFSET + 16].fill(0);
+    market.data[MARKET_FLOOR_PRICE_OFFSET + 4] = 1;
+    svm.set_account(DEFAULT_MAYFLOWER_MARKET, market).unwrap();
 }
 
 #[test]
-fn test_configure_recovery_locked_denied() {
+fn test_reinvest_below_min_amount_is_noop() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    // 10 SOL floor value, 4 SOL debt -> 6 SOL capacity; 10% of it is 0.6 SOL
+    plant_reinvest_capacity(&mut svm, &h.admin_asset.pubkey(), 10_000_000_000, 4_000_000_000);
 
-    let recovery_wallet = Keypair::new();
-    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
+    let ix = ix_reinvest_with_mode(
+        &h.keeper.pubkey(), &h.keeper_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 0,
+        ReinvestMode::Fraction { bps: 1_000 }, 1_000_000_000,
+    );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.keeper]).unwrap();
+    assert!(find_event::<Reinvested>(&logs).is_none());
+    assert_eq!(read_position(&svm, &h.position_pda).user_debt, 0);
+}
 
-    // First: configure with lock_config = true
-    let recovery_asset1 = Keypair::new();
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset1.pubkey(),
-        &recovery_wallet.pubkey(),
-        None,
-        &h.collection,
-        86400,
-        true, // lock it
-        None,
+#[test]
+fn test_reinvest_target_ltv_already_reached_is_noop() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    // 60% LTV already, target 50%
+    plant_reinvest_capacity(&mut svm, &h.admin_asset.pubkey(), 10_000_000_000, 6_000_000_000);
+
+    let ix = ix_reinvest_with_mode(
+        &h.keeper.pubkey(), &h.keeper_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 0,
+        ReinvestMode::TargetLtv { bps: 5_000 }, 0,
     );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset1]).unwrap();
-
-    // Second: try to reconfigure — should fail because config is locked
-    let recovery_asset2 = Keypair::new();
-    let ix2 = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset2.pubkey(),
-        &recovery_wallet.pubkey(),
-        Some(&recovery_asset1.pubkey()),
-        &h.collection,
-        172800,
-        false,
-        None,
-    );
-    assert!(
-        send_tx(&mut svm, &[ix2], &[&h.admin, &recovery_asset2]).is_err(),
-        "should not be able to reconfigure recovery when locked"
-    );
-
-    // Verify original config unchanged
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.recovery_asset, recovery_asset1.pubkey());
-    assert_eq!(pos.recovery_lockout_secs, 86400);
-}
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.keeper]).unwrap();
+    assert!(find_event::<Reinvested>(&logs).is_none());
+}
 
 #[test]
-fn test_configure_recovery_replace_requires_old_asset() {
+fn test_reinvest_invalid_bps_rejected() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    for mode in [
+        ReinvestMode::Fraction { bps: 0 },
+        ReinvestMode::Fraction { bps: 10_001 },
+        ReinvestMode::TargetLtv { bps: 0 },
+        ReinvestMode::TargetLtv { bps: 10_001 },
+    ] {
+        let ix = ix_reinvest_with_mode(
+            &h.keeper.pubkey(), &h.keeper_asset,
+            &h.position_pda, &h.admin_asset.pubkey(), 0, mode, 0,
+        );
+        assert!(send_tx(&mut svm, &[ix], &[&h.keeper]).is_err());
+    }
+}
 
-    let recovery_wallet = Keypair::new();
-    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
+// ===========================================================================
+// Keeper bounty tests
+// ===========================================================================
 
-    // First: configure a recovery key
-    let recovery_asset1 = Keypair::new();
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset1.pubkey(),
-        &recovery_wallet.pubkey(),
-        None,
-        &h.collection,
-        86400,
-        false,
-        None,
-    );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset1]).unwrap();
+fn ix_configure_keeper(
+    h: &TestHarness,
+    bounty_bps: u16,
+    max_bounty: u64,
+    min_borrow: u64,
+    max_spread_bps: u16,
+    mode: ReinvestMode,
+) -> Instruction {
+    let mut data = sighash("configure_keeper");
+    data.extend_from_slice(&bounty_bps.to_le_bytes());
+    data.extend_from_slice(&max_bounty.to_le_bytes());
+    data.extend_from_slice(&min_borrow.to_le_bytes());
+    data.extend_from_slice(&max_spread_bps.to_le_bytes());
+    mode.serialize(&mut data).unwrap();
 
-    // Second: try to replace WITHOUT providing old_recovery_asset — should fail
-    let recovery_asset2 = Keypair::new();
-    let ix2 = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset2.pubkey(),
-        &recovery_wallet.pubkey(),
-        None, // omitting old_recovery_asset
-        &h.collection,
-        172800,
-        false,
-        None,
-    );
-    assert!(
-        send_tx(&mut svm, &[ix2], &[&h.admin, &recovery_asset2]).is_err(),
-        "should not be able to replace recovery key without providing old_recovery_asset"
-    );
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new_readonly(h.admin.pubkey(), true),
+            AccountMeta::new_readonly(h.admin_asset.pubkey(), false),
+            AccountMeta::new(h.position_pda, false),
+            AccountMeta::new_readonly(config_pda().0, false),
+        ],
+    )
+}
 
-    // Verify original config unchanged
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.recovery_asset, recovery_asset1.pubkey());
-    assert_eq!(pos.recovery_lockout_secs, 86400);
+/// Keyless reinvest: the program ID stands in for the missing key asset.
+fn ix_keeper_reinvest(keeper: &Pubkey, h: &TestHarness, mode: ReinvestMode) -> Instruction {
+    let mut ix = ix_reinvest_with_mode(
+        keeper, &program_id(), &h.position_pda, &h.admin_asset.pubkey(), 0, mode, 0,
+    );
+    let n = ix.accounts.len();
+    ix.accounts[n - 3] = AccountMeta::new(get_ata(keeper, &DEFAULT_WSOL_MINT), false); // keeper_base_ata
+    ix
 }
 
 #[test]
-fn test_configure_recovery_operator_denied() {
+fn test_configure_keeper() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    let mode = ReinvestMode::Fraction { bps: 5_000 };
 
-    let recovery_wallet = Keypair::new();
-    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
-    let recovery_asset = Keypair::new();
+    // Over the 1% bounty cap, or missing a spread limit / bounty cap
+    for (bps, max, spread) in [(101, 1_000_000, 300), (50, 1_000_000, 0), (50, 0, 300)] {
+        let ix = ix_configure_keeper(&h, bps, max, 0, spread, mode);
+        assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    }
 
-    // Operator does not have PERM_MANAGE_KEYS
-    let ix = ix_configure_recovery(
-        &h.operator.pubkey(),
-        &h.operator_asset,
-        &h.position_pda,
-        &recovery_asset.pubkey(),
-        &recovery_wallet.pubkey(),
-        None,
-        &h.collection,
-        86400,
-        false,
-        None,
-    );
-    assert!(
-        send_tx(&mut svm, &[ix], &[&h.operator, &recovery_asset]).is_err(),
-        "operator should not be able to configure recovery"
-    );
+    let ix = ix_configure_keeper(&h, 50, 1_000_000, 2_000_000_000, 300, mode);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let event = find_event::<KeeperConfigured>(&logs).unwrap();
+    assert_eq!(event.bounty_bps, 50);
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.keeper_bounty_bps, 50);
+    assert_eq!(pos.keeper_bounty_max, 1_000_000);
+    assert_eq!(pos.keeper_min_borrow, 2_000_000_000);
+    assert_eq!(pos.keeper_max_spread_bps, 300);
+    assert_eq!(pos.keeper_mode, mode);
+
+    // Operators cannot change the policy
+    let mut ix = ix_configure_keeper(&h, 0, 0, 0, 0, mode);
+    ix.accounts[0] = AccountMeta::new_readonly(h.operator.pubkey(), true);
+    ix.accounts[1] = AccountMeta::new_readonly(h.operator_asset, false);
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
 }
 
 #[test]
-fn test_execute_recovery_ok() {
+fn test_keeper_reinvest_requires_opt_in_and_mode() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    let stranger = Keypair::new();
+    svm.airdrop(&stranger.pubkey(), 1_000_000_000).unwrap();
+    let mode = ReinvestMode::Fraction { bps: 5_000 };
 
-    let recovery_holder = Keypair::new();
-    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
-    let recovery_asset = Keypair::new();
+    let ix = ix_keeper_reinvest(&stranger.pubkey(), &h, mode);
+    assert!(send_tx(&mut svm, &[ix], &[&stranger]).is_err());
 
-    // Configure recovery with 1-second lockout (minimum for testing)
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset.pubkey(),
-        &recovery_holder.pubkey(),
-        None,
-        &h.collection,
-        1, // 1 second lockout
-        false,
-        None,
-    );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+    let ix = ix_configure_keeper(&h, 50, 1_000_000, 0, 300, mode);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
 
-    // Advance time past the lockout period
-    advance_clock(&mut svm, 100);
+    // Any other mode is refused
+    let ix = ix_keeper_reinvest(&stranger.pubkey(), &h, ReinvestMode::Fraction { bps: 10_000 });
+    assert!(send_tx(&mut svm, &[ix], &[&stranger]).is_err());
 
-    // Execute recovery
-    let new_admin_asset = Keypair::new();
-    let ix = ix_execute_recovery(
-        &recovery_holder.pubkey(),
-        &recovery_asset.pubkey(),
-        &h.position_pda,
-        &h.admin_asset.pubkey(),
-        &new_admin_asset.pubkey(),
-        &h.collection,
-    );
-    send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).unwrap();
+    // Zero capacity: succeeds as a no-op
+    let ix = ix_keeper_reinvest(&stranger.pubkey(), &h, mode);
+    send_tx(&mut svm, &[ix], &[&stranger]).unwrap();
+}
 
-    // Verify position state
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.current_admin_asset, new_admin_asset.pubkey(),
-        "current_admin_asset should be updated to new admin");
-    assert_eq!(pos.authority_seed, h.admin_asset.pubkey(),
-        "authority_seed should remain unchanged");
-    assert_eq!(pos.recovery_asset, Pubkey::default(),
-        "recovery_asset should be cleared after recovery");
-    assert_eq!(pos.recovery_lockout_secs, 0,
-        "recovery_lockout_secs should be cleared");
-    assert!(!pos.recovery_config_locked,
-        "recovery_config_locked should be cleared");
+#[test]
+fn test_keeper_reinvest_below_min_borrow_is_noop() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let stranger = Keypair::new();
+    svm.airdrop(&stranger.pubkey(), 1_000_000_000).unwrap();
+    let mode = ReinvestMode::Fraction { bps: 10_000 };
+    // 6 SOL of capacity, but the owner only pays keepers from 10 SOL up
+    plant_reinvest_capacity(&mut svm, &h.admin_asset.pubkey(), 10_000_000_000, 4_000_000_000);
+    let ix = ix_configure_keeper(&h, 50, 1_000_000, 10_000_000_000, 300, mode);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
 
-    // Verify new admin key NFT has correct attributes
-    let new_admin_account = svm.get_account(&new_admin_asset.pubkey())
-        .expect("new admin asset should exist");
-    let attrs = extract_asset_attributes(&new_admin_account);
-    assert_eq!(find_attribute(&attrs, "permissions"), Some("63"),
-        "new admin key should have full admin permissions");
-    assert_eq!(
-        find_attribute(&attrs, "position"),
-        Some(h.admin_asset.pubkey().to_string().as_str()),
-        "new admin key should be bound to original authority_seed"
-    );
+    let ix = ix_keeper_reinvest(&stranger.pubkey(), &h, mode);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&stranger]).unwrap();
+    assert!(find_event::<Reinvested>(&logs).is_none());
+    assert_eq!(read_position(&svm, &h.position_pda).user_debt, 0);
+}
 
-    // Verify old admin key was burned (MPL-Core may leave a 1-byte stub)
-    let old_admin_after = svm.get_account(&h.admin_asset.pubkey());
-    assert!(
-        old_admin_after.is_none()
-            || old_admin_after.as_ref().unwrap().data.is_empty()
-            || old_admin_after.as_ref().unwrap().data.len() == 1,
-        "old admin asset should be burned"
-    );
+// ===========================================================================
+// Leverage ceiling tests
+// ===========================================================================
 
-    // Verify recovery key was burned
-    let recovery_after = svm.get_account(&recovery_asset.pubkey());
-    assert!(
-        recovery_after.is_none()
-            || recovery_after.as_ref().unwrap().data.is_empty()
-            || recovery_after.as_ref().unwrap().data.len() == 1,
-        "recovery asset should be burned"
-    );
+fn ix_configure_leverage(
+    admin: &Pubkey,
+    key_asset: &Pubkey,
+    position_pda: &Pubkey,
+    max_debt_to_floor_bps: u16,
+) -> Instruction {
+    let (config_pda, _) = config_pda();
+
+    let mut data = sighash("configure_leverage");
+    data.extend_from_slice(&max_debt_to_floor_bps.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*key_asset, false),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(config_pda, false),
+        ],
+    )
 }
 
 #[test]
-fn test_execute_recovery_too_early() {
+fn test_borrow_over_leverage_ceiling_rejected() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    // 10 SOL floor value, 4 SOL debt, 50% ceiling -> at most 1 SOL more
+    plant_reinvest_capacity(&mut svm, &h.admin_asset.pubkey(), 10_000_000_000, 4_000_000_000);
 
-    let recovery_holder = Keypair::new();
-    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
-    let recovery_asset = Keypair::new();
+    let ix = ix_configure_leverage(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, 5_000);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ev: LeverageConfigured = find_event(&logs).expect("LeverageConfigured event not emitted");
+    assert_eq!(ev.max_debt_to_floor_bps, 5_000);
+    assert_eq!(read_position(&svm, &h.position_pda).max_debt_to_floor_bps, 5_000);
 
-    // Configure recovery with a long lockout
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset.pubkey(),
-        &recovery_holder.pubkey(),
-        None,
-        &h.collection,
-        999999, // very long lockout
-        false,
-        None,
+    let ix = ix_borrow(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), 2_000_000_000,
     );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
 
-    // Try to execute recovery immediately — should fail
-    let new_admin_asset = Keypair::new();
-    let ix = ix_execute_recovery(
-        &recovery_holder.pubkey(),
-        &recovery_asset.pubkey(),
-        &h.position_pda,
-        &h.admin_asset.pubkey(),
-        &new_admin_asset.pubkey(),
-        &h.collection,
-    );
-    assert!(
-        send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).is_err(),
-        "execute_recovery should fail before lockout expires"
+    let ix = ix_borrow(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), 1_000_000_000,
     );
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).user_debt, 1_000_000_000);
+}
 
-    // Verify position unchanged
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.current_admin_asset, h.admin_asset.pubkey());
-    assert_eq!(pos.recovery_asset, recovery_asset.pubkey());
+#[test]
+fn test_configure_leverage_invalid_bps_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let ix = ix_configure_leverage(&h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, 10_001);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
 }
 
 #[test]
-fn test_execute_recovery_heartbeat_resets_lockout() {
+fn test_configure_leverage_operator_denied() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    let ix = ix_configure_leverage(&h.operator.pubkey(), &h.operator_asset, &h.position_pda, 5_000);
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+    assert_eq!(read_position(&svm, &h.position_pda).max_debt_to_floor_bps, 0);
+}
 
-    let recovery_holder = Keypair::new();
-    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
-    let recovery_asset = Keypair::new();
+// ===========================================================================
+// Co-signing tests
+// ===========================================================================
 
-    // Configure recovery with 1-second lockout
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset.pubkey(),
-        &recovery_holder.pubkey(),
-        None,
-        &h.collection,
-        1,
-        false,
-        None,
-    );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+fn ix_configure_cosign(
+    admin: &Pubkey,
+    key_asset: &Pubkey,
+    position_pda: &Pubkey,
+    sell_threshold: u64,
+    borrow_threshold: u64,
+    required: u8,
+    approvers: &[Pubkey],
+) -> Instruction {
+    let (config_pda, _) = config_pda();
 
-    // Advance time past lockout
-    advance_clock(&mut svm, 100);
+    let mut data = sighash("configure_cosign");
+    data.extend_from_slice(&sell_threshold.to_le_bytes());
+    data.extend_from_slice(&borrow_threshold.to_le_bytes());
+    data.push(required);
+    data.extend_from_slice(&(approvers.len() as u32).to_le_bytes());
+    for approver in approvers {
+        data.extend_from_slice(approver.as_ref());
+    }
 
-    // Admin sends heartbeat — resets last_admin_activity
-    let hb = ix_heartbeat(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-    );
-    send_tx(&mut svm, &[hb], &[&h.admin]).unwrap();
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*key_asset, false),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(config_pda, false),
+        ],
+    )
+}
 
-    // Now try to execute recovery — should fail because heartbeat reset the timer
-    let new_admin_asset = Keypair::new();
-    let ix = ix_execute_recovery(
-        &recovery_holder.pubkey(),
-        &recovery_asset.pubkey(),
-        &h.position_pda,
-        &h.admin_asset.pubkey(),
-        &new_admin_asset.pubkey(),
-        &h.collection,
+/// Append (wallet, key asset) co-signer pairs as remaining accounts.
+fn with_cosigners(mut ix: Instruction, cosigners: &[(Pubkey, Pubkey)]) -> Instruction {
+    for (wallet, key_asset) in cosigners {
+        ix.accounts.push(AccountMeta::new_readonly(*wallet, true));
+        ix.accounts.push(AccountMeta::new_readonly(*key_asset, false));
+    }
+    ix
+}
+
+/// 2-of-2 policy between the admin and operator keys on withdraws above 100_000 shares
+/// and borrows above 100_000 lamports.
+fn setup_cosign(svm: &mut LiteSVM, h: &TestHarness) {
+    let ix = ix_configure_cosign(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda,
+        100_000, 100_000, 2, &[h.admin_asset.pubkey(), h.operator_asset],
     );
-    assert!(
-        send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).is_err(),
-        "execute_recovery should fail after heartbeat resets lockout"
+    let logs = send_tx_logs(svm, &[ix], &[&h.admin]).unwrap();
+    let ev: CosignConfigured = find_event(&logs).expect("CosignConfigured event not emitted");
+    assert_eq!(ev.required, 2);
+    assert_eq!(ev.approvers, vec![h.admin_asset.pubkey(), h.operator_asset]);
+
+    let buy = ix_buy(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
     );
+    send_tx(svm, &[buy], &[&h.admin]).unwrap();
 }
 
 #[test]
-fn test_execute_recovery_no_recovery_configured() {
+fn test_withdraw_above_cosign_threshold_needs_cosigner() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    setup_cosign(&mut svm, &h);
 
-    // Try to execute recovery without configuring one
-    let recovery_holder = Keypair::new();
-    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
-    let fake_recovery = Pubkey::new_unique();
-    let new_admin_asset = Keypair::new();
+    // Admin alone cannot withdraw above the threshold
+    let ix = ix_withdraw(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), 500_000,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
 
-    let ix = ix_execute_recovery(
-        &recovery_holder.pubkey(),
-        &fake_recovery,
-        &h.position_pda,
-        &h.admin_asset.pubkey(),
-        &new_admin_asset.pubkey(),
-        &h.collection,
+    // At or below the threshold no co-signer is needed
+    let ix = ix_withdraw(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), 100_000,
     );
-    assert!(
-        send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).is_err(),
-        "execute_recovery should fail when no recovery is configured"
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    // With the operator co-signing it goes through
+    let ix = with_cosigners(
+        ix_withdraw(
+            &h.admin.pubkey(), &h.admin_asset.pubkey(),
+            None, &h.position_pda, &h.admin_asset.pubkey(), 500_000,
+        ),
+        &[(h.operator.pubkey(), h.operator_asset)],
     );
+    send_tx(&mut svm, &[ix], &[&h.admin, &h.operator]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, 400_000);
 }
 
 #[test]
-fn test_delegated_keys_survive_recovery() {
+fn test_borrow_cosigner_not_approver_rejected() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    setup_cosign(&mut svm, &h);
 
-    let recovery_holder = Keypair::new();
-    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
-    let recovery_asset = Keypair::new();
-
-    // Configure recovery
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset.pubkey(),
-        &recovery_holder.pubkey(),
-        None,
-        &h.collection,
-        1,
-        false,
-        None,
-    );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
-
-    // Advance time past lockout
-    advance_clock(&mut svm, 100);
-
-    // Execute recovery
-    let new_admin_asset = Keypair::new();
-    let ix = ix_execute_recovery(
-        &recovery_holder.pubkey(),
-        &recovery_asset.pubkey(),
-        &h.position_pda,
-        &h.admin_asset.pubkey(),
-        &new_admin_asset.pubkey(),
-        &h.collection,
+    let ix = with_cosigners(
+        ix_borrow(
+            &h.admin.pubkey(), &h.admin_asset.pubkey(),
+            None, &h.position_pda, &h.admin_asset.pubkey(), 500_000,
+        ),
+        &[(h.depositor.pubkey(), h.depositor_asset)],
     );
-    send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).unwrap();
-
-    // Verify operator key state still exists
-    let op_ks = read_key_state(&svm, &h.operator_key_state);
-    assert_eq!(op_ks.asset, h.operator_asset,
-        "operator key_state should still exist after recovery");
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin, &h.depositor]).is_err());
 
-    // Verify operator can still buy (authority_seed unchanged, key binding intact)
-    let ix = ix_buy(
-        &h.operator.pubkey(),
-        &h.operator_asset,
-        &h.position_pda,
-        &h.admin_asset.pubkey(), // authority_seed is still the original admin asset
-        500_000,
+    // The same approver key cannot be counted twice
+    let ix = with_cosigners(
+        ix_borrow(
+            &h.admin.pubkey(), &h.admin_asset.pubkey(),
+            None, &h.position_pda, &h.admin_asset.pubkey(), 500_000,
+        ),
+        &[(h.admin.pubkey(), h.admin_asset.pubkey())],
     );
-    send_tx(&mut svm, &[ix], &[&h.operator]).unwrap();
-
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.deposited_nav, 500_000, "operator buy should still work after recovery");
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    assert_eq!(read_position(&svm, &h.position_pda).user_debt, 0);
 }
 
 #[test]
-fn test_replace_recovery_key() {
+fn test_split_withdraws_share_cosign_allowance() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
-
-    let recovery_wallet = Keypair::new();
-    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
-
-    // First recovery config
-    let recovery_asset1 = Keypair::new();
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset1.pubkey(),
-        &recovery_wallet.pubkey(),
-        None,
-        &h.collection,
-        86400,
-        false, // don't lock
-        None,
+    setup_cosign(&mut svm, &h);
+    let withdraw = |amount| ix_withdraw(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), amount,
     );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset1]).unwrap();
 
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.recovery_asset, recovery_asset1.pubkey());
+    // Small withdraws add up: the third one would take the day's total past 100_000
+    send_tx(&mut svm, &[withdraw(40_000)], &[&h.admin]).unwrap();
+    send_tx(&mut svm, &[withdraw(40_001)], &[&h.admin]).unwrap();
+    assert!(send_tx(&mut svm, &[withdraw(40_002)], &[&h.admin]).is_err());
+    send_tx(&mut svm, &[withdraw(19_999)], &[&h.admin]).unwrap();
+    let bucket = read_position(&svm, &h.position_pda).cosign_sell_bucket;
+    assert_eq!(bucket.level, 0);
 
-    // Replace with new recovery key (passing old one to burn)
-    let recovery_asset2 = Keypair::new();
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset2.pubkey(),
-        &recovery_wallet.pubkey(),
-        Some(&recovery_asset1.pubkey()),
-        &h.collection,
-        172800, // new lockout
-        false,
-        Some("backup-v2"),
-    );
-    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset2]).unwrap();
+    // Co-signed withdraws don't need the allowance
+    let ix = with_cosigners(withdraw(40_003), &[(h.operator.pubkey(), h.operator_asset)]);
+    send_tx(&mut svm, &[ix], &[&h.admin, &h.operator]).unwrap();
 
-    // Verify replacement
-    let pos = read_position(&svm, &h.position_pda);
-    assert_eq!(pos.recovery_asset, recovery_asset2.pubkey());
-    assert_eq!(pos.recovery_lockout_secs, 172800);
+    // Half a day refills half the allowance
+    advance_clock(&mut svm, 43_200);
+    assert!(send_tx(&mut svm, &[withdraw(50_001)], &[&h.admin]).is_err());
+    send_tx(&mut svm, &[withdraw(50_000)], &[&h.admin]).unwrap();
 
-    // Verify old recovery key was burned (MPL-Core may leave a 1-byte stub)
-    let old_recovery = svm.get_account(&recovery_asset1.pubkey());
-    assert!(
-        old_recovery.is_none()
-            || old_recovery.as_ref().unwrap().data.is_empty()
-            || old_recovery.as_ref().unwrap().data.len() == 1,
-        "old recovery asset should be burned when replaced"
+    // Borrows have their own allowance
+    let borrow = ix_borrow(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), 100_000,
     );
+    send_tx(&mut svm, &[borrow], &[&h.admin]).unwrap();
 }
 
-// ── authorize_key and revoke_key update last_admin_activity ──────────
-
 #[test]
-fn test_authorize_key_updates_last_admin_activity() {
+fn test_set_payout_address_needs_cosigners() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    setup_cosign(&mut svm, &h);
+    let attacker = Pubkey::new_unique();
 
-    let pos_before = read_position(&svm, &h.position_pda);
-
-    // Advance clock so we can detect the timestamp change
-    advance_clock(&mut svm, 100);
-
-    // Authorize a new key
-    let new_asset = Keypair::new();
-    let target = Keypair::new();
-    let ix = ix_authorize_key(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &new_asset.pubkey(),
-        &target.pubkey(),
-        PRESET_DEPOSITOR, // Depositor
-        0, 0, 0, 0, 0, 0,
-        &h.collection,
+    // A single stolen admin key can't redirect payouts
+    let set = || ix_set_payout_address(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, Some(attacker),
     );
-    send_tx(&mut svm, &[ix], &[&h.admin, &new_asset]).unwrap();
+    assert!(send_tx(&mut svm, &[set()], &[&h.admin]).is_err());
 
-    let pos_after = read_position(&svm, &h.position_pda);
-    assert!(
-        pos_after.last_admin_activity > pos_before.last_admin_activity,
-        "authorize_key should update last_admin_activity"
-    );
+    let ix = with_cosigners(set(), &[(h.operator.pubkey(), h.operator_asset)]);
+    send_tx(&mut svm, &[ix], &[&h.admin, &h.operator]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).payout_address, attacker);
 }
 
 #[test]
-fn test_revoke_key_updates_last_admin_activity() {
+fn test_disable_active_cosign_policy_needs_approvals() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
+    setup_cosign(&mut svm, &h);
 
-    let pos_before = read_position(&svm, &h.position_pda);
-
-    // Advance clock so we can detect the timestamp change
-    advance_clock(&mut svm, 100);
-
-    // Revoke the operator key
-    let ix = ix_revoke_key(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.operator_asset,
-        &h.operator_key_state,
-        &h.collection,
+    let disable = || ix_configure_cosign(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, 0, 0, 0, &[],
     );
-    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    assert!(send_tx(&mut svm, &[disable()], &[&h.admin]).is_err());
 
-    let pos_after = read_position(&svm, &h.position_pda);
-    assert!(
-        pos_after.last_admin_activity > pos_before.last_admin_activity,
-        "revoke_key should update last_admin_activity"
-    );
+    let ix = with_cosigners(disable(), &[(h.operator.pubkey(), h.operator_asset)]);
+    send_tx(&mut svm, &[ix], &[&h.admin, &h.operator]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).cosign_required, 0);
 }
 
-// ── configure_recovery lockout upper bound ──────────────────────────
-
 #[test]
-fn test_configure_recovery_lockout_too_large() {
+fn test_configure_cosign_invalid_policy_rejected() {
     let (mut svm, _) = setup();
     let h = full_setup(&mut svm);
-    let recovery_wallet = Keypair::new();
-    let recovery_asset = Keypair::new();
-
-    // Attempt to configure with i64::MAX lockout (exceeds 10-year cap)
-    let ix = ix_configure_recovery(
-        &h.admin.pubkey(),
-        &h.admin_asset.pubkey(),
-        &h.position_pda,
-        &recovery_asset.pubkey(),
-        &recovery_wallet.pubkey(),
-        None,
-        &h.collection,
-        i64::MAX, // exceeds 10-year max
-        false,
-        Some("test"),
-    );
-    let result = send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]);
-    assert!(result.is_err(), "lockout exceeding 10 years should be rejected");
+    let a = h.admin_asset.pubkey();
+    for (required, approvers) in [
+        (3u8, vec![a, h.operator_asset]),  // more approvals than approvers
+        (1, vec![a, a]),                   // duplicate approver
+        (1, vec![Pubkey::default()]),      // empty slot
+        (1, Vec::new()),                   // no approvers
+        (0, vec![a]),                      // disabled but approvers left set
+    ] {
+        let ix = ix_configure_cosign(
+            &h.admin.pubkey(), &a, &h.position_pda, 0, 0, required, &approvers,
+        );
+        assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    }
 }
 
 // ===========================================================================
-// Promo tests
+// Sync position tests
 // ===========================================================================
 
-// ---------------------------------------------------------------------------
-// Promo instruction builders
-// ---------------------------------------------------------------------------
-
-fn promo_pda(authority_seed: &Pubkey, name_suffix: &str) -> (Pubkey, u8) {
-    Pubkey::find_program_address(
-        &[PromoConfig::SEED, authority_seed.as_ref(), name_suffix.as_bytes()],
-        &program_id(),
+fn ix_sync_position(caller: &Pubkey, position_pda: &Pubkey, personal_position: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("sync_position"),
+        vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(*personal_position, false),
+        ],
     )
 }
 
-fn claim_receipt_pda(promo: &Pubkey, claimer: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(
-        &[ClaimReceipt::SEED, promo.as_ref(), claimer.as_ref()],
-        &program_id(),
-    )
+#[test]
+fn test_sync_position_overwrites_local_accounting() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let buy = ix_buy(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
+    );
+    send_tx(&mut svm, &[buy], &[&h.admin]).unwrap();
+    let before = read_position(&svm, &h.position_pda);
+
+    // Simulate drift: Mayflower now reports fewer shares and more debt
+    let shares = before.deposited_nav - 10_000;
+    let debt = before.user_debt + 25_000;
+    plant_reinvest_capacity(&mut svm, &h.admin_asset.pubkey(), shares, debt);
+
+    // Permissionless: an outsider can sync
+    let (_, pp_pda, _, _, _, _) = mayflower_addrs(&h.admin_asset.pubkey());
+    let ix = ix_sync_position(&h.outsider.pubkey(), &h.position_pda, &pp_pda);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.outsider]).unwrap();
+
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.deposited_nav, shares);
+    assert_eq!(pos.user_debt, debt);
+
+    let ev: PositionSynced = find_event(&logs).expect("PositionSynced event not emitted");
+    assert_eq!(ev.caller, h.outsider.pubkey());
+    assert_eq!(ev.nav_delta, -10_000);
+    assert_eq!(ev.debt_delta, 25_000);
 }
 
-fn read_promo_config(svm: &LiteSVM, pda: &Pubkey) -> PromoConfig {
-    let account = svm.get_account(pda).unwrap();
-    PromoConfig::try_deserialize(&mut account.data.as_slice()).unwrap()
+#[test]
+fn test_sync_position_saturates_deltas() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let before = read_position(&svm, &h.position_pda);
+
+    // Mayflower reports more shares than fit in an i64 delta
+    plant_reinvest_capacity(&mut svm, &h.admin_asset.pubkey(), u64::MAX, before.user_debt);
+    let (_, pp_pda, _, _, _, _) = mayflower_addrs(&h.admin_asset.pubkey());
+    let ix = ix_sync_position(&h.outsider.pubkey(), &h.position_pda, &pp_pda);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.outsider]).unwrap();
+    let ev: PositionSynced = find_event(&logs).expect("PositionSynced event not emitted");
+    assert_eq!(ev.nav_delta, i64::MAX);
+    assert_eq!(ev.debt_delta, 0);
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, u64::MAX);
+
+    // ...and back down again
+    plant_reinvest_capacity(&mut svm, &h.admin_asset.pubkey(), 0, before.user_debt);
+    svm.expire_blockhash();
+    let ix = ix_sync_position(&h.outsider.pubkey(), &h.position_pda, &pp_pda);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.outsider]).unwrap();
+    let ev: PositionSynced = find_event(&logs).expect("PositionSynced event not emitted");
+    assert_eq!(ev.nav_delta, i64::MIN);
 }
 
-fn read_claim_receipt(svm: &LiteSVM, pda: &Pubkey) -> ClaimReceipt {
-    let account = svm.get_account(pda).unwrap();
-    ClaimReceipt::try_deserialize(&mut account.data.as_slice()).unwrap()
+#[test]
+fn test_sync_position_wrong_personal_position_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let (_, _, escrow_pda, _, _, _) = mayflower_addrs(&h.admin_asset.pubkey());
+    let ix = ix_sync_position(&h.outsider.pubkey(), &h.position_pda, &escrow_pda);
+    assert!(send_tx(&mut svm, &[ix], &[&h.outsider]).is_err());
 }
 
-fn ix_create_promo(
+// ===========================================================================
+// Withdrawal queue tests
+// ===========================================================================
+
+fn withdraw_request_pda(position_pda: &Pubkey, request_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[WithdrawRequest::SEED, position_pda.as_ref(), &request_id.to_le_bytes()],
+        &program_id(),
+    )
+    .0
+}
+
+fn ix_configure_withdraw_queue(
     admin: &Pubkey,
-    admin_asset: &Pubkey,
-    name_suffix: &str,
-    permissions: u8,
-    borrow_capacity: u64,
-    borrow_refill_period: u64,
-    sell_capacity: u64,
-    sell_refill_period: u64,
-    total_borrow_limit: u64,
-    total_sell_limit: u64,
-    min_deposit_lamports: u64,
-    max_claims: u32,
-    initial_fill_bps: u16,
-    image_uri: &str,
-    market_name: &str,
+    key_asset: &Pubkey,
+    position_pda: &Pubkey,
+    delay_secs: i64,
+    threshold: u64,
 ) -> Instruction {
-    let (pos_pda, _) = position_pda(admin_asset);
-    let position = read_position_seed(admin_asset);
-    let (promo, _) = promo_pda(&position, name_suffix);
-
-    // Anchor discriminator + Borsh-serialized args
-    // name_suffix is the first arg (#[instruction(name_suffix: String)])
-    let mut data = sighash("create_promo");
-    // String: 4-byte LE length + bytes
-    data.extend_from_slice(&(name_suffix.len() as u32).to_le_bytes());
-    data.extend_from_slice(name_suffix.as_bytes());
-    data.push(permissions);
-    data.extend_from_slice(&borrow_capacity.to_le_bytes());
-    data.extend_from_slice(&borrow_refill_period.to_le_bytes());
-    data.extend_from_slice(&sell_capacity.to_le_bytes());
-    data.extend_from_slice(&sell_refill_period.to_le_bytes());
-    data.extend_from_slice(&total_borrow_limit.to_le_bytes());
-    data.extend_from_slice(&total_sell_limit.to_le_bytes());
-    data.extend_from_slice(&min_deposit_lamports.to_le_bytes());
-    data.extend_from_slice(&max_claims.to_le_bytes());
-    data.extend_from_slice(&initial_fill_bps.to_le_bytes());
-    // image_uri: String
-    data.extend_from_slice(&(image_uri.len() as u32).to_le_bytes());
-    data.extend_from_slice(image_uri.as_bytes());
-    // market_name: String
-    data.extend_from_slice(&(market_name.len() as u32).to_le_bytes());
-    data.extend_from_slice(market_name.as_bytes());
+    let mut data = sighash("configure_withdraw_queue");
+    data.extend_from_slice(&delay_secs.to_le_bytes());
+    data.extend_from_slice(&threshold.to_le_bytes());
 
     Instruction::new_with_bytes(
         program_id(),
         &data,
         vec![
-            AccountMeta::new(*admin, true),                                    // admin
-            AccountMeta::new_readonly(*admin_asset, false),                    // admin_key_asset
-            AccountMeta::new_readonly(pos_pda, false),                         // position
-            AccountMeta::new(promo, false),                                    // promo (init)
-            AccountMeta::new_readonly(config_pda().0, false),                  // config
-            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),  // system_program
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*key_asset, false),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(config_pda().0, false),
         ],
     )
 }
 
-/// Helper: get the authority_seed from a position created with the given admin_asset.
-/// This is just the admin_asset pubkey (authority_seed = first admin asset pubkey).
-fn read_position_seed(admin_asset: &Pubkey) -> Pubkey {
-    // authority_seed is always the admin_asset pubkey from create_position
-    *admin_asset
+fn ix_request_withdraw(
+    signer: &Pubkey,
+    key_asset: &Pubkey,
+    key_state: Option<&Pubkey>,
+    position_pda: &Pubkey,
+    request_id: u64,
+    amount: u64,
+) -> Instruction {
+    let mut data = sighash("request_withdraw");
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // min_out = 0 (no slippage check)
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*signer, true),
+            AccountMeta::new_readonly(*key_asset, false),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(config_pda().0, false),
+            AccountMeta::new(withdraw_request_pda(position_pda, request_id), false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new_readonly(*key_state.unwrap_or(&program_id()), false),
+        ],
+    )
 }
 
-fn ix_update_promo(
-    admin: &Pubkey,
+/// `withdraw`'s accounts followed by the request and its requester.
+fn ix_execute_withdraw(
+    signer: &Pubkey,
+    key_asset: &Pubkey,
+    position_pda: &Pubkey,
     admin_asset: &Pubkey,
-    promo_pda_key: &Pubkey,
-    active: Option<bool>,
-    max_claims: Option<u32>,
+    request_id: u64,
+    requester: &Pubkey,
 ) -> Instruction {
-    let (pos_pda, _) = position_pda(admin_asset);
+    let mut ix = ix_withdraw(signer, key_asset, None, position_pda, admin_asset, 0);
+    ix.data = sighash("execute_withdraw");
+    ix.accounts.push(AccountMeta::new(withdraw_request_pda(position_pda, request_id), false));
+    ix.accounts.push(AccountMeta::new(*requester, false));
+    ix
+}
 
-    let mut data = sighash("update_promo");
-    // Option<bool>
-    match active {
-        Some(v) => {
-            data.push(1); // Some
-            data.push(if v { 1 } else { 0 });
-        }
-        None => data.push(0), // None
-    }
-    // Option<u32>
-    match max_claims {
-        Some(v) => {
-            data.push(1); // Some
-            data.extend_from_slice(&v.to_le_bytes());
-        }
-        None => data.push(0), // None
+fn ix_cancel_withdraw(
+    signer: &Pubkey,
+    key_asset: &Pubkey,
+    position_pda: &Pubkey,
+    request_id: u64,
+    requester: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("cancel_withdraw"),
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*key_asset, false),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(config_pda().0, false),
+            AccountMeta::new(withdraw_request_pda(position_pda, request_id), false),
+            AccountMeta::new(*requester, false),
+        ],
+    )
+}
+
+/// 1 hour queue for withdraws above 100_000 shares, with 1_000_000 shares deposited.
+fn setup_withdraw_queue(svm: &mut LiteSVM, h: &TestHarness) {
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+    send_tx(
+        svm,
+        &[
+            ix_configure_withdraw_queue(&admin, &admin_asset, &h.position_pda, 3600, 100_000),
+            ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000_000),
+        ],
+        &[&h.admin],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_queued_withdraw_executes_after_delay() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    setup_withdraw_queue(&mut svm, &h);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+
+    // Direct withdraw above the threshold is refused; at the threshold it goes through
+    let ix = ix_withdraw(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 500_000);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    let ix = ix_withdraw(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 100_000);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    let ix = ix_request_withdraw(&admin, &admin_asset, None, &h.position_pda, 0, 500_000);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ev: WithdrawRequested = find_event(&logs).expect("WithdrawRequested event not emitted");
+    assert_eq!(ev.amount, 500_000);
+    assert_eq!(read_position(&svm, &h.position_pda).withdraw_request_count, 1);
+
+    // Too early
+    let ix = ix_execute_withdraw(&admin, &admin_asset, &h.position_pda, &admin_asset, 0, &admin);
+    assert!(send_tx(&mut svm, std::slice::from_ref(&ix), &[&h.admin]).is_err());
+
+    advance_clock(&mut svm, 3600);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ev: Withdrawn = find_event(&logs).expect("Withdrawn event not emitted");
+    assert_eq!(ev.shares_sold, 500_000);
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, 400_000);
+    let request = svm.get_account(&withdraw_request_pda(&h.position_pda, 0));
+    assert!(request.is_none_or(|a| a.lamports == 0), "request should be closed");
+}
+
+#[test]
+fn test_guardian_cancels_queued_withdraw() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    setup_withdraw_queue(&mut svm, &h);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+
+    let guardian_key = Keypair::new();
+    send_tx(
+        &mut svm,
+        &[ix_authorize_key(
+            &admin, &admin_asset, &h.position_pda,
+            &guardian_key.pubkey(), &h.outsider.pubkey(), PERM_GUARDIAN,
+            0, 0, 0, 0, 0, 0, &h.collection,
+        )],
+        &[&h.admin, &guardian_key],
+    )
+    .unwrap();
+
+    let ix = ix_request_withdraw(&admin, &admin_asset, None, &h.position_pda, 0, 500_000);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    // A key without the guardian bit cannot cancel
+    let ix = ix_cancel_withdraw(&h.operator.pubkey(), &h.operator_asset, &h.position_pda, 0, &admin);
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+
+    let ix = ix_cancel_withdraw(&h.outsider.pubkey(), &guardian_key.pubkey(), &h.position_pda, 0, &admin);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.outsider]).unwrap();
+    let ev: WithdrawCancelled = find_event(&logs).expect("WithdrawCancelled event not emitted");
+    assert_eq!(ev.cancelled_by, guardian_key.pubkey());
+    assert_eq!(ev.amount, 500_000);
+
+    advance_clock(&mut svm, 3600);
+    let ix = ix_execute_withdraw(&admin, &admin_asset, &h.position_pda, &admin_asset, 0, &admin);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, 1_000_000);
+}
+
+#[test]
+fn test_queued_withdraw_only_requesting_key_can_execute() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    setup_withdraw_queue(&mut svm, &h);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+
+    let ix = ix_request_withdraw(&admin, &admin_asset, None, &h.position_pda, 0, 500_000);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    advance_clock(&mut svm, 3600);
+
+    let ix = ix_execute_withdraw(
+        &h.operator.pubkey(), &h.operator_asset, &h.position_pda, &admin_asset, 0, &admin,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+}
+
+#[test]
+fn test_split_withdraws_share_queue_allowance() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    setup_withdraw_queue(&mut svm, &h);
+    let withdraw = |amount| ix_withdraw(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), amount,
+    );
+
+    // Direct withdraws add up: the third one would take the hour's total past 100_000
+    send_tx(&mut svm, &[withdraw(60_000)], &[&h.admin]).unwrap();
+    send_tx(&mut svm, &[withdraw(40_000)], &[&h.admin]).unwrap();
+    assert!(send_tx(&mut svm, &[withdraw(1)], &[&h.admin]).is_err());
+    assert_eq!(read_position(&svm, &h.position_pda).withdraw_queue_bucket.level, 0);
+
+    // Half the delay refills half the allowance
+    advance_clock(&mut svm, 1800);
+    assert!(send_tx(&mut svm, &[withdraw(50_001)], &[&h.admin]).is_err());
+    send_tx(&mut svm, &[withdraw(50_000)], &[&h.admin]).unwrap();
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, 850_000);
+}
+
+#[test]
+fn test_configure_withdraw_queue_invalid_delay_rejected() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    for delay in [-1, 2_592_001] {
+        let ix = ix_configure_withdraw_queue(
+            &h.admin.pubkey(), &h.admin_asset.pubkey(), &h.position_pda, delay, 0,
+        );
+        assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
     }
+}
+
+// ===========================================================================
+// Pause tests
+// ===========================================================================
+
+fn ix_set_protocol_paused(admin: &Pubkey, paused: bool) -> Instruction {
+    let mut data = sighash("set_protocol_paused");
+    data.push(paused as u8);
 
     Instruction::new_with_bytes(
         program_id(),
         &data,
         vec![
-            AccountMeta::new(*admin, true),                   // admin
-            AccountMeta::new_readonly(*admin_asset, false),    // admin_key_asset
-            AccountMeta::new_readonly(pos_pda, false),         // position
-            AccountMeta::new(*promo_pda_key, false),           // promo (mut)
-            AccountMeta::new_readonly(config_pda().0, false),  // config
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda().0, false),
         ],
     )
 }
 
-fn ix_claim_promo_key(
-    claimer: &Pubkey,
-    promo_pda_key: &Pubkey,
-    admin_asset: &Pubkey,
-    key_asset: &Pubkey,
-    collection: &Pubkey,
-    amount: u64,
-) -> Instruction {
-    let (pos_pda, _) = position_pda(admin_asset);
-    let (claim_receipt, _) = claim_receipt_pda(promo_pda_key, claimer);
-    let (ks_pda, _) = key_state_pda(key_asset);
-    let (cfg_pda, _) = config_pda();
-    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
-    let (program_pda, pp_pda, escrow_pda, log_pda, wsol_ata, nav_sol_ata) = mayflower_addrs(admin_asset);
-
-    let mut data = sighash("claim_promo_key");
-    data.extend_from_slice(&amount.to_le_bytes());
+fn ix_set_market_paused(admin: &Pubkey, paused: bool) -> Instruction {
+    let mut data = sighash("set_market_paused");
+    data.push(paused as u8);
 
     Instruction::new_with_bytes(
         program_id(),
         &data,
         vec![
-            AccountMeta::new(*claimer, true),                                  // claimer
-            AccountMeta::new(*promo_pda_key, false),                           // promo (mut)
-            AccountMeta::new(claim_receipt, false),                            // claim_receipt (init)
-            AccountMeta::new(pos_pda, false),                                  // position (mut)
-            AccountMeta::new(*key_asset, true),                                // key_asset (signer)
-            AccountMeta::new(ks_pda, false),                                   // key_state (init)
-            AccountMeta::new_readonly(cfg_pda, false),                         // config
-            AccountMeta::new(*collection, false),                              // collection
-            AccountMeta::new_readonly(MPL_CORE_ID, false),                     // mpl_core_program
-            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),  // system_program
-            AccountMeta::new_readonly(mc_pda, false),                          // market_config
-            AccountMeta::new(program_pda, false),                              // program_pda
-            AccountMeta::new(pp_pda, false),                                   // personal_position
-            AccountMeta::new(escrow_pda, false),                               // user_shares
-            AccountMeta::new(nav_sol_ata, false),                              // user_nav_sol_ata
-            AccountMeta::new(wsol_ata, false),                                 // user_wsol_ata
-            AccountMeta::new_readonly(MAYFLOWER_TENANT, false),                // tenant
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config_pda().0, false),
+            AccountMeta::new(market_config_pda(&DEFAULT_NAV_SOL_MINT).0, false),
+        ],
+    )
+}
+
+fn ix_migrate_config(admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("migrate_config"),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config_pda().0, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
+fn ix_migrate_market_config(admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("migrate_market_config"),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(config_pda().0, false),
+            AccountMeta::new(market_config_pda(&DEFAULT_NAV_SOL_MINT).0, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
+/// Cut an account's data back to `len` bytes to stand in for an old layout.
+fn truncate_account(svm: &mut LiteSVM, pda: &Pubkey, len: usize) {
+    let mut account = svm.get_account(pda).unwrap();
+    account.data.truncate(len);
+    svm.set_account(*pda, account).unwrap();
+}
+
+#[test]
+fn test_protocol_pause_blocks_risk_increasing_actions() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+    send_tx(
+        &mut svm,
+        &[
+            ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000_000),
+            ix_borrow(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 100_000),
+        ],
+        &[&h.admin],
+    )
+    .unwrap();
+
+    // Only the protocol admin can pause
+    assert!(send_tx(&mut svm, &[ix_set_protocol_paused(&h.operator.pubkey(), true)], &[&h.operator]).is_err());
+    send_tx(&mut svm, &[ix_set_protocol_paused(&admin, true)], &[&h.admin]).unwrap();
+    assert!(read_protocol_config(&svm).paused);
+
+    for ix in [
+        ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000),
+        ix_withdraw(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 1_000),
+        ix_borrow(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 1_000),
+        ix_reinvest(&admin, &admin_asset, &h.position_pda, &admin_asset, 10_000),
+    ] {
+        assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    }
+
+    // Repay and heartbeat stay available
+    send_tx(&mut svm, &[ix_repay(&admin, &admin_asset, &h.position_pda, &admin_asset, 50_000)], &[&h.admin]).unwrap();
+    send_tx(&mut svm, &[ix_heartbeat(&admin, &admin_asset, &h.position_pda)], &[&h.admin]).unwrap();
+
+    send_tx(&mut svm, &[ix_set_protocol_paused(&admin, false)], &[&h.admin]).unwrap();
+    send_tx(&mut svm, &[ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000)], &[&h.admin]).unwrap();
+}
+
+#[test]
+fn test_market_pause_blocks_market() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+
+    assert!(send_tx(&mut svm, &[ix_set_market_paused(&h.operator.pubkey(), true)], &[&h.operator]).is_err());
+    send_tx(&mut svm, &[ix_set_market_paused(&admin, true)], &[&h.admin]).unwrap();
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    assert!(read_market_config(&svm, &mc_pda).paused);
+    assert!(!read_protocol_config(&svm).paused);
+
+    let ix = ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+
+    send_tx(&mut svm, &[ix_set_market_paused(&admin, false)], &[&h.admin]).unwrap();
+    let ix = ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+}
+
+#[test]
+fn test_migrate_configs_add_paused_flag() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+    let pending = Pubkey::new_unique();
+    send_tx(&mut svm, &[ix_transfer_admin(&admin.pubkey(), &pending)], &[&admin]).unwrap();
+
+    // Already current: nothing to migrate
+    assert!(send_tx(&mut svm, &[ix_migrate_config(&admin.pubkey())], &[&admin]).is_err());
+    assert!(send_tx(&mut svm, &[ix_migrate_market_config(&admin.pubkey())], &[&admin]).is_err());
+
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    truncate_account(&mut svm, &config_pda().0, 105);
+    truncate_account(&mut svm, &mc_pda, 265);
+
+    send_tx(&mut svm, &[ix_migrate_config(&admin.pubkey())], &[&admin]).unwrap();
+    send_tx(&mut svm, &[ix_migrate_market_config(&admin.pubkey())], &[&admin]).unwrap();
+
+    let config = read_protocol_config(&svm);
+    assert_eq!(config.admin, admin.pubkey());
+    assert_eq!(config.pending_admin, pending);
+    assert!(!config.paused);
+    let account = svm.get_account(&mc_pda).unwrap();
+    assert_eq!(account.data.len(), MarketConfig::SIZE);
+    assert!(account.lamports >= svm.minimum_balance_for_rent_exemption(MarketConfig::SIZE));
+    let mc = read_market_config(&svm, &mc_pda);
+    assert_eq!(mc.nav_mint, DEFAULT_NAV_SOL_MINT);
+    assert!(!mc.paused);
+    assert!(!mc.retired);
+    assert_eq!(mc.pending_update_at, 0);
+}
+
+// ===========================================================================
+// Market lifecycle tests
+// ===========================================================================
+
+fn ix_update_market_config(admin: &Pubkey, update: &MarketUpdate, delay_secs: i64) -> Instruction {
+    let mut data = sighash("update_market_config");
+    update.serialize(&mut data).unwrap();
+    data.extend_from_slice(&delay_secs.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config_pda().0, false),
+            AccountMeta::new(market_config_pda(&DEFAULT_NAV_SOL_MINT).0, false),
+            AccountMeta::new_readonly(DEFAULT_MARKET_META, false),
+            AccountMeta::new_readonly(update.mayflower_market, false),
+        ],
+    )
+}
+
+fn ix_apply_market_config_update(caller: &Pubkey, mayflower_market: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("apply_market_config_update"),
+        vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(market_config_pda(&DEFAULT_NAV_SOL_MINT).0, false),
+            AccountMeta::new_readonly(DEFAULT_MARKET_META, false),
+            AccountMeta::new_readonly(*mayflower_market, false),
+        ],
+    )
+}
+
+fn ix_retire_market_config(admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("retire_market_config"),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config_pda().0, false),
+            AccountMeta::new(market_config_pda(&DEFAULT_NAV_SOL_MINT).0, false),
+        ],
+    )
+}
+
+/// Point the default MarketMeta at `update`'s accounts, as a Mayflower migration would.
+fn migrate_market_meta(svm: &mut LiteSVM, update: &MarketUpdate) {
+    plant_market_meta_account(
+        svm,
+        &mayflower::MarketAddresses {
+            market_group: update.market_group,
+            mayflower_market: update.mayflower_market,
+            market_base_vault: update.market_base_vault,
+            market_nav_vault: update.market_nav_vault,
+            fee_vault: update.fee_vault,
+            ..default_market_addresses()
+        },
+    );
+}
+
+/// The default market's accounts with a migrated fee vault.
+fn moved_fee_vault_update() -> MarketUpdate {
+    MarketUpdate {
+        market_group: DEFAULT_MARKET_GROUP,
+        mayflower_market: DEFAULT_MAYFLOWER_MARKET,
+        market_base_vault: DEFAULT_MARKET_BASE_VAULT,
+        market_nav_vault: DEFAULT_MARKET_NAV_VAULT,
+        fee_vault: Pubkey::new_unique(),
+    }
+}
+
+#[test]
+fn test_update_market_config_immediate() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    let update = moved_fee_vault_update();
+
+    let non_admin = Keypair::new();
+    svm.airdrop(&non_admin.pubkey(), 1_000_000_000).unwrap();
+    let ix = ix_update_market_config(&non_admin.pubkey(), &update, 0);
+    assert!(send_tx(&mut svm, &[ix], &[&non_admin]).is_err());
+
+    // Rejected until Mayflower's MarketMeta references the new fee vault
+    let ix = ix_update_market_config(&admin.pubkey(), &update, 0);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+    migrate_market_meta(&mut svm, &update);
+
+    svm.expire_blockhash();
+    let ix = ix_update_market_config(&admin.pubkey(), &update, 0);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    let ev: MarketConfigUpdateApplied = find_event(&logs).expect("MarketConfigUpdateApplied event not emitted");
+    assert_eq!(ev.market_config, mc_pda);
+    assert_eq!(ev.update.fee_vault, update.fee_vault);
+    assert!(find_event::<MarketConfigUpdateQueued>(&logs).is_none());
+    let mc = read_market_config(&svm, &mc_pda);
+    assert_eq!(mc.fee_vault, update.fee_vault);
+    assert_eq!(mc.market_meta, DEFAULT_MARKET_META);
+    assert_eq!(mc.pending_update_at, 0);
+}
+
+#[test]
+fn test_update_market_config_timelocked() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    let update = moved_fee_vault_update();
+
+    // Nothing staged yet
+    let ix = ix_apply_market_config_update(&admin.pubkey(), &DEFAULT_MAYFLOWER_MARKET);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+
+    let ix = ix_update_market_config(&admin.pubkey(), &update, 86_400);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    let mc = read_market_config(&svm, &mc_pda);
+    assert_eq!(mc.fee_vault, DEFAULT_FEE_VAULT);
+    assert_eq!(mc.pending_update.fee_vault, update.fee_vault);
+    let ev: MarketConfigUpdateQueued = find_event(&logs).expect("MarketConfigUpdateQueued event not emitted");
+    assert_eq!(ev.market_config, mc_pda);
+    assert_eq!(ev.update.fee_vault, update.fee_vault);
+    assert_eq!(ev.applies_at, mc.pending_update_at);
+    assert!(find_event::<MarketConfigUpdateApplied>(&logs).is_none());
+
+    // Anyone can apply, but only after the notice period
+    let cranker = Keypair::new();
+    svm.airdrop(&cranker.pubkey(), 1_000_000_000).unwrap();
+    let ix = ix_apply_market_config_update(&cranker.pubkey(), &update.mayflower_market);
+    assert!(send_tx(&mut svm, std::slice::from_ref(&ix), &[&cranker]).is_err());
+
+    // Due, but Mayflower has not migrated yet
+    advance_clock(&mut svm, 86_400);
+    assert!(send_tx(&mut svm, std::slice::from_ref(&ix), &[&cranker]).is_err());
+
+    migrate_market_meta(&mut svm, &update);
+    svm.expire_blockhash();
+    let logs = send_tx_logs(&mut svm, &[ix], &[&cranker]).unwrap();
+    let ev: MarketConfigUpdateApplied = find_event(&logs).expect("MarketConfigUpdateApplied event not emitted");
+    assert_eq!(ev.update.fee_vault, update.fee_vault);
+    let mc = read_market_config(&svm, &mc_pda);
+    assert_eq!(mc.fee_vault, update.fee_vault);
+    assert_eq!(mc.pending_update_at, 0);
+}
+
+#[test]
+fn test_retired_market_blocks_new_positions_but_not_exits() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+    send_tx(
+        &mut svm,
+        &[
+            ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000_000),
+            ix_borrow(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 100_000),
+        ],
+        &[&h.admin],
+    )
+    .unwrap();
+
+    let logs = send_tx_logs(&mut svm, &[ix_retire_market_config(&admin)], &[&h.admin]).unwrap();
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    let ev: MarketConfigRetired = find_event(&logs).expect("MarketConfigRetired event not emitted");
+    assert_eq!(ev.market_config, mc_pda);
+    assert_eq!(ev.nav_mint, DEFAULT_NAV_SOL_MINT);
+    assert!(read_market_config(&svm, &mc_pda).retired);
+    // Retiring is one-way
+    assert!(send_tx(&mut svm, &[ix_retire_market_config(&admin)], &[&h.admin]).is_err());
+
+    let new_asset = Keypair::new();
+    plant_position_stubs(&mut svm, &new_asset.pubkey());
+    let ix = ix_create_position(&admin, &new_asset.pubkey(), &h.collection);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin, &new_asset]).is_err());
+
+    let ix = ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    let ix = ix_borrow(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 1_000);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+
+    send_tx(&mut svm, &[ix_repay(&admin, &admin_asset, &h.position_pda, &admin_asset, 100_000)], &[&h.admin]).unwrap();
+    let ix = ix_withdraw(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 500_000);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+}
+
+#[test]
+fn test_create_market_config_rejects_inconsistent_accounts() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+
+    // A typo in the fee vault no longer matches the MarketMeta
+    let mut ix = ix_create_market_config(&admin.pubkey());
+    let fee_vault_arg = ix.data.len() - 32;
+    ix.data[fee_vault_arg..].copy_from_slice(Pubkey::new_unique().as_ref());
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+
+    // A market that points at a different MarketMeta is rejected too
+    let mut market = svm.get_account(&DEFAULT_MAYFLOWER_MARKET).unwrap();
+    let saved = market.clone();
+    market.data[MARKET_META_REF_OFFSET..MARKET_META_REF_OFFSET + 32]
+        .copy_from_slice(Pubkey::new_unique().as_ref());
+    svm.set_account(DEFAULT_MAYFLOWER_MARKET, market).unwrap();
+    assert!(send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).is_err());
+
+    svm.set_account(DEFAULT_MAYFLOWER_MARKET, saved).unwrap();
+    svm.expire_blockhash();
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+}
+
+// ===========================================================================
+// Protocol fee tests
+// ===========================================================================
+
+fn ix_set_protocol_fee(admin: &Pubkey, fee_bps: u16, treasury: &Pubkey) -> Instruction {
+    let mut data = sighash("set_protocol_fee");
+    data.extend_from_slice(&fee_bps.to_le_bytes());
+    data.extend_from_slice(treasury.as_ref());
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda().0, false),
+        ],
+    )
+}
+
+#[test]
+fn test_set_protocol_fee() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    let config = read_protocol_config(&svm);
+    assert_eq!(config.fee_bps, 0);
+    assert_eq!(config.treasury, Pubkey::default());
+
+    let treasury = Pubkey::new_unique();
+    let non_admin = Keypair::new();
+    svm.airdrop(&non_admin.pubkey(), 1_000_000_000).unwrap();
+    let ix = ix_set_protocol_fee(&non_admin.pubkey(), 100, &treasury);
+    assert!(send_tx(&mut svm, &[ix], &[&non_admin]).is_err());
+
+    // Above the hard cap
+    let ix = ix_set_protocol_fee(&admin.pubkey(), 1_001, &treasury);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+    // A fee needs somewhere to go
+    let ix = ix_set_protocol_fee(&admin.pubkey(), 100, &Pubkey::default());
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+
+    send_tx(&mut svm, &[ix_set_protocol_fee(&admin.pubkey(), 1_000, &treasury)], &[&admin]).unwrap();
+    let config = read_protocol_config(&svm);
+    assert_eq!(config.fee_bps, 1_000);
+    assert_eq!(config.treasury, treasury);
+
+    // Turning the fee off needs no treasury
+    let ix = ix_set_protocol_fee(&admin.pubkey(), 0, &Pubkey::default());
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    assert_eq!(read_protocol_config(&svm).fee_bps, 0);
+}
+
+#[test]
+fn test_migrate_config_adds_fee() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    let before = read_protocol_config(&svm);
+
+    // Back to the released layout: [..][pending_admin][bump]
+    truncate_account(&mut svm, &config_pda().0, 105);
+    send_tx(&mut svm, &[ix_migrate_config(&admin.pubkey())], &[&admin]).unwrap();
+
+    let config = read_protocol_config(&svm);
+    assert_eq!(config.collection, before.collection);
+    assert_eq!(config.bump, before.bump);
+    assert!(!config.paused);
+    assert_eq!(config.fee_bps, 0);
+    assert_eq!(config.treasury, Pubkey::default());
+
+    // Only released layouts migrate
+    truncate_account(&mut svm, &config_pda().0, 106);
+    svm.expire_blockhash();
+    assert!(send_tx(&mut svm, &[ix_migrate_config(&admin.pubkey())], &[&admin]).is_err());
+}
+
+#[test]
+fn test_reinvest_creates_treasury_ata() {
+    let (mut svm, admin) = setup();
+    let h = full_setup(&mut svm);
+    let treasury = Pubkey::new_unique();
+    let treasury_ata = get_ata(&treasury, &DEFAULT_WSOL_MINT);
+    send_tx(&mut svm, &[ix_set_protocol_fee(&admin.pubkey(), 1_000, &treasury)], &[&admin]).unwrap();
+
+    // 10 SOL floor value, 4 SOL debt -> 6 SOL capacity; 10% of it is 0.6 SOL. The mock
+    // borrow doesn't move tokens, so the wSOL ATA is planted with the borrowed amount.
+    plant_reinvest_capacity(&mut svm, &h.admin_asset.pubkey(), 10_000_000_000, 4_000_000_000);
+    let mut mint = svm.get_account(&DEFAULT_WSOL_MINT).unwrap();
+    mint.data[44] = 9; // decimals
+    mint.data[45] = 1; // is_initialized
+    svm.set_account(DEFAULT_WSOL_MINT, mint).unwrap();
+    let (program_pda, .., wsol_ata, _) = mayflower_addrs(&h.admin_asset.pubkey());
+    let mut ata = svm.get_account(&wsol_ata).unwrap();
+    ata.data[..32].copy_from_slice(DEFAULT_WSOL_MINT.as_ref());
+    ata.data[32..64].copy_from_slice(program_pda.as_ref());
+    ata.data[64..72].copy_from_slice(&600_000_000u64.to_le_bytes());
+    ata.data[108] = 1; // AccountState::Initialized
+    svm.set_account(wsol_ata, ata).unwrap();
+    assert!(svm.get_account(&treasury_ata).is_none_or(|a| a.data.is_empty()));
+
+    let mut ix = ix_reinvest_with_mode(
+        &h.keeper.pubkey(), &h.keeper_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 0,
+        ReinvestMode::Fraction { bps: 1_000 }, 0,
+    );
+    let n = ix.accounts.len();
+    ix.accounts[n - 4] = AccountMeta::new(treasury_ata, false); // treasury_base_ata
+    // The ATA is missing and there's nothing to create it with
+    assert!(send_tx(&mut svm, &[ix.clone()], &[&h.keeper]).is_err());
+    // Only the configured treasury's ATA is created
+    ix.accounts[n - 2] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    ix.accounts[n - 1] = AccountMeta::new_readonly(ATA_PROGRAM_ID, false);
+    assert!(send_tx(&mut svm, &[ix.clone()], &[&h.keeper]).is_err());
+
+    ix.accounts[n - 2] = AccountMeta::new_readonly(treasury, false); // treasury
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.keeper]).unwrap();
+    let ev: ProtocolFeeCollected = find_event(&logs).expect("ProtocolFeeCollected event not emitted");
+    assert_eq!(ev.treasury, treasury);
+    assert_eq!(ev.amount, 60_000_000);
+    assert_eq!(token_amount(&svm, &treasury_ata), 60_000_000);
+    let ev: Reinvested = find_event(&logs).expect("Reinvested event not emitted");
+    assert_eq!(ev.amount_invested, 540_000_000);
+}
+
+fn ix_migrate_position(payer: &Pubkey, position_pda: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("migrate_position"),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
+#[test]
+fn test_migrate_position_from_baseline_layout() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+    let before = read_position(&svm, &h.position_pda);
+
+    // Already current: nothing to migrate
+    let ix = ix_migrate_position(&h.operator.pubkey(), &h.position_pda);
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+
+    // Back to the pre-payout layout (238 bytes): the position can't be loaded
+    truncate_account(&mut svm, &h.position_pda, 238);
+    let ix = ix_heartbeat(&admin, &admin_asset, &h.position_pda);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+
+    // Any payer can migrate; only the PositionState PDA is accepted
+    let ix = ix_migrate_position(&h.operator.pubkey(), &h.operator_key_state);
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+    let ix = ix_migrate_position(&h.operator.pubkey(), &h.position_pda);
+    send_tx(&mut svm, &[ix], &[&h.operator]).unwrap();
+
+    let account = svm.get_account(&h.position_pda).unwrap();
+    assert_eq!(account.data.len(), PositionState::SIZE);
+    assert!(account.lamports >= svm.minimum_balance_for_rent_exemption(PositionState::SIZE));
+
+    let position = read_position(&svm, &h.position_pda);
+    assert_eq!(position.authority_seed, before.authority_seed);
+    assert_eq!(position.current_admin_asset, before.current_admin_asset);
+    assert_eq!(position.deposited_nav, before.deposited_nav);
+    assert_eq!(position.payout_address, Pubkey::default());
+    assert_eq!(position.cosign_required, 0);
+    assert_eq!(position.withdraw_delay_secs, 0);
+    assert_eq!(position.keeper_bounty_bps, 0);
+    assert_eq!(position.keeper_mode, ReinvestMode::Fraction { bps: 10_000 });
+    assert_eq!(position.share_mint, Pubkey::default());
+    assert_eq!(position.pending_withdraw_requests, 0);
+
+    send_tx(&mut svm, &[ix_heartbeat(&admin, &admin_asset, &h.position_pda)], &[&h.admin]).unwrap();
+    let ix = ix_migrate_position(&h.operator.pubkey(), &h.position_pda);
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+}
+
+fn ix_migrate_key_state(payer: &Pubkey, key_state: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("migrate_key_state"),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*key_state, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
+#[test]
+fn test_migrate_key_state_from_baseline_layout() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let before = read_key_state(&svm, &h.operator_key_state);
+
+    let ix = ix_migrate_key_state(&h.depositor.pubkey(), &h.operator_key_state);
+    assert!(send_tx(&mut svm, &[ix], &[&h.depositor]).is_err());
+
+    // Back to the pre-expiry layout (169 bytes): revoke_key can't load it
+    truncate_account(&mut svm, &h.operator_key_state, 169);
+    let revoke = ix_revoke_key(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.operator_asset,
+        &h.operator_key_state,
+        &h.collection,
+    );
+    assert!(send_tx(&mut svm, &[revoke.clone()], &[&h.admin]).is_err());
+
+    // Only a KeyState PDA is accepted
+    let ix = ix_migrate_key_state(&h.depositor.pubkey(), &h.position_pda);
+    assert!(send_tx(&mut svm, &[ix], &[&h.depositor]).is_err());
+    let ix = ix_migrate_key_state(&h.depositor.pubkey(), &h.operator_key_state);
+    send_tx(&mut svm, &[ix], &[&h.depositor]).unwrap();
+
+    let account = svm.get_account(&h.operator_key_state).unwrap();
+    assert_eq!(account.data.len(), KeyState::SIZE);
+    assert!(account.lamports >= svm.minimum_balance_for_rent_exemption(KeyState::SIZE));
+    let ks = read_key_state(&svm, &h.operator_key_state);
+    assert_eq!(ks.asset, before.asset);
+    assert_eq!(ks.authority_seed, before.authority_seed);
+    assert_eq!(ks.expires_at, 0);
+    assert_eq!(ks.promo, Pubkey::default());
+    assert_eq!(ks.principal_shares, 0);
+    assert_eq!(ks.debt, 0);
+    assert!(!ks.principal_refundable);
+
+    send_tx(&mut svm, &[revoke], &[&h.admin]).unwrap();
+}
+
+// ===========================================================================
+// MPL-Core asset tests
+// ===========================================================================
+
+#[test]
+fn test_create_position_creates_mpl_core_asset() {
+    let (mut svm, _) = setup();
+    let admin = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 10_000_000_000).unwrap();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+
+    // Create collection
+    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
+    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+    let collection = coll_kp.pubkey();
+
+    // Create MarketConfig for default navSOL market
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+
+    let asset_kp = Keypair::new();
+    plant_position_stubs(&mut svm, &asset_kp.pubkey());
+    send_tx(
+        &mut svm,
+        &[ix_create_position(&admin.pubkey(), &asset_kp.pubkey(), &collection)],
+        &[&admin, &asset_kp],
+    )
+    .unwrap();
+
+    // MPL-Core asset should exist
+    let asset_account = svm.get_account(&asset_kp.pubkey());
+    assert!(asset_account.is_some(), "MPL-Core asset should exist");
+    let asset_data = asset_account.unwrap();
+    assert_eq!(asset_data.owner, MPL_CORE_ID, "asset should be owned by MPL-Core");
+    // First byte should be Key::AssetV1 = 1
+    assert_eq!(asset_data.data[0], 1, "first byte should be AssetV1 discriminator");
+}
+
+#[test]
+fn test_authorize_key_creates_mpl_core_asset() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    // Operator's MPL-Core asset should exist
+    let asset_account = svm.get_account(&h.operator_asset);
+    assert!(asset_account.is_some(), "operator MPL-Core asset should exist");
+    let asset_data = asset_account.unwrap();
+    assert_eq!(asset_data.owner, MPL_CORE_ID, "asset should be owned by MPL-Core");
+    assert_eq!(asset_data.data[0], 1, "first byte should be AssetV1 discriminator");
+}
+
+#[test]
+fn test_revoke_burns_mpl_core_asset() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    // Authorize a key to the admin's own wallet
+    let extra_asset = Keypair::new();
+    send_tx(
+        &mut svm,
+        &[ix_authorize_key(
+            &h.admin.pubkey(),
+            &h.admin_asset.pubkey(),
+            &h.position_pda,
+            &extra_asset.pubkey(),
+            &h.admin.pubkey(),
+            PRESET_OPERATOR,
+            0, 0, 0, 0, 0, 0,
+            &h.collection,
+        )],
+        &[&h.admin, &extra_asset],
+    )
+    .unwrap();
+    let (extra_ks, _) = key_state_pda(&extra_asset.pubkey());
+
+    // Verify asset exists before revoke
+    assert!(svm.get_account(&extra_asset.pubkey()).is_some());
+    assert!(svm.get_account(&extra_ks).is_some());
+
+    // Revoke and burn via PermanentBurnDelegate
+    let ix = ix_revoke_key(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &extra_asset.pubkey(),
+        &extra_ks,
+        &h.collection,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    // KeyState should be closed
+    assert!(svm.get_account(&extra_ks).is_none());
+
+    // After MPL-Core burn, account is resized to 1 byte with Key::Uninitialized (0)
+    let asset_after = svm.get_account(&extra_asset.pubkey());
+    assert!(
+        asset_after.is_none()
+            || asset_after.as_ref().unwrap().data.is_empty()
+            || asset_after.as_ref().unwrap().data.len() == 1,
+        "MPL-Core asset should be burned"
+    );
+}
+
+// ===========================================================================
+// Collection tests
+// ===========================================================================
+
+#[test]
+fn test_create_collection() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+
+    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
+    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+
+    let config = read_protocol_config(&svm);
+    assert_eq!(config.collection, coll_kp.pubkey());
+
+    // Collection asset should exist (owned by MPL-Core program)
+    let coll_account = svm.get_account(&coll_kp.pubkey());
+    assert!(coll_account.is_some(), "collection asset should exist");
+    assert_eq!(coll_account.unwrap().owner, MPL_CORE_ID);
+}
+
+#[test]
+fn test_create_collection_twice_rejected() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+
+    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
+    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+
+    // Second attempt should fail
+    let (coll_ix2, coll_kp2) = ix_create_collection(&admin.pubkey());
+    assert!(send_tx(&mut svm, &[coll_ix2], &[&admin, &coll_kp2]).is_err());
+}
+
+#[test]
+fn test_create_position_without_collection_rejected() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+
+    // Try to create position without creating collection first
+    let asset_kp = Keypair::new();
+    // Use a dummy pubkey for collection since none exists
+    let dummy_collection = Pubkey::new_unique();
+    let result = send_tx(
+        &mut svm,
+        &[ix_create_position(&admin.pubkey(), &asset_kp.pubkey(), &dummy_collection)],
+        &[&admin, &asset_kp],
+    );
+    assert!(result.is_err());
+}
+
+/// Extract the name string from an MPL-Core AssetV1 account's raw data.
+/// Layout: 1 (key) + 32 (owner) + 1 (update_authority tag) + 32 (authority pubkey) + 4 (name len) + name bytes
+fn extract_asset_name(data: &[u8]) -> String {
+    let name_len_offset = 1 + 32 + 1 + 32; // = 66
+    let name_len = u32::from_le_bytes(data[name_len_offset..name_len_offset + 4].try_into().unwrap()) as usize;
+    let name_start = name_len_offset + 4;
+    String::from_utf8(data[name_start..name_start + name_len].to_vec()).unwrap()
+}
+
+#[test]
+fn test_create_position_custom_name() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
+    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+    let collection = coll_kp.pubkey();
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+
+    let asset_kp = Keypair::new();
+    plant_position_stubs(&mut svm, &asset_kp.pubkey());
+    send_tx(
+        &mut svm,
+        &[ix_create_position_named(
+            &admin.pubkey(),
+            &asset_kp.pubkey(),
+            &collection,
+            "My Vault",
+        )],
+        &[&admin, &asset_kp],
+    )
+    .unwrap();
+
+    // Verify the MPL-Core asset has the base name + suffix
+    let asset_account = svm.get_account(&asset_kp.pubkey()).expect("asset should exist");
+    assert_eq!(asset_account.owner, MPL_CORE_ID);
+    let name = extract_asset_name(&asset_account.data);
+    assert_eq!(name, "H\u{00e4}rdig Admin Key - My Vault");
+}
+
+/// Extract attribute values from an MPL-Core asset account using fetch_plugin.
+/// Returns the list of (key, value) pairs.
+fn extract_asset_attributes(account: &Account) -> Vec<(String, String)> {
+    use mpl_core::{accounts::BaseAssetV1, fetch_plugin, types::{Attributes, PluginType}};
+    let key = Pubkey::new_unique(); // dummy key, not used in fetch_plugin
+    let mut lamports = account.lamports;
+    let mut data = account.data.clone();
+    let account_info = solana_sdk::account_info::AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &account.owner,
+        false,
+        0,
+    );
+    let (_, attributes, _) = fetch_plugin::<BaseAssetV1, Attributes>(
+        &account_info,
+        PluginType::Attributes,
+    )
+    .expect("failed to fetch Attributes plugin");
+    attributes
+        .attribute_list
+        .into_iter()
+        .map(|a| (a.key, a.value))
+        .collect()
+}
+
+/// Find a specific attribute value by key from the attribute list.
+fn find_attribute<'a>(attrs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+#[test]
+fn test_admin_key_has_market_attribute() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
+    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+    let collection = coll_kp.pubkey();
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+
+    let asset_kp = Keypair::new();
+    plant_position_stubs(&mut svm, &asset_kp.pubkey());
+    send_tx(
+        &mut svm,
+        &[ix_create_position_with_market(
+            &admin.pubkey(),
+            &asset_kp.pubkey(),
+            &collection,
+            Some("My Vault"),
+            "navSOL",
+        )],
+        &[&admin, &asset_kp],
+    )
+    .unwrap();
+
+    // Read admin asset's attributes
+    let asset_account = svm.get_account(&asset_kp.pubkey()).expect("asset should exist");
+    let attrs = extract_asset_attributes(&asset_account);
+
+    // Verify "market" attribute is set
+    assert_eq!(
+        find_attribute(&attrs, "market"),
+        Some("navSOL"),
+        "admin key should have market=navSOL attribute"
+    );
+
+    // Verify "position" attribute points to itself (admin asset)
+    assert_eq!(
+        find_attribute(&attrs, "position"),
+        Some(asset_kp.pubkey().to_string().as_str()),
+        "admin key position attribute should be its own pubkey"
+    );
+}
+
+#[test]
+fn test_admin_key_market_attribute_custom_value() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
+    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+    let collection = coll_kp.pubkey();
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+
+    let asset_kp = Keypair::new();
+    plant_position_stubs(&mut svm, &asset_kp.pubkey());
+    send_tx(
+        &mut svm,
+        &[ix_create_position_with_market(
+            &admin.pubkey(),
+            &asset_kp.pubkey(),
+            &collection,
+            None,
+            "navETH",
+        )],
+        &[&admin, &asset_kp],
+    )
+    .unwrap();
+
+    let asset_account = svm.get_account(&asset_kp.pubkey()).expect("asset should exist");
+    let attrs = extract_asset_attributes(&asset_account);
+    assert_eq!(
+        find_attribute(&attrs, "market"),
+        Some("navETH"),
+        "admin key should have market=navETH attribute"
+    );
+}
+
+#[test]
+fn test_delegated_key_has_market_and_position_attributes() {
+    let (mut svm, admin) = setup();
+    send_tx(&mut svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
+    send_tx(&mut svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+    let collection = coll_kp.pubkey();
+    send_tx(&mut svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+
+    // Create position with market_name = "navSOL" and custom name
+    let admin_asset = Keypair::new();
+    plant_position_stubs(&mut svm, &admin_asset.pubkey());
+    send_tx(
+        &mut svm,
+        &[ix_create_position_with_market(
+            &admin.pubkey(),
+            &admin_asset.pubkey(),
+            &collection,
+            Some("My Vault"),
+            "navSOL",
+        )],
+        &[&admin, &admin_asset],
+    )
+    .unwrap();
+
+    let (pos_pda, _) = position_pda(&admin_asset.pubkey());
+
+    // Authorize an operator key
+    let operator = Keypair::new();
+    svm.airdrop(&operator.pubkey(), 5_000_000_000).unwrap();
+    let op_asset = Keypair::new();
+    send_tx(
+        &mut svm,
+        &[ix_authorize_key(
+            &admin.pubkey(),
+            &admin_asset.pubkey(),
+            &pos_pda,
+            &op_asset.pubkey(),
+            &operator.pubkey(),
+            PRESET_OPERATOR,
+            0, 0, 0, 0, 0, 0,
+            &collection,
+        )],
+        &[&admin, &op_asset],
+    )
+    .unwrap();
+
+    // Read the delegated key's attributes
+    let op_account = svm.get_account(&op_asset.pubkey()).expect("operator asset should exist");
+    let attrs = extract_asset_attributes(&op_account);
+
+    // Verify "market" attribute is inherited from admin key
+    assert_eq!(
+        find_attribute(&attrs, "market"),
+        Some("navSOL"),
+        "delegated key should have market=navSOL attribute from admin"
+    );
+
+    // Verify "position_name" attribute matches admin asset's name
+    assert_eq!(
+        find_attribute(&attrs, "position_name"),
+        Some("H\u{00e4}rdig Admin Key - My Vault"),
+        "delegated key should have position_name matching admin asset name"
+    );
+
+    // Verify "position" attribute still points to admin asset pubkey
+    assert_eq!(
+        find_attribute(&attrs, "position"),
+        Some(admin_asset.pubkey().to_string().as_str()),
+        "delegated key position attribute should point to admin asset"
+    );
+}
+
+// ===========================================================================
+// Recovery instruction builders
+// ===========================================================================
+
+fn ix_heartbeat(
+    admin: &Pubkey,
+    admin_key_asset: &Pubkey,
+    position_pda: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("heartbeat"),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*admin_key_asset, false),
+            AccountMeta::new(*position_pda, false),
+            AccountMeta::new_readonly(config_pda().0, false),          // config
+        ],
+    )
+}
+
+fn ix_configure_recovery(
+    admin: &Pubkey,
+    admin_key_asset: &Pubkey,
+    position_pda: &Pubkey,
+    recovery_asset: &Pubkey,
+    target_wallet: &Pubkey,
+    old_recovery_asset: Option<&Pubkey>,
+    collection: &Pubkey,
+    lockout_secs: i64,
+    lock_config: bool,
+    name: Option<&str>,
+) -> Instruction {
+    let (cfg_pda, _) = config_pda();
+
+    let mut data = sighash("configure_recovery");
+    data.extend_from_slice(&lockout_secs.to_le_bytes());
+    data.push(if lock_config { 1 } else { 0 });
+    // name: Option<String>
+    match name {
+        Some(n) => {
+            data.push(1); // Some
+            data.extend_from_slice(&(n.len() as u32).to_le_bytes());
+            data.extend_from_slice(n.as_bytes());
+        }
+        None => {
+            data.push(0); // None
+        }
+    }
+
+    // For Option<UncheckedAccount>, Anchor expects the account always present.
+    // Pass program_id() as the "None" sentinel.
+    let old_recovery = old_recovery_asset.copied().unwrap_or(program_id());
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*admin, true),                    // admin
+            AccountMeta::new_readonly(*admin_key_asset, false), // admin_key_asset
+            AccountMeta::new(*position_pda, false),             // position
+            AccountMeta::new(*recovery_asset, true),            // recovery_asset (signer)
+            AccountMeta::new_readonly(*target_wallet, false),   // target_wallet
+            AccountMeta::new(old_recovery, false),              // old_recovery_asset (Option)
+            AccountMeta::new_readonly(cfg_pda, false),          // config
+            AccountMeta::new(*collection, false),               // collection
+            AccountMeta::new_readonly(MPL_CORE_ID, false),      // mpl_core_program
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
+fn ix_execute_recovery(
+    recovery_holder: &Pubkey,
+    recovery_key_asset: &Pubkey,
+    position_pda: &Pubkey,
+    old_admin_asset: &Pubkey,
+    new_admin_asset: &Pubkey,
+    collection: &Pubkey,
+) -> Instruction {
+    let (cfg_pda, _) = config_pda();
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("execute_recovery"),
+        vec![
+            AccountMeta::new(*recovery_holder, true),           // recovery_holder
+            AccountMeta::new(*recovery_key_asset, false),          // recovery_key_asset (mut for burn)
+            AccountMeta::new(*position_pda, false),              // position
+            AccountMeta::new(*old_admin_asset, false),           // old_admin_asset
+            AccountMeta::new(*new_admin_asset, true),            // new_admin_asset (signer)
+            AccountMeta::new_readonly(cfg_pda, false),           // config
+            AccountMeta::new(*collection, false),                // collection
+            AccountMeta::new_readonly(MPL_CORE_ID, false),       // mpl_core_program
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
+
+// ===========================================================================
+// Recovery tests
+// ===========================================================================
+
+#[test]
+fn test_heartbeat_admin_ok() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let pos_before = read_position(&svm, &h.position_pda);
+
+    // Advance clock so we can detect the timestamp change
+    advance_clock(&mut svm, 10);
+
+    let ix = ix_heartbeat(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    let pos_after = read_position(&svm, &h.position_pda);
+    assert!(
+        pos_after.last_admin_activity >= pos_before.last_admin_activity,
+        "heartbeat should update last_admin_activity"
+    );
+}
+
+#[test]
+fn test_heartbeat_operator_denied() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    // Operator does NOT have PERM_MANAGE_KEYS, so heartbeat should fail
+    let ix = ix_heartbeat(
+        &h.operator.pubkey(),
+        &h.operator_asset,
+        &h.position_pda,
+    );
+    assert!(
+        send_tx(&mut svm, &[ix], &[&h.operator]).is_err(),
+        "operator should not be able to heartbeat"
+    );
+}
+
+#[test]
+fn test_heartbeat_outsider_denied() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    // Outsider has no key at all — use a random pubkey as the asset
+    let fake_asset = Pubkey::new_unique();
+    let ix = ix_heartbeat(
+        &h.outsider.pubkey(),
+        &fake_asset,
+        &h.position_pda,
+    );
+    assert!(
+        send_tx(&mut svm, &[ix], &[&h.outsider]).is_err(),
+        "outsider should not be able to heartbeat"
+    );
+}
+
+#[test]
+fn test_configure_recovery_ok() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_wallet = Keypair::new();
+    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
+    let recovery_asset = Keypair::new();
+
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset.pubkey(),
+        &recovery_wallet.pubkey(),
+        None,
+        &h.collection,
+        86400, // 1 day lockout
+        false,
+        None,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.recovery_asset, recovery_asset.pubkey());
+    assert_eq!(pos.recovery_lockout_secs, 86400);
+    assert!(!pos.recovery_config_locked);
+
+    // Verify recovery key NFT was created with correct attributes
+    let rec_account = svm.get_account(&recovery_asset.pubkey()).expect("recovery asset should exist");
+    let attrs = extract_asset_attributes(&rec_account);
+    assert_eq!(find_attribute(&attrs, "permissions"), Some("0"));
+    assert_eq!(find_attribute(&attrs, "recovery"), Some("true"));
+    assert_eq!(
+        find_attribute(&attrs, "position"),
+        Some(h.admin_asset.pubkey().to_string().as_str()),
+        "recovery key should be bound to admin asset (authority_seed)"
+    );
+}
+
+#[test]
+fn test_configure_recovery_with_lock() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_wallet = Keypair::new();
+    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
+    let recovery_asset = Keypair::new();
+
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset.pubkey(),
+        &recovery_wallet.pubkey(),
+        None,
+        &h.collection,
+        604800, // 7 day lockout
+        true,   // lock config
+        Some("backup"),
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.recovery_asset, recovery_asset.pubkey());
+    assert_eq!(pos.recovery_lockout_secs, 604800);
+    assert!(pos.recovery_config_locked);
+}
+
+#[test]
+fn test_configure_recovery_locked_denied() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_wallet = Keypair::new();
+    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
+
+    // First: configure with lock_config = true
+    let recovery_asset1 = Keypair::new();
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset1.pubkey(),
+        &recovery_wallet.pubkey(),
+        None,
+        &h.collection,
+        86400,
+        true, // lock it
+        None,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset1]).unwrap();
+
+    // Second: try to reconfigure — should fail because config is locked
+    let recovery_asset2 = Keypair::new();
+    let ix2 = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset2.pubkey(),
+        &recovery_wallet.pubkey(),
+        Some(&recovery_asset1.pubkey()),
+        &h.collection,
+        172800,
+        false,
+        None,
+    );
+    assert!(
+        send_tx(&mut svm, &[ix2], &[&h.admin, &recovery_asset2]).is_err(),
+        "should not be able to reconfigure recovery when locked"
+    );
+
+    // Verify original config unchanged
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.recovery_asset, recovery_asset1.pubkey());
+    assert_eq!(pos.recovery_lockout_secs, 86400);
+}
+
+#[test]
+fn test_configure_recovery_replace_requires_old_asset() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_wallet = Keypair::new();
+    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
+
+    // First: configure a recovery key
+    let recovery_asset1 = Keypair::new();
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset1.pubkey(),
+        &recovery_wallet.pubkey(),
+        None,
+        &h.collection,
+        86400,
+        false,
+        None,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset1]).unwrap();
+
+    // Second: try to replace WITHOUT providing old_recovery_asset — should fail
+    let recovery_asset2 = Keypair::new();
+    let ix2 = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset2.pubkey(),
+        &recovery_wallet.pubkey(),
+        None, // omitting old_recovery_asset
+        &h.collection,
+        172800,
+        false,
+        None,
+    );
+    assert!(
+        send_tx(&mut svm, &[ix2], &[&h.admin, &recovery_asset2]).is_err(),
+        "should not be able to replace recovery key without providing old_recovery_asset"
+    );
+
+    // Verify original config unchanged
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.recovery_asset, recovery_asset1.pubkey());
+    assert_eq!(pos.recovery_lockout_secs, 86400);
+}
+
+#[test]
+fn test_configure_recovery_operator_denied() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_wallet = Keypair::new();
+    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
+    let recovery_asset = Keypair::new();
+
+    // Operator does not have PERM_MANAGE_KEYS
+    let ix = ix_configure_recovery(
+        &h.operator.pubkey(),
+        &h.operator_asset,
+        &h.position_pda,
+        &recovery_asset.pubkey(),
+        &recovery_wallet.pubkey(),
+        None,
+        &h.collection,
+        86400,
+        false,
+        None,
+    );
+    assert!(
+        send_tx(&mut svm, &[ix], &[&h.operator, &recovery_asset]).is_err(),
+        "operator should not be able to configure recovery"
+    );
+}
+
+#[test]
+fn test_execute_recovery_ok() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_holder = Keypair::new();
+    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
+    let recovery_asset = Keypair::new();
+
+    // Configure recovery with 1-second lockout (minimum for testing)
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset.pubkey(),
+        &recovery_holder.pubkey(),
+        None,
+        &h.collection,
+        1, // 1 second lockout
+        false,
+        None,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+
+    // Advance time past the lockout period
+    advance_clock(&mut svm, 100);
+
+    // Execute recovery
+    let new_admin_asset = Keypair::new();
+    let ix = ix_execute_recovery(
+        &recovery_holder.pubkey(),
+        &recovery_asset.pubkey(),
+        &h.position_pda,
+        &h.admin_asset.pubkey(),
+        &new_admin_asset.pubkey(),
+        &h.collection,
+    );
+    send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).unwrap();
+
+    // Verify position state
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.current_admin_asset, new_admin_asset.pubkey(),
+        "current_admin_asset should be updated to new admin");
+    assert_eq!(pos.authority_seed, h.admin_asset.pubkey(),
+        "authority_seed should remain unchanged");
+    assert_eq!(pos.recovery_asset, Pubkey::default(),
+        "recovery_asset should be cleared after recovery");
+    assert_eq!(pos.recovery_lockout_secs, 0,
+        "recovery_lockout_secs should be cleared");
+    assert!(!pos.recovery_config_locked,
+        "recovery_config_locked should be cleared");
+
+    // Verify new admin key NFT has correct attributes
+    let new_admin_account = svm.get_account(&new_admin_asset.pubkey())
+        .expect("new admin asset should exist");
+    let attrs = extract_asset_attributes(&new_admin_account);
+    assert_eq!(find_attribute(&attrs, "permissions"), Some("63"),
+        "new admin key should have full admin permissions");
+    assert_eq!(
+        find_attribute(&attrs, "position"),
+        Some(h.admin_asset.pubkey().to_string().as_str()),
+        "new admin key should be bound to original authority_seed"
+    );
+
+    // Verify old admin key was burned (MPL-Core may leave a 1-byte stub)
+    let old_admin_after = svm.get_account(&h.admin_asset.pubkey());
+    assert!(
+        old_admin_after.is_none()
+            || old_admin_after.as_ref().unwrap().data.is_empty()
+            || old_admin_after.as_ref().unwrap().data.len() == 1,
+        "old admin asset should be burned"
+    );
+
+    // Verify recovery key was burned
+    let recovery_after = svm.get_account(&recovery_asset.pubkey());
+    assert!(
+        recovery_after.is_none()
+            || recovery_after.as_ref().unwrap().data.is_empty()
+            || recovery_after.as_ref().unwrap().data.len() == 1,
+        "recovery asset should be burned"
+    );
+}
+
+#[test]
+fn test_execute_recovery_too_early() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_holder = Keypair::new();
+    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
+    let recovery_asset = Keypair::new();
+
+    // Configure recovery with a long lockout
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset.pubkey(),
+        &recovery_holder.pubkey(),
+        None,
+        &h.collection,
+        999999, // very long lockout
+        false,
+        None,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+
+    // Try to execute recovery immediately — should fail
+    let new_admin_asset = Keypair::new();
+    let ix = ix_execute_recovery(
+        &recovery_holder.pubkey(),
+        &recovery_asset.pubkey(),
+        &h.position_pda,
+        &h.admin_asset.pubkey(),
+        &new_admin_asset.pubkey(),
+        &h.collection,
+    );
+    assert!(
+        send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).is_err(),
+        "execute_recovery should fail before lockout expires"
+    );
+
+    // Verify position unchanged
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.current_admin_asset, h.admin_asset.pubkey());
+    assert_eq!(pos.recovery_asset, recovery_asset.pubkey());
+}
+
+#[test]
+fn test_execute_recovery_heartbeat_resets_lockout() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_holder = Keypair::new();
+    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
+    let recovery_asset = Keypair::new();
+
+    // Configure recovery with 1-second lockout
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset.pubkey(),
+        &recovery_holder.pubkey(),
+        None,
+        &h.collection,
+        1,
+        false,
+        None,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+
+    // Advance time past lockout
+    advance_clock(&mut svm, 100);
+
+    // Admin sends heartbeat — resets last_admin_activity
+    let hb = ix_heartbeat(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+    );
+    send_tx(&mut svm, &[hb], &[&h.admin]).unwrap();
+
+    // Now try to execute recovery — should fail because heartbeat reset the timer
+    let new_admin_asset = Keypair::new();
+    let ix = ix_execute_recovery(
+        &recovery_holder.pubkey(),
+        &recovery_asset.pubkey(),
+        &h.position_pda,
+        &h.admin_asset.pubkey(),
+        &new_admin_asset.pubkey(),
+        &h.collection,
+    );
+    assert!(
+        send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).is_err(),
+        "execute_recovery should fail after heartbeat resets lockout"
+    );
+}
+
+#[test]
+fn test_execute_recovery_no_recovery_configured() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    // Try to execute recovery without configuring one
+    let recovery_holder = Keypair::new();
+    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
+    let fake_recovery = Pubkey::new_unique();
+    let new_admin_asset = Keypair::new();
+
+    let ix = ix_execute_recovery(
+        &recovery_holder.pubkey(),
+        &fake_recovery,
+        &h.position_pda,
+        &h.admin_asset.pubkey(),
+        &new_admin_asset.pubkey(),
+        &h.collection,
+    );
+    assert!(
+        send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).is_err(),
+        "execute_recovery should fail when no recovery is configured"
+    );
+}
+
+#[test]
+fn test_delegated_keys_survive_recovery() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_holder = Keypair::new();
+    svm.airdrop(&recovery_holder.pubkey(), 5_000_000_000).unwrap();
+    let recovery_asset = Keypair::new();
+
+    // Configure recovery
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset.pubkey(),
+        &recovery_holder.pubkey(),
+        None,
+        &h.collection,
+        1,
+        false,
+        None,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]).unwrap();
+
+    // Advance time past lockout
+    advance_clock(&mut svm, 100);
+
+    // Execute recovery
+    let new_admin_asset = Keypair::new();
+    let ix = ix_execute_recovery(
+        &recovery_holder.pubkey(),
+        &recovery_asset.pubkey(),
+        &h.position_pda,
+        &h.admin_asset.pubkey(),
+        &new_admin_asset.pubkey(),
+        &h.collection,
+    );
+    send_tx(&mut svm, &[ix], &[&recovery_holder, &new_admin_asset]).unwrap();
+
+    // Verify operator key state still exists
+    let op_ks = read_key_state(&svm, &h.operator_key_state);
+    assert_eq!(op_ks.asset, h.operator_asset,
+        "operator key_state should still exist after recovery");
+
+    // Verify operator can still buy (authority_seed unchanged, key binding intact)
+    let ix = ix_buy(
+        &h.operator.pubkey(),
+        &h.operator_asset,
+        &h.position_pda,
+        &h.admin_asset.pubkey(), // authority_seed is still the original admin asset
+        500_000,
+    );
+    send_tx(&mut svm, &[ix], &[&h.operator]).unwrap();
+
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.deposited_nav, 500_000, "operator buy should still work after recovery");
+}
+
+#[test]
+fn test_replace_recovery_key() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let recovery_wallet = Keypair::new();
+    svm.airdrop(&recovery_wallet.pubkey(), 5_000_000_000).unwrap();
+
+    // First recovery config
+    let recovery_asset1 = Keypair::new();
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset1.pubkey(),
+        &recovery_wallet.pubkey(),
+        None,
+        &h.collection,
+        86400,
+        false, // don't lock
+        None,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset1]).unwrap();
+
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.recovery_asset, recovery_asset1.pubkey());
+
+    // Replace with new recovery key (passing old one to burn)
+    let recovery_asset2 = Keypair::new();
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset2.pubkey(),
+        &recovery_wallet.pubkey(),
+        Some(&recovery_asset1.pubkey()),
+        &h.collection,
+        172800, // new lockout
+        false,
+        Some("backup-v2"),
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset2]).unwrap();
+
+    // Verify replacement
+    let pos = read_position(&svm, &h.position_pda);
+    assert_eq!(pos.recovery_asset, recovery_asset2.pubkey());
+    assert_eq!(pos.recovery_lockout_secs, 172800);
+
+    // Verify old recovery key was burned (MPL-Core may leave a 1-byte stub)
+    let old_recovery = svm.get_account(&recovery_asset1.pubkey());
+    assert!(
+        old_recovery.is_none()
+            || old_recovery.as_ref().unwrap().data.is_empty()
+            || old_recovery.as_ref().unwrap().data.len() == 1,
+        "old recovery asset should be burned when replaced"
+    );
+}
+
+// ── authorize_key and revoke_key update last_admin_activity ──────────
+
+#[test]
+fn test_authorize_key_updates_last_admin_activity() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let pos_before = read_position(&svm, &h.position_pda);
+
+    // Advance clock so we can detect the timestamp change
+    advance_clock(&mut svm, 100);
+
+    // Authorize a new key
+    let new_asset = Keypair::new();
+    let target = Keypair::new();
+    let ix = ix_authorize_key(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &new_asset.pubkey(),
+        &target.pubkey(),
+        PRESET_DEPOSITOR, // Depositor
+        0, 0, 0, 0, 0, 0,
+        &h.collection,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin, &new_asset]).unwrap();
+
+    let pos_after = read_position(&svm, &h.position_pda);
+    assert!(
+        pos_after.last_admin_activity > pos_before.last_admin_activity,
+        "authorize_key should update last_admin_activity"
+    );
+}
+
+#[test]
+fn test_revoke_key_updates_last_admin_activity() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let pos_before = read_position(&svm, &h.position_pda);
+
+    // Advance clock so we can detect the timestamp change
+    advance_clock(&mut svm, 100);
+
+    // Revoke the operator key
+    let ix = ix_revoke_key(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.operator_asset,
+        &h.operator_key_state,
+        &h.collection,
+    );
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    let pos_after = read_position(&svm, &h.position_pda);
+    assert!(
+        pos_after.last_admin_activity > pos_before.last_admin_activity,
+        "revoke_key should update last_admin_activity"
+    );
+}
+
+// ── configure_recovery lockout upper bound ──────────────────────────
+
+#[test]
+fn test_configure_recovery_lockout_too_large() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let recovery_wallet = Keypair::new();
+    let recovery_asset = Keypair::new();
+
+    // Attempt to configure with i64::MAX lockout (exceeds 10-year cap)
+    let ix = ix_configure_recovery(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &recovery_asset.pubkey(),
+        &recovery_wallet.pubkey(),
+        None,
+        &h.collection,
+        i64::MAX, // exceeds 10-year max
+        false,
+        Some("test"),
+    );
+    let result = send_tx(&mut svm, &[ix], &[&h.admin, &recovery_asset]);
+    assert!(result.is_err(), "lockout exceeding 10 years should be rejected");
+}
+
+// ===========================================================================
+// Promo tests
+// ===========================================================================
+
+// ---------------------------------------------------------------------------
+// Promo instruction builders
+// ---------------------------------------------------------------------------
+
+fn promo_pda(authority_seed: &Pubkey, name_suffix: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PromoConfig::SEED, authority_seed.as_ref(), name_suffix.as_bytes()],
+        &program_id(),
+    )
+}
+
+fn claim_receipt_pda(promo: &Pubkey, claimer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ClaimReceipt::SEED, promo.as_ref(), claimer.as_ref()],
+        &program_id(),
+    )
+}
+
+fn read_promo_config(svm: &LiteSVM, pda: &Pubkey) -> PromoConfig {
+    let account = svm.get_account(pda).unwrap();
+    PromoConfig::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+fn read_claim_receipt(svm: &LiteSVM, pda: &Pubkey) -> ClaimReceipt {
+    let account = svm.get_account(pda).unwrap();
+    ClaimReceipt::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+fn ix_create_promo(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    name_suffix: &str,
+    permissions: u16,
+    borrow_capacity: u64,
+    borrow_refill_period: u64,
+    sell_capacity: u64,
+    sell_refill_period: u64,
+    total_borrow_limit: u64,
+    total_sell_limit: u64,
+    min_deposit_lamports: u64,
+    max_claims: u32,
+    initial_fill_bps: u16,
+    image_uri: &str,
+    market_name: &str,
+) -> Instruction {
+    ix_create_promo_expiring(
+        admin, admin_asset, name_suffix, permissions,
+        borrow_capacity, borrow_refill_period, sell_capacity, sell_refill_period,
+        total_borrow_limit, total_sell_limit, min_deposit_lamports, max_claims,
+        initial_fill_bps, image_uri, market_name,
+        0, // key_expires_at: never
+    )
+}
+
+fn ix_create_promo_expiring(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    name_suffix: &str,
+    permissions: u16,
+    borrow_capacity: u64,
+    borrow_refill_period: u64,
+    sell_capacity: u64,
+    sell_refill_period: u64,
+    total_borrow_limit: u64,
+    total_sell_limit: u64,
+    min_deposit_lamports: u64,
+    max_claims: u32,
+    initial_fill_bps: u16,
+    image_uri: &str,
+    market_name: &str,
+    key_expires_at: i64,
+) -> Instruction {
+    ix_create_promo_with_terms(
+        admin, admin_asset, name_suffix, permissions,
+        KeyLimits {
+            sell_bucket_capacity: sell_capacity,
+            sell_refill_period_slots: sell_refill_period,
+            borrow_bucket_capacity: borrow_capacity,
+            borrow_refill_period_slots: borrow_refill_period,
+            total_sell_limit,
+            total_borrow_limit,
+        },
+        PromoTerms { min_deposit_lamports, max_claims, initial_fill_bps, key_expires_at, refundable_principal: false },
+        image_uri, market_name,
+    )
+}
+
+fn ix_create_promo_with_terms(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    name_suffix: &str,
+    permissions: u16,
+    limits: KeyLimits,
+    terms: PromoTerms,
+    image_uri: &str,
+    market_name: &str,
+) -> Instruction {
+    let (pos_pda, _) = position_pda(admin_asset);
+    let position = read_position_seed(admin_asset);
+    let (promo, _) = promo_pda(&position, name_suffix);
+
+    // Anchor discriminator + Borsh-serialized args
+    // name_suffix is the first arg (#[instruction(name_suffix: String)])
+    let mut data = sighash("create_promo");
+    // String: 4-byte LE length + bytes
+    data.extend_from_slice(&(name_suffix.len() as u32).to_le_bytes());
+    data.extend_from_slice(name_suffix.as_bytes());
+    data.extend_from_slice(&permissions.to_le_bytes());
+    limits.serialize(&mut data).unwrap();
+    terms.serialize(&mut data).unwrap();
+    // image_uri: String
+    data.extend_from_slice(&(image_uri.len() as u32).to_le_bytes());
+    data.extend_from_slice(image_uri.as_bytes());
+    // market_name: String
+    data.extend_from_slice(&(market_name.len() as u32).to_le_bytes());
+    data.extend_from_slice(market_name.as_bytes());
+    PromoGate::default().serialize(&mut data).unwrap(); // no collection gate, open to anyone
+    PromoWindow::default().serialize(&mut data).unwrap(); // claims open indefinitely
+    data.extend_from_slice(&[0u8; 48]); // budget: uncapped, no shared buckets
+    data.extend_from_slice(&[0u8; 20]); // referral_reward: none
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*admin, true),                                    // admin
+            AccountMeta::new_readonly(*admin_asset, false),                    // admin_key_asset
+            AccountMeta::new_readonly(pos_pda, false),                         // position
+            AccountMeta::new(promo, false),                                    // promo (init)
+            AccountMeta::new_readonly(config_pda().0, false),                  // config
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),  // system_program
+        ],
+    )
+}
+
+/// Helper: get the authority_seed from a position created with the given admin_asset.
+/// This is just the admin_asset pubkey (authority_seed = first admin asset pubkey).
+fn read_position_seed(admin_asset: &Pubkey) -> Pubkey {
+    // authority_seed is always the admin_asset pubkey from create_position
+    *admin_asset
+}
+
+fn ix_update_promo(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    promo_pda_key: &Pubkey,
+    active: Option<bool>,
+    max_claims: Option<u32>,
+) -> Instruction {
+    let (pos_pda, _) = position_pda(admin_asset);
+
+    let mut data = sighash("update_promo");
+    // Option<bool>
+    match active {
+        Some(v) => {
+            data.push(1); // Some
+            data.push(if v { 1 } else { 0 });
+        }
+        None => data.push(0), // None
+    }
+    // Option<u32>
+    match max_claims {
+        Some(v) => {
+            data.push(1); // Some
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        None => data.push(0), // None
+    }
+    data.push(0); // merkle_root: None
+    data.push(0); // starts_at: None
+    data.push(0); // ends_at: None
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*admin, true),                   // admin
+            AccountMeta::new_readonly(*admin_asset, false),    // admin_key_asset
+            AccountMeta::new_readonly(pos_pda, false),         // position
+            AccountMeta::new(*promo_pda_key, false),           // promo (mut)
+            AccountMeta::new_readonly(config_pda().0, false),  // config
+        ],
+    )
+}
+
+fn ix_claim_promo_key(
+    claimer: &Pubkey,
+    promo_pda_key: &Pubkey,
+    admin_asset: &Pubkey,
+    key_asset: &Pubkey,
+    collection: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    ix_claim_promo_key_with_proof(
+        claimer, promo_pda_key, admin_asset, key_asset, collection, amount, 0, &[],
+    )
+}
+
+fn ix_claim_promo_key_with_proof(
+    claimer: &Pubkey,
+    promo_pda_key: &Pubkey,
+    admin_asset: &Pubkey,
+    key_asset: &Pubkey,
+    collection: &Pubkey,
+    amount: u64,
+    allowlist_min_deposit: u64,
+    proof: &[[u8; 32]],
+) -> Instruction {
+    let (pos_pda, _) = position_pda(admin_asset);
+    let (claim_receipt, _) = claim_receipt_pda(promo_pda_key, claimer);
+    let (ks_pda, _) = key_state_pda(key_asset);
+    let (cfg_pda, _) = config_pda();
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    let (program_pda, pp_pda, escrow_pda, log_pda, wsol_ata, nav_sol_ata) = mayflower_addrs(admin_asset);
+
+    let mut data = sighash("claim_promo_key");
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&allowlist_min_deposit.to_le_bytes());
+    proof.to_vec().serialize(&mut data).unwrap();
+    data.push(0); // referrer: None
+
+    Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(*claimer, true),                                  // claimer
+            AccountMeta::new(*promo_pda_key, false),                           // promo (mut)
+            AccountMeta::new(claim_receipt, false),                            // claim_receipt (init)
+            AccountMeta::new(pos_pda, false),                                  // position (mut)
+            AccountMeta::new(*key_asset, true),                                // key_asset (signer)
+            AccountMeta::new(ks_pda, false),                                   // key_state (init)
+            AccountMeta::new_readonly(cfg_pda, false),                         // config
+            AccountMeta::new(*collection, false),                              // collection
+            AccountMeta::new_readonly(MPL_CORE_ID, false),                     // mpl_core_program
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),  // system_program
+            AccountMeta::new_readonly(mc_pda, false),                          // market_config
+            AccountMeta::new(program_pda, false),                              // program_pda
+            AccountMeta::new(pp_pda, false),                                   // personal_position
+            AccountMeta::new(escrow_pda, false),                               // user_shares
+            AccountMeta::new(nav_sol_ata, false),                              // user_nav_sol_ata
+            AccountMeta::new(wsol_ata, false),                                 // user_wsol_ata
+            AccountMeta::new_readonly(MAYFLOWER_TENANT, false),                // tenant
             AccountMeta::new_readonly(DEFAULT_MARKET_GROUP, false),            // market_group
             AccountMeta::new_readonly(DEFAULT_MARKET_META, false),             // market_meta
             AccountMeta::new(DEFAULT_MAYFLOWER_MARKET, false),                 // mayflower_market
@@ -3765,69 +6419,452 @@ fn ix_claim_promo_key(
             AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false),            // mayflower_program
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),                    // token_program
             AccountMeta::new(log_pda, false),                                  // log_account
+            AccountMeta::new_readonly(program_id(), false),                    // gate_asset (None)
+            AccountMeta::new_readonly(program_id(), false),                    // gate_token_account (None)
+            AccountMeta::new_readonly(program_id(), false),                    // gate_metadata (None)
+            AccountMeta::new_readonly(program_id(), false),                    // gate_receipt (None)
+            AccountMeta::new_readonly(program_id(), false),                    // referrer_key_asset (None)
+            AccountMeta::new_readonly(program_id(), false),                    // referrer_key_state (None)
+        ],
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Promo test helpers
+// ---------------------------------------------------------------------------
+
+/// Minimal setup: init protocol + collection + market config + position.
+/// Returns (admin, admin_asset keypair, position_pda, collection pubkey).
+fn promo_setup(svm: &mut LiteSVM) -> (Keypair, Keypair, Pubkey, Pubkey) {
+    let admin = Keypair::new();
+    svm.airdrop(&admin.pubkey(), 10_000_000_000).unwrap();
+
+    // Init protocol
+    send_tx(svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+
+    // Create collection
+    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
+    send_tx(svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
+    let collection = coll_kp.pubkey();
+
+    // Create MarketConfig
+    send_tx(svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+
+    // Create position
+    let admin_asset = Keypair::new();
+    plant_position_stubs(svm, &admin_asset.pubkey());
+    send_tx(
+        svm,
+        &[ix_create_position(
+            &admin.pubkey(),
+            &admin_asset.pubkey(),
+            &collection,
+        )],
+        &[&admin, &admin_asset],
+    )
+    .unwrap();
+
+    let (pos_pda, _) = position_pda(&admin_asset.pubkey());
+
+    (admin, admin_asset, pos_pda, collection)
+}
+
+// ---------------------------------------------------------------------------
+// 1. test_create_promo — happy path
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_create_promo() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+
+    let name_suffix = "Test Promo";
+    let permissions: u16 = PERM_BUY | PERM_LIMITED_BORROW;
+    let borrow_capacity: u64 = 20_000_000;
+    let borrow_refill_period: u64 = 1000;
+    let sell_capacity: u64 = 0;
+    let sell_refill_period: u64 = 0;
+    let min_deposit: u64 = 10_000_000;
+    let max_claims: u32 = 100;
+    let image_uri = "https://example.com/img.png";
+
+    let ix = ix_create_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        name_suffix,
+        permissions,
+        borrow_capacity,
+        borrow_refill_period,
+        sell_capacity,
+        sell_refill_period,
+        0, 0,
+        min_deposit,
+        max_claims,
+        10_000, // initial_fill_bps: 100% full
+        image_uri,
+        "navSOL",
+    );
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+
+    // Verify the PromoConfig PDA
+    let authority_seed = admin_asset.pubkey();
+    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+    let promo = read_promo_config(&svm, &pda);
+
+    assert_eq!(promo.authority_seed, authority_seed);
+    assert_eq!(promo.permissions, permissions);
+    assert_eq!(promo.borrow_capacity, borrow_capacity);
+    assert_eq!(promo.borrow_refill_period, borrow_refill_period);
+    assert_eq!(promo.sell_capacity, sell_capacity);
+    assert_eq!(promo.sell_refill_period, sell_refill_period);
+    assert_eq!(promo.min_deposit_lamports, min_deposit);
+    assert_eq!(promo.max_claims, max_claims);
+    assert_eq!(promo.claims_count, 0);
+    assert!(promo.active);
+    assert_eq!(promo.name_suffix, name_suffix);
+    assert_eq!(promo.image_uri, image_uri);
+    assert_eq!(promo.market_name, "navSOL");
+}
+
+// ---------------------------------------------------------------------------
+// 2. test_create_promo_non_admin_rejected
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_create_promo_non_admin_rejected() {
+    let (mut svm, _) = setup();
+    let (_admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+
+    // A non-admin wallet tries to create a promo
+    let non_admin = Keypair::new();
+    svm.airdrop(&non_admin.pubkey(), 5_000_000_000).unwrap();
+
+    // Build the ix with non_admin as signer but still referencing the admin's position
+    let name_suffix = "Evil Promo";
+    let (pos_pda, _) = position_pda(&admin_asset.pubkey());
+    let authority_seed = admin_asset.pubkey();
+    let (promo, _) = promo_pda(&authority_seed, name_suffix);
+
+    let mut data = sighash("create_promo");
+    data.extend_from_slice(&(name_suffix.len() as u32).to_le_bytes());
+    data.extend_from_slice(name_suffix.as_bytes());
+    data.extend_from_slice(&PERM_BUY.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // borrow_capacity
+    data.extend_from_slice(&0u64.to_le_bytes()); // borrow_refill_period
+    data.extend_from_slice(&0u64.to_le_bytes()); // sell_capacity
+    data.extend_from_slice(&0u64.to_le_bytes()); // sell_refill_period
+    data.extend_from_slice(&0u64.to_le_bytes()); // total_borrow_limit
+    data.extend_from_slice(&0u64.to_le_bytes()); // total_sell_limit
+    data.extend_from_slice(&0u64.to_le_bytes()); // min_deposit_lamports
+    data.extend_from_slice(&10u32.to_le_bytes()); // max_claims
+    let uri = "";
+    data.extend_from_slice(&(uri.len() as u32).to_le_bytes());
+    let mn = "";
+    data.extend_from_slice(&(mn.len() as u32).to_le_bytes());
+
+    let ix = Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(non_admin.pubkey(), true),                        // admin (non-admin signer)
+            AccountMeta::new_readonly(admin_asset.pubkey(), false),             // admin_key_asset
+            AccountMeta::new_readonly(pos_pda, false),                         // position
+            AccountMeta::new(promo, false),                                    // promo (init)
+            AccountMeta::new_readonly(config_pda().0, false),                  // config
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    );
+
+    let result = send_tx(&mut svm, &[ix], &[&non_admin]);
+    assert!(result.is_err(), "non-admin should not be able to create promo");
+}
+
+// ---------------------------------------------------------------------------
+// 3. test_create_multiple_promos_per_position
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_create_multiple_promos_per_position() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+
+    let authority_seed = admin_asset.pubkey();
+
+    // Create promo A
+    let ix_a = ix_create_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        "Promo A",
+        PERM_BUY,
+        0, 0, 0, 0, 0, 0, 5_000_000, 50, 10_000, "", "navSOL",
+    );
+    send_tx(&mut svm, &[ix_a], &[&admin]).unwrap();
+
+    // Create promo B
+    let ix_b = ix_create_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        "Promo B",
+        PERM_BUY | PERM_LIMITED_BORROW,
+        10_000_000, 500, 0, 0, 0, 0, 1_000_000, 200, 10_000, "https://example.com/b.png", "navSOL",
+    );
+    send_tx(&mut svm, &[ix_b], &[&admin]).unwrap();
+
+    // Verify promo A
+    let (pda_a, _) = promo_pda(&authority_seed, "Promo A");
+    let promo_a = read_promo_config(&svm, &pda_a);
+    assert_eq!(promo_a.name_suffix, "Promo A");
+    assert_eq!(promo_a.permissions, PERM_BUY);
+    assert_eq!(promo_a.max_claims, 50);
+    assert!(promo_a.active);
+
+    // Verify promo B
+    let (pda_b, _) = promo_pda(&authority_seed, "Promo B");
+    let promo_b = read_promo_config(&svm, &pda_b);
+    assert_eq!(promo_b.name_suffix, "Promo B");
+    assert_eq!(promo_b.permissions, PERM_BUY | PERM_LIMITED_BORROW);
+    assert_eq!(promo_b.borrow_capacity, 10_000_000);
+    assert_eq!(promo_b.max_claims, 200);
+    assert_eq!(promo_b.image_uri, "https://example.com/b.png");
+}
+
+// ---------------------------------------------------------------------------
+// 4. test_update_promo
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_update_promo() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+
+    let authority_seed = admin_asset.pubkey();
+    let name_suffix = "Update Me";
+
+    // Create promo
+    let ix = ix_create_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        name_suffix,
+        PERM_BUY,
+        0, 0, 0, 0, 0, 0, 0, 100, 10_000, "", "",
+    );
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+
+    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+
+    // Update: set active=false
+    let ix_deactivate = ix_update_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        &pda,
+        Some(false),
+        None,
+    );
+    send_tx(&mut svm, &[ix_deactivate], &[&admin]).unwrap();
+    let promo = read_promo_config(&svm, &pda);
+    assert!(!promo.active, "promo should be inactive after update");
+
+    // Update: set max_claims=50
+    let ix_max = ix_update_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        &pda,
+        None,
+        Some(50),
+    );
+    send_tx(&mut svm, &[ix_max], &[&admin]).unwrap();
+    let promo = read_promo_config(&svm, &pda);
+    assert_eq!(promo.max_claims, 50);
+
+    // Re-activate
+    let ix_reactivate = ix_update_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        &pda,
+        Some(true),
+        None,
+    );
+    send_tx(&mut svm, &[ix_reactivate], &[&admin]).unwrap();
+    let promo = read_promo_config(&svm, &pda);
+    assert!(promo.active, "promo should be active after re-activation");
+}
+
+// ---------------------------------------------------------------------------
+// 5. test_update_promo_max_below_current_rejected
+// ---------------------------------------------------------------------------
+
+fn ix_migrate_promo(payer: &Pubkey, promo: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("migrate_promo"),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*promo, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
         ],
     )
 }
 
-// ---------------------------------------------------------------------------
-// Promo test helpers
-// ---------------------------------------------------------------------------
+/// Rewrite a promo in the v0 layout (345 bytes, u8 permissions).
+fn plant_v0_promo(svm: &mut LiteSVM, pda: &Pubkey, promo: &PromoConfig) {
+    let mut data = PromoConfig::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(promo.authority_seed.as_ref());
+    data.push(promo.permissions as u8);
+    for v in [
+        promo.borrow_capacity,
+        promo.borrow_refill_period,
+        promo.sell_capacity,
+        promo.sell_refill_period,
+        promo.min_deposit_lamports,
+    ] {
+        data.extend_from_slice(&v.to_le_bytes());
+    }
+    data.extend_from_slice(&promo.max_claims.to_le_bytes());
+    data.extend_from_slice(&promo.claims_count.to_le_bytes());
+    data.push(promo.active as u8);
+    data.extend_from_slice(&promo.total_borrow_limit.to_le_bytes());
+    data.extend_from_slice(&promo.total_sell_limit.to_le_bytes());
+    data.extend_from_slice(&promo.initial_fill_bps.to_le_bytes());
+    for s in [&promo.name_suffix, &promo.image_uri, &promo.market_name] {
+        data.extend_from_slice(&(s.len() as u32).to_le_bytes());
+        data.extend_from_slice(s.as_bytes());
+    }
+    data.push(promo.bump);
+    data.resize(345, 0);
+
+    let mut account = svm.get_account(pda).unwrap();
+    account.lamports = svm.minimum_balance_for_rent_exemption(345);
+    account.data = data;
+    svm.set_account(*pda, account).unwrap();
+}
+
+#[test]
+fn test_migrate_promo_from_baseline_layout() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+    let name_suffix = "Old Promo";
 
-/// Minimal setup: init protocol + collection + market config + position.
-/// Returns (admin, admin_asset keypair, position_pda, collection pubkey).
-fn promo_setup(svm: &mut LiteSVM) -> (Keypair, Keypair, Pubkey, Pubkey) {
-    let admin = Keypair::new();
-    svm.airdrop(&admin.pubkey(), 10_000_000_000).unwrap();
+    let ix = ix_create_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        name_suffix,
+        PERM_BUY | PERM_LIMITED_BORROW,
+        100_000, 1_000, 0, 0, 500_000, 0, 10_000, 25, 5_000, "https://x/img.png", "navSOL",
+    );
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), name_suffix);
+    let before = read_promo_config(&svm, &pda);
 
-    // Init protocol
-    send_tx(svm, &[ix_init_protocol(&admin.pubkey())], &[&admin]).unwrap();
+    let payer = Keypair::new();
+    svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+    assert!(send_tx(&mut svm, &[ix_migrate_promo(&payer.pubkey(), &pda)], &[&payer]).is_err());
 
-    // Create collection
-    let (coll_ix, coll_kp) = ix_create_collection(&admin.pubkey());
-    send_tx(svm, &[coll_ix], &[&admin, &coll_kp]).unwrap();
-    let collection = coll_kp.pubkey();
+    plant_v0_promo(&mut svm, &pda, &before);
+    let ix = ix_update_promo(&admin.pubkey(), &admin_asset.pubkey(), &pda, None, Some(50));
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
 
-    // Create MarketConfig
-    send_tx(svm, &[ix_create_market_config(&admin.pubkey())], &[&admin]).unwrap();
+    send_tx(&mut svm, &[ix_migrate_promo(&payer.pubkey(), &pda)], &[&payer]).unwrap();
 
-    // Create position
-    let admin_asset = Keypair::new();
-    plant_position_stubs(svm, &admin_asset.pubkey());
-    send_tx(
-        svm,
-        &[ix_create_position(
-            &admin.pubkey(),
-            &admin_asset.pubkey(),
-            &collection,
-        )],
-        &[&admin, &admin_asset],
-    )
-    .unwrap();
+    let account = svm.get_account(&pda).unwrap();
+    assert_eq!(account.data.len(), PromoConfig::SIZE);
+    assert!(account.lamports >= svm.minimum_balance_for_rent_exemption(PromoConfig::SIZE));
+    let promo = read_promo_config(&svm, &pda);
+    assert_eq!(promo.authority_seed, before.authority_seed);
+    assert_eq!(promo.permissions, PERM_BUY | PERM_LIMITED_BORROW);
+    assert_eq!(promo.borrow_capacity, 100_000);
+    assert_eq!(promo.borrow_refill_period, 1_000);
+    assert_eq!(promo.total_borrow_limit, 500_000);
+    assert_eq!(promo.min_deposit_lamports, 10_000);
+    assert_eq!(promo.max_claims, 25);
+    assert_eq!(promo.initial_fill_bps, 5_000);
+    assert!(promo.active);
+    assert_eq!(promo.name_suffix, name_suffix);
+    assert_eq!(promo.image_uri, "https://x/img.png");
+    assert_eq!(promo.market_name, "navSOL");
+    assert_eq!(promo.bump, before.bump);
+    assert_eq!(promo.key_expires_at, 0);
+    assert_eq!(promo.merkle_root, [0u8; 32]);
+    assert_eq!(promo.gate_collection, Pubkey::default());
+    assert_eq!((promo.starts_at, promo.ends_at), (0, 0));
+    assert_eq!(promo.budget_borrow_limit, 0);
+    assert_eq!(promo.referral_reward, ReferralReward::default());
+    assert!(!promo.refundable_principal);
+
+    let ix = ix_update_promo(&admin.pubkey(), &admin_asset.pubkey(), &pda, None, Some(50));
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    assert_eq!(read_promo_config(&svm, &pda).max_claims, 50);
+    assert!(send_tx(&mut svm, &[ix_migrate_promo(&payer.pubkey(), &pda)], &[&payer]).is_err());
+}
 
-    let (pos_pda, _) = position_pda(&admin_asset.pubkey());
+#[test]
+fn test_update_promo_max_below_current_rejected() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
 
-    (admin, admin_asset, pos_pda, collection)
+    let authority_seed = admin_asset.pubkey();
+    let name_suffix = "Max Test";
+
+    // Create promo with max_claims=0 (unlimited) to allow claiming
+    let ix = ix_create_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        name_suffix,
+        PERM_BUY,
+        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "",
+    );
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+
+    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+
+    // Claim once to increment claims_count to 1
+    let claimer = Keypair::new();
+    svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
+    let key_asset = Keypair::new();
+    let ix_claim = ix_claim_promo_key(
+        &claimer.pubkey(),
+        &pda,
+        &admin_asset.pubkey(),
+        &key_asset.pubkey(),
+        &collection,
+        0, // min_deposit_lamports = 0 for this promo
+    );
+    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
+
+    // Verify claims_count is now 1
+    let promo = read_promo_config(&svm, &pda);
+    assert_eq!(promo.claims_count, 1);
+
+    // Try to set max_claims=0 — this is unlimited, so it should succeed
+    // (0 means unlimited, which is >= any claims_count)
+    // Actually, looking at the code: require!(max_claims >= promo.claims_count)
+    // 0 < 1, so this should fail. 0 means "unlimited" semantically, but the check is numeric.
+    let ix_set_zero = ix_update_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        &pda,
+        None,
+        Some(0),
+    );
+    let result = send_tx(&mut svm, &[ix_set_zero], &[&admin]);
+    assert!(result.is_err(), "setting max_claims below claims_count should fail");
 }
 
 // ---------------------------------------------------------------------------
-// 1. test_create_promo — happy path
+// 6. test_claim_promo_key — happy path
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_create_promo() {
+fn test_claim_promo_key() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
 
-    let name_suffix = "Test Promo";
-    let permissions: u8 = PERM_BUY | PERM_LIMITED_BORROW;
+    let authority_seed = admin_asset.pubkey();
+    let name_suffix = "Claim Me";
+    let permissions: u16 = PERM_BUY | PERM_LIMITED_BORROW;
     let borrow_capacity: u64 = 20_000_000;
     let borrow_refill_period: u64 = 1000;
-    let sell_capacity: u64 = 0;
-    let sell_refill_period: u64 = 0;
-    let min_deposit: u64 = 10_000_000;
-    let max_claims: u32 = 100;
-    let image_uri = "https://example.com/img.png";
 
+    // Create promo
     let ix = ix_create_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
@@ -3835,293 +6872,398 @@ fn test_create_promo() {
         permissions,
         borrow_capacity,
         borrow_refill_period,
-        sell_capacity,
-        sell_refill_period,
         0, 0,
-        min_deposit,
-        max_claims,
+        40_000_000, 0, // total_borrow_limit
+        10_000_000,
+        100,
         10_000, // initial_fill_bps: 100% full
-        image_uri,
+        "https://example.com/img.png",
         "navSOL",
     );
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
 
-    // Verify the PromoConfig PDA
-    let authority_seed = admin_asset.pubkey();
     let (pda, _) = promo_pda(&authority_seed, name_suffix);
+
+    // Claimer claims a key
+    let claimer = Keypair::new();
+    svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
+    let key_asset = Keypair::new();
+
+    let ix_claim = ix_claim_promo_key(
+        &claimer.pubkey(),
+        &pda,
+        &admin_asset.pubkey(),
+        &key_asset.pubkey(),
+        &collection,
+        10_000_000, // amount = min_deposit_lamports
+    );
+    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
+
+    // Verify ClaimReceipt PDA was created
+    let (receipt_pda, _) = claim_receipt_pda(&pda, &claimer.pubkey());
+    let receipt = read_claim_receipt(&svm, &receipt_pda);
+    assert_eq!(receipt.claimer, claimer.pubkey());
+    assert_eq!(receipt.promo, pda);
+
+    // Verify KeyState PDA was created with correct rate limits and authority_seed
+    let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
+    let ks = read_key_state(&svm, &ks_pda);
+    assert_eq!(ks.asset, key_asset.pubkey());
+    assert_eq!(ks.authority_seed, authority_seed);
+    assert_eq!(ks.borrow_bucket.capacity, borrow_capacity);
+    assert_eq!(ks.borrow_bucket.refill_period, borrow_refill_period);
+    assert_eq!(ks.borrow_bucket.level, borrow_capacity); // starts full
+    // No sell bucket configured
+    assert_eq!(ks.sell_bucket.capacity, 0);
+
+    // Verify claims_count incremented
     let promo = read_promo_config(&svm, &pda);
+    assert_eq!(promo.claims_count, 1);
 
-    assert_eq!(promo.authority_seed, authority_seed);
-    assert_eq!(promo.permissions, permissions);
-    assert_eq!(promo.borrow_capacity, borrow_capacity);
-    assert_eq!(promo.borrow_refill_period, borrow_refill_period);
-    assert_eq!(promo.sell_capacity, sell_capacity);
-    assert_eq!(promo.sell_refill_period, sell_refill_period);
-    assert_eq!(promo.min_deposit_lamports, min_deposit);
-    assert_eq!(promo.max_claims, max_claims);
-    assert_eq!(promo.claims_count, 0);
-    assert!(promo.active);
-    assert_eq!(promo.name_suffix, name_suffix);
-    assert_eq!(promo.image_uri, image_uri);
-    assert_eq!(promo.market_name, "navSOL");
+    // Verify the key NFT exists (account should be owned by MPL-Core)
+    let key_account = svm.get_account(&key_asset.pubkey()).unwrap();
+    assert_eq!(key_account.owner, MPL_CORE_ID);
+
+    // Verify the key NFT has a promo attribute matching the promo PDA
+    let attrs = extract_asset_attributes(&key_account);
+    let promo_attr = attrs.iter().find(|(k, _)| k == "promo");
+    assert!(promo_attr.is_some(), "key NFT should have a promo attribute");
+    assert_eq!(promo_attr.unwrap().1, pda.to_string());
+    assert_eq!(find_attribute(&attrs, "limited_borrow"), Some("0.02 SOL / 6 minutes"));
+    assert_eq!(find_attribute(&attrs, "total_borrow_limit"), Some("0.04 SOL"));
+    assert_eq!(find_attribute(&attrs, "limited_sell"), None);
 }
 
 // ---------------------------------------------------------------------------
-// 2. test_create_promo_non_admin_rejected
+// 7. test_claim_promo_key_duplicate_rejected
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_create_promo_non_admin_rejected() {
+fn test_claim_promo_key_duplicate_rejected() {
     let (mut svm, _) = setup();
-    let (_admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
-
-    // A non-admin wallet tries to create a promo
-    let non_admin = Keypair::new();
-    svm.airdrop(&non_admin.pubkey(), 5_000_000_000).unwrap();
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
 
-    // Build the ix with non_admin as signer but still referencing the admin's position
-    let name_suffix = "Evil Promo";
-    let (pos_pda, _) = position_pda(&admin_asset.pubkey());
     let authority_seed = admin_asset.pubkey();
-    let (promo, _) = promo_pda(&authority_seed, name_suffix);
+    let name_suffix = "No Dupes";
 
-    let mut data = sighash("create_promo");
-    data.extend_from_slice(&(name_suffix.len() as u32).to_le_bytes());
-    data.extend_from_slice(name_suffix.as_bytes());
-    data.push(PERM_BUY);
-    data.extend_from_slice(&0u64.to_le_bytes()); // borrow_capacity
-    data.extend_from_slice(&0u64.to_le_bytes()); // borrow_refill_period
-    data.extend_from_slice(&0u64.to_le_bytes()); // sell_capacity
-    data.extend_from_slice(&0u64.to_le_bytes()); // sell_refill_period
-    data.extend_from_slice(&0u64.to_le_bytes()); // total_borrow_limit
-    data.extend_from_slice(&0u64.to_le_bytes()); // total_sell_limit
-    data.extend_from_slice(&0u64.to_le_bytes()); // min_deposit_lamports
-    data.extend_from_slice(&10u32.to_le_bytes()); // max_claims
-    let uri = "";
-    data.extend_from_slice(&(uri.len() as u32).to_le_bytes());
-    let mn = "";
-    data.extend_from_slice(&(mn.len() as u32).to_le_bytes());
+    let ix = ix_create_promo(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        name_suffix,
+        PERM_BUY,
+        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "",
+    );
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
 
-    let ix = Instruction::new_with_bytes(
-        program_id(),
-        &data,
-        vec![
-            AccountMeta::new(non_admin.pubkey(), true),                        // admin (non-admin signer)
-            AccountMeta::new_readonly(admin_asset.pubkey(), false),             // admin_key_asset
-            AccountMeta::new_readonly(pos_pda, false),                         // position
-            AccountMeta::new(promo, false),                                    // promo (init)
-            AccountMeta::new_readonly(config_pda().0, false),                  // config
-            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
-        ],
+    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+
+    // First claim succeeds
+    let claimer = Keypair::new();
+    svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
+    let key_asset_1 = Keypair::new();
+    let ix_claim_1 = ix_claim_promo_key(
+        &claimer.pubkey(),
+        &pda,
+        &admin_asset.pubkey(),
+        &key_asset_1.pubkey(),
+        &collection,
+        0, // min_deposit_lamports = 0 for this promo
     );
+    send_tx(&mut svm, &[ix_claim_1], &[&claimer, &key_asset_1]).unwrap();
 
-    let result = send_tx(&mut svm, &[ix], &[&non_admin]);
-    assert!(result.is_err(), "non-admin should not be able to create promo");
+    // Second claim from same wallet should fail (ClaimReceipt PDA collision)
+    let key_asset_2 = Keypair::new();
+    let ix_claim_2 = ix_claim_promo_key(
+        &claimer.pubkey(),
+        &pda,
+        &admin_asset.pubkey(),
+        &key_asset_2.pubkey(),
+        &collection,
+        0,
+    );
+    let result = send_tx(&mut svm, &[ix_claim_2], &[&claimer, &key_asset_2]);
+    assert!(result.is_err(), "duplicate claim from same wallet should fail");
 }
 
 // ---------------------------------------------------------------------------
-// 3. test_create_multiple_promos_per_position
+// 8. test_claim_promo_key_inactive_rejected
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_create_multiple_promos_per_position() {
+fn test_claim_promo_key_inactive_rejected() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
 
     let authority_seed = admin_asset.pubkey();
+    let name_suffix = "Paused";
 
-    // Create promo A
-    let ix_a = ix_create_promo(
+    // Create promo
+    let ix = ix_create_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
-        "Promo A",
+        name_suffix,
         PERM_BUY,
-        0, 0, 0, 0, 0, 0, 5_000_000, 50, 10_000, "", "navSOL",
+        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "",
     );
-    send_tx(&mut svm, &[ix_a], &[&admin]).unwrap();
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
 
-    // Create promo B
-    let ix_b = ix_create_promo(
+    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+
+    // Deactivate promo
+    let ix_deactivate = ix_update_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
-        "Promo B",
-        PERM_BUY | PERM_LIMITED_BORROW,
-        10_000_000, 500, 0, 0, 0, 0, 1_000_000, 200, 10_000, "https://example.com/b.png", "navSOL",
+        &pda,
+        Some(false),
+        None,
     );
-    send_tx(&mut svm, &[ix_b], &[&admin]).unwrap();
-
-    // Verify promo A
-    let (pda_a, _) = promo_pda(&authority_seed, "Promo A");
-    let promo_a = read_promo_config(&svm, &pda_a);
-    assert_eq!(promo_a.name_suffix, "Promo A");
-    assert_eq!(promo_a.permissions, PERM_BUY);
-    assert_eq!(promo_a.max_claims, 50);
-    assert!(promo_a.active);
+    send_tx(&mut svm, &[ix_deactivate], &[&admin]).unwrap();
 
-    // Verify promo B
-    let (pda_b, _) = promo_pda(&authority_seed, "Promo B");
-    let promo_b = read_promo_config(&svm, &pda_b);
-    assert_eq!(promo_b.name_suffix, "Promo B");
-    assert_eq!(promo_b.permissions, PERM_BUY | PERM_LIMITED_BORROW);
-    assert_eq!(promo_b.borrow_capacity, 10_000_000);
-    assert_eq!(promo_b.max_claims, 200);
-    assert_eq!(promo_b.image_uri, "https://example.com/b.png");
+    // Try to claim — should fail with PromoInactive
+    let claimer = Keypair::new();
+    svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
+    let key_asset = Keypair::new();
+    let ix_claim = ix_claim_promo_key(
+        &claimer.pubkey(),
+        &pda,
+        &admin_asset.pubkey(),
+        &key_asset.pubkey(),
+        &collection,
+        0, // min_deposit_lamports = 0 for this promo
+    );
+    let result = send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]);
+    assert!(result.is_err(), "claiming from inactive promo should fail");
 }
 
 // ---------------------------------------------------------------------------
-// 4. test_update_promo
+// 9. test_claim_promo_key_max_claims_reached
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_update_promo() {
+fn test_claim_promo_key_max_claims_reached() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
 
     let authority_seed = admin_asset.pubkey();
-    let name_suffix = "Update Me";
+    let name_suffix = "Max One";
 
-    // Create promo
+    // Create promo with max_claims=1
     let ix = ix_create_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
         name_suffix,
         PERM_BUY,
-        0, 0, 0, 0, 0, 0, 0, 100, 10_000, "", "",
+        0, 0, 0, 0, 0, 0, 0, 1, 10_000, "", "",
     );
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
 
     let (pda, _) = promo_pda(&authority_seed, name_suffix);
 
-    // Update: set active=false
-    let ix_deactivate = ix_update_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
+    // Wallet A claims successfully
+    let claimer_a = Keypair::new();
+    svm.airdrop(&claimer_a.pubkey(), 5_000_000_000).unwrap();
+    let key_a = Keypair::new();
+    let ix_a = ix_claim_promo_key(
+        &claimer_a.pubkey(),
         &pda,
-        Some(false),
-        None,
+        &admin_asset.pubkey(),
+        &key_a.pubkey(),
+        &collection,
+        0, // min_deposit_lamports = 0 for this promo
     );
-    send_tx(&mut svm, &[ix_deactivate], &[&admin]).unwrap();
+    send_tx(&mut svm, &[ix_a], &[&claimer_a, &key_a]).unwrap();
+
+    // Verify claims_count is 1
     let promo = read_promo_config(&svm, &pda);
-    assert!(!promo.active, "promo should be inactive after update");
+    assert_eq!(promo.claims_count, 1);
 
-    // Update: set max_claims=50
-    let ix_max = ix_update_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
+    // Wallet B tries to claim — should fail with PromoMaxClaimsReached
+    let claimer_b = Keypair::new();
+    svm.airdrop(&claimer_b.pubkey(), 5_000_000_000).unwrap();
+    let key_b = Keypair::new();
+    let ix_b = ix_claim_promo_key(
+        &claimer_b.pubkey(),
         &pda,
-        None,
-        Some(50),
+        &admin_asset.pubkey(),
+        &key_b.pubkey(),
+        &collection,
+        0,
     );
-    send_tx(&mut svm, &[ix_max], &[&admin]).unwrap();
-    let promo = read_promo_config(&svm, &pda);
-    assert_eq!(promo.max_claims, 50);
+    let result = send_tx(&mut svm, &[ix_b], &[&claimer_b, &key_b]);
+    assert!(result.is_err(), "claim should fail when max_claims reached");
+}
 
-    // Re-activate
-    let ix_reactivate = ix_update_promo(
+// ---------------------------------------------------------------------------
+// 10. test_promo_key_can_buy — end-to-end: claim promo key, then buy with it
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_promo_key_can_buy() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, pos_pda, collection) = promo_setup(&mut svm);
+
+    let authority_seed = admin_asset.pubkey();
+    let name_suffix = "Buy Promo";
+    let permissions: u16 = PERM_BUY | PERM_LIMITED_BORROW;
+
+    // Create promo
+    let ix = ix_create_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
+        name_suffix,
+        permissions,
+        20_000_000, // borrow_capacity
+        1000,       // borrow_refill_period
+        0, 0,       // no sell limits
+        0, 0,       // no total limits
+        0,          // min_deposit_lamports = 0 (allow any amount)
+        100,
+        10_000, // initial_fill_bps: 100% full
+        "",
+        "navSOL",
+    );
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+
+    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+
+    // Claimer claims a promo key with amount=0 (no deposit at claim time)
+    let claimer = Keypair::new();
+    svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
+    let key_asset = Keypair::new();
+
+    let ix_claim = ix_claim_promo_key(
+        &claimer.pubkey(),
         &pda,
-        Some(true),
-        None,
+        &admin_asset.pubkey(),
+        &key_asset.pubkey(),
+        &collection,
+        0,
     );
-    send_tx(&mut svm, &[ix_reactivate], &[&admin]).unwrap();
-    let promo = read_promo_config(&svm, &pda);
-    assert!(promo.active, "promo should be active after re-activation");
+    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
+
+    // Verify position deposited_nav is 0 before buy
+    let pos = read_position(&svm, &pos_pda);
+    assert_eq!(pos.deposited_nav, 0, "deposited_nav should be 0 before buy");
+
+    // Now use the promo key to call buy
+    let buy_ix = ix_buy(
+        &claimer.pubkey(),
+        &key_asset.pubkey(),
+        &pos_pda,
+        &admin_asset.pubkey(),
+        1_000_000,
+    );
+    send_tx(&mut svm, &[buy_ix], &[&claimer]).unwrap();
+
+    // Verify deposited_nav increased (mock Mayflower does 1:1)
+    let pos = read_position(&svm, &pos_pda);
+    assert_eq!(pos.deposited_nav, 1_000_000, "deposited_nav should reflect the buy");
 }
 
 // ---------------------------------------------------------------------------
-// 5. test_update_promo_max_below_current_rejected
+// 11. test_promo_key_cannot_sell — promo key with BUY perm denied on withdraw
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_update_promo_max_below_current_rejected() {
+fn test_promo_key_cannot_sell() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
+    let (admin, admin_asset, pos_pda, collection) = promo_setup(&mut svm);
 
     let authority_seed = admin_asset.pubkey();
-    let name_suffix = "Max Test";
+    let name_suffix = "No Sell";
+    let permissions: u16 = PERM_BUY | PERM_LIMITED_BORROW;
 
-    // Create promo with max_claims=0 (unlimited) to allow claiming
+    // Create promo
     let ix = ix_create_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
         name_suffix,
-        PERM_BUY,
-        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "",
+        permissions,
+        20_000_000,
+        1000,
+        0, 0,
+        0, 0,
+        0,
+        100,
+        10_000, // initial_fill_bps: 100% full
+        "",
+        "navSOL",
     );
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
 
     let (pda, _) = promo_pda(&authority_seed, name_suffix);
 
-    // Claim once to increment claims_count to 1
+    // Admin buys first so there's something to sell
+    let buy_ix = ix_buy(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        &pos_pda,
+        &admin_asset.pubkey(),
+        1_000_000,
+    );
+    send_tx(&mut svm, &[buy_ix], &[&admin]).unwrap();
+
+    // Claimer claims a promo key
     let claimer = Keypair::new();
     svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
     let key_asset = Keypair::new();
+
     let ix_claim = ix_claim_promo_key(
         &claimer.pubkey(),
         &pda,
         &admin_asset.pubkey(),
         &key_asset.pubkey(),
         &collection,
-        0, // min_deposit_lamports = 0 for this promo
+        0,
     );
     send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
 
-    // Verify claims_count is now 1
-    let promo = read_promo_config(&svm, &pda);
-    assert_eq!(promo.claims_count, 1);
-
-    // Try to set max_claims=0 — this is unlimited, so it should succeed
-    // (0 means unlimited, which is >= any claims_count)
-    // Actually, looking at the code: require!(max_claims >= promo.claims_count)
-    // 0 < 1, so this should fail. 0 means "unlimited" semantically, but the check is numeric.
-    let ix_set_zero = ix_update_promo(
-        &admin.pubkey(),
+    // Try to sell (withdraw) using the promo key — should fail
+    let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
+    let sell_ix = ix_withdraw(
+        &claimer.pubkey(),
+        &key_asset.pubkey(),
+        Some(&ks_pda),
+        &pos_pda,
         &admin_asset.pubkey(),
-        &pda,
-        None,
-        Some(0),
+        500_000,
     );
-    let result = send_tx(&mut svm, &[ix_set_zero], &[&admin]);
-    assert!(result.is_err(), "setting max_claims below claims_count should fail");
+    let result = send_tx(&mut svm, &[sell_ix], &[&claimer]);
+    assert!(result.is_err(), "promo key without SELL permission should not be able to withdraw");
 }
 
 // ---------------------------------------------------------------------------
-// 6. test_claim_promo_key — happy path
+// 12. test_claim_promo_below_min_deposit_rejected
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_claim_promo_key() {
+fn test_claim_promo_below_min_deposit_rejected() {
     let (mut svm, _) = setup();
     let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
 
-    let authority_seed = admin_asset.pubkey();
-    let name_suffix = "Claim Me";
-    let permissions: u8 = PERM_BUY | PERM_LIMITED_BORROW;
-    let borrow_capacity: u64 = 20_000_000;
-    let borrow_refill_period: u64 = 1000;
+    let authority_seed = admin_asset.pubkey();
+    let name_suffix = "Min Deposit";
+    let min_deposit: u64 = 10_000_000;
 
-    // Create promo
+    // Create promo with min_deposit_lamports = 10_000_000
     let ix = ix_create_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
         name_suffix,
-        permissions,
-        borrow_capacity,
-        borrow_refill_period,
-        0, 0,
+        PERM_BUY,
+        0, 0, 0, 0,
         0, 0,
-        10_000_000,
+        min_deposit,
         100,
         10_000, // initial_fill_bps: 100% full
-        "https://example.com/img.png",
+        "",
         "navSOL",
     );
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
 
     let (pda, _) = promo_pda(&authority_seed, name_suffix);
 
-    // Claimer claims a key
+    // Claim with amount = 5_000_000, below min_deposit_lamports
     let claimer = Keypair::new();
     svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
     let key_asset = Keypair::new();
@@ -4132,104 +7274,82 @@ fn test_claim_promo_key() {
         &admin_asset.pubkey(),
         &key_asset.pubkey(),
         &collection,
-        10_000_000, // amount = min_deposit_lamports
+        5_000_000, // below min_deposit
     );
-    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
-
-    // Verify ClaimReceipt PDA was created
-    let (receipt_pda, _) = claim_receipt_pda(&pda, &claimer.pubkey());
-    let receipt = read_claim_receipt(&svm, &receipt_pda);
-    assert_eq!(receipt.claimer, claimer.pubkey());
-    assert_eq!(receipt.promo, pda);
-
-    // Verify KeyState PDA was created with correct rate limits and authority_seed
-    let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
-    let ks = read_key_state(&svm, &ks_pda);
-    assert_eq!(ks.asset, key_asset.pubkey());
-    assert_eq!(ks.authority_seed, authority_seed);
-    assert_eq!(ks.borrow_bucket.capacity, borrow_capacity);
-    assert_eq!(ks.borrow_bucket.refill_period, borrow_refill_period);
-    assert_eq!(ks.borrow_bucket.level, borrow_capacity); // starts full
-    // No sell bucket configured
-    assert_eq!(ks.sell_bucket.capacity, 0);
-
-    // Verify claims_count incremented
-    let promo = read_promo_config(&svm, &pda);
-    assert_eq!(promo.claims_count, 1);
-
-    // Verify the key NFT exists (account should be owned by MPL-Core)
-    let key_account = svm.get_account(&key_asset.pubkey()).unwrap();
-    assert_eq!(key_account.owner, MPL_CORE_ID);
-
-    // Verify the key NFT has a promo attribute matching the promo PDA
-    let attrs = extract_asset_attributes(&key_account);
-    let promo_attr = attrs.iter().find(|(k, _)| k == "promo");
-    assert!(promo_attr.is_some(), "key NFT should have a promo attribute");
-    assert_eq!(promo_attr.unwrap().1, pda.to_string());
+    let result = send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]);
+    assert!(result.is_err(), "claim with amount below min_deposit should fail");
 }
 
 // ---------------------------------------------------------------------------
-// 7. test_claim_promo_key_duplicate_rejected
+// 13. test_claim_promo_deposited_nav_updated
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_claim_promo_key_duplicate_rejected() {
+fn test_claim_promo_deposited_nav_updated() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
+    let (admin, admin_asset, pos_pda, collection) = promo_setup(&mut svm);
 
     let authority_seed = admin_asset.pubkey();
-    let name_suffix = "No Dupes";
+    let name_suffix = "Deposit Nav";
 
+    // Create promo with min_deposit = 0 to allow any amount
     let ix = ix_create_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
         name_suffix,
         PERM_BUY,
-        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "",
+        0, 0, 0, 0,
+        0, 0,
+        0, // no minimum
+        100,
+        10_000, // initial_fill_bps: 100% full
+        "",
+        "navSOL",
     );
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
 
     let (pda, _) = promo_pda(&authority_seed, name_suffix);
 
-    // First claim succeeds
+    // Verify deposited_nav starts at 0
+    let pos_before = read_position(&svm, &pos_pda);
+    assert_eq!(pos_before.deposited_nav, 0);
+
+    // Claim with amount = 2_000_000
     let claimer = Keypair::new();
     svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
-    let key_asset_1 = Keypair::new();
-    let ix_claim_1 = ix_claim_promo_key(
+    let key_asset = Keypair::new();
+    let deposit_amount: u64 = 2_000_000;
+
+    let ix_claim = ix_claim_promo_key(
         &claimer.pubkey(),
         &pda,
         &admin_asset.pubkey(),
-        &key_asset_1.pubkey(),
+        &key_asset.pubkey(),
         &collection,
-        0, // min_deposit_lamports = 0 for this promo
+        deposit_amount,
     );
-    send_tx(&mut svm, &[ix_claim_1], &[&claimer, &key_asset_1]).unwrap();
+    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
 
-    // Second claim from same wallet should fail (ClaimReceipt PDA collision)
-    let key_asset_2 = Keypair::new();
-    let ix_claim_2 = ix_claim_promo_key(
-        &claimer.pubkey(),
-        &pda,
-        &admin_asset.pubkey(),
-        &key_asset_2.pubkey(),
-        &collection,
-        0,
+    // Verify deposited_nav increased by deposit_amount (mock Mayflower does 1:1)
+    let pos_after = read_position(&svm, &pos_pda);
+    assert_eq!(
+        pos_after.deposited_nav, deposit_amount,
+        "deposited_nav should increase by the deposited amount (1:1 mock)"
     );
-    let result = send_tx(&mut svm, &[ix_claim_2], &[&claimer, &key_asset_2]);
-    assert!(result.is_err(), "duplicate claim from same wallet should fail");
 }
 
 // ---------------------------------------------------------------------------
-// 8. test_claim_promo_key_inactive_rejected
+// ---------------------------------------------------------------------------
+// 14. test_update_promo_non_admin_rejected
 // ---------------------------------------------------------------------------
 
 #[test]
-fn test_claim_promo_key_inactive_rejected() {
+fn test_update_promo_non_admin_rejected() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
+    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
 
     let authority_seed = admin_asset.pubkey();
-    let name_suffix = "Paused";
+    let name_suffix = "No Touch";
 
     // Create promo
     let ix = ix_create_promo(
@@ -4237,415 +7357,1017 @@ fn test_claim_promo_key_inactive_rejected() {
         &admin_asset.pubkey(),
         name_suffix,
         PERM_BUY,
-        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "",
+        0, 0, 0, 0, 0, 0, 0, 100, 10_000, "", "",
     );
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
 
     let (pda, _) = promo_pda(&authority_seed, name_suffix);
 
-    // Deactivate promo
-    let ix_deactivate = ix_update_promo(
+    // Non-admin tries to update the promo
+    let non_admin = Keypair::new();
+    svm.airdrop(&non_admin.pubkey(), 5_000_000_000).unwrap();
+
+    // Build update_promo ix with non_admin as signer but referencing admin's asset
+    let (pos_pda, _) = position_pda(&admin_asset.pubkey());
+    let mut data = sighash("update_promo");
+    // active = Some(false)
+    data.push(1); // Some
+    data.push(0); // false
+    // max_claims = None
+    data.push(0);
+    // merkle_root = None
+    data.push(0);
+
+    let ix_update = Instruction::new_with_bytes(
+        program_id(),
+        &data,
+        vec![
+            AccountMeta::new(non_admin.pubkey(), true),            // admin (non-admin signer)
+            AccountMeta::new_readonly(admin_asset.pubkey(), false), // admin_key_asset
+            AccountMeta::new_readonly(pos_pda, false),             // position
+            AccountMeta::new(pda, false),                          // promo (mut)
+            AccountMeta::new_readonly(config_pda().0, false),      // config
+        ],
+    );
+
+    let result = send_tx(&mut svm, &[ix_update], &[&non_admin]);
+    assert!(result.is_err(), "non-admin should not be able to update promo");
+}
+
+// ---------------------------------------------------------------------------
+// 16. test_create_promo_invalid_permissions_rejected
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_create_promo_invalid_permissions_rejected() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+
+    // Try to create a promo with PERM_SELL, which is not in KeyCreatorOrigin::Promo's allowed set.
+    // Promo only allows PERM_BUY | PERM_LIMITED_BORROW.
+    let ix = ix_create_promo(
         &admin.pubkey(),
         &admin_asset.pubkey(),
-        &pda,
-        Some(false),
-        None,
+        "Bad Perms",
+        PERM_SELL, // invalid for promo origin
+        0, 0, 0, 0, 0, 0, 0, 100, 10_000, "", "",
     );
-    send_tx(&mut svm, &[ix_deactivate], &[&admin]).unwrap();
+    let result = send_tx(&mut svm, &[ix], &[&admin]);
+    assert!(result.is_err(), "creating promo with PERM_SELL should fail (not in Promo allowed set)");
+}
+
+// ---------------------------------------------------------------------------
+// test_claim_promo_key_with_expiry
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_claim_promo_key_with_expiry() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
+
+    let name_suffix = "Short Lived";
+    let key_expires_at = now(&svm) + 86_400;
+    let ix = ix_create_promo_expiring(
+        &admin.pubkey(),
+        &admin_asset.pubkey(),
+        name_suffix,
+        PERM_BUY,
+        0, 0, 0, 0, 0, 0,
+        0,
+        0,
+        10_000,
+        "",
+        "navSOL",
+        key_expires_at,
+    );
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), name_suffix);
+    assert_eq!(read_promo_config(&svm, &pda).key_expires_at, key_expires_at);
 
-    // Try to claim — should fail with PromoInactive
     let claimer = Keypair::new();
     svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
     let key_asset = Keypair::new();
     let ix_claim = ix_claim_promo_key(
-        &claimer.pubkey(),
-        &pda,
-        &admin_asset.pubkey(),
-        &key_asset.pubkey(),
-        &collection,
-        0, // min_deposit_lamports = 0 for this promo
+        &claimer.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection, 0,
     );
-    let result = send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]);
-    assert!(result.is_err(), "claiming from inactive promo should fail");
+    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
+
+    let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
+    assert_eq!(read_key_state(&svm, &ks_pda).expires_at, key_expires_at);
+    let attrs = extract_asset_attributes(&svm.get_account(&key_asset.pubkey()).unwrap());
+    let expires_str = key_expires_at.to_string();
+    assert_eq!(find_attribute(&attrs, "expires_at"), Some(expires_str.as_str()));
+
+    // Claims after the expiry are rejected
+    advance_clock(&mut svm, 86_400);
+    let late = Keypair::new();
+    svm.airdrop(&late.pubkey(), 5_000_000_000).unwrap();
+    let late_asset = Keypair::new();
+    let ix_claim = ix_claim_promo_key(
+        &late.pubkey(), &pda, &admin_asset.pubkey(), &late_asset.pubkey(), &collection, 0,
+    );
+    assert!(send_tx(&mut svm, &[ix_claim], &[&late, &late_asset]).is_err());
 }
 
 // ---------------------------------------------------------------------------
-// 9. test_claim_promo_key_max_claims_reached
+// test_claim_promo_key_allowlist
 // ---------------------------------------------------------------------------
 
+fn ix_set_promo_root(admin: &Pubkey, admin_asset: &Pubkey, promo_pda_key: &Pubkey, root: [u8; 32]) -> Instruction {
+    let mut ix = ix_update_promo(admin, admin_asset, promo_pda_key, None, None);
+    ix.data.truncate(ix.data.len() - 3);
+    ix.data.push(1); // merkle_root: Some
+    ix.data.extend_from_slice(&root);
+    ix.data.extend_from_slice(&[0, 0]); // starts_at, ends_at: None
+    ix
+}
+
 #[test]
-fn test_claim_promo_key_max_claims_reached() {
+fn test_claim_promo_key_allowlist() {
     let (mut svm, _) = setup();
     let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
 
-    let authority_seed = admin_asset.pubkey();
-    let name_suffix = "Max One";
-
-    // Create promo with max_claims=1
+    let name_suffix = "Invite Only";
     let ix = ix_create_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
-        name_suffix,
-        PERM_BUY,
-        0, 0, 0, 0, 0, 0, 0, 1, 10_000, "", "",
+        &admin.pubkey(), &admin_asset.pubkey(), name_suffix, PERM_BUY,
+        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "navSOL",
     );
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), name_suffix);
 
-    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+    let invited = Keypair::new();
+    let whale = Keypair::new();
+    let outsider = Keypair::new();
+    for kp in [&invited, &whale, &outsider] {
+        svm.airdrop(&kp.pubkey(), 5_000_000_000).unwrap();
+    }
+    let leaves = vec![
+        allowlist_leaf(&invited.pubkey(), 0),
+        allowlist_leaf(&whale.pubkey(), 1_000_000_000),
+        allowlist_leaf(&Pubkey::new_unique(), 0),
+    ];
+    let root = allowlist_root(&leaves);
+    let ix = ix_set_promo_root(&admin.pubkey(), &admin_asset.pubkey(), &pda, root);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    assert_eq!(find_event::<PromoUpdated>(&logs).unwrap().merkle_root, root);
+    assert_eq!(read_promo_config(&svm, &pda).merkle_root, root);
+
+    let claim = |svm: &mut LiteSVM, who: &Keypair, amount: u64, min: u64, proof: &[[u8; 32]]| {
+        let key_asset = Keypair::new();
+        let ix = ix_claim_promo_key_with_proof(
+            &who.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection,
+            amount, min, proof,
+        );
+        send_tx(svm, &[ix], &[who, &key_asset])
+    };
 
-    // Wallet A claims successfully
-    let claimer_a = Keypair::new();
-    svm.airdrop(&claimer_a.pubkey(), 5_000_000_000).unwrap();
-    let key_a = Keypair::new();
-    let ix_a = ix_claim_promo_key(
-        &claimer_a.pubkey(),
-        &pda,
-        &admin_asset.pubkey(),
-        &key_a.pubkey(),
-        &collection,
-        0, // min_deposit_lamports = 0 for this promo
+    // Not on the list, with or without borrowing someone else's proof
+    assert!(claim(&mut svm, &outsider, 0, 0, &[]).is_err());
+    assert!(claim(&mut svm, &outsider, 0, 0, &allowlist_proof(&leaves, 0)).is_err());
+    // Listed with a deposit floor: must claim with that floor and deposit at least it
+    let whale_proof = allowlist_proof(&leaves, 1);
+    assert!(claim(&mut svm, &whale, 0, 0, &whale_proof).is_err());
+    assert!(claim(&mut svm, &whale, 0, 1_000_000_000, &whale_proof).is_err());
+
+    claim(&mut svm, &invited, 0, 0, &allowlist_proof(&leaves, 0)).unwrap();
+    assert_eq!(read_promo_config(&svm, &pda).claims_count, 1);
+
+    // Clearing the root opens the promo to everyone
+    let ix = ix_set_promo_root(&admin.pubkey(), &admin_asset.pubkey(), &pda, [0u8; 32]);
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    claim(&mut svm, &outsider, 0, 0, &[]).unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// test_claim_promo_key_collection_gate
+// ---------------------------------------------------------------------------
+
+fn gate_receipt_pda(promo: &Pubkey, gate_asset: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[GateReceipt::SEED, promo.as_ref(), gate_asset.as_ref()],
+        &program_id(),
+    )
+}
+
+fn ix_create_promo_gated(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    name_suffix: &str,
+    gate_collection: &Pubkey,
+) -> Instruction {
+    let mut ix = ix_create_promo(
+        admin, admin_asset, name_suffix, PERM_BUY,
+        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "navSOL",
     );
-    send_tx(&mut svm, &[ix_a], &[&claimer_a, &key_a]).unwrap();
+    ix.data.truncate(ix.data.len() - 118);
+    PromoGate { collection: Some(*gate_collection), ..Default::default() }
+        .serialize(&mut ix.data)
+        .unwrap();
+    ix.data.extend_from_slice(&[0u8; 16]); // window: unbounded
+    ix.data.extend_from_slice(&[0u8; 68]); // budget, referral_reward: none
+    ix
+}
+
+fn ix_claim_promo_key_gated(
+    claimer: &Pubkey,
+    promo_pda_key: &Pubkey,
+    admin_asset: &Pubkey,
+    key_asset: &Pubkey,
+    collection: &Pubkey,
+    gate_asset: &Pubkey,
+) -> Instruction {
+    let mut ix = ix_claim_promo_key(claimer, promo_pda_key, admin_asset, key_asset, collection, 0);
+    let n = ix.accounts.len();
+    ix.accounts[n - 6] = AccountMeta::new_readonly(*gate_asset, false);                          // gate_asset
+    ix.accounts[n - 3] = AccountMeta::new(gate_receipt_pda(promo_pda_key, gate_asset).0, false); // gate_receipt (init)
+    ix
+}
 
-    // Verify claims_count is 1
-    let promo = read_promo_config(&svm, &pda);
-    assert_eq!(promo.claims_count, 1);
+#[test]
+fn test_claim_promo_key_collection_gate() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
 
-    // Wallet B tries to claim — should fail with PromoMaxClaimsReached
-    let claimer_b = Keypair::new();
-    svm.airdrop(&claimer_b.pubkey(), 5_000_000_000).unwrap();
-    let key_b = Keypair::new();
-    let ix_b = ix_claim_promo_key(
-        &claimer_b.pubkey(),
-        &pda,
+    // An open promo hands out Härdig keys; a second promo is gated on holding one
+    let ix = ix_create_promo(
+        &admin.pubkey(), &admin_asset.pubkey(), "Open", PERM_BUY,
+        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "navSOL",
+    );
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let (open_pda, _) = promo_pda(&admin_asset.pubkey(), "Open");
+    let ix = ix_create_promo_gated(&admin.pubkey(), &admin_asset.pubkey(), "Holders", &collection);
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let (gated_pda, _) = promo_pda(&admin_asset.pubkey(), "Holders");
+    let promo = read_promo_config(&svm, &gated_pda);
+    assert_eq!(promo.gate_collection, collection);
+    assert_eq!(promo.gate_standard, GateStandard::MplCore);
+
+    let holder = Keypair::new();
+    let other = Keypair::new();
+    for kp in [&holder, &other] {
+        svm.airdrop(&kp.pubkey(), 5_000_000_000).unwrap();
+    }
+    let gate_nft = Keypair::new();
+    let ix = ix_claim_promo_key(
+        &holder.pubkey(), &open_pda, &admin_asset.pubkey(), &gate_nft.pubkey(), &collection, 0,
+    );
+    send_tx(&mut svm, &[ix], &[&holder, &gate_nft]).unwrap();
+
+    // No gate asset, or one the claimer does not hold
+    let key_asset = Keypair::new();
+    let ix = ix_claim_promo_key(
+        &holder.pubkey(), &gated_pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection, 0,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&holder, &key_asset]).is_err());
+    let ix = ix_claim_promo_key_gated(
+        &holder.pubkey(), &gated_pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection,
         &admin_asset.pubkey(),
-        &key_b.pubkey(),
-        &collection,
-        0,
     );
-    let result = send_tx(&mut svm, &[ix_b], &[&claimer_b, &key_b]);
-    assert!(result.is_err(), "claim should fail when max_claims reached");
+    assert!(send_tx(&mut svm, &[ix], &[&holder, &key_asset]).is_err());
+
+    // Holding a key from the collection unlocks the claim
+    let ix = ix_claim_promo_key_gated(
+        &holder.pubkey(), &gated_pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection,
+        &gate_nft.pubkey(),
+    );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&holder, &key_asset]).unwrap();
+    assert_eq!(find_event::<PromoKeyClaimed>(&logs).unwrap().gate_asset, gate_nft.pubkey());
+    let (receipt_pda, _) = gate_receipt_pda(&gated_pda, &gate_nft.pubkey());
+    let account = svm.get_account(&receipt_pda).unwrap();
+    let receipt = GateReceipt::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(receipt.claimer, holder.pubkey());
+    assert_eq!(receipt.promo, gated_pda);
+
+    // Hand the NFT to another wallet: it cannot claim from the same promo again
+    let mut account = svm.get_account(&gate_nft.pubkey()).unwrap();
+    account.data[1..33].copy_from_slice(other.pubkey().as_ref());
+    svm.set_account(gate_nft.pubkey(), account).unwrap();
+    let key_asset = Keypair::new();
+    let ix = ix_claim_promo_key_gated(
+        &other.pubkey(), &gated_pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection,
+        &gate_nft.pubkey(),
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&other, &key_asset]).is_err());
+    assert_eq!(read_promo_config(&svm, &gated_pda).claims_count, 1);
 }
 
 // ---------------------------------------------------------------------------
-// 10. test_promo_key_can_buy — end-to-end: claim promo key, then buy with it
+// test_claim_promo_key_window
 // ---------------------------------------------------------------------------
 
+fn ix_create_promo_window(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    name_suffix: &str,
+    starts_at: i64,
+    ends_at: i64,
+) -> Instruction {
+    let mut ix = ix_create_promo(
+        admin, admin_asset, name_suffix, PERM_BUY,
+        0, 0, 0, 0, 0, 0, 0, 0, 10_000, "", "navSOL",
+    );
+    ix.data.truncate(ix.data.len() - 84);
+    PromoWindow { starts_at, ends_at }.serialize(&mut ix.data).unwrap();
+    ix.data.extend_from_slice(&[0u8; 68]); // budget, referral_reward: none
+    ix
+}
+
+fn ix_set_promo_window(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    promo_pda_key: &Pubkey,
+    starts_at: Option<i64>,
+    ends_at: Option<i64>,
+) -> Instruction {
+    let mut ix = ix_update_promo(admin, admin_asset, promo_pda_key, None, None);
+    ix.data.truncate(ix.data.len() - 2);
+    starts_at.serialize(&mut ix.data).unwrap();
+    ends_at.serialize(&mut ix.data).unwrap();
+    ix
+}
+
 #[test]
-fn test_promo_key_can_buy() {
+fn test_claim_promo_key_window() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, pos_pda, collection) = promo_setup(&mut svm);
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
+    let now = svm.get_sysvar::<Clock>().unix_timestamp;
 
-    let authority_seed = admin_asset.pubkey();
-    let name_suffix = "Buy Promo";
-    let permissions: u8 = PERM_BUY | PERM_LIMITED_BORROW;
+    // Empty or already-ended windows are rejected
+    let ix = ix_create_promo_window(&admin.pubkey(), &admin_asset.pubkey(), "Bad", now + 100, now + 100);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+    let ix = ix_create_promo_window(&admin.pubkey(), &admin_asset.pubkey(), "Bad", 0, now);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
 
-    // Create promo
-    let ix = ix_create_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
-        name_suffix,
-        permissions,
-        20_000_000, // borrow_capacity
-        1000,       // borrow_refill_period
-        0, 0,       // no sell limits
-        0, 0,       // no total limits
-        0,          // min_deposit_lamports = 0 (allow any amount)
-        100,
-        10_000, // initial_fill_bps: 100% full
-        "",
-        "navSOL",
-    );
+    let name_suffix = "Launch Week";
+    let (starts_at, ends_at) = (now + 3_600, now + 7 * 86_400);
+    let ix = ix_create_promo_window(&admin.pubkey(), &admin_asset.pubkey(), name_suffix, starts_at, ends_at);
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), name_suffix);
+    let promo = read_promo_config(&svm, &pda);
+    assert_eq!((promo.starts_at, promo.ends_at), (starts_at, ends_at));
 
-    let (pda, _) = promo_pda(&authority_seed, name_suffix);
-
-    // Claimer claims a promo key with amount=0 (no deposit at claim time)
     let claimer = Keypair::new();
     svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
-    let key_asset = Keypair::new();
-
-    let ix_claim = ix_claim_promo_key(
-        &claimer.pubkey(),
-        &pda,
-        &admin_asset.pubkey(),
-        &key_asset.pubkey(),
-        &collection,
-        0,
-    );
-    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
-
-    // Verify position deposited_nav is 0 before buy
-    let pos = read_position(&svm, &pos_pda);
-    assert_eq!(pos.deposited_nav, 0, "deposited_nav should be 0 before buy");
+    let claim = |svm: &mut LiteSVM, who: &Keypair| {
+        let key_asset = Keypair::new();
+        let ix = ix_claim_promo_key(
+            &who.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection, 0,
+        );
+        send_tx(svm, &[ix], &[who, &key_asset])
+    };
 
-    // Now use the promo key to call buy
-    let buy_ix = ix_buy(
-        &claimer.pubkey(),
-        &key_asset.pubkey(),
-        &pos_pda,
-        &admin_asset.pubkey(),
-        1_000_000,
-    );
-    send_tx(&mut svm, &[buy_ix], &[&claimer]).unwrap();
+    // Too early, then open
+    assert!(claim(&mut svm, &claimer).is_err());
+    advance_clock(&mut svm, 3_600);
+    claim(&mut svm, &claimer).unwrap();
 
-    // Verify deposited_nav increased (mock Mayflower does 1:1)
-    let pos = read_position(&svm, &pos_pda);
-    assert_eq!(pos.deposited_nav, 1_000_000, "deposited_nav should reflect the buy");
+    // Closing the window early through update_promo
+    let ix = ix_set_promo_window(&admin.pubkey(), &admin_asset.pubkey(), &pda, None, Some(now + 3_700));
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    assert_eq!(find_event::<PromoUpdated>(&logs).unwrap().ends_at, now + 3_700);
+    advance_clock(&mut svm, 100);
+    let late = Keypair::new();
+    svm.airdrop(&late.pubkey(), 5_000_000_000).unwrap();
+    assert!(claim(&mut svm, &late).is_err());
+
+    // An end before the start is rejected; clearing the end reopens the promo
+    let ix = ix_set_promo_window(&admin.pubkey(), &admin_asset.pubkey(), &pda, None, Some(starts_at - 1));
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+    let ix = ix_set_promo_window(&admin.pubkey(), &admin_asset.pubkey(), &pda, None, Some(0));
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    claim(&mut svm, &late).unwrap();
 }
 
 // ---------------------------------------------------------------------------
-// 11. test_promo_key_cannot_sell — promo key with BUY perm denied on withdraw
+// test_promo_budget_shared_across_keys
 // ---------------------------------------------------------------------------
 
+fn ix_create_promo_budget(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    name_suffix: &str,
+    borrow_capacity: u64,
+    budget: PromoBudget,
+) -> Instruction {
+    let mut ix = ix_create_promo(
+        admin, admin_asset, name_suffix, PERM_BUY | PERM_LIMITED_BORROW,
+        borrow_capacity, 1_000, 0, 0, 0, 0, 0, 0, 10_000, "", "navSOL",
+    );
+    ix.data.truncate(ix.data.len() - 68);
+    budget.serialize(&mut ix.data).unwrap();
+    ix.data.extend_from_slice(&[0u8; 20]); // referral_reward: none
+    ix
+}
+
+/// Point the trailing `promo` account of a withdraw/borrow instruction at `promo`.
+fn with_promo(mut ix: Instruction, promo: &Pubkey) -> Instruction {
+    *ix.accounts.last_mut().unwrap() = AccountMeta::new(*promo, false);
+    ix
+}
+
 #[test]
-fn test_promo_key_cannot_sell() {
+fn test_promo_budget_shared_across_keys() {
     let (mut svm, _) = setup();
     let (admin, admin_asset, pos_pda, collection) = promo_setup(&mut svm);
 
-    let authority_seed = admin_asset.pubkey();
-    let name_suffix = "No Sell";
-    let permissions: u8 = PERM_BUY | PERM_LIMITED_BORROW;
+    // A shared bucket without a refill period is rejected
+    let bad = PromoBudget { borrow_capacity: 1_000, ..Default::default() };
+    let ix = ix_create_promo_budget(&admin.pubkey(), &admin_asset.pubkey(), "Bad", 0, bad);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+
+    // Each key may borrow 20M, but the whole campaign only 25M
+    let name_suffix = "Budgeted";
+    let budget = PromoBudget { total_borrow_limit: 25_000_000, ..Default::default() };
+    let ix = ix_create_promo_budget(&admin.pubkey(), &admin_asset.pubkey(), name_suffix, 20_000_000, budget);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    assert_eq!(find_event::<PromoCreated>(&logs).unwrap().budget, budget);
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), name_suffix);
+
+    let mut keys = Vec::new();
+    for _ in 0..2 {
+        let claimer = Keypair::new();
+        svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
+        let key_asset = Keypair::new();
+        let ix = ix_claim_promo_key(
+            &claimer.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection, 1_000_000_000,
+        );
+        send_tx(&mut svm, &[ix], &[&claimer, &key_asset]).unwrap();
+        let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
+        assert_eq!(read_key_state(&svm, &ks_pda).promo, pda);
+        keys.push((claimer, key_asset.pubkey(), ks_pda));
+    }
+    let borrow = |svm: &mut LiteSVM, (claimer, key_asset, ks_pda): &(Keypair, Pubkey, Pubkey), amount| {
+        let ix = ix_borrow(
+            &claimer.pubkey(), key_asset, Some(ks_pda), &pos_pda, &admin_asset.pubkey(), amount,
+        );
+        send_tx(svm, &[with_promo(ix, &pda)], &[claimer])
+    };
 
-    // Create promo
-    let ix = ix_create_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
-        name_suffix,
-        permissions,
-        20_000_000,
-        1000,
-        0, 0,
-        0, 0,
-        0,
-        100,
-        10_000, // initial_fill_bps: 100% full
-        "",
-        "navSOL",
+    // Promo keys must present their promo
+    let (claimer, key_asset, ks_pda) = &keys[0];
+    let ix = ix_borrow(
+        &claimer.pubkey(), key_asset, Some(ks_pda), &pos_pda, &admin_asset.pubkey(), 1_000_000,
     );
-    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    assert!(send_tx(&mut svm, &[ix], &[claimer]).is_err());
 
-    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+    // First key draws 15M; the second can only take what is left of the 25M
+    borrow(&mut svm, &keys[0], 15_000_000).unwrap();
+    assert!(borrow(&mut svm, &keys[1], 15_000_000).is_err());
+    borrow(&mut svm, &keys[1], 10_000_000).unwrap();
+    assert!(borrow(&mut svm, &keys[0], 1).is_err());
 
-    // Admin buys first so there's something to sell
-    let buy_ix = ix_buy(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
-        &pos_pda,
-        &admin_asset.pubkey(),
-        1_000_000,
+    let promo = read_promo_config(&svm, &pda);
+    assert_eq!(promo.budget_borrowed, 25_000_000);
+    assert_eq!(promo.budget_borrow_limit, 25_000_000);
+}
+
+// ---------------------------------------------------------------------------
+// test_claim_promo_key_referral
+// ---------------------------------------------------------------------------
+
+fn ix_create_promo_referral(
+    admin: &Pubkey,
+    admin_asset: &Pubkey,
+    name_suffix: &str,
+    reward: ReferralReward,
+) -> Instruction {
+    let mut ix = ix_create_promo(
+        admin, admin_asset, name_suffix, PERM_BUY | PERM_LIMITED_BORROW,
+        20_000_000, 1_000, 0, 0, 50_000_000, 0, 0, 0, 10_000, "", "navSOL",
     );
-    send_tx(&mut svm, &[buy_ix], &[&admin]).unwrap();
+    ix.data.truncate(ix.data.len() - 20);
+    reward.serialize(&mut ix.data).unwrap();
+    ix
+}
+
+/// Claim naming `referrer`, who holds `referrer_key_asset` (with its KeyState, if any).
+fn ix_claim_promo_key_referred(
+    claimer: &Pubkey,
+    promo_pda_key: &Pubkey,
+    admin_asset: &Pubkey,
+    key_asset: &Pubkey,
+    collection: &Pubkey,
+    referrer: &Pubkey,
+    referrer_key_asset: &Pubkey,
+    referrer_key_state: Option<&Pubkey>,
+) -> Instruction {
+    let mut ix = ix_claim_promo_key(claimer, promo_pda_key, admin_asset, key_asset, collection, 0);
+    ix.data.pop();
+    Some(*referrer).serialize(&mut ix.data).unwrap();
+    let n = ix.accounts.len();
+    ix.accounts[n - 2] = AccountMeta::new_readonly(*referrer_key_asset, false);
+    ix.accounts[n - 1] = AccountMeta::new(referrer_key_state.copied().unwrap_or(program_id()), false);
+    ix
+}
+
+#[test]
+fn test_claim_promo_key_referral() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
+
+    // A bonus without a per-referrer cap is rejected
+    let reward = ReferralReward {
+        borrow_capacity_bonus: 5_000_000,
+        total_borrow_limit_bonus: 10_000_000,
+        max_referrals_per_referrer: 0,
+    };
+    let ix = ix_create_promo_referral(&admin.pubkey(), &admin_asset.pubkey(), "Refer", reward);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+
+    let reward = ReferralReward { max_referrals_per_referrer: 1, ..reward };
+    let ix = ix_create_promo_referral(&admin.pubkey(), &admin_asset.pubkey(), "Refer", reward);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    assert_eq!(find_event::<PromoCreated>(&logs).unwrap().referral_reward, reward);
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), "Refer");
+
+    // Referrer claims a key without being referred
+    let referrer = Keypair::new();
+    svm.airdrop(&referrer.pubkey(), 5_000_000_000).unwrap();
+    let referrer_asset = Keypair::new();
+    let ix = ix_claim_promo_key(
+        &referrer.pubkey(), &pda, &admin_asset.pubkey(), &referrer_asset.pubkey(), &collection, 0,
+    );
+    send_tx(&mut svm, &[ix], &[&referrer, &referrer_asset]).unwrap();
+    let (referrer_ks, _) = key_state_pda(&referrer_asset.pubkey());
+    assert_eq!(read_claim_receipt(&svm, &claim_receipt_pda(&pda, &referrer.pubkey()).0).referrer, Pubkey::default());
 
-    // Claimer claims a promo key
     let claimer = Keypair::new();
     svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
-    let key_asset = Keypair::new();
-
-    let ix_claim = ix_claim_promo_key(
-        &claimer.pubkey(),
-        &pda,
-        &admin_asset.pubkey(),
-        &key_asset.pubkey(),
-        &collection,
-        0,
-    );
-    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
+    let claim = |svm: &mut LiteSVM, who: &Pubkey, asset: &Pubkey, ks: Option<&Pubkey>| {
+        let key_asset = Keypair::new();
+        let ix = ix_claim_promo_key_referred(
+            &claimer.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection, who, asset, ks,
+        );
+        send_tx_logs(svm, &[ix], &[&claimer, &key_asset])
+    };
 
-    // Try to sell (withdraw) using the promo key — should fail
-    let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
-    let sell_ix = ix_withdraw(
-        &claimer.pubkey(),
-        &key_asset.pubkey(),
-        Some(&ks_pda),
-        &pos_pda,
-        &admin_asset.pubkey(),
-        500_000,
+    // Naming a wallet that does not hold the key, or the claimer itself, is rejected
+    let stranger = Pubkey::new_unique();
+    assert!(claim(&mut svm, &stranger, &referrer_asset.pubkey(), Some(&referrer_ks)).is_err());
+    assert!(claim(&mut svm, &claimer.pubkey(), &referrer_asset.pubkey(), Some(&referrer_ks)).is_err());
+    // A reward promo needs the referrer's KeyState to credit
+    assert!(claim(&mut svm, &referrer.pubkey(), &referrer_asset.pubkey(), None).is_err());
+
+    let logs = claim(&mut svm, &referrer.pubkey(), &referrer_asset.pubkey(), Some(&referrer_ks)).unwrap();
+    assert_eq!(find_event::<PromoKeyClaimed>(&logs).unwrap().referrer, referrer.pubkey());
+    let receipt = read_claim_receipt(&svm, &claim_receipt_pda(&pda, &claimer.pubkey()).0);
+    assert_eq!(receipt.referrer, referrer.pubkey());
+
+    let ks = read_key_state(&svm, &referrer_ks);
+    assert_eq!(ks.borrow_bucket.capacity, 25_000_000);
+    assert_eq!(ks.borrow_bucket.level, 25_000_000);
+    assert_eq!(ks.total_borrow_limit, 60_000_000);
+    assert_eq!(ks.referrals_credited, 1);
+
+    // Past the cap, referred claims still go through but credit nothing
+    let second = Keypair::new();
+    svm.airdrop(&second.pubkey(), 5_000_000_000).unwrap();
+    let key_asset = Keypair::new();
+    let ix = ix_claim_promo_key_referred(
+        &second.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection,
+        &referrer.pubkey(), &referrer_asset.pubkey(), Some(&referrer_ks),
+    );
+    send_tx(&mut svm, &[ix], &[&second, &key_asset]).unwrap();
+    let ks = read_key_state(&svm, &referrer_ks);
+    assert_eq!(ks.borrow_bucket.capacity, 25_000_000);
+    assert_eq!(ks.total_borrow_limit, 60_000_000);
+    assert_eq!(ks.referrals_credited, 1);
+
+    // The admin key has no KeyState and may refer without one
+    let admin_referred = Keypair::new();
+    svm.airdrop(&admin_referred.pubkey(), 5_000_000_000).unwrap();
+    let key_asset = Keypair::new();
+    let ix = ix_claim_promo_key_referred(
+        &admin_referred.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection,
+        &admin.pubkey(), &admin_asset.pubkey(), None,
     );
-    let result = send_tx(&mut svm, &[sell_ix], &[&claimer]);
-    assert!(result.is_err(), "promo key without SELL permission should not be able to withdraw");
+    send_tx(&mut svm, &[ix], &[&admin_referred, &key_asset]).unwrap();
 }
 
 // ---------------------------------------------------------------------------
-// 12. test_claim_promo_below_min_deposit_rejected
+// test_close_promo_and_receipts
 // ---------------------------------------------------------------------------
 
+fn ix_close_promo(admin: &Pubkey, admin_asset: &Pubkey, promo_pda_key: &Pubkey) -> Instruction {
+    let (pos_pda, _) = position_pda(admin_asset);
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("close_promo"),
+        vec![
+            AccountMeta::new(*admin, true),                   // admin
+            AccountMeta::new_readonly(*admin_asset, false),    // admin_key_asset
+            AccountMeta::new_readonly(pos_pda, false),         // position
+            AccountMeta::new(*promo_pda_key, false),           // promo (mut)
+            AccountMeta::new_readonly(config_pda().0, false),  // config
+        ],
+    )
+}
+
+/// Close `(receipt, claimer)` pairs of `promo_pda_key`.
+fn ix_close_claim_receipts(promo_pda_key: &Pubkey, receipts: &[(Pubkey, Pubkey)]) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(*promo_pda_key, false)]; // promo
+    for (receipt, claimer) in receipts {
+        accounts.push(AccountMeta::new(*receipt, false));
+        accounts.push(AccountMeta::new(*claimer, false));
+    }
+    Instruction::new_with_bytes(program_id(), &sighash("close_claim_receipts"), accounts)
+}
+
 #[test]
-fn test_claim_promo_below_min_deposit_rejected() {
+fn test_close_promo_and_receipts() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, collection) = promo_setup(&mut svm);
-
-    let authority_seed = admin_asset.pubkey();
-    let name_suffix = "Min Deposit";
-    let min_deposit: u64 = 10_000_000;
+    let (admin, admin_asset, pos_pda, collection) = promo_setup(&mut svm);
 
-    // Create promo with min_deposit_lamports = 10_000_000
-    let ix = ix_create_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
-        name_suffix,
-        PERM_BUY,
-        0, 0, 0, 0,
-        0, 0,
-        min_deposit,
-        100,
-        10_000, // initial_fill_bps: 100% full
-        "",
-        "navSOL",
-    );
+    let name_suffix = "Closing";
+    let budget = PromoBudget { total_borrow_limit: 25_000_000, ..Default::default() };
+    let ix = ix_create_promo_budget(&admin.pubkey(), &admin_asset.pubkey(), name_suffix, 20_000_000, budget);
     send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), name_suffix);
 
-    let (pda, _) = promo_pda(&authority_seed, name_suffix);
-
-    // Claim with amount = 5_000_000, below min_deposit_lamports
     let claimer = Keypair::new();
     svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
     let key_asset = Keypair::new();
+    let ix = ix_claim_promo_key(
+        &claimer.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection, 1_000_000_000,
+    );
+    send_tx(&mut svm, &[ix], &[&claimer, &key_asset]).unwrap();
+    let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
+    let (receipt, _) = claim_receipt_pda(&pda, &claimer.pubkey());
 
-    let ix_claim = ix_claim_promo_key(
-        &claimer.pubkey(),
-        &pda,
-        &admin_asset.pubkey(),
-        &key_asset.pubkey(),
-        &collection,
-        5_000_000, // below min_deposit
+    // Neither the promo nor its receipts can be closed while it is active
+    let ix = ix_close_promo(&admin.pubkey(), &admin_asset.pubkey(), &pda);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+    let ix = ix_close_claim_receipts(&pda, &[(receipt, claimer.pubkey())]);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+
+    let ix = ix_update_promo(&admin.pubkey(), &admin_asset.pubkey(), &pda, Some(false), None);
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let promo_rent = svm.get_account(&pda).unwrap().lamports;
+    let ix = ix_close_promo(&admin.pubkey(), &admin_asset.pubkey(), &pda);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    let event: PromoClosed = find_event(&logs).expect("PromoClosed event");
+    assert_eq!(event.claims_count, 1);
+    let tombstone = svm.get_account(&pda).unwrap();
+    assert_eq!(tombstone.data.len(), PromoConfig::CLOSED_SIZE);
+    assert_eq!(tombstone.lamports + event.refunded, promo_rent);
+
+    // The name cannot be reused, and the promo can no longer be claimed from
+    let ix = ix_create_promo_budget(&admin.pubkey(), &admin_asset.pubkey(), name_suffix, 20_000_000, budget);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+    let other = Keypair::new();
+    svm.airdrop(&other.pubkey(), 5_000_000_000).unwrap();
+    let other_asset = Keypair::new();
+    let ix = ix_claim_promo_key(
+        &other.pubkey(), &pda, &admin_asset.pubkey(), &other_asset.pubkey(), &collection, 1_000_000_000,
+    );
+    assert!(send_tx(&mut svm, &[ix], &[&other, &other_asset]).is_err());
+
+    // The campaign budget closes with the promo: no more limited borrows
+    let ix = ix_borrow(
+        &claimer.pubkey(), &key_asset.pubkey(), Some(&ks_pda), &pos_pda, &admin_asset.pubkey(), 1_000_000,
     );
-    let result = send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]);
-    assert!(result.is_err(), "claim with amount below min_deposit should fail");
+    assert!(send_tx(&mut svm, &[with_promo(ix, &pda)], &[&claimer]).is_err());
+
+    // Receipt rent only goes back to the wallet that paid it
+    let ix = ix_close_claim_receipts(&pda, &[(receipt, admin.pubkey())]);
+    assert!(send_tx(&mut svm, &[ix], &[&admin]).is_err());
+    let receipt_rent = svm.get_account(&receipt).unwrap().lamports;
+    let before = svm.get_account(&claimer.pubkey()).unwrap().lamports;
+    let ix = ix_close_claim_receipts(&pda, &[(receipt, claimer.pubkey())]);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    assert_eq!(find_event::<ClaimReceiptsClosed>(&logs).unwrap().closed, 1);
+    assert!(svm.get_account(&receipt).is_none());
+    assert_eq!(svm.get_account(&claimer.pubkey()).unwrap().lamports, before + receipt_rent);
 }
 
 // ---------------------------------------------------------------------------
-// 13. test_claim_promo_deposited_nav_updated
+// test_promo_principal_refund
 // ---------------------------------------------------------------------------
 
+fn ix_create_promo_principal(admin: &Pubkey, admin_asset: &Pubkey, name_suffix: &str) -> Instruction {
+    ix_create_promo_with_terms(
+        admin, admin_asset, name_suffix, PERM_BUY | PERM_LIMITED_BORROW,
+        KeyLimits { borrow_bucket_capacity: 20_000_000, borrow_refill_period_slots: 1_000, ..Default::default() },
+        PromoTerms { initial_fill_bps: 10_000, refundable_principal: true, ..Default::default() },
+        "", "navSOL",
+    )
+}
+
 #[test]
-fn test_claim_promo_deposited_nav_updated() {
+fn test_promo_principal_refund() {
     let (mut svm, _) = setup();
     let (admin, admin_asset, pos_pda, collection) = promo_setup(&mut svm);
 
-    let authority_seed = admin_asset.pubkey();
-    let name_suffix = "Deposit Nav";
+    let name_suffix = "Refundable";
+    let ix = ix_create_promo_principal(&admin.pubkey(), &admin_asset.pubkey(), name_suffix);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&admin]).unwrap();
+    assert!(find_event::<PromoCreated>(&logs).unwrap().refundable_principal);
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), name_suffix);
 
-    // Create promo with min_deposit = 0 to allow any amount
-    let ix = ix_create_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
-        name_suffix,
-        PERM_BUY,
-        0, 0, 0, 0,
-        0, 0,
-        0, // no minimum
-        100,
-        10_000, // initial_fill_bps: 100% full
-        "",
-        "navSOL",
+    let claimer = Keypair::new();
+    svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
+    let key_asset = Keypair::new();
+    let ix = ix_claim_promo_key(
+        &claimer.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection, 1_000_000_000,
     );
-    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let logs = send_tx_logs(&mut svm, &[ix], &[&claimer, &key_asset]).unwrap();
+    let shares = find_event::<PromoKeyClaimed>(&logs).unwrap().shares_received;
+    let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
+    let ks = read_key_state(&svm, &ks_pda);
+    assert_eq!(ks.principal_shares, shares);
+    assert!(ks.principal_refundable);
 
-    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+    // Borrowing attributes debt to the key, which holds back part of its principal
+    let ix = ix_borrow(
+        &claimer.pubkey(), &key_asset.pubkey(), Some(&ks_pda), &pos_pda, &admin_asset.pubkey(), 10_000_000,
+    );
+    send_tx(&mut svm, &[with_promo(ix, &pda)], &[&claimer]).unwrap();
+    assert_eq!(read_key_state(&svm, &ks_pda).debt, 10_000_000);
+    let withdraw = |svm: &mut LiteSVM, amount| {
+        let ix = ix_withdraw(
+            &claimer.pubkey(), &key_asset.pubkey(), Some(&ks_pda), &pos_pda, &admin_asset.pubkey(), amount,
+        );
+        send_tx(svm, &[ix], &[&claimer])
+    };
+    assert!(withdraw(&mut svm, shares).is_err());
 
-    // Verify deposited_nav starts at 0
-    let pos_before = read_position(&svm, &pos_pda);
-    assert_eq!(pos_before.deposited_nav, 0);
+    // Without its KeyState the key has no sell permission at all
+    let ix = ix_withdraw(&claimer.pubkey(), &key_asset.pubkey(), None, &pos_pda, &admin_asset.pubkey(), 1);
+    assert!(send_tx(&mut svm, &[ix], &[&claimer]).is_err());
+
+    // The key repays its own debt (no PERM_REPAY needed), then takes its principal back
+    let mut ix = ix_repay(&claimer.pubkey(), &key_asset.pubkey(), &pos_pda, &admin_asset.pubkey(), 10_000_000);
+    *ix.accounts.last_mut().unwrap() = AccountMeta::new(ks_pda, false);
+    send_tx(&mut svm, &[ix], &[&claimer]).unwrap();
+    assert_eq!(read_key_state(&svm, &ks_pda).debt, 0);
+
+    withdraw(&mut svm, shares).unwrap();
+    assert_eq!(read_key_state(&svm, &ks_pda).principal_shares, 0);
+    assert!(withdraw(&mut svm, 1).is_err());
+}
+
+#[test]
+fn test_promo_principal_queued_withdraw() {
+    let (mut svm, _) = setup();
+    let (admin, admin_asset, pos_pda, collection) = promo_setup(&mut svm);
+
+    let name_suffix = "Refundable";
+    let ix = ix_create_promo_principal(&admin.pubkey(), &admin_asset.pubkey(), name_suffix);
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let (pda, _) = promo_pda(&admin_asset.pubkey(), name_suffix);
 
-    // Claim with amount = 2_000_000
     let claimer = Keypair::new();
     svm.airdrop(&claimer.pubkey(), 5_000_000_000).unwrap();
     let key_asset = Keypair::new();
-    let deposit_amount: u64 = 2_000_000;
+    let ix = ix_claim_promo_key(
+        &claimer.pubkey(), &pda, &admin_asset.pubkey(), &key_asset.pubkey(), &collection, 1_000_000_000,
+    );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&claimer, &key_asset]).unwrap();
+    let shares = find_event::<PromoKeyClaimed>(&logs).unwrap().shares_received;
+    let (ks_pda, _) = key_state_pda(&key_asset.pubkey());
 
-    let ix_claim = ix_claim_promo_key(
-        &claimer.pubkey(),
-        &pda,
-        &admin_asset.pubkey(),
-        &key_asset.pubkey(),
-        &collection,
-        deposit_amount,
+    // Every withdraw has to wait an hour
+    let ix = ix_configure_withdraw_queue(&admin.pubkey(), &admin_asset.pubkey(), &pos_pda, 3600, 0);
+    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+    let ix = ix_withdraw(
+        &claimer.pubkey(), &key_asset.pubkey(), Some(&ks_pda), &pos_pda, &admin_asset.pubkey(), shares,
     );
-    send_tx(&mut svm, &[ix_claim], &[&claimer, &key_asset]).unwrap();
+    assert!(send_tx(&mut svm, &[ix], &[&claimer]).is_err());
 
-    // Verify deposited_nav increased by deposit_amount (mock Mayflower does 1:1)
-    let pos_after = read_position(&svm, &pos_pda);
-    assert_eq!(
-        pos_after.deposited_nav, deposit_amount,
-        "deposited_nav should increase by the deposited amount (1:1 mock)"
+    // Without its KeyState the key has no sell permission to queue with
+    let ix = ix_request_withdraw(&claimer.pubkey(), &key_asset.pubkey(), None, &pos_pda, 0, shares);
+    assert!(send_tx(&mut svm, &[ix], &[&claimer]).is_err());
+    let ix = ix_request_withdraw(
+        &claimer.pubkey(), &key_asset.pubkey(), Some(&ks_pda), &pos_pda, 0, shares,
     );
+    send_tx(&mut svm, &[ix], &[&claimer]).unwrap();
+
+    advance_clock(&mut svm, 3600);
+    let mut ix = ix_withdraw(
+        &claimer.pubkey(), &key_asset.pubkey(), Some(&ks_pda), &pos_pda, &admin_asset.pubkey(), 0,
+    );
+    ix.data = sighash("execute_withdraw");
+    ix.accounts.push(AccountMeta::new(withdraw_request_pda(&pos_pda, 0), false));
+    ix.accounts.push(AccountMeta::new(claimer.pubkey(), false));
+    send_tx(&mut svm, &[ix], &[&claimer]).unwrap();
+    assert_eq!(read_key_state(&svm, &ks_pda).principal_shares, 0);
 }
 
 // ---------------------------------------------------------------------------
-// ---------------------------------------------------------------------------
-// 14. test_update_promo_non_admin_rejected
+// test_share_token_mint_and_redeem
 // ---------------------------------------------------------------------------
 
-#[test]
-fn test_update_promo_non_admin_rejected() {
-    let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+fn share_mint_pda(position: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PositionState::SHARE_MINT_SEED, position.as_ref()], &program_id())
+}
 
-    let authority_seed = admin_asset.pubkey();
-    let name_suffix = "No Touch";
+/// Turn the planted navSOL mint stub into an initialized 9-decimal SPL mint.
+fn plant_nav_mint(svm: &mut LiteSVM) {
+    let mut account = svm.get_account(&DEFAULT_NAV_SOL_MINT).unwrap();
+    account.data[44] = 9; // decimals
+    account.data[45] = 1; // is_initialized
+    svm.set_account(DEFAULT_NAV_SOL_MINT, account).unwrap();
+}
 
-    // Create promo
-    let ix = ix_create_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
-        name_suffix,
-        PERM_BUY,
-        0, 0, 0, 0, 0, 0, 0, 100, 10_000, "", "",
-    );
-    send_tx(&mut svm, &[ix], &[&admin]).unwrap();
+fn token_amount(svm: &LiteSVM, ata: &Pubkey) -> u64 {
+    let data = svm.get_account(ata).unwrap().data;
+    u64::from_le_bytes(data[64..72].try_into().unwrap())
+}
 
-    let (pda, _) = promo_pda(&authority_seed, name_suffix);
+fn ix_create_ata(payer: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        ATA_PROGRAM_ID,
+        &[1], // CreateIdempotent
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(get_ata(wallet, mint), false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new_readonly(SPL_TOKEN_ID, false),
+        ],
+    )
+}
 
-    // Non-admin tries to update the promo
-    let non_admin = Keypair::new();
-    svm.airdrop(&non_admin.pubkey(), 5_000_000_000).unwrap();
+fn ix_enable_share_mint(admin: &Pubkey, admin_asset: &Pubkey) -> Instruction {
+    let (program_pda, pp_pda, ..) = mayflower_addrs(admin_asset);
+    let (pos_pda, _) = position_pda(admin_asset);
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    let (share_mint, _) = share_mint_pda(&pos_pda);
 
-    // Build update_promo ix with non_admin as signer but referencing admin's asset
-    let (pos_pda, _) = position_pda(&admin_asset.pubkey());
-    let mut data = sighash("update_promo");
-    // active = Some(false)
-    data.push(1); // Some
-    data.push(0); // false
-    // max_claims = None
-    data.push(0);
+    Instruction::new_with_bytes(
+        program_id(),
+        &sighash("enable_share_mint"),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(*admin_asset, false),
+            AccountMeta::new(pos_pda, false),
+            AccountMeta::new_readonly(config_pda().0, false),
+            AccountMeta::new_readonly(mc_pda, false),
+            AccountMeta::new_readonly(program_pda, false),
+            AccountMeta::new_readonly(pp_pda, false),
+            AccountMeta::new_readonly(DEFAULT_NAV_SOL_MINT, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new_readonly(SPL_TOKEN_ID, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        ],
+    )
+}
 
-    let ix_update = Instruction::new_with_bytes(
+fn ix_redeem_shares(holder: &Pubkey, admin_asset: &Pubkey, amount: u64) -> Instruction {
+    let (program_pda, pp_pda, escrow_pda, log_pda, wsol_ata, nav_sol_ata) = mayflower_addrs(admin_asset);
+    let (pos_pda, _) = position_pda(admin_asset);
+    let (mc_pda, _) = market_config_pda(&DEFAULT_NAV_SOL_MINT);
+    let (share_mint, _) = share_mint_pda(&pos_pda);
+
+    let mut data = sighash("redeem_shares");
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // min_out = 0 (no slippage check)
+
+    Instruction::new_with_bytes(
         program_id(),
         &data,
         vec![
-            AccountMeta::new(non_admin.pubkey(), true),            // admin (non-admin signer)
-            AccountMeta::new_readonly(admin_asset.pubkey(), false), // admin_key_asset
-            AccountMeta::new_readonly(pos_pda, false),             // position
-            AccountMeta::new(pda, false),                          // promo (mut)
-            AccountMeta::new_readonly(config_pda().0, false),      // config
+            AccountMeta::new(*holder, true),
+            AccountMeta::new(pos_pda, false),
+            AccountMeta::new_readonly(config_pda().0, false),
+            AccountMeta::new_readonly(mc_pda, false),
+            AccountMeta::new(share_mint, false),
+            AccountMeta::new(get_ata(holder, &share_mint), false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new(program_pda, false),
+            AccountMeta::new(pp_pda, false),
+            AccountMeta::new(escrow_pda, false),
+            AccountMeta::new(nav_sol_ata, false),
+            AccountMeta::new(wsol_ata, false),
+            AccountMeta::new_readonly(MAYFLOWER_TENANT, false),
+            AccountMeta::new_readonly(DEFAULT_MARKET_GROUP, false),
+            AccountMeta::new_readonly(DEFAULT_MARKET_META, false),
+            AccountMeta::new(DEFAULT_MAYFLOWER_MARKET, false),
+            AccountMeta::new(DEFAULT_NAV_SOL_MINT, false),
+            AccountMeta::new(DEFAULT_MARKET_BASE_VAULT, false),
+            AccountMeta::new(DEFAULT_MARKET_NAV_VAULT, false),
+            AccountMeta::new(DEFAULT_FEE_VAULT, false),
+            AccountMeta::new_readonly(DEFAULT_WSOL_MINT, false),
+            AccountMeta::new_readonly(MAYFLOWER_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SPL_TOKEN_ID, false),
+            AccountMeta::new(log_pda, false),
         ],
-    );
+    )
+}
 
-    let result = send_tx(&mut svm, &[ix_update], &[&non_admin]);
-    assert!(result.is_err(), "non-admin should not be able to update promo");
+/// Point a buy at the position's share mint and the depositor's share ATA.
+fn with_share_accounts(mut ix: Instruction, share_mint: &Pubkey, depositor: &Pubkey) -> Instruction {
+    let n = ix.accounts.len();
+    ix.accounts[n - 2] = AccountMeta::new(*share_mint, false);
+    ix.accounts[n - 1] = AccountMeta::new(get_ata(depositor, share_mint), false);
+    ix
 }
 
-// ---------------------------------------------------------------------------
-// 16. test_create_promo_invalid_permissions_rejected
-// ---------------------------------------------------------------------------
+#[test]
+fn test_share_token_mint_and_redeem() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    plant_nav_mint(&mut svm);
+    let (share_mint, _) = share_mint_pda(&h.position_pda);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
+
+    // Nothing to redeem before the position is tokenized
+    let ix = ix_redeem_shares(&admin, &admin_asset, 1);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+
+    // Only the admin can tokenize
+    let ix = ix_enable_share_mint(&h.operator.pubkey(), &h.operator_asset);
+    assert!(send_tx(&mut svm, &[ix], &[&h.operator]).is_err());
+
+    let ix = ix_enable_share_mint(&admin, &admin_asset);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    assert_eq!(find_event::<ShareMintEnabled>(&logs).unwrap().share_mint, share_mint);
+    assert_eq!(read_position(&svm, &h.position_pda).share_mint, share_mint);
+
+    // Buys must now mint to the depositor's own token account
+    let buy = |amount| ix_buy(&h.depositor.pubkey(), &h.depositor_asset, &h.position_pda, &admin_asset, amount);
+    assert!(send_tx(&mut svm, &[buy(1_000_000)], &[&h.depositor]).is_err());
+    let create_atas = [
+        ix_create_ata(&h.depositor.pubkey(), &h.depositor.pubkey(), &share_mint),
+        ix_create_ata(&h.depositor.pubkey(), &admin, &share_mint),
+    ];
+    send_tx(&mut svm, &create_atas, &[&h.depositor]).unwrap();
+    let ix = with_share_accounts(buy(1_000_000), &share_mint, &admin);
+    assert!(send_tx(&mut svm, &[ix], &[&h.depositor]).is_err());
+    let ix = with_share_accounts(buy(1_000_000), &share_mint, &h.depositor.pubkey());
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.depositor]).unwrap();
+    assert_eq!(find_event::<Deposited>(&logs).unwrap().share_tokens_minted, 1_000_000);
+    let depositor_ata = get_ata(&h.depositor.pubkey(), &share_mint);
+    assert_eq!(token_amount(&svm, &depositor_ata), 1_000_000);
+
+    // Any holder redeems without a key; more than the balance fails
+    let ix = ix_redeem_shares(&h.depositor.pubkey(), &admin_asset, 2_000_000);
+    assert!(send_tx(&mut svm, &[ix], &[&h.depositor]).is_err());
+    let ix = ix_redeem_shares(&h.depositor.pubkey(), &admin_asset, 400_000);
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.depositor]).unwrap();
+    let event: SharesRedeemed = find_event(&logs).expect("SharesRedeemed event");
+    assert_eq!(event.tokens_burned, 400_000);
+    assert_eq!(event.shares_sold, 400_000);
+    assert_eq!(token_amount(&svm, &depositor_ata), 600_000);
+    assert_eq!(read_position(&svm, &h.position_pda).deposited_nav, 600_000);
+}
 
 #[test]
-fn test_create_promo_invalid_permissions_rejected() {
+fn test_share_token_excludes_key_payouts() {
     let (mut svm, _) = setup();
-    let (admin, admin_asset, _pos_pda, _collection) = promo_setup(&mut svm);
+    let h = full_setup(&mut svm);
+    plant_nav_mint(&mut svm);
+    let admin = h.admin.pubkey();
+    let admin_asset = h.admin_asset.pubkey();
 
-    // Try to create a promo with PERM_SELL, which is not in KeyCreatorOrigin::Promo's allowed set.
-    // Promo only allows PERM_BUY | PERM_LIMITED_BORROW.
-    let ix = ix_create_promo(
-        &admin.pubkey(),
-        &admin_asset.pubkey(),
-        "Bad Perms",
-        PERM_SELL, // invalid for promo origin
-        0, 0, 0, 0, 0, 0, 0, 100, 10_000, "", "",
+    // Existing holdings would have to be minted to someone: only empty positions tokenize
+    let ix = ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 1_000_000);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ix = ix_enable_share_mint(&admin, &admin_asset);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    let ix = ix_withdraw(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 1_000_000);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    // Redeeming skips key policies, so they can't be active when tokenizing
+    let payout = Pubkey::new_unique();
+    let ix = ix_set_payout_address(&admin, &admin_asset, &h.position_pda, Some(payout));
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ix = ix_enable_share_mint(&admin, &admin_asset);
+    assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    let ix = ix_set_payout_address(&admin, &admin_asset, &h.position_pda, None);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ix = ix_enable_share_mint(&admin, &admin_asset);
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    // ...nor be switched on afterwards
+    for ix in [
+        ix_set_payout_address(&admin, &admin_asset, &h.position_pda, Some(payout)),
+        ix_configure_withdraw_queue(&admin, &admin_asset, &h.position_pda, 3_600, 0),
+        ix_configure_cosign(&admin, &admin_asset, &h.position_pda, 0, 0, 1, &[admin_asset]),
+    ] {
+        assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    }
+
+    // navSOL only leaves through redeem_shares, so supply stays in step
+    let (share_mint, _) = share_mint_pda(&h.position_pda);
+    send_tx(&mut svm, &[ix_create_ata(&admin, &admin, &share_mint)], &[&h.admin]).unwrap();
+    let ix = with_share_accounts(
+        ix_buy(&admin, &admin_asset, &h.position_pda, &admin_asset, 2_000_000),
+        &share_mint,
+        &admin,
     );
-    let result = send_tx(&mut svm, &[ix], &[&admin]);
-    assert!(result.is_err(), "creating promo with PERM_SELL should fail (not in Promo allowed set)");
+    send_tx(&mut svm, &[ix], &[&h.admin]).unwrap();
+    for ix in [
+        ix_withdraw(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 100_000),
+        ix_borrow(&admin, &admin_asset, None, &h.position_pda, &admin_asset, 100_000),
+        ix_reinvest(&admin, &admin_asset, &h.position_pda, &admin_asset, 10_000),
+    ] {
+        assert!(send_tx(&mut svm, &[ix], &[&h.admin]).is_err());
+    }
+    let position = read_position(&svm, &h.position_pda);
+    assert_eq!(token_amount(&svm, &get_ata(&admin, &share_mint)), position.deposited_nav);
 }
 
 // ===========================================================================
@@ -5243,3 +8965,113 @@ fn test_set_position_artwork() {
         PositionState::try_deserialize(&mut pos_account.data.as_slice()).unwrap();
     assert_eq!(pos.artwork_id, None);
 }
+
+// ===========================================================================
+// Event tests
+// ===========================================================================
+
+#[test]
+fn test_buy_emits_deposited_event() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let ix = ix_buy(
+        &h.operator.pubkey(), &h.operator_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 2_000_000,
+    );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.operator]).unwrap();
+
+    let ev: Deposited = find_event(&logs).expect("Deposited event not emitted");
+    assert_eq!(ev.position, h.position_pda);
+    assert_eq!(ev.key_asset, h.operator_asset);
+    assert_eq!(ev.permissions, PRESET_OPERATOR);
+    assert_eq!(ev.amount, 2_000_000);
+    assert_eq!(ev.shares_received, 2_000_000);
+    assert_eq!(ev.deposited_nav, 2_000_000);
+    assert_eq!(ev.share_tokens_minted, 0);
+}
+
+#[test]
+fn test_withdraw_emits_withdrawn_event() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let buy = ix_buy(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        &h.position_pda, &h.admin_asset.pubkey(), 3_000_000,
+    );
+    send_tx(&mut svm, &[buy], &[&h.admin]).unwrap();
+
+    let ix = ix_withdraw(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(), None,
+        &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
+    );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+
+    let ev: Withdrawn = find_event(&logs).expect("Withdrawn event not emitted");
+    assert_eq!(ev.key_asset, h.admin_asset.pubkey());
+    assert_eq!(ev.permissions, PRESET_ADMIN);
+    assert_eq!(ev.shares_sold, 1_000_000);
+    assert_eq!(ev.deposited_nav, 2_000_000);
+}
+
+#[test]
+fn test_borrow_and_repay_emit_events() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+    let borrow = ix_borrow(
+        &h.admin.pubkey(), &h.admin_asset.pubkey(),
+        None, &h.position_pda, &h.admin_asset.pubkey(), 1_000_000,
+    );
+    let logs = send_tx_logs(&mut svm, &[borrow], &[&h.admin]).unwrap();
+    let ev: Borrowed = find_event(&logs).expect("Borrowed event not emitted");
+    assert_eq!(ev.actual_borrowed, 1_000_000);
+    assert_eq!(ev.user_debt, 1_000_000);
+
+    let repay = ix_repay(
+        &h.depositor.pubkey(), &h.depositor_asset,
+        &h.position_pda, &h.admin_asset.pubkey(), 400_000,
+    );
+    let logs = send_tx_logs(&mut svm, &[repay], &[&h.depositor]).unwrap();
+    let ev: Repaid = find_event(&logs).expect("Repaid event not emitted");
+    assert_eq!(ev.key_asset, h.depositor_asset);
+    assert_eq!(ev.permissions, PRESET_DEPOSITOR);
+    assert_eq!(ev.actual_repaid, 400_000);
+    assert_eq!(ev.user_debt, 600_000);
+}
+
+#[test]
+fn test_authorize_and_revoke_emit_events() {
+    let (mut svm, _) = setup();
+    let h = full_setup(&mut svm);
+
+    let target = Keypair::new();
+    let new_asset = Keypair::new();
+    let ix = ix_authorize_key(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &h.position_pda,
+        &new_asset.pubkey(),
+        &target.pubkey(),
+        PRESET_DEPOSITOR,
+        0, 0, 0, 0, 0, 0,
+        &h.collection,
+    );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin, &new_asset]).unwrap();
+    let ev: KeyAuthorized = find_event(&logs).expect("KeyAuthorized event not emitted");
+    assert_eq!(ev.admin_key_asset, h.admin_asset.pubkey());
+    assert_eq!(ev.key_asset, new_asset.pubkey());
+    assert_eq!(ev.target_wallet, target.pubkey());
+    assert_eq!(ev.permissions, PRESET_DEPOSITOR);
+
+    let (new_ks, _) = key_state_pda(&new_asset.pubkey());
+    let ix = ix_revoke_key(
+        &h.admin.pubkey(),
+        &h.admin_asset.pubkey(),
+        &new_asset.pubkey(),
+        &new_ks,
+        &h.collection,
+    );
+    let logs = send_tx_logs(&mut svm, &[ix], &[&h.admin]).unwrap();
+    let ev: KeyRevoked = find_event(&logs).expect("KeyRevoked event not emitted");
+    assert_eq!(ev.position, h.position_pda);
+    assert_eq!(ev.key_asset, new_asset.pubkey());
+}
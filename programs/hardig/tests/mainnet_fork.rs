@@ -160,6 +160,8 @@ fn ix_create_market_config(admin: &Pubkey) -> Instruction {
             AccountMeta::new(*admin, true),
             AccountMeta::new_readonly(config_pda, false),
             AccountMeta::new(mc_pda, false),
+            AccountMeta::new_readonly(mayflower::DEFAULT_MARKET_META, false),
+            AccountMeta::new_readonly(mayflower::DEFAULT_MAYFLOWER_MARKET, false),
             AccountMeta::new_readonly(system_program::ID, false),
         ],
     )
@@ -241,7 +243,7 @@ fn ix_authorize_key(
     admin_asset: &Pubkey,
     new_asset: &Pubkey,
     target_wallet: &Pubkey,
-    role: u8,
+    role: u16,
     sell_bucket_capacity: u64,
     sell_refill_period_slots: u64,
     borrow_bucket_capacity: u64,
@@ -253,7 +255,7 @@ fn ix_authorize_key(
     let (ks_pda, _) = key_state_pda(new_asset);
 
     let mut data = sighash("authorize_key");
-    data.push(role);
+    data.extend_from_slice(&role.to_le_bytes());
     data.extend_from_slice(&sell_bucket_capacity.to_le_bytes());
     data.extend_from_slice(&sell_refill_period_slots.to_le_bytes());
     data.extend_from_slice(&borrow_bucket_capacity.to_le_bytes());
@@ -372,6 +374,8 @@ fn ix_borrow_with_cpi(
             AccountMeta::new_readonly(mayflower::MAYFLOWER_PROGRAM_ID, false),
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),
             AccountMeta::new(log_account, false),
+            AccountMeta::new(program_id(), false),                    // payout (Option)
+            AccountMeta::new(program_id(), false),                    // promo (Option)
         ],
     )
 }
@@ -455,6 +459,10 @@ fn ix_reinvest_with_cpi(
             AccountMeta::new_readonly(mayflower::MAYFLOWER_PROGRAM_ID, false),
             AccountMeta::new_readonly(SPL_TOKEN_ID, false),
             AccountMeta::new(log_account, false),
+            AccountMeta::new_readonly(program_id(), false),           // treasury_base_ata (None)
+            AccountMeta::new_readonly(program_id(), false),           // keeper_base_ata (None)
+            AccountMeta::new_readonly(program_id(), false),           // treasury (None)
+            AccountMeta::new_readonly(program_id(), false),           // associated_token_program (None)
         ],
     )
 }
@@ -533,6 +541,53 @@ fn test_mainnet_fork_read_floor_price() {
     );
 }
 
+#[test]
+#[ignore]
+fn test_mainnet_fork_market_meta_layout() {
+    let client = rpc();
+    let meta = client.get_account(&mayflower::DEFAULT_MARKET_META).unwrap();
+    let market = client.get_account(&mayflower::DEFAULT_MAYFLOWER_MARKET).unwrap();
+    assert_eq!(meta.owner, mayflower::MAYFLOWER_PROGRAM_ID);
+    assert_eq!(market.owner, mayflower::MAYFLOWER_PROGRAM_ID);
+    assert_eq!(meta.data[..8], mayflower::MARKET_META_DISCRIMINATOR);
+    assert_eq!(market.data[..8], mayflower::MARKET_DISCRIMINATOR);
+    assert!(meta.data.len() >= mayflower::MARKET_META_MIN_SIZE);
+
+    let pubkey_at = |data: &[u8], offset: usize| {
+        Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+    };
+    assert_eq!(
+        pubkey_at(&market.data, mayflower::MARKET_META_REF_OFFSET),
+        mayflower::DEFAULT_MARKET_META
+    );
+    for (name, offset, expected) in [
+        ("market_group", mayflower::META_MARKET_GROUP_OFFSET, mayflower::DEFAULT_MARKET_GROUP),
+        ("base_mint", mayflower::META_BASE_MINT_OFFSET, mayflower::DEFAULT_WSOL_MINT),
+        ("nav_mint", mayflower::META_NAV_MINT_OFFSET, mayflower::DEFAULT_NAV_SOL_MINT),
+        ("market", mayflower::META_MARKET_OFFSET, mayflower::DEFAULT_MAYFLOWER_MARKET),
+        ("base_vault", mayflower::META_BASE_VAULT_OFFSET, mayflower::DEFAULT_MARKET_BASE_VAULT),
+        ("nav_vault", mayflower::META_NAV_VAULT_OFFSET, mayflower::DEFAULT_MARKET_NAV_VAULT),
+        ("fee_vault", mayflower::META_FEE_VAULT_OFFSET, mayflower::DEFAULT_FEE_VAULT),
+    ] {
+        assert_eq!(pubkey_at(&meta.data, offset), expected, "MarketMeta {} at offset {}", name, offset);
+    }
+
+    // The same check create_market_config runs
+    let addresses = mayflower::MarketAddresses {
+        nav_mint: mayflower::DEFAULT_NAV_SOL_MINT,
+        base_mint: mayflower::DEFAULT_WSOL_MINT,
+        market_group: mayflower::DEFAULT_MARKET_GROUP,
+        market_meta: mayflower::DEFAULT_MARKET_META,
+        mayflower_market: mayflower::DEFAULT_MAYFLOWER_MARKET,
+        market_base_vault: mayflower::DEFAULT_MARKET_BASE_VAULT,
+        market_nav_vault: mayflower::DEFAULT_MARKET_NAV_VAULT,
+        fee_vault: mayflower::DEFAULT_FEE_VAULT,
+    };
+    mayflower::validate_market_addresses(&addresses, &meta.data, &market.data).unwrap();
+
+    println!("MarketMeta: {} bytes", meta.data.len());
+}
+
 #[test]
 #[ignore]
 fn test_mainnet_fork_init_protocol_and_position() {
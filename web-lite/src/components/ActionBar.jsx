@@ -3,12 +3,15 @@ import {
   canSell,
   canBorrow,
   canRepay,
+  canUnwind,
   canReinvest,
   canAuthorize,
   canRevoke,
   canInitProtocol,
   canCreatePosition,
   positionPda,
+  paused,
+  retired,
   refreshing,
 } from '../state.js';
 
@@ -41,6 +44,9 @@ export function ActionBar({ onAction }) {
           <button disabled={!canRepay.value} onClick={() => onAction('repay')}>
             Repay
           </button>
+          <button disabled={!canUnwind.value} onClick={() => onAction('unwind')}>
+            Unwind
+          </button>
           <button disabled={!canReinvest.value} onClick={() => onAction('reinvest')}>
             Reinvest
           </button>
@@ -50,6 +56,16 @@ export function ActionBar({ onAction }) {
           <button disabled={!canRevoke.value} onClick={() => onAction('revoke')}>
             Revoke Key
           </button>
+          {paused.value && (
+            <span style={{ color: 'var(--red)', fontSize: '13px', alignSelf: 'center' }}>
+              Paused: only repay and unwind are available
+            </span>
+          )}
+          {!paused.value && retired.value && (
+            <span style={{ color: 'var(--red)', fontSize: '13px', alignSelf: 'center' }}>
+              Market retired: only sell, repay and unwind are available
+            </span>
+          )}
         </>
       )}
       <button
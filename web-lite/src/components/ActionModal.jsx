@@ -22,14 +22,17 @@ import {
   buildWithdraw,
   buildBorrow,
   buildRepay,
+  buildUnwind,
   buildReinvest,
   buildAuthorizeKey,
   buildRevokeKey,
   buildCreatePromo,
   buildUpdatePromo,
+  buildClosePromo,
 } from '../instructions/index.js';
+import { discoverPromoReceipts } from '../discovery.js';
 import { availableMarkets, marketEntryToPubkeys } from '../markets.js';
-import { parseSolToLamports, lamportsToSol, shortPubkey, formatDelta, permissionsName, navTokenName, explorerUrl, PERM_BUY, PERM_SELL, PERM_BORROW, PERM_REPAY, PERM_REINVEST, PERM_MANAGE_KEYS, PERM_LIMITED_SELL, PERM_LIMITED_BORROW, PRESET_OPERATOR } from '../utils.js';
+import { parseSolToLamports, lamportsToSol, shortPubkey, formatDelta, permissionsName, navTokenName, explorerUrl, PERM_BUY, PERM_SELL, PERM_BORROW, PERM_REPAY, PERM_REINVEST, PERM_MANAGE_KEYS, PERM_LIMITED_SELL, PERM_LIMITED_BORROW, PERM_UNWIND, PERM_GUARDIAN, PRESET_OPERATOR } from '../utils.js';
 import { deriveMarketConfigPda } from '../constants.js';
 
 // Phase: form | building | confirm | result
@@ -45,6 +48,7 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
 
   // Form fields
   const [amount, setAmount] = useState('');
+  const [targetDebt, setTargetDebt] = useState('0');
   const [targetWallet, setTargetWallet] = useState(wallet.publicKey?.toBase58() || '');
   const [permissions, setPermissions] = useState(String(PRESET_OPERATOR));
   const [revokeIdx, setRevokeIdx] = useState('0');
@@ -150,6 +154,13 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
           built = await buildRepay(program, walletPk, lam);
           break;
         }
+        case 'unwind': {
+          const lam = parseSolToLamports(amount);
+          if (!lam) { setError('Invalid SOL amount'); setPhase('form'); return; }
+          const target = parseSolToLamports(targetDebt || '0') || 0;
+          built = await buildUnwind(program, walletPk, lam, target);
+          break;
+        }
         case 'reinvest':
           built = await buildReinvest(program, walletPk);
           break;
@@ -208,6 +219,15 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
           built = await buildUpdatePromo(program, walletPk, promo.pda, !promo.config.active, null);
           break;
         }
+        case 'closePromo': {
+          const pi3 = actionData?.promoIndex;
+          const promo3 = discoveredPromos.value[pi3];
+          if (!promo3) { setError('Promo not found'); setPhase('form'); return; }
+          if (promo3.config.active) { setError('Pause the promo before closing it'); setPhase('form'); return; }
+          const receipts = await discoverPromoReceipts(connection, promo3.pda);
+          built = await buildClosePromo(program, walletPk, promo3.pda, receipts);
+          break;
+        }
         case 'editPromoMaxClaims': {
           const pi2 = actionData?.promoIndex;
           const promo2 = discoveredPromos.value[pi2];
@@ -232,7 +252,7 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
   }
 
   // Auto-submit for no-form actions (useEffect, not during render)
-  const noFormActions = ['initProtocol', 'reinvest', 'togglePromo'];
+  const noFormActions = ['initProtocol', 'reinvest', 'togglePromo', 'closePromo'];
   useEffect(() => {
     if (noFormActions.includes(action) && !didAutoSubmit.current) {
       didAutoSubmit.current = true;
@@ -304,11 +324,13 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
     sell: 'Sell navSOL',
     borrow: 'Borrow SOL',
     repay: 'Repay SOL',
+    unwind: 'Unwind (Sell + Repay)',
     reinvest: 'Reinvest',
     authorize: 'Authorize Key',
     revoke: 'Revoke Key',
     createPromo: 'Create Promo',
     togglePromo: 'Toggle Promo',
+    closePromo: 'Close Promo',
     editPromoMaxClaims: 'Edit Max Claims',
   };
 
@@ -338,7 +360,7 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
 
         {phase === 'form' && !noFormActions.includes(action) && (
           <div>
-            {(action === 'buy' || action === 'sell' || action === 'borrow' || action === 'repay') && (
+            {(action === 'buy' || action === 'sell' || action === 'borrow' || action === 'repay' || action === 'unwind') && (
               <div class="form-group">
                 <label>Amount (SOL)</label>
                 <input
@@ -346,7 +368,7 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
                   value={amount}
                   onInput={(e) => setAmount(e.target.value)}
                   placeholder={
-                    action === 'sell'
+                    action === 'sell' || action === 'unwind'
                       ? lamportsToSol(position.value?.depositedNav || 0)
                       : action === 'repay'
                       ? lamportsToSol(position.value?.userDebt || 0)
@@ -357,6 +379,18 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
               </div>
             )}
 
+            {action === 'unwind' && (
+              <div class="form-group">
+                <label>Target Debt (SOL)</label>
+                <input
+                  type="text"
+                  value={targetDebt}
+                  onInput={(e) => setTargetDebt(e.target.value)}
+                  placeholder="0.0"
+                />
+              </div>
+            )}
+
             {action === 'createPosition' && (
               <>
                 {availableMarkets.value.length > 0 && (
@@ -427,6 +461,8 @@ export function ActionModal({ action, actionData, onClose, onRefresh }) {
                       [PERM_REINVEST, 'Reinvest'],
                       [PERM_LIMITED_SELL, 'Limited Sell'],
                       [PERM_LIMITED_BORROW, 'Limited Borrow'],
+                      [PERM_UNWIND, 'Unwind'],
+                      [PERM_GUARDIAN, 'Guardian'],
                     ].map(([bit, name]) => {
                       const p = parseInt(permissions) || 0;
                       return (
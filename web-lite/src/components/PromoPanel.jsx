@@ -59,6 +59,15 @@ export function PromoPanel({ onAction }) {
                     >
                       Max Claims
                     </button>
+                    {!c.active && (
+                      <button
+                        class="btn"
+                        style={{ padding: '2px 8px', fontSize: '11px', marginLeft: '4px' }}
+                        onClick={() => onAction('closePromo', { promoIndex: i })}
+                      >
+                        Close
+                      </button>
+                    )}
                   </td>
                 </tr>,
                 <tr key={`${i}-detail`} class="sub-row">
@@ -34,8 +34,8 @@ export const PP_DEPOSITED_SHARES_OFFSET = 104;
 export const PP_DEBT_OFFSET = 112;
 export const MARKET_FLOOR_PRICE_OFFSET = 104;
 
-// KeyState account size: discriminator(8) + authority_seed(32) + asset(32) + bump(1) + sell_bucket(32) + borrow_bucket(32) + total_sell_limit(8) + total_sold(8) + total_borrow_limit(8) + total_borrowed(8) = 169
-export const KEY_STATE_SIZE = 169;
+// KeyState account size: discriminator(8) + authority_seed(32) + asset(32) + bump(1) + sell_bucket(32) + borrow_bucket(32) + total_sell_limit(8) + total_sold(8) + total_borrow_limit(8) + total_borrowed(8) + expires_at(8) + promo(32) + principal_shares(8) + debt(8) + principal_refundable(1) + referrals_credited(4) = 230
+export const KEY_STATE_SIZE = 230;
 // Offset of authority_seed within KeyState (for memcmp filtering by position)
 export const KEY_STATE_AUTHORITY_SEED_OFFSET = 8;
 
@@ -7,7 +7,7 @@ import {
   deriveMarketConfigPda,
   DEFAULT_NAV_SOL_MINT,
 } from './constants.js';
-import { parseKeyState } from './rateLimits.js';
+import { parseBucket, parseKeyState } from './rateLimits.js';
 import {
   positionPda,
   position,
@@ -25,6 +25,8 @@ import {
   discoveredPositions,
   activePositionIndex,
   discoveredPromos,
+  protocolPaused,
+  protocolFee,
 } from './state.js';
 import { shortPubkey, permissionsName, PERM_MANAGE_KEYS, PRESET_ADMIN } from './utils.js';
 import { deriveConfigPda } from './constants.js';
@@ -35,18 +37,30 @@ export async function checkProtocol(connection) {
     const info = await connection.getAccountInfo(configPda);
     protocolExists.value = info !== null;
     if (info && info.data.length >= 72) {
-      // ProtocolConfig layout: discriminator(8) + admin(32) + collection(32) + bump(1)
+      // ProtocolConfig layout: discriminator(8) + admin(32) + collection(32)
+      // + pending_admin(32) + bump(1) + paused(1) + fee_bps(2) + treasury(32)
       // collection is at bytes 40-72
       const collectionPubkey = new PublicKey(info.data.slice(40, 72));
       collection.value = collectionPubkey.equals(PublicKey.default)
         ? null
         : collectionPubkey;
+      protocolPaused.value = info.data.length >= 106 && info.data[105] !== 0;
+      protocolFee.value = info.data.length >= 140
+        ? {
+            feeBps: new DataView(info.data.buffer, info.data.byteOffset).getUint16(106, true),
+            treasury: new PublicKey(info.data.slice(108, 140)),
+          }
+        : { feeBps: 0, treasury: null };
     } else {
       collection.value = null;
+      protocolPaused.value = false;
+      protocolFee.value = { feeBps: 0, treasury: null };
     }
   } catch (e) {
     protocolExists.value = false;
     collection.value = null;
+    protocolPaused.value = false;
+    protocolFee.value = { feeBps: 0, treasury: null };
   }
 }
 
@@ -112,13 +126,13 @@ function readAttributeFromAssetData(data, attributeName) {
 
 /**
  * Read the permissions attribute from an MPL-Core asset.
- * Returns the permissions u8 value, or null if not found.
+ * Returns the permissions u16 value, or null if not found.
  */
 function readPermissionsFromAssetData(data) {
   const value = readAttributeFromAssetData(data, 'permissions');
   if (value === null) return null;
   const parsed = parseInt(value, 10);
-  return (!isNaN(parsed) && parsed >= 0 && parsed <= 255) ? parsed : null;
+  return (!isNaN(parsed) && parsed >= 0 && parsed <= 0xFFFF) ? parsed : null;
 }
 
 /**
@@ -136,7 +150,7 @@ export async function discoverPosition(connection, wallet) {
   // (small account set), then load specific MPL-Core assets by pubkey.
   // This avoids getProgramAccounts on MPL Core which most RPC providers reject.
 
-  const POSITION_SIZE = 238; // PositionState account size (8+32+32+32+8+8+2+8+1+1+32+32+8+1+33)
+  const POSITION_SIZE = 632; // PositionState account size (8+32+32+32+8+8+2+8+1+1+32+32+8+1+33+32+2+8+8+1+160+8+8+8+2+8+8+2+3+32+32+32+32+8)
 
   const [positionAccounts, keyStateAccounts] = await Promise.all([
     connection.getProgramAccounts(PROGRAM_ID, {
@@ -279,6 +293,33 @@ export async function discoverPosition(connection, wallet) {
       // bytes 120-121: _reserved_u16 (skip)
       const lastAdminActivity = Number(view.getBigInt64(122, true));
       const bump = data[130];
+      // bytes 238-269: payout_address (default = pay the signer)
+      const payout = new PublicKey(data.slice(238, 270));
+      const payoutAddress = payout.equals(PublicKey.default) ? null : payout;
+      // bytes 270-271: max_debt_to_floor_bps (0 = no leverage ceiling)
+      const maxDebtToFloorBps = view.getUint16(270, true);
+      // bytes 272-448: co-signing policy (cosign_required 0 = none)
+      const cosignSellThreshold = Number(view.getBigUint64(272, true));
+      const cosignBorrowThreshold = Number(view.getBigUint64(280, true));
+      const cosignRequired = data[288];
+      const cosignApprovers = [];
+      for (let i = 0; i < 5; i++) {
+        const approver = new PublicKey(data.slice(289 + i * 32, 321 + i * 32));
+        if (!approver.equals(PublicKey.default)) cosignApprovers.push(approver);
+      }
+      // bytes 449-472: withdrawal queue (withdraw_delay_secs 0 = no queue)
+      const withdrawDelaySecs = Number(view.getBigInt64(449, true));
+      const withdrawQueueThreshold = Number(view.getBigUint64(457, true));
+      const withdrawRequestCount = Number(view.getBigUint64(465, true));
+      // bytes 473-495: keeper bounty policy (keeper_bounty_bps 0 = keepers disabled)
+      const keeperBountyBps = view.getUint16(473, true);
+      const keeperBountyMax = Number(view.getBigUint64(475, true));
+      const keeperMinBorrow = Number(view.getBigUint64(483, true));
+      const keeperMaxSpreadBps = view.getUint16(491, true);
+      const keeperMode = { kind: data[493] === 0 ? 'fraction' : 'targetLtv', bps: view.getUint16(494, true) };
+      // bytes 496-527: share_mint (default = no share token)
+      const shareMintKey = new PublicKey(data.slice(496, 528));
+      const shareMint = shareMintKey.equals(PublicKey.default) ? null : shareMintKey;
 
       const posData = {
         adminAsset,
@@ -289,6 +330,21 @@ export async function discoverPosition(connection, wallet) {
         userDebt,
         lastAdminActivity,
         bump,
+        payoutAddress,
+        maxDebtToFloorBps,
+        cosignSellThreshold,
+        cosignBorrowThreshold,
+        cosignRequired,
+        cosignApprovers,
+        withdrawDelaySecs,
+        withdrawQueueThreshold,
+        withdrawRequestCount,
+        keeperBountyBps,
+        keeperBountyMax,
+        keeperMinBorrow,
+        keeperMaxSpreadBps,
+        keeperMode,
+        shareMint,
       };
 
       position.value = posData;
@@ -313,6 +369,10 @@ export async function discoverPosition(connection, wallet) {
             marketBaseVault: new PublicKey(mcData.slice(168, 200)),
             marketNavVault: new PublicKey(mcData.slice(200, 232)),
             feeVault: new PublicKey(mcData.slice(232, 264)),
+            // byte 265: paused (absent before the MarketConfig migration)
+            paused: mcData.length >= 266 && mcData[265] !== 0,
+            // byte 266: retired (exits only)
+            retired: mcData.length >= 267 && mcData[266] !== 0,
           };
         }
       } catch (e) {
@@ -380,6 +440,10 @@ export async function discoverPosition(connection, wallet) {
           mint: delegatedPks[i],
           permissions,
           heldBySigner: !!held,
+          promo: positionKeyStates[i].buckets?.promo ?? null,
+          principalShares: positionKeyStates[i].buckets?.principalShares ?? 0,
+          principalDebt: positionKeyStates[i].buckets?.debt ?? 0,
+          principalRefundable: !!positionKeyStates[i].buckets?.principalRefundable,
           ...attachBuckets(delegatedPks[i]),
         });
       }
@@ -454,14 +518,18 @@ export async function selectActivePosition(index, connection) {
 
 /**
  * Discover PromoConfig accounts for the active position's authority_seed.
- * PromoConfig layout (345 bytes total):
- *   discriminator(8) + authority_seed(32) + permissions(1) + borrow_capacity(8) +
+ * PromoConfig layout (552 bytes total):
+ *   discriminator(8) + authority_seed(32) + permissions(2) + borrow_capacity(8) +
  *   borrow_refill_period(8) + sell_capacity(8) + sell_refill_period(8) +
  *   min_deposit_lamports(8) + max_claims(4) + claims_count(4) + active(1) +
- *   total_borrow_limit(8) + total_sell_limit(8) + initial_fill_bps(2) +
+ *   total_borrow_limit(8) + total_sell_limit(8) + initial_fill_bps(2) + key_expires_at(8) +
+ *   merkle_root(32) + gate_collection(32) + gate_standard(1) + starts_at(8) + ends_at(8) +
+ *   budget_borrow_limit(8) + budget_borrowed(8) + budget_sell_limit(8) + budget_sold(8) +
+ *   budget_borrow_bucket(32) + budget_sell_bucket(32) + referral_reward(20) +
+ *   refundable_principal(1) +
  *   name_suffix: String(4+max64) + image_uri: String(4+max128) + market_name: String(4+max32) + bump(1)
  */
-const PROMO_CONFIG_SIZE = 345;
+const PROMO_CONFIG_SIZE = 552;
 
 function parseBorshString(data, offset) {
   if (offset + 4 > data.length) return { value: '', bytesRead: 4 };
@@ -500,8 +568,8 @@ export async function discoverPromos(connection) {
       const authoritySeed = new PublicKey(data.slice(offset, offset + 32));
       offset += 32;
 
-      const permissions = data[offset];
-      offset += 1;
+      const permissions = view.getUint16(offset, true);
+      offset += 2;
 
       const borrowCapacity = Number(view.getBigUint64(offset, true));
       offset += 8;
@@ -536,6 +604,51 @@ export async function discoverPromos(connection) {
       const initialFillBps = view.getUint16(offset, true); // u16 LE
       offset += 2;
 
+      const keyExpiresAt = Number(view.getBigInt64(offset, true)); // i64 LE, 0 = never
+      offset += 8;
+
+      // Claim allowlist merkle root (all zeros = open to anyone)
+      const root = data.slice(offset, offset + 32);
+      const merkleRoot = root.every((b) => b === 0) ? null : root;
+      offset += 32;
+
+      // Collection gate (default pubkey = ungated); standard 0 = MPL-Core, 1 = Token Metadata
+      const gate = new PublicKey(data.slice(offset, offset + 32));
+      const gateCollection = gate.equals(PublicKey.default) ? null : gate;
+      offset += 32;
+
+      const gateStandard = data[offset];
+      offset += 1;
+
+      // Claim window (i64 LE unix timestamps, 0 = unbounded)
+      const startsAt = Number(view.getBigInt64(offset, true));
+      offset += 8;
+
+      const endsAt = Number(view.getBigInt64(offset, true));
+      offset += 8;
+
+      // Campaign-wide budget shared by every key claimed from this promo (limit 0 = uncapped)
+      const budgetBorrowLimit = Number(view.getBigUint64(offset, true));
+      const budgetBorrowed = Number(view.getBigUint64(offset + 8, true));
+      const budgetSellLimit = Number(view.getBigUint64(offset + 16, true));
+      const budgetSold = Number(view.getBigUint64(offset + 24, true));
+      offset += 32;
+
+      const budgetBorrowBucket = parseBucket(data.slice(offset, offset + 32));
+      offset += 32;
+
+      const budgetSellBucket = parseBucket(data.slice(offset, offset + 32));
+      offset += 32;
+
+      // Referral reward credited to the referrer's key per referred claim
+      const referralCapacityBonus = Number(view.getBigUint64(offset, true));
+      const referralLimitBonus = Number(view.getBigUint64(offset + 8, true));
+      const referralMaxPerReferrer = view.getUint32(offset + 16, true);
+      offset += 20;
+
+      const refundablePrincipal = data[offset] !== 0;
+      offset += 1;
+
       const nameSuffixResult = parseBorshString(data, offset);
       const nameSuffix = nameSuffixResult.value;
       offset += nameSuffixResult.bytesRead;
@@ -562,6 +675,22 @@ export async function discoverPromos(connection) {
           totalBorrowLimit,
           totalSellLimit,
           initialFillBps,
+          keyExpiresAt,
+          merkleRoot,
+          gateCollection,
+          gateStandard,
+          startsAt,
+          endsAt,
+          budgetBorrowLimit,
+          budgetBorrowed,
+          budgetSellLimit,
+          budgetSold,
+          budgetBorrowBucket,
+          budgetSellBucket,
+          referralCapacityBonus,
+          referralLimitBonus,
+          referralMaxPerReferrer,
+          refundablePrincipal,
           minDepositLamports,
           claimsCount,
           maxClaims,
@@ -583,3 +712,36 @@ export async function discoverPromos(connection) {
     discoveredPromos.value = [];
   }
 }
+
+// ClaimReceipt and GateReceipt are both 105 bytes; the discriminator tells them apart
+const RECEIPT_SIZE = 105;
+const CLAIM_RECEIPT_DISC = [223, 233, 11, 229, 124, 165, 207, 28];
+const GATE_RECEIPT_DISC = [35, 127, 226, 16, 183, 137, 220, 29];
+
+/**
+ * Find the ClaimReceipts / GateReceipts of a promo as { pda, claimer } pairs,
+ * for close_claim_receipts once the promo is closed.
+ */
+export async function discoverPromoReceipts(connection, promoPda) {
+  // ClaimReceipt: claimer(32) then promo(32); GateReceipt: promo(32), gate_asset(32), claimer(32)
+  const layouts = [
+    { disc: CLAIM_RECEIPT_DISC, promoOffset: 40, claimerOffset: 8 },
+    { disc: GATE_RECEIPT_DISC, promoOffset: 8, claimerOffset: 72 },
+  ];
+  const receipts = [];
+  for (const { disc, promoOffset, claimerOffset } of layouts) {
+    const accounts = await connection.getProgramAccounts(PROGRAM_ID, {
+      filters: [
+        { dataSize: RECEIPT_SIZE },
+        { memcmp: { offset: promoOffset, bytes: promoPda.toBase58() } },
+      ],
+      commitment: 'confirmed',
+    });
+    for (const { pubkey, account } of accounts) {
+      if (!disc.every((b, i) => account.data[i] === b)) continue;
+      const claimer = new PublicKey(account.data.slice(claimerOffset, claimerOffset + 32));
+      receipts.push({ pda: pubkey, claimer });
+    }
+  }
+  return receipts;
+}
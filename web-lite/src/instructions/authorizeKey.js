@@ -8,7 +8,7 @@ import {
 import { myKeyAsset, positionPda, collection } from '../state.js';
 import { shortPubkey, permissionsName } from '../utils.js';
 
-export async function buildAuthorizeKey(program, wallet, targetWalletStr, permissionsU8, sellCapacity = 0, sellRefillSlots = 0, borrowCapacity = 0, borrowRefillSlots = 0, totalSellLimit = 0, totalBorrowLimit = 0, name = null) {
+export async function buildAuthorizeKey(program, wallet, targetWalletStr, permissions, sellCapacity = 0, sellRefillSlots = 0, borrowCapacity = 0, borrowRefillSlots = 0, totalSellLimit = 0, totalBorrowLimit = 0, name = null, expiresAt = 0) {
   const targetWallet = new PublicKey(targetWalletStr);
   const posPda = positionPda.value;
   const adminKeyAsset = myKeyAsset.value;
@@ -19,7 +19,14 @@ export async function buildAuthorizeKey(program, wallet, targetWalletStr, permis
   const [keyStatePda] = deriveKeyStatePda(newKeyAsset);
 
   const ix = await program.methods
-    .authorizeKey(permissionsU8, new BN(sellCapacity), new BN(sellRefillSlots), new BN(borrowCapacity), new BN(borrowRefillSlots), new BN(totalSellLimit), new BN(totalBorrowLimit), name)
+    .authorizeKey(permissions, {
+      sellBucketCapacity: new BN(sellCapacity),
+      sellRefillPeriodSlots: new BN(sellRefillSlots),
+      borrowBucketCapacity: new BN(borrowCapacity),
+      borrowRefillPeriodSlots: new BN(borrowRefillSlots),
+      totalSellLimit: new BN(totalSellLimit),
+      totalBorrowLimit: new BN(totalBorrowLimit),
+    }, name, new BN(expiresAt))
     .accounts({
       admin: wallet,
       adminKeyAsset: adminKeyAsset,
@@ -37,7 +44,7 @@ export async function buildAuthorizeKey(program, wallet, targetWalletStr, permis
     description: [
       'Authorize Key',
       `Target: ${shortPubkey(targetWallet)}`,
-      `Permissions: ${permissionsName(permissionsU8)} (0x${permissionsU8.toString(16).padStart(2, '0')})`,
+      `Permissions: ${permissionsName(permissions)} (0x${permissions.toString(16).padStart(2, '0')})`,
       `Key Asset: ${shortPubkey(newKeyAsset)}`,
     ],
     instructions: [ix],
@@ -9,7 +9,7 @@ import {
   MAYFLOWER_PROGRAM_ID,
   DEFAULT_WSOL_MINT,
 } from '../constants.js';
-import { myKeyAsset, positionPda, position, marketConfigPda, marketConfig } from '../state.js';
+import { myKeyAsset, keyring, positionPda, position, marketConfigPda, marketConfig } from '../state.js';
 import { shortPubkey, lamportsToSol } from '../utils.js';
 
 export async function buildBorrow(program, wallet, amountLamports) {
@@ -26,6 +26,7 @@ export async function buildBorrow(program, wallet, amountLamports) {
 
   // Include keyState if the key might be rate-limited
   const [keyStatePda] = deriveKeyStatePda(keyAsset);
+  const keyPromo = keyring.value.find((k) => k.mint.equals(keyAsset))?.promo ?? null;
 
   const ix = await program.methods
     .borrow(new BN(amountLamports))
@@ -48,6 +49,8 @@ export async function buildBorrow(program, wallet, amountLamports) {
       mayflowerMarket: mc.mayflowerMarket,
       mayflowerProgram: MAYFLOWER_PROGRAM_ID,
       logAccount: logPda,
+      payout: position.value.payoutAddress, // null unless the admin pinned one
+      promo: keyPromo, // campaign budget of the promo this key was claimed from
     })
     .instruction();
 
@@ -56,6 +59,10 @@ export async function buildBorrow(program, wallet, amountLamports) {
       'Borrow',
       `Amount: ${lamportsToSol(amountLamports)} SOL`,
       `Position: ${shortPubkey(posPda)}`,
+      ...(position.value.payoutAddress ? [`Payout: ${shortPubkey(position.value.payoutAddress)}`] : []),
+      ...(position.value.cosignRequired > 0 && amountLamports > position.value.cosignBorrowThreshold
+        ? [`Needs ${position.value.cosignRequired} approver keys to co-sign`]
+        : []),
     ],
     instructions: [ix],
     extraSigners: [],
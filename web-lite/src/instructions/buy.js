@@ -1,5 +1,5 @@
 import { SystemProgram } from '@solana/web3.js';
-import { createSyncNativeInstruction } from '@solana/spl-token';
+import { createSyncNativeInstruction, createAssociatedTokenAccountIdempotentInstruction } from '@solana/spl-token';
 import { BN } from '@coral-xyz/anchor';
 import {
   deriveProgramPda,
@@ -39,6 +39,13 @@ export async function buildBuy(program, wallet, amountLamports) {
   });
   const syncIx = createSyncNativeInstruction(wsolAta, TOKEN_PROGRAM_ID);
 
+  // Tokenized positions mint share tokens to the depositor
+  const shareMint = position.value.shareMint;
+  const shareAta = shareMint ? getAta(wallet, shareMint) : null;
+  const shareAtaIxs = shareMint
+    ? [createAssociatedTokenAccountIdempotentInstruction(wallet, shareAta, wallet, shareMint)]
+    : [];
+
   // Slippage protection: estimate min_out using floor price.
   // buy: input SOL lamports -> output navSOL lamports
   // expected_nav = amount * 1e9 / floor_price, then apply 1% slippage
@@ -74,6 +81,8 @@ export async function buildBuy(program, wallet, amountLamports) {
       wsolMint: mc.baseMint,
       mayflowerProgram: MAYFLOWER_PROGRAM_ID,
       logAccount: logPda,
+      shareMint: shareMint, // null unless the position has a share token
+      depositorShareAta: shareAta,
     })
     .instruction();
 
@@ -83,8 +92,9 @@ export async function buildBuy(program, wallet, amountLamports) {
       `Amount: ${lamportsToSol(amountLamports)} SOL`,
       `Position: ${shortPubkey(posPda)}`,
       `Permissions: ${permissionsName(myPermissions.value)}`,
+      ...(shareMint ? [`Mints share tokens: ${shortPubkey(shareMint)}`] : []),
     ],
-    instructions: [transferIx, syncIx, buyIx],
+    instructions: [...shareAtaIxs, transferIx, syncIx, buyIx],
     extraSigners: [],
   };
 }
@@ -0,0 +1,63 @@
+import { TransactionInstruction } from '@solana/web3.js';
+import { PROGRAM_ID, deriveConfigPda } from '../constants.js';
+import { myKeyAsset, positionPda } from '../state.js';
+import { shortPubkey } from '../utils.js';
+
+// Anchor discriminator: sha256("global:close_promo")[..8]
+const CLOSE_PROMO_DISC = new Uint8Array([1, 129, 64, 57, 92, 182, 63, 91]);
+// Anchor discriminator: sha256("global:close_claim_receipts")[..8]
+const CLOSE_CLAIM_RECEIPTS_DISC = new Uint8Array([103, 96, 235, 182, 146, 35, 9, 208]);
+
+// (receipt, claimer) pairs that fit in one transaction next to close_promo
+const MAX_RECEIPTS_PER_TX = 10;
+
+/**
+ * Close a paused promo and sweep up to MAX_RECEIPTS_PER_TX of its receipts in
+ * the same transaction (receipts can only be closed once the promo is).
+ * `receipts`: [{ pda, claimer }] from discoverPromoReceipts.
+ */
+export async function buildClosePromo(program, wallet, promoPda, receipts = []) {
+  const posPda = positionPda.value;
+  const adminKeyAsset = myKeyAsset.value;
+
+  const [configPda] = deriveConfigPda();
+  const instructions = [
+    new TransactionInstruction({
+      programId: PROGRAM_ID,
+      keys: [
+        { pubkey: wallet, isSigner: true, isWritable: true },
+        { pubkey: adminKeyAsset, isSigner: false, isWritable: false },
+        { pubkey: posPda, isSigner: false, isWritable: false },
+        { pubkey: promoPda, isSigner: false, isWritable: true },
+        { pubkey: configPda, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.from(CLOSE_PROMO_DISC),
+    }),
+  ];
+
+  const batch = receipts.slice(0, MAX_RECEIPTS_PER_TX);
+  if (batch.length > 0) {
+    const keys = [{ pubkey: promoPda, isSigner: false, isWritable: false }];
+    for (const { pda, claimer } of batch) {
+      keys.push({ pubkey: pda, isSigner: false, isWritable: true });
+      keys.push({ pubkey: claimer, isSigner: false, isWritable: true });
+    }
+    instructions.push(new TransactionInstruction({
+      programId: PROGRAM_ID,
+      keys,
+      data: Buffer.from(CLOSE_CLAIM_RECEIPTS_DISC),
+    }));
+  }
+
+  const description = [
+    'Close Promo',
+    `Promo: ${shortPubkey(promoPda)}`,
+    'Rent returns to you; the name cannot be reused',
+    `Close ${batch.length} receipt(s), rent back to each claimer`,
+  ];
+  if (receipts.length > batch.length) {
+    description.push(`${receipts.length - batch.length} more receipt(s) left for close_claim_receipts`);
+  }
+
+  return { description, instructions, extraSigners: [] };
+}
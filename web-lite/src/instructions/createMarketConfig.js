@@ -34,6 +34,8 @@ export async function buildCreateMarketConfig(
       admin: wallet,
       config: configPda,
       marketConfig: mcPda,
+      mayflowerMarketMeta: marketMeta,
+      mayflowerMarket: mayflowerMarket,
     })
     .instruction();
 
@@ -33,6 +33,16 @@ function encodeU64(value) {
   return buf;
 }
 
+/**
+ * Encode an i64 as 8 bytes LE.
+ */
+function encodeI64(value) {
+  const buf = new Uint8Array(8);
+  const view = new DataView(buf.buffer);
+  view.setBigInt64(0, BigInt(value), true);
+  return buf;
+}
+
 /**
  * Encode a u16 as 2 bytes LE.
  */
@@ -68,7 +78,16 @@ export async function buildCreatePromo(
   maxClaims,
   initialFillBps,
   imageUri,
-  marketName = ''
+  marketName = '',
+  keyExpiresAt = 0,
+  merkleRoot = null,
+  gateCollection = null,
+  gateStandard = 0, // GateStandard: 0 = MplCore, 1 = TokenMetadata
+  startsAt = 0,
+  endsAt = 0,
+  budget = null, // { totalBorrowLimit, totalSellLimit, borrowCapacity, borrowRefillPeriod, sellCapacity, sellRefillPeriod }
+  referralReward = null, // { borrowCapacityBonus, totalBorrowLimitBonus, maxReferralsPerReferrer } credited to referrers
+  refundablePrincipal = false // claimers may withdraw their own deposit back
 ) {
   const posPda = positionPda.value;
   const adminKeyAsset = myKeyAsset.value;
@@ -77,33 +96,66 @@ export async function buildCreatePromo(
   const [promoPda] = derivePromoPda(authoritySeed, nameSuffix);
 
   // Build instruction data:
-  // discriminator(8) + name_suffix(String) + permissions(u8) + borrow_capacity(u64) +
-  // borrow_refill_period(u64) + sell_capacity(u64) + sell_refill_period(u64) +
-  // total_borrow_limit(u64) + total_sell_limit(u64) +
-  // min_deposit_lamports(u64) + max_claims(u32) + initial_fill_bps(u16) +
-  // image_uri(String) + market_name(String)
+  // discriminator(8) + name_suffix(String) + permissions(u16) +
+  // limits(KeyLimits: sell_bucket_capacity, sell_refill_period_slots,
+  //   borrow_bucket_capacity, borrow_refill_period_slots, total_sell_limit,
+  //   total_borrow_limit, all u64) +
+  // terms(PromoTerms: min_deposit_lamports u64 + max_claims u32 +
+  //   initial_fill_bps u16 + key_expires_at i64 + refundable_principal bool) +
+  // image_uri(String) + market_name(String) +
+  // gate(PromoGate: collection Option<Pubkey> + standard u8 enum +
+  //   merkle_root [u8; 32], all zeros = open to anyone) +
+  // window(PromoWindow: starts_at i64 + ends_at i64, 0 = unbounded) +
+  // budget(PromoBudget: 6 x u64, all zeros = uncapped campaign) +
+  // referral_reward(ReferralReward: 2 x u64 + max_referrals_per_referrer u32,
+  //   all zeros = no reward; a bonus needs a nonzero cap)
   const nameSuffixBytes = encodeBorshString(nameSuffix);
   const imageUriBytes = encodeBorshString(imageUri);
   const marketNameBytes = encodeBorshString(marketName);
 
-  const dataLen = 8 + nameSuffixBytes.length + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 4 + 2 + imageUriBytes.length + marketNameBytes.length;
+  const dataLen = 8 + nameSuffixBytes.length + 2 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 4 + 2 + 8 + 1 + imageUriBytes.length + marketNameBytes.length + (gateCollection ? 33 : 1) + 1 + 32 + 8 + 8 + 48 + 20;
   const data = new Uint8Array(dataLen);
   let offset = 0;
 
   data.set(CREATE_PROMO_DISC, offset); offset += 8;
   data.set(nameSuffixBytes, offset); offset += nameSuffixBytes.length;
-  data[offset] = permissions; offset += 1;
-  data.set(encodeU64(borrowCapacity), offset); offset += 8;
-  data.set(encodeU64(borrowRefillPeriod), offset); offset += 8;
+  data.set(encodeU16(permissions), offset); offset += 2;
   data.set(encodeU64(sellCapacity), offset); offset += 8;
   data.set(encodeU64(sellRefillPeriod), offset); offset += 8;
-  data.set(encodeU64(totalBorrowLimit), offset); offset += 8;
+  data.set(encodeU64(borrowCapacity), offset); offset += 8;
+  data.set(encodeU64(borrowRefillPeriod), offset); offset += 8;
   data.set(encodeU64(totalSellLimit), offset); offset += 8;
+  data.set(encodeU64(totalBorrowLimit), offset); offset += 8;
   data.set(encodeU64(minDepositLamports), offset); offset += 8;
   data.set(encodeU32(maxClaims), offset); offset += 4;
   data.set(encodeU16(initialFillBps), offset); offset += 2;
+  data.set(encodeI64(keyExpiresAt), offset); offset += 8;
+  data[offset] = refundablePrincipal ? 1 : 0; offset += 1;
   data.set(imageUriBytes, offset); offset += imageUriBytes.length;
   data.set(marketNameBytes, offset); offset += marketNameBytes.length;
+  if (gateCollection) {
+    data[offset] = 1; offset += 1;
+    data.set(gateCollection.toBytes(), offset); offset += 32;
+  } else {
+    data[offset] = 0; offset += 1;
+  }
+  data[offset] = gateStandard; offset += 1;
+  if (merkleRoot) data.set(merkleRoot, offset);
+  offset += 32;
+  data.set(encodeI64(startsAt), offset); offset += 8;
+  data.set(encodeI64(endsAt), offset); offset += 8;
+  const b = budget || {};
+  for (const v of [
+    b.totalBorrowLimit, b.totalSellLimit,
+    b.borrowCapacity, b.borrowRefillPeriod,
+    b.sellCapacity, b.sellRefillPeriod,
+  ]) {
+    data.set(encodeU64(v || 0), offset); offset += 8;
+  }
+  const r = referralReward || {};
+  data.set(encodeU64(r.borrowCapacityBonus || 0), offset); offset += 8;
+  data.set(encodeU64(r.totalBorrowLimitBonus || 0), offset); offset += 8;
+  data.set(encodeU32(r.maxReferralsPerReferrer || 0), offset); offset += 4;
 
   const [configPda] = deriveConfigPda();
   const keys = [
@@ -128,6 +180,16 @@ export async function buildCreatePromo(
       `Permissions: ${permissionsName(permissions)} (0x${permissions.toString(16).padStart(2, '0')})`,
       `Min Deposit: ${lamportsToSol(minDepositLamports)} SOL`,
       `Max Claims: ${maxClaims === 0 ? 'Unlimited' : maxClaims}`,
+      ...(merkleRoot ? ['Claims limited to allowlist'] : []),
+      ...(gateCollection ? [`Gated on collection: ${shortPubkey(gateCollection)}`] : []),
+      ...(startsAt ? [`Claims open: ${new Date(startsAt * 1000).toISOString()}`] : []),
+      ...(endsAt ? [`Claims close: ${new Date(endsAt * 1000).toISOString()}`] : []),
+      ...(b.totalBorrowLimit ? [`Campaign borrow budget: ${lamportsToSol(b.totalBorrowLimit)} SOL`] : []),
+      ...(b.totalSellLimit ? [`Campaign sell budget: ${lamportsToSol(b.totalSellLimit)} SOL`] : []),
+      ...(r.borrowCapacityBonus || r.totalBorrowLimitBonus
+        ? [`Referral reward: +${lamportsToSol(r.borrowCapacityBonus || 0)} SOL capacity, +${lamportsToSol(r.totalBorrowLimitBonus || 0)} SOL limit, up to ${r.maxReferralsPerReferrer || 0} per referrer`]
+        : []),
+      ...(refundablePrincipal ? ['Claimers can withdraw their deposit'] : []),
       `Promo PDA: ${shortPubkey(promoPda)}`,
     ],
     instructions: [ix],
@@ -4,9 +4,11 @@ export { buildBuy } from './buy.js';
 export { buildWithdraw } from './withdraw.js';
 export { buildBorrow } from './borrow.js';
 export { buildRepay } from './repay.js';
+export { buildUnwind } from './unwind.js';
 export { buildReinvest } from './reinvest.js';
 export { buildAuthorizeKey } from './authorizeKey.js';
 export { buildRevokeKey } from './revokeKey.js';
 export { buildCreateMarketConfig } from './createMarketConfig.js';
 export { buildCreatePromo } from './createPromo.js';
 export { buildUpdatePromo } from './updatePromo.js';
+export { buildClosePromo } from './closePromo.js';
@@ -11,10 +11,12 @@ import {
   DEFAULT_WSOL_MINT,
   DEFAULT_NAV_SOL_MINT,
 } from '../constants.js';
-import { myKeyAsset, positionPda, myPermissions, position, marketConfigPda, marketConfig, mfFloorPrice, mfBorrowCapacity } from '../state.js';
-import { shortPubkey, permissionsName } from '../utils.js';
+import { myKeyAsset, positionPda, myPermissions, position, marketConfigPda, marketConfig, mfFloorPrice, mfBorrowCapacity, protocolFee } from '../state.js';
+import { shortPubkey, permissionsName, lamportsToSol } from '../utils.js';
 
-export async function buildReinvest(program, wallet) {
+// mode: { fraction: { bps } } borrows that share of available capacity;
+// { targetLtv: { bps } } borrows only up to debt = bps of the floor value.
+export async function buildReinvest(program, wallet, mode = { fraction: { bps: 10000 } }, minAmountLamports = 0) {
   const keyAsset = myKeyAsset.value;
   const posPda = positionPda.value;
   const mc = marketConfig.value;
@@ -28,26 +30,32 @@ export async function buildReinvest(program, wallet) {
   const [logPda] = deriveLogAccount();
   const wsolAta = getAta(programPda, baseMint);
   const navAta = getAta(programPda, navMint);
+  // Protocol fee is skimmed from the borrowed SOL into the treasury's ATA
+  const { feeBps, treasury } = protocolFee.value;
+  const treasuryAta = feeBps > 0 ? getAta(treasury, baseMint) : null;
 
   const computeIx = ComputeBudgetProgram.setComputeUnitLimit({
     units: 400_000,
   });
 
-  // Slippage protection: reinvest borrows max capacity then buys navSOL.
+  // Slippage protection: reinvest borrows (a fraction of) capacity then buys navSOL.
   // We estimate min_out from locally-known borrow capacity and floor price.
   // Wider tolerance (2%) since capacity may shift between read and execution.
+  // Target-LTV borrows depend on on-chain debt, so no estimate is made for them.
   const floorPrice = mfFloorPrice.value;
   const borrowCap = mfBorrowCapacity.value;
   let minOut;
-  if (floorPrice > 0 && borrowCap > 0) {
-    const expectedNav = BigInt(borrowCap) * BigInt(1_000_000_000) / BigInt(floorPrice);
+  if (mode.fraction && floorPrice > 0 && borrowCap > 0) {
+    const borrow = BigInt(borrowCap) * BigInt(mode.fraction.bps) / BigInt(10_000)
+      * BigInt(10_000 - feeBps) / BigInt(10_000);
+    const expectedNav = borrow * BigInt(1_000_000_000) / BigInt(floorPrice);
     minOut = new BN((expectedNav * BigInt(98) / BigInt(100)).toString());
   } else {
-    minOut = new BN(0); // floor price or capacity unavailable; no slippage protection
+    minOut = new BN(0); // estimate unavailable; no slippage protection
   }
 
   const ix = await program.methods
-    .reinvest(minOut, 0)
+    .reinvest(minOut, 0, mode, new BN(minAmountLamports))
     .accounts({
       signer: wallet,
       keyAsset: keyAsset,
@@ -70,6 +78,8 @@ export async function buildReinvest(program, wallet) {
       wsolMint: mc.baseMint,
       mayflowerProgram: MAYFLOWER_PROGRAM_ID,
       logAccount: logPda,
+      treasuryBaseAta: treasuryAta,
+      keeperBaseAta: null,
     })
     .instruction();
 
@@ -78,7 +88,11 @@ export async function buildReinvest(program, wallet) {
       'Reinvest',
       `Position: ${shortPubkey(posPda)}`,
       `Permissions: ${permissionsName(myPermissions.value)}`,
-      'Borrows available capacity and buys more navSOL',
+      mode.targetLtv
+        ? `Borrows up to ${mode.targetLtv.bps / 100}% LTV and buys more navSOL`
+        : `Borrows ${mode.fraction.bps / 100}% of available capacity and buys more navSOL`,
+      ...(minAmountLamports > 0 ? [`Skipped below: ${lamportsToSol(minAmountLamports)} SOL`] : []),
+      ...(feeBps > 0 ? [`Protocol fee: ${feeBps / 100}% of borrowed SOL`] : []),
     ],
     instructions: [computeIx, ix],
     extraSigners: [],
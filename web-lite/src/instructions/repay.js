@@ -5,12 +5,13 @@ import {
   deriveProgramPda,
   derivePersonalPosition,
   deriveLogAccount,
+  deriveKeyStatePda,
   getAta,
   MAYFLOWER_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
   DEFAULT_WSOL_MINT,
 } from '../constants.js';
-import { myKeyAsset, positionPda, position, marketConfigPda, marketConfig } from '../state.js';
+import { myKeyAsset, positionPda, position, marketConfigPda, marketConfig, keyring } from '../state.js';
 import { shortPubkey, lamportsToSol } from '../utils.js';
 
 export async function buildRepay(program, wallet, amountLamports) {
@@ -25,6 +26,10 @@ export async function buildRepay(program, wallet, amountLamports) {
   const [logAccount] = deriveLogAccount();
   const wsolAta = getAta(programPda, baseMint);
 
+  // Keys with a refundable principal repay the debt attributed to them
+  const [keyStatePda] = deriveKeyStatePda(keyAsset);
+  const principalKey = keyring.value.find((k) => k.mint.equals(keyAsset))?.principalRefundable;
+
   // Pre-IXs: wrap SOL + sync native
   const transferIx = SystemProgram.transfer({
     fromPubkey: wallet,
@@ -49,6 +54,7 @@ export async function buildRepay(program, wallet, amountLamports) {
       mayflowerMarket: mc.mayflowerMarket,
       mayflowerProgram: MAYFLOWER_PROGRAM_ID,
       logAccount: logAccount,
+      keyState: principalKey ? keyStatePda : null,
     })
     .instruction();
 
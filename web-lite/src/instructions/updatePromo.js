@@ -30,22 +30,66 @@ function encodeOptionU32(value) {
   return buf;
 }
 
-export async function buildUpdatePromo(program, wallet, promoPda, active, maxClaims) {
+/**
+ * Encode an Option<[u8; 32]>: 0x00 for None, 0x01 + 32 bytes for Some.
+ */
+function encodeOptionBytes32(value) {
+  if (value === null || value === undefined) {
+    return new Uint8Array([0x00]);
+  }
+  const buf = new Uint8Array(33);
+  buf[0] = 0x01;
+  buf.set(value, 1);
+  return buf;
+}
+
+/**
+ * Encode an Option<i64>: 0x00 for None, 0x01 + 8-byte LE for Some.
+ */
+function encodeOptionI64(value) {
+  if (value === null || value === undefined) {
+    return new Uint8Array([0x00]);
+  }
+  const buf = new Uint8Array(9);
+  buf[0] = 0x01;
+  const view = new DataView(buf.buffer);
+  view.setBigInt64(1, BigInt(value), true);
+  return buf;
+}
+
+export async function buildUpdatePromo(
+  program,
+  wallet,
+  promoPda,
+  active,
+  maxClaims,
+  merkleRoot = null,
+  startsAt = null,
+  endsAt = null
+) {
   const posPda = positionPda.value;
   const adminKeyAsset = myKeyAsset.value;
 
   // Build instruction data:
-  // discriminator(8) + active(Option<bool>) + max_claims(Option<u32>)
+  // discriminator(8) + active(Option<bool>) + max_claims(Option<u32>) + merkle_root(Option<[u8; 32]>) +
+  // starts_at(Option<i64>) + ends_at(Option<i64>)
   const activeBytes = encodeOptionBool(active);
   const maxClaimsBytes = encodeOptionU32(maxClaims);
+  const merkleRootBytes = encodeOptionBytes32(merkleRoot);
+  const startsAtBytes = encodeOptionI64(startsAt);
+  const endsAtBytes = encodeOptionI64(endsAt);
 
-  const dataLen = 8 + activeBytes.length + maxClaimsBytes.length;
+  const dataLen = 8 + activeBytes.length + maxClaimsBytes.length + merkleRootBytes.length +
+    startsAtBytes.length + endsAtBytes.length;
   const data = new Uint8Array(dataLen);
   let offset = 0;
 
   data.set(UPDATE_PROMO_DISC, offset); offset += 8;
   data.set(activeBytes, offset); offset += activeBytes.length;
   data.set(maxClaimsBytes, offset); offset += maxClaimsBytes.length;
+  data.set(merkleRootBytes, offset); offset += merkleRootBytes.length;
+  data.set(startsAtBytes, offset); offset += startsAtBytes.length;
+  data.set(endsAtBytes, offset); offset += endsAtBytes.length;
 
   const [configPda] = deriveConfigPda();
   const keys = [
@@ -69,6 +113,15 @@ export async function buildUpdatePromo(program, wallet, promoPda, active, maxCla
   if (maxClaims !== null && maxClaims !== undefined) {
     changes.push(`Max Claims: ${maxClaims === 0 ? 'Unlimited' : maxClaims}`);
   }
+  if (merkleRoot !== null && merkleRoot !== undefined) {
+    changes.push(merkleRoot.every((b) => b === 0) ? 'Allowlist: removed' : 'Allowlist: rotated');
+  }
+  if (startsAt !== null && startsAt !== undefined) {
+    changes.push(`Claims open: ${startsAt === 0 ? 'now' : new Date(startsAt * 1000).toISOString()}`);
+  }
+  if (endsAt !== null && endsAt !== undefined) {
+    changes.push(`Claims close: ${endsAt === 0 ? 'never' : new Date(endsAt * 1000).toISOString()}`);
+  }
 
   return {
     description: [
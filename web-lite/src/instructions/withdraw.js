@@ -11,7 +11,7 @@ import {
   DEFAULT_WSOL_MINT,
   DEFAULT_NAV_SOL_MINT,
 } from '../constants.js';
-import { myKeyAsset, positionPda, position, marketConfigPda, marketConfig, mfFloorPrice } from '../state.js';
+import { myKeyAsset, keyring, positionPda, position, marketConfigPda, marketConfig, mfFloorPrice } from '../state.js';
 import { shortPubkey, lamportsToSol, PERM_LIMITED_SELL } from '../utils.js';
 
 export async function buildWithdraw(program, wallet, amountLamports) {
@@ -31,6 +31,7 @@ export async function buildWithdraw(program, wallet, amountLamports) {
 
   // Include keyState if the key might be rate-limited
   const [keyStatePda] = deriveKeyStatePda(keyAsset);
+  const keyPromo = keyring.value.find((k) => k.mint.equals(keyAsset))?.promo ?? null;
 
   // Slippage protection: estimate min_out using floor price.
   // sell/withdraw: input navSOL lamports -> output SOL lamports
@@ -68,6 +69,8 @@ export async function buildWithdraw(program, wallet, amountLamports) {
       wsolMint: mc.baseMint,
       mayflowerProgram: MAYFLOWER_PROGRAM_ID,
       logAccount: logPda,
+      payout: position.value.payoutAddress, // null unless the admin pinned one
+      promo: keyPromo, // campaign budget of the promo this key was claimed from
     })
     .instruction();
 
@@ -76,6 +79,13 @@ export async function buildWithdraw(program, wallet, amountLamports) {
       'Sell navSOL',
       `Amount: ${lamportsToSol(amountLamports)} SOL`,
       `Position: ${shortPubkey(posPda)}`,
+      ...(position.value.payoutAddress ? [`Payout: ${shortPubkey(position.value.payoutAddress)}`] : []),
+      ...(position.value.cosignRequired > 0 && amountLamports > position.value.cosignSellThreshold
+        ? [`Needs ${position.value.cosignRequired} approver keys to co-sign`]
+        : []),
+      ...(position.value.withdrawDelaySecs > 0 && amountLamports > position.value.withdrawQueueThreshold
+        ? [`Above ${lamportsToSol(position.value.withdrawQueueThreshold)} the sell must be queued (full client)`]
+        : []),
     ],
     instructions: [ix],
     extraSigners: [],
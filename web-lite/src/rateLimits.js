@@ -13,7 +13,7 @@ import { deriveKeyStatePda, KEY_STATE_SIZE } from './constants.js';
  * @param {Uint8Array} data  Raw bytes starting at the bucket offset.
  * @returns {{ capacity: number, refillPeriod: number, level: number, lastUpdate: number }}
  */
-function parseBucket(data) {
+export function parseBucket(data) {
   const view = new DataView(data.buffer, data.byteOffset);
   return {
     capacity: Number(view.getBigUint64(0, true)),
@@ -26,7 +26,7 @@ function parseBucket(data) {
 /**
  * Parse a KeyState account into its component fields.
  *
- * KeyState layout (169 bytes):
+ * KeyState layout (209 bytes):
  *   discriminator    (8 bytes)
  *   authority_seed   (32 bytes)  [offset 8]   — memcmp filterable
  *   asset            (32 bytes)  [offset 40]
@@ -37,6 +37,8 @@ function parseBucket(data) {
  *   total_sold       (8 bytes)   [offset 145]
  *   total_borrow_limit (8 bytes) [offset 153]
  *   total_borrowed   (8 bytes)   [offset 161]
+ *   expires_at       (8 bytes)   [offset 169] — i64 unix timestamp, 0 = never
+ *   promo            (32 bytes)  [offset 177] — PromoConfig PDA, default = not a promo key
  *
  * @param {Uint8Array} data  Raw account data (must be >= KEY_STATE_SIZE).
  * @returns {{ sellBucket: object, borrowBucket: object, authoritySeed: PublicKey } | null}
@@ -51,6 +53,11 @@ export function parseKeyState(data) {
     totalSold: Number(new DataView(data.buffer, data.byteOffset).getBigUint64(145, true)),
     totalBorrowLimit: Number(new DataView(data.buffer, data.byteOffset).getBigUint64(153, true)),
     totalBorrowed: Number(new DataView(data.buffer, data.byteOffset).getBigUint64(161, true)),
+    expiresAt: Number(new DataView(data.buffer, data.byteOffset).getBigInt64(169, true)),
+    promo: data.slice(177, 209).every((b) => b === 0) ? null : new PublicKey(data.slice(177, 209)),
+    principalShares: Number(new DataView(data.buffer, data.byteOffset).getBigUint64(209, true)),
+    debt: Number(new DataView(data.buffer, data.byteOffset).getBigUint64(217, true)),
+    principalRefundable: data[225] !== 0,
   };
 }
 
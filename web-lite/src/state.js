@@ -1,5 +1,5 @@
 import { signal, computed } from '@preact/signals';
-import { PERM_BUY, PERM_SELL, PERM_BORROW, PERM_REPAY, PERM_REINVEST, PERM_MANAGE_KEYS, PERM_LIMITED_SELL, PERM_LIMITED_BORROW } from './utils.js';
+import { PERM_BUY, PERM_SELL, PERM_BORROW, PERM_REPAY, PERM_REINVEST, PERM_MANAGE_KEYS, PERM_LIMITED_SELL, PERM_LIMITED_BORROW, PERM_UNWIND } from './utils.js';
 
 // Cluster — persisted in localStorage so custom RPC URLs survive page reloads
 export const cluster = signal(localStorage.getItem('hardig_cluster') || 'localnet');
@@ -15,11 +15,13 @@ export const connected = signal(false);
 // Protocol
 export const protocolExists = signal(false);
 export const collection = signal(null);
+export const protocolPaused = signal(false);
+export const protocolFee = signal({ feeBps: 0, treasury: null }); // taken from reinvest borrows
 
 // Position
 export const positionPda = signal(null);
 export const position = signal(null);
-export const myPermissions = signal(null); // u8 bitmask
+export const myPermissions = signal(null); // u16 bitmask
 export const myKeyAsset = signal(null);
 export const myNftMint = signal(null);
 export const keyring = signal([]);
@@ -62,27 +64,52 @@ function hasPerm(perm) {
 // Computed permissions
 export const cpiReady = computed(() => mayflowerInitialized.value);
 
+// Emergency pause (protocol-wide or this market): buy/sell/borrow/reinvest are blocked
+export const paused = computed(
+  () => protocolPaused.value || !!(marketConfig.value && marketConfig.value.paused)
+);
+
+// Retired market: no new buys, borrows or reinvests; exits stay open
+export const retired = computed(
+  () => !!(marketConfig.value && marketConfig.value.retired)
+);
+
 export const canBuy = computed(
-  () => cpiReady.value && hasPerm(PERM_BUY)
+  () => cpiReady.value && !paused.value && !retired.value && hasPerm(PERM_BUY)
 );
 
+// Keys claimed from a refundable-principal promo can withdraw their own deposit
+// and repay the debt attributed to them, whatever their permissions
+const principalRefundable = computed(() => {
+  const asset = myKeyAsset.value;
+  const entry = asset && keyring.value.find((k) => k.mint.equals(asset));
+  return !!entry?.principalRefundable;
+});
+
 export const canSell = computed(
-  () => cpiReady.value && (hasPerm(PERM_SELL) || hasPerm(PERM_LIMITED_SELL))
+  () => cpiReady.value && !paused.value && (hasPerm(PERM_SELL) || hasPerm(PERM_LIMITED_SELL) || principalRefundable.value)
 );
 
 export const canBorrow = computed(
-  () => cpiReady.value && (hasPerm(PERM_BORROW) || hasPerm(PERM_LIMITED_BORROW))
+  () => cpiReady.value && !paused.value && !retired.value && (hasPerm(PERM_BORROW) || hasPerm(PERM_LIMITED_BORROW))
 );
 
 export const canRepay = computed(() => {
   if (!cpiReady.value) return false;
-  if (!hasPerm(PERM_REPAY)) return false;
+  if (!hasPerm(PERM_REPAY) && !principalRefundable.value) return false;
+  const pos = position.value;
+  return pos && pos.userDebt > 0;
+});
+
+export const canUnwind = computed(() => {
+  if (!cpiReady.value) return false;
+  if (!hasPerm(PERM_UNWIND) && !hasPerm(PERM_MANAGE_KEYS)) return false;
   const pos = position.value;
   return pos && pos.userDebt > 0;
 });
 
 export const canReinvest = computed(
-  () => cpiReady.value && hasPerm(PERM_REINVEST)
+  () => cpiReady.value && !paused.value && !retired.value && hasPerm(PERM_REINVEST)
 );
 
 export const canAuthorize = computed(
@@ -42,6 +42,8 @@ export const PERM_REINVEST = 0x10;
 export const PERM_MANAGE_KEYS = 0x20;
 export const PERM_LIMITED_SELL = 0x40;
 export const PERM_LIMITED_BORROW = 0x80;
+export const PERM_UNWIND = 0x100;
+export const PERM_GUARDIAN = 0x200;
 
 export const PRESET_ADMIN = 0x3F;
 export const PRESET_OPERATOR = 0x19;
@@ -60,6 +62,8 @@ export function permissionsName(permissions) {
     [PERM_MANAGE_KEYS, 'ManageKeys'],
     [PERM_LIMITED_SELL, 'LimSell'],
     [PERM_LIMITED_BORROW, 'LimBorrow'],
+    [PERM_UNWIND, 'Unwind'],
+    [PERM_GUARDIAN, 'Guardian'],
   ];
   const names = bits.filter(([bit]) => (permissions & bit) !== 0).map(([, name]) => name);
   return names.length > 0 ? names.join(', ') : 'None';